@@ -31,12 +31,14 @@ impl Config {
     ) -> NetworkConfig<ProviderImpl<DB>> {
         let peer_config = reth_network::PeersConfig::default()
             .with_trusted_nodes(self.peers.trusted_nodes.clone())
-            .with_connect_trusted_nodes_only(self.peers.connect_trusted_nodes_only);
+            .with_connect_trusted_nodes_only(self.peers.connect_trusted_nodes_only)
+            .with_persisted_peers(self.peers.persisted_peers.clone());
         NetworkConfig::builder(Arc::new(ProviderImpl::new(db)), rng_secret_key())
             .boot_nodes(mainnet_nodes())
             .peer_config(peer_config)
             .genesis_hash(genesis_hash)
             .chain_id(chain_id)
+            .dns_discovery(Default::default())
             .set_discovery(disable_discovery)
             .build()
     }
@@ -55,6 +57,12 @@ pub struct StageConfig {
     pub sender_recovery: SenderRecoveryConfig,
     /// Execution stage configuration.
     pub execution: ExecutionConfig,
+    /// Account and storage hashing stages configuration.
+    pub hashing: HashingConfig,
+    /// Merkle stage configuration.
+    pub merkle: MerkleConfig,
+    /// Account and storage history indexing stages configuration.
+    pub index_history: IndexHistoryConfig,
 }
 
 /// Header stage configuration.
@@ -66,11 +74,21 @@ pub struct HeadersConfig {
     pub downloader_batch_size: u64,
     /// The number of times to retry downloading a set of headers.
     pub downloader_retries: usize,
+    /// The maximum number of header requests to have in flight at a time.
+    ///
+    /// The maximum number of headers downloaded at the same time is `downloader_batch_size *
+    /// downloader_concurrency`.
+    pub downloader_concurrency: usize,
 }
 
 impl Default for HeadersConfig {
     fn default() -> Self {
-        Self { commit_threshold: 10_000, downloader_batch_size: 1000, downloader_retries: 5 }
+        Self {
+            commit_threshold: 10_000,
+            downloader_batch_size: 1000,
+            downloader_retries: 5,
+            downloader_concurrency: 10,
+        }
     }
 }
 
@@ -142,3 +160,44 @@ impl Default for ExecutionConfig {
         Self { commit_threshold: 5_000 }
     }
 }
+
+/// Account and storage hashing stages configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HashingConfig {
+    /// The number of blocks above which the hashed state is rebuilt from scratch instead of being
+    /// updated from the changesets.
+    pub clean_threshold: u64,
+}
+
+impl Default for HashingConfig {
+    fn default() -> Self {
+        Self { clean_threshold: 500_000 }
+    }
+}
+
+/// Merkle stage configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MerkleConfig {
+    /// The number of blocks above which the state trie is rebuilt from scratch instead of being
+    /// updated incrementally.
+    pub clean_threshold: u64,
+}
+
+impl Default for MerkleConfig {
+    fn default() -> Self {
+        Self { clean_threshold: 50_000 }
+    }
+}
+
+/// Account and storage history indexing stages configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IndexHistoryConfig {
+    /// The maximum number of blocks to index before committing progress to the database.
+    pub commit_threshold: u64,
+}
+
+impl Default for IndexHistoryConfig {
+    fn default() -> Self {
+        Self { commit_threshold: 100_000 }
+    }
+}
@@ -135,7 +135,7 @@ impl<'a, DB: Database> DbTool<'a, DB> {
 
         self.db.update(|tx| {
             chain.iter().try_for_each(|block| {
-                insert_canonical_block(tx, block, true)?;
+                insert_canonical_block(tx, block, true, false)?;
                 Ok::<_, eyre::Error>(())
             })
         })??;
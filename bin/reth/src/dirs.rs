@@ -21,6 +21,28 @@ pub fn database_path() -> Option<PathBuf> {
     data_dir().map(|root| root.join("db"))
 }
 
+/// Returns the path to the JWT secret of the authenticated engine API.
+///
+/// Refer to [dirs_next::data_dir] for cross-platform behavior.
+pub fn jwt_secret_path() -> Option<PathBuf> {
+    data_dir().map(|root| root.join("jwt.hex"))
+}
+
+/// Returns the path to the file of the known peers, which is written on shutdown and read on
+/// startup.
+///
+/// Refer to [dirs_next::data_dir] for cross-platform behavior.
+pub fn known_peers_path() -> Option<PathBuf> {
+    data_dir().map(|root| root.join("known-peers.json"))
+}
+
+/// Returns the path to the journal of local transactions.
+///
+/// Refer to [dirs_next::data_dir] for cross-platform behavior.
+pub fn transactions_journal_path() -> Option<PathBuf> {
+    data_dir().map(|root| root.join("transactions.rlp"))
+}
+
 /// Returns the path to the reth configuration directory.
 ///
 /// Refer to [dirs_next::config_dir] for cross-platform behavior.
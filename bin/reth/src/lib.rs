@@ -19,6 +19,11 @@ pub mod util;
 
 use clap::Parser;
 use reth_primitives::NodeRecord;
+use reth_rpc_builder::RethRpcModule;
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    path::PathBuf,
+};
 
 #[derive(Debug, Parser)]
 /// Parameters for configuring the network more granularly via CLI
@@ -35,4 +40,129 @@ struct NetworkOpts {
     /// Connect only to trusted peers
     #[arg(long)]
     trusted_only: bool,
+
+    /// The path to the file of the known peers.
+    ///
+    /// The known peers are written to this file on shutdown and restored from it on startup.
+    /// Defaults to `known-peers.json` in the reth data directory.
+    #[arg(long, value_name = "FILE")]
+    peers_file: Option<PathBuf>,
+
+    /// Do not persist the known peers across restarts.
+    #[arg(long, conflicts_with = "peers_file")]
+    no_persist_peers: bool,
+}
+
+impl NetworkOpts {
+    /// Returns the path to the file of the known peers, if the peers should be persisted.
+    fn persistent_peers_file(&self) -> Option<PathBuf> {
+        if self.no_persist_peers {
+            return None
+        }
+        self.peers_file.clone().or_else(dirs::known_peers_path)
+    }
+}
+
+#[derive(Debug, Parser)]
+/// Parameters for configuring the transaction pool via CLI
+struct TxPoolOpts {
+    /// The path to the journal of local transactions.
+    ///
+    /// Local transactions are recorded in the journal and added back to the pool on startup.
+    /// Defaults to `transactions.rlp` in the reth data directory.
+    #[arg(long = "txpool.journal", value_name = "FILE")]
+    journal: Option<PathBuf>,
+
+    /// Do not journal local transactions.
+    #[arg(long = "txpool.nojournal", conflicts_with = "journal")]
+    no_journal: bool,
+
+    /// How often the journal is rewritten with the local transactions of the pool, in seconds.
+    #[arg(
+        long = "txpool.rejournal",
+        value_name = "SECONDS",
+        default_value_t = 3600,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    rejournal: u64,
+
+    /// The path to a file the whole pool is written to on shutdown.
+    ///
+    /// The transactions of the file are added back to the pool on startup.
+    #[arg(long = "txpool.persist", value_name = "FILE")]
+    persist_file: Option<PathBuf>,
+}
+
+impl TxPoolOpts {
+    /// Returns the path to the journal of local transactions, if local transactions should be
+    /// journaled.
+    fn journal_file(&self) -> Option<PathBuf> {
+        if self.no_journal {
+            return None
+        }
+        self.journal.clone().or_else(dirs::transactions_journal_path)
+    }
+}
+
+#[derive(Debug, Parser)]
+/// Parameters for configuring the rpc servers more granularly via CLI
+struct RpcServerOpts {
+    /// Enable the HTTP-RPC server
+    #[arg(long)]
+    http: bool,
+
+    /// Http server address to listen on
+    #[arg(long = "http.addr", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    http_addr: IpAddr,
+
+    /// Http server port to listen on
+    #[arg(long = "http.port", default_value_t = 8545)]
+    http_port: u16,
+
+    /// Rpc modules to be configured for the http server, defaults to `eth,net,web3`
+    #[arg(long = "http.api", value_delimiter = ',')]
+    http_api: Vec<RethRpcModule>,
+
+    /// Enable the WS-RPC server
+    #[arg(long)]
+    ws: bool,
+
+    /// Ws server address to listen on
+    #[arg(long = "ws.addr", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    ws_addr: IpAddr,
+
+    /// Ws server port to listen on
+    #[arg(long = "ws.port", default_value_t = 8546)]
+    ws_port: u16,
+
+    /// Rpc modules to be configured for the ws server, defaults to `eth,net,web3`
+    #[arg(long = "ws.api", value_delimiter = ',')]
+    ws_api: Vec<RethRpcModule>,
+
+    /// Enable the IPC-RPC server
+    #[arg(long)]
+    ipc: bool,
+
+    /// Filename for the IPC socket/pipe
+    #[arg(long = "ipc.path", default_value = "/tmp/reth.ipc")]
+    ipc_path: String,
+
+    /// Rpc modules to be configured for the ipc server, defaults to `eth,net,web3`
+    #[arg(long = "ipc.api", value_delimiter = ',')]
+    ipc_api: Vec<RethRpcModule>,
+
+    /// Auth server address of the engine API to listen on
+    #[arg(long = "authrpc.addr", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    auth_addr: IpAddr,
+
+    /// Auth server port of the engine API to listen on
+    #[arg(long = "authrpc.port", default_value_t = 8551)]
+    auth_port: u16,
+
+    /// Path to the JWT secret the consensus layer client authenticates with.
+    ///
+    /// A random secret is created if the file does not exist. Defaults to `jwt.hex` in the reth
+    /// data directory.
+    #[arg(long = "authrpc.jwtsecret", value_name = "PATH")]
+    auth_jwtsecret: Option<PathBuf>,
 }
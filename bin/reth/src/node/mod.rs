@@ -3,30 +3,54 @@
 //! Starts the client
 use crate::{
     config::Config,
-    dirs::{ConfigPath, DbPath},
+    dirs::{jwt_secret_path, ConfigPath, DbPath},
     prometheus_exporter,
     util::{
         chainspec::{chain_spec_value_parser, ChainSpecification},
         init::{init_db, init_genesis},
     },
-    NetworkOpts,
+    NetworkOpts, RpcServerOpts, TxPoolOpts,
 };
 use clap::{crate_version, Parser};
 use fdlimit::raise_fd_limit;
-use reth_consensus::BeaconConsensus;
+use reth_consensus::{engine::EthConsensusEngine, BeaconConsensus};
 use reth_downloaders::{bodies, headers};
 use reth_executor::Config as ExecutorConfig;
-use reth_interfaces::consensus::ForkchoiceState;
-use reth_primitives::H256;
+use reth_interfaces::{consensus::ForkchoiceState, sync::ChainSync};
+use reth_network::peers::write_persisted_peers;
+use reth_primitives::{IntoRecoveredTransaction, H256};
+use reth_provider::{canon_state_notification_channel, ProviderImpl};
+use reth_rpc::EngineApi;
+use reth_rpc_builder::{
+    launch_auth_server, IpcServerBuilder, JwtSecret, RethRpcModule, RpcModuleBuilder,
+    RpcModuleConfig, RpcServerBuilder, ServerBuilder, TransportRpcModules,
+};
 use reth_stages::{
     metrics::HeaderMetrics,
     stages::{
-        bodies::BodyStage, execution::ExecutionStage, headers::HeaderStage,
+        bodies::BodyStage, execution::ExecutionStage, hashing_account::AccountHashingStage,
+        hashing_storage::StorageHashingStage, headers::HeaderStage,
+        index_account_history::IndexAccountHistoryStage,
+        index_storage_history::IndexStorageHistoryStage, merkle::MerkleStage,
         sender_recovery::SenderRecoveryStage, total_difficulty::TotalDifficultyStage,
     },
+    PipelineSync,
+};
+use reth_transaction_pool::{
+    journal::{
+        load_transactions, maintain_transaction_journal, save_transactions, TransactionJournal,
+    },
+    maintain_transaction_pool, CostOrdering, EthTransactionValidator, Pool, PoolConfig,
+    PooledTransaction, TransactionOrigin, TransactionPool,
+};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
-use std::{net::SocketAddr, sync::Arc};
-use tracing::{debug, info};
+use tokio::sync::mpsc::unbounded_channel;
+use tracing::{debug, info, warn};
 
 /// Start the client
 #[derive(Debug, Parser)]
@@ -70,17 +94,24 @@ pub struct Command {
 
     /// Set the chain tip manually for testing purposes.
     ///
+    /// The node syncs to the tip as if the consensus layer had sent a forkchoice update to it.
+    ///
     /// NOTE: This is a temporary flag
     #[arg(long = "debug.tip")]
     tip: Option<H256>,
 
     #[clap(flatten)]
     network: NetworkOpts,
+
+    #[clap(flatten)]
+    rpc: RpcServerOpts,
+
+    #[clap(flatten)]
+    txpool: TxPoolOpts,
 }
 
 impl Command {
     /// Execute `node` command
-    // TODO: RPC
     pub async fn execute(&self) -> eyre::Result<()> {
         // Raise the fd limit of the process.
         // Does not do anything on windows.
@@ -96,6 +127,14 @@ impl Command {
 
         info!("reth {} starting", crate_version!());
 
+        let peers_file = self.network.persistent_peers_file();
+        if let Some(peers_file) = &peers_file {
+            match config.peers.clone().with_persisted_peers_file(peers_file) {
+                Ok(peers) => config.peers = peers,
+                Err(err) => warn!(%err, file = %peers_file.display(), "Failed to load known peers"),
+            }
+        }
+
         info!("Opening database at {}", &self.db);
         let db = Arc::new(init_db(&self.db)?);
         info!("Database open");
@@ -121,12 +160,16 @@ impl Command {
         // cloneable on its own
         // TODO: Remove magic numbers
         let fetch_client = Arc::new(network.fetch_client().await?);
-        let mut pipeline = reth_stages::Pipeline::default()
+        // executed and unwound blocks are announced to the transaction pool
+        let (canon_state_tx, canon_state_rx) = canon_state_notification_channel(256);
+        let executor_config = ExecutorConfig::from(&self.chain.consensus);
+        let pipeline = reth_stages::Pipeline::default()
             .with_sync_state_updater(network.clone())
             .push(HeaderStage {
                 downloader: headers::linear::LinearDownloadBuilder::default()
                     .batch_size(config.stages.headers.downloader_batch_size)
                     .retries(config.stages.headers.downloader_retries)
+                    .concurrency(config.stages.headers.downloader_concurrency)
                     .build(consensus.clone(), fetch_client.clone()),
                 consensus: consensus.clone(),
                 client: fetch_client.clone(),
@@ -155,24 +198,247 @@ impl Command {
                 commit_threshold: config.stages.sender_recovery.commit_threshold,
             })
             .push(ExecutionStage {
-                config: ExecutorConfig::new_ethereum(),
+                config: executor_config.clone(),
                 commit_threshold: config.stages.execution.commit_threshold,
+                notifications: Some(canon_state_tx.clone()),
+            })
+            .push(AccountHashingStage { clean_threshold: config.stages.hashing.clean_threshold })
+            .push(StorageHashingStage { clean_threshold: config.stages.hashing.clean_threshold })
+            .push(MerkleStage { clean_threshold: config.stages.merkle.clean_threshold })
+            .push(IndexAccountHistoryStage {
+                commit_threshold: config.stages.index_history.commit_threshold,
+            })
+            .push(IndexStorageHistoryStage {
+                commit_threshold: config.stages.index_history.commit_threshold,
             });
 
+        // The pipeline runs whenever the consensus engine syncs to a new head
+        let mut sync =
+            PipelineSync::new(pipeline, db.clone(), consensus.clone(), executor_config.clone())
+                .with_notifications(canon_state_tx);
+
         if let Some(tip) = self.tip {
             debug!("Tip manually set: {}", tip);
-            consensus.notify_fork_choice_state(ForkchoiceState {
+            sync.sync_to(ForkchoiceState {
                 head_block_hash: tip,
                 safe_block_hash: tip,
                 finalized_block_hash: tip,
+            });
+        }
+
+        // transactions are validated for the block on top of the local head
+        let consensus_config = &self.chain.consensus;
+        let validator = EthTransactionValidator::<_, PooledTransaction>::new(
+            ProviderImpl::new(db.clone()),
+            chain_id,
+        )
+        .berlin_block(consensus_config.berlin_block)
+        .london_block(consensus_config.london_block)
+        .shanghai_block(consensus_config.shanghai_block);
+        let pool = Pool::new(
+            Arc::new(validator),
+            Arc::new(CostOrdering::default()),
+            PoolConfig::default(),
+        );
+        tokio::spawn(maintain_transaction_pool(
+            ProviderImpl::new(db.clone()),
+            pool.clone(),
+            canon_state_rx,
+        ));
+
+        // local transactions are added back first so they keep their origin
+        if let Some(journal_file) = self.txpool.journal_file() {
+            match load_transactions(&pool, TransactionOrigin::Local, &journal_file).await {
+                Ok(count) => {
+                    info!(count, file = %journal_file.display(), "Loaded local transactions")
+                }
+                Err(err) => {
+                    warn!(%err, file = %journal_file.display(), "Failed to load local transactions")
+                }
+            }
+            tokio::spawn(maintain_transaction_journal(
+                pool.clone(),
+                TransactionJournal::new(journal_file),
+                Duration::from_secs(self.txpool.rejournal),
+            ));
+        }
+        if let Some(persist_file) = &self.txpool.persist_file {
+            match load_transactions(&pool, TransactionOrigin::External, persist_file).await {
+                Ok(count) => {
+                    info!(count, file = %persist_file.display(), "Loaded pool transactions")
+                }
+                Err(err) => {
+                    warn!(%err, file = %persist_file.display(), "Failed to load pool transactions")
+                }
+            }
+        }
+        // also written when dropped, so the pool is kept if the node exits early
+        let persist_pool = self
+            .txpool
+            .persist_file
+            .clone()
+            .map(|file| PersistPoolOnDrop { pool: pool.clone(), file: Some(file) });
+        let (engine_tx, engine_rx) = unbounded_channel();
+
+        let rpc_modules = |config: RpcModuleConfig| {
+            RpcModuleBuilder::new(ProviderImpl::new(db.clone()), pool.clone(), network.clone())
+                .with_executor_config(executor_config.clone())
+                .with_config(config)
+        };
+
+        let mut modules = TransportRpcModules::default();
+        let mut rpc_server = RpcServerBuilder::default();
+        if self.rpc.http {
+            let addr = SocketAddr::new(self.rpc.http_addr, self.rpc.http_port);
+            modules.http = Some(rpc_modules(rpc_module_config(&self.rpc.http_api)).build());
+            rpc_server = rpc_server.with_http(ServerBuilder::default(), addr);
+        }
+        if self.rpc.ws {
+            let addr = SocketAddr::new(self.rpc.ws_addr, self.rpc.ws_port);
+            modules.ws = Some(rpc_modules(rpc_module_config(&self.rpc.ws_api)).build());
+            rpc_server = rpc_server.with_ws(ServerBuilder::default(), addr);
+        }
+        if self.rpc.ipc {
+            modules.ipc = Some(rpc_modules(rpc_module_config(&self.rpc.ipc_api)).build());
+            rpc_server = rpc_server.with_ipc(IpcServerBuilder::default(), &self.rpc.ipc_path);
+        }
+        let rpc_handle = rpc_server.start(modules).await?;
+        info!(
+            http = ?rpc_handle.http_local_addr,
+            ws = ?rpc_handle.ws_local_addr,
+            ipc = ?rpc_handle.ipc_endpoint,
+            "Started rpc servers"
+        );
+
+        let jwt_path =
+            self.rpc.auth_jwtsecret.clone().or_else(jwt_secret_path).ok_or_else(|| {
+                eyre::eyre!("Could not determine the JWT secret path. Set one manually.")
             })?;
+        let jwt_secret = JwtSecret::try_create(&jwt_path)?;
+        let auth_module = rpc_modules(RpcModuleConfig::All).build_auth(EngineApi::new(engine_tx));
+        let auth_addr = SocketAddr::new(self.rpc.auth_addr, self.rpc.auth_port);
+        let (auth_addr, _auth_handle) =
+            launch_auth_server(auth_module, auth_addr, jwt_secret).await?;
+        info!(
+            %auth_addr,
+            jwt_secret = %jwt_path.display(),
+            "Started authenticated engine API server"
+        );
+
+        let engine = EthConsensusEngine::new(
+            Arc::new(ProviderImpl::new(db.clone())),
+            pool.clone(),
+            self.chain.consensus.clone(),
+            engine_rx,
+        )
+        .with_sync(sync);
+
+        info!("Starting consensus engine");
+        tokio::select! {
+            _ = engine => {}
+            _ = shutdown_signal() => info!("Received shutdown signal"),
         }
 
-        // Run pipeline
-        info!("Starting pipeline");
-        pipeline.run(db.clone()).await?;
+        if let Some(peers_file) = peers_file {
+            let known_peers = network.peers_handle().persisted_peers().await;
+            match write_persisted_peers(&peers_file, &known_peers) {
+                Ok(()) => {
+                    info!(peers = known_peers.len(), file = %peers_file.display(), "Wrote known peers")
+                }
+                Err(err) => {
+                    warn!(%err, file = %peers_file.display(), "Failed to write known peers")
+                }
+            }
+        }
+
+        if let Some(persist_pool) = persist_pool {
+            persist_pool.persist().await;
+        }
 
         info!("Finishing up");
         Ok(())
     }
 }
+
+/// Resolves once the node receives ctrl-c or, on unix, SIGTERM.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut sigterm) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = sigterm.recv() => {}
+            }
+            return
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Writes the transactions of the pool to the `--txpool.persist` file.
+///
+/// On shutdown the transactions are written via [`PersistPoolOnDrop::persist`], dropping the guard
+/// before that writes them as a fallback, e.g. if the node exits early with an error.
+struct PersistPoolOnDrop<P>
+where
+    P: TransactionPool,
+    P::Transaction: IntoRecoveredTransaction,
+{
+    pool: P,
+    /// The file to write to, unset once the transactions were written.
+    file: Option<PathBuf>,
+}
+
+impl<P> PersistPoolOnDrop<P>
+where
+    P: TransactionPool + 'static,
+    P::Transaction: IntoRecoveredTransaction,
+{
+    /// Writes the transactions on a blocking thread.
+    async fn persist(mut self) {
+        let Some(file) = self.file.take() else { return };
+        let pool = self.pool.clone();
+        if let Err(err) = tokio::task::spawn_blocking(move || persist_pool(&pool, &file)).await {
+            warn!(%err, "Failed to write pool transactions")
+        }
+    }
+}
+
+impl<P> Drop for PersistPoolOnDrop<P>
+where
+    P: TransactionPool,
+    P::Transaction: IntoRecoveredTransaction,
+{
+    fn drop(&mut self) {
+        if let Some(file) = self.file.take() {
+            persist_pool(&self.pool, &file);
+        }
+    }
+}
+
+/// Writes the transactions of the pool to the file and logs the outcome.
+fn persist_pool<P>(pool: &P, file: &Path)
+where
+    P: TransactionPool,
+    P::Transaction: IntoRecoveredTransaction,
+{
+    match save_transactions(pool, file, false) {
+        Ok(count) => info!(count, file = %file.display(), "Wrote pool transactions"),
+        Err(err) => warn!(%err, file = %file.display(), "Failed to write pool transactions"),
+    }
+}
+
+/// Returns the modules of the `--http.api`, `--ws.api` or `--ipc.api` flags, `eth,net,web3` if
+/// none are given.
+fn rpc_module_config(modules: &[RethRpcModule]) -> RpcModuleConfig {
+    if modules.is_empty() {
+        RpcModuleConfig::Selection(vec![
+            RethRpcModule::Eth,
+            RethRpcModule::Net,
+            RethRpcModule::Web3,
+        ])
+    } else {
+        RpcModuleConfig::Selection(modules.to_vec())
+    }
+}
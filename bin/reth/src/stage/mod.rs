@@ -186,6 +186,7 @@ impl Command {
                 let mut stage = ExecutionStage {
                     config: ExecutorConfig::new_ethereum(),
                     commit_threshold: num_blocks,
+                    notifications: None,
                 };
                 if !self.skip_unwind {
                     stage.unwind(&mut tx, unwind).await?;
@@ -71,6 +71,8 @@ pub struct Header {
     pub uncle_hash: H256,
     /// Base fee per gas.
     pub base_fee_per_gas: Option<JsonU256>,
+    /// Withdrawals root.
+    pub withdrawals_root: Option<H256>,
 }
 
 impl From<Header> for SealedHeader {
@@ -93,6 +95,7 @@ impl From<Header> for SealedHeader {
                 state_root: value.state_root,
                 parent_hash: value.parent_hash,
                 logs_bloom: Bloom::default(), // TODO: ?
+                withdrawals_root: value.withdrawals_root,
             },
             value.hash,
         )
@@ -114,6 +117,23 @@ pub struct Block {
     pub uncle_headers: Option<Vec<Header>>,
     /// Transaction Sequence
     pub transaction_sequence: Option<Vec<TransactionSequence>>,
+    /// Withdrawals
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+/// Ethereum blockchain test data withdrawal.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    /// Withdrawal index.
+    pub index: JsonU256,
+    /// Validator index.
+    pub validator_index: JsonU256,
+    /// Withdrawal recipient.
+    pub address: Address,
+    /// Withdrawal amount in gwei.
+    pub amount: JsonU256,
 }
 
 /// Transaction Sequence in block
@@ -232,9 +252,7 @@ impl From<ForkSpec> for reth_executor::SpecUpgrades {
             ForkSpec::MergeEOF => Self::new_paris_activated(),
             ForkSpec::MergeMeterInitCode => Self::new_paris_activated(),
             ForkSpec::MergePush0 => Self::new_paris_activated(),
-            ForkSpec::Shanghai => {
-                panic!("Not supported")
-            }
+            ForkSpec::Shanghai => Self::new_shanghai_activated(),
             ForkSpec::ByzantiumToConstantinopleAt5 | ForkSpec::Constantinople => {
                 panic!("Overriden with PETERSBURG")
             }
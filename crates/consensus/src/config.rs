@@ -5,12 +5,10 @@ use reth_primitives::{BlockNumber, U256};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-/// Initial base fee as defined in: https://eips.ethereum.org/EIPS/eip-1559
-pub const EIP1559_INITIAL_BASE_FEE: u64 = 1_000_000_000;
-/// Base fee max change denominator as defined in: https://eips.ethereum.org/EIPS/eip-1559
-pub const EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
-/// Elasticity multiplier as defined in: https://eips.ethereum.org/EIPS/eip-1559
-pub const EIP1559_ELASTICITY_MULTIPLIER: u64 = 2;
+pub use reth_primitives::{
+    EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR, EIP1559_ELASTICITY_MULTIPLIER,
+    EIP1559_INITIAL_BASE_FEE,
+};
 
 /// Common configuration for consensus algorithms.
 #[derive(Debug, Clone)]
@@ -43,12 +41,23 @@ pub struct Config {
     pub petersburg_block: BlockNumber,
     /// Istanbul switch block.
     pub istanbul_block: BlockNumber,
+    /// Muir Glacier switch block, which delays the difficulty bomb.
+    #[cfg_attr(feature = "serde", serde(default = "unscheduled"))]
+    pub muir_glacier_block: BlockNumber,
     /// EIP-2728 switch block.
     pub berlin_block: BlockNumber,
     /// EIP-1559 switch block.
     pub london_block: BlockNumber,
+    /// Arrow Glacier switch block, which delays the difficulty bomb.
+    #[cfg_attr(feature = "serde", serde(default = "unscheduled"))]
+    pub arrow_glacier_block: BlockNumber,
+    /// Gray Glacier switch block, which delays the difficulty bomb.
+    #[cfg_attr(feature = "serde", serde(default = "unscheduled"))]
+    pub gray_glacier_block: BlockNumber,
     /// The Merge/Paris hard-fork block number.
     pub paris_block: BlockNumber,
+    /// The Shanghai hard-fork block number, which enables withdrawals.
+    pub shanghai_block: BlockNumber,
     /// Terminal total difficulty after the paris hard-fork to reach before The Merge is considered
     /// activated.
     #[cfg_attr(feature = "serde", serde(rename = "terminalTotalDifficulty"))]
@@ -69,14 +78,24 @@ impl Default for Config {
             constantinople_block: 7280000,
             petersburg_block: 7280000,
             istanbul_block: 9069000,
+            muir_glacier_block: 9200000,
             berlin_block: 12244000,
             london_block: 12965000,
+            arrow_glacier_block: 13773000,
+            gray_glacier_block: 15050000,
             paris_block: 15537394,
+            shanghai_block: 17034870,
             merge_terminal_total_difficulty: 58750000000000000000000,
         }
     }
 }
 
+/// The block number of forks that are not part of a chain specification.
+#[cfg(feature = "serde")]
+fn unscheduled() -> BlockNumber {
+    BlockNumber::MAX
+}
+
 impl From<&Config> for ExecutorConfig {
     fn from(value: &Config) -> Self {
         Self {
@@ -92,7 +111,7 @@ impl From<&Config> for ExecutorConfig {
                 berlin: value.berlin_block,
                 london: value.london_block,
                 paris: value.paris_block,
-                shanghai: u64::MAX, // TODO: change once known
+                shanghai: value.shanghai_block,
             },
         }
     }
@@ -2,7 +2,7 @@
 use crate::{verification, Config};
 use reth_interfaces::consensus::{Consensus, Error, ForkchoiceState};
 use reth_primitives::{BlockNumber, SealedBlock, SealedHeader, H256};
-use tokio::sync::{watch, watch::error::SendError};
+use tokio::sync::watch;
 
 /// Ethereum beacon consensus
 ///
@@ -27,14 +27,6 @@ impl BeaconConsensus {
             config,
         }
     }
-
-    /// Notifies all listeners of the latest [ForkchoiceState].
-    pub fn notify_fork_choice_state(
-        &self,
-        state: ForkchoiceState,
-    ) -> Result<(), SendError<ForkchoiceState>> {
-        self.channel.0.send(state)
-    }
 }
 
 impl Consensus for BeaconConsensus {
@@ -42,6 +34,11 @@ impl Consensus for BeaconConsensus {
         self.channel.1.clone()
     }
 
+    fn notify_fork_choice_state(&self, state: ForkchoiceState) {
+        // The channel can not be closed, it holds a receiver itself
+        let _ = self.channel.0.send(state);
+    }
+
     fn validate_header(&self, header: &SealedHeader, parent: &SealedHeader) -> Result<(), Error> {
         verification::validate_header_standalone(header, &self.config)?;
         verification::validate_header_regarding_parent(parent, header, &self.config)?;
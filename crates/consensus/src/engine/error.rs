@@ -38,6 +38,29 @@ pub enum EngineApiError {
     /// Received pre-merge payload.
     #[error("Received pre-merge payload.")]
     PayloadPreMerge,
+    /// Received withdrawals for a block before shanghai.
+    #[error("Withdrawals are not supported before shanghai: block {number}")]
+    HasWithdrawalsPreShanghai {
+        /// The number of the block.
+        number: u64,
+    },
+    /// Missing withdrawals for a block after shanghai.
+    #[error("Withdrawals are required after shanghai: block {number}")]
+    NoWithdrawalsPostShanghai {
+        /// The number of the block.
+        number: u64,
+    },
+    /// Received withdrawals in a V1 request.
+    #[error("Withdrawals are not supported in V1")]
+    WithdrawalsNotSupportedInV1,
+    /// Invalid payload attributes timestamp.
+    #[error("Invalid payload attributes timestamp: {invalid}. Latest: {latest}")]
+    PayloadAttributesTimestamp {
+        /// The payload attributes timestamp.
+        invalid: u64,
+        /// The timestamp of the head block.
+        latest: u64,
+    },
     /// Unknown payload requested.
     #[error("Unknown payload")]
     PayloadUnknown,
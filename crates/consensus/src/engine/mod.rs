@@ -1,28 +1,29 @@
-use futures::StreamExt;
-use reth_executor::{
-    executor,
-    revm_wrap::{State, SubState},
+use futures::{future, FutureExt, StreamExt};
+use reth_interfaces::{
+    consensus::{self, ForkchoiceState},
+    sync::{ChainSync, NoopChainSync},
 };
-use reth_interfaces::consensus::ForkchoiceState;
 use reth_primitives::{
     proofs::{self, EMPTY_LIST_HASH},
     rpc::{BlockId, H256 as EthersH256},
-    Header, SealedBlock, TransactionSigned, H64, U256,
+    BlockNumber, Header, IntoRecoveredTransaction, SealedBlock, TransactionSigned, Withdrawal, H64,
+    U256,
 };
-use reth_provider::{BlockProvider, HeaderProvider, StateProvider};
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
 use reth_rlp::Decodable;
 use reth_rpc_types::engine::{
     ExecutionPayload, ForkchoiceUpdated, PayloadAttributes, PayloadStatus, PayloadStatusEnum,
     TransitionConfiguration,
 };
+use reth_transaction_pool::TransactionPool;
 use std::{
     collections::HashMap,
     future::Future,
     pin::Pin,
     sync::Arc,
-    task::{ready, Context, Poll},
+    task::{Context, Poll},
 };
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc::UnboundedReceiver, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 use crate::Config;
@@ -30,20 +31,34 @@ use crate::Config;
 mod error;
 pub use error::{EngineApiError, EngineApiResult};
 
+mod payload;
+pub use payload::payload_id;
+use payload::PayloadJob;
+
 /// The Engine API response sender
 pub type EngineApiSender<Ok> = oneshot::Sender<EngineApiResult<Ok>>;
 
+/// The Engine API response that is resolved outside of the engine's task.
+pub type EngineApiFuture<Ok> = Pin<Box<dyn Future<Output = EngineApiResult<Ok>> + Send>>;
+
 /// Consensus engine API trait.
 pub trait ConsensusEngine {
-    /// Retrieves payload from local cache.
-    fn get_payload(&self, payload_id: H64) -> Option<ExecutionPayload>;
+    /// Retrieves the payload with the given id.
+    ///
+    /// If the payload is still being built, the job is stopped and the best payload built so far
+    /// is returned. The returned future resolves once the job has a payload with a known state
+    /// root.
+    fn get_payload(&mut self, payload_id: H64) -> EngineApiFuture<ExecutionPayload>;
 
     /// Receives a payload to validate and execute.
-    fn new_payload(&mut self, payload: ExecutionPayload) -> EngineApiResult<PayloadStatus>;
+    ///
+    /// The returned future resolves once the payload has been executed.
+    fn new_payload(&mut self, payload: ExecutionPayload) -> EngineApiFuture<PayloadStatus>;
 
-    /// Updates the fork choice state
+    /// Updates the fork choice state and starts building a payload on top of the head block if
+    /// payload attributes are given.
     fn fork_choice_updated(
-        &self,
+        &mut self,
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<PayloadAttributes>,
     ) -> EngineApiResult<ForkchoiceUpdated>;
@@ -76,28 +91,92 @@ pub enum EngineMessage {
 }
 
 /// The consensus engine API implementation
+///
+/// Forkchoice updates to unknown blocks start a sync of the chain, blocks on top of the local
+/// head are executed and committed directly, see [ChainSync].
 #[must_use = "EthConsensusEngine does nothing unless polled."]
-pub struct EthConsensusEngine<Client> {
+pub struct EthConsensusEngine<Client, Pool, S = NoopChainSync> {
     client: Arc<Client>,
+    /// The transaction pool the payloads are built from.
+    pool: Pool,
+    /// Syncs the chain to the heads of the forkchoice updates and commits new payloads.
+    sync: S,
     /// Consensus configuration
     config: Config,
     rx: UnboundedReceiverStream<EngineMessage>,
+    /// The jobs building the payloads that were not requested yet.
+    payload_jobs: HashMap<H64, PayloadJob>,
     // TODO: Placeholder for storing future blocks. Make cache bounded.
     // Use [lru](https://crates.io/crates/lru) crate
     local_store: HashMap<H64, ExecutionPayload>,
     // remote_store: HashMap<H64, ExecutionPayload>,
+    /// Sends the response to the message that is being resolved outside of the engine's task.
+    ///
+    /// Messages are handled in order, so no other message is handled until it has finished.
+    pending_response: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+}
+
+impl<Client, Pool> EthConsensusEngine<Client, Pool> {
+    /// Creates a new consensus engine that handles the messages of the given receiver and builds
+    /// payloads from the given pool.
+    pub fn new(
+        client: Arc<Client>,
+        pool: Pool,
+        config: Config,
+        rx: UnboundedReceiver<EngineMessage>,
+    ) -> Self {
+        Self {
+            client,
+            pool,
+            sync: NoopChainSync::default(),
+            config,
+            rx: UnboundedReceiverStream::new(rx),
+            payload_jobs: Default::default(),
+            local_store: Default::default(),
+            pending_response: None,
+        }
+    }
+}
+
+impl<Client, Pool, S> EthConsensusEngine<Client, Pool, S> {
+    /// Sets the [ChainSync] that is driven by the engine.
+    ///
+    /// Without it, the engine never syncs and answers `SYNCING` to every new payload that extends
+    /// the local head.
+    pub fn with_sync<T: ChainSync>(self, sync: T) -> EthConsensusEngine<Client, Pool, T> {
+        EthConsensusEngine {
+            client: self.client,
+            pool: self.pool,
+            sync,
+            config: self.config,
+            rx: self.rx,
+            payload_jobs: self.payload_jobs,
+            local_store: self.local_store,
+            pending_response: self.pending_response,
+        }
+    }
 }
 
-impl<Client: HeaderProvider + BlockProvider + StateProvider> EthConsensusEngine<Client> {
+impl<Client, Pool, S> EthConsensusEngine<Client, Pool, S>
+where
+    Client: HeaderProvider + BlockProvider + StateProviderFactory,
+    Pool: TransactionPool,
+    Pool::Transaction: IntoRecoveredTransaction,
+    S: ChainSync,
+{
     fn on_message(&mut self, msg: EngineMessage) {
         match msg {
             EngineMessage::GetPayload(payload_id, tx) => {
-                // NOTE: Will always result in `PayloadUnknown` since we don't support block
-                // building for now.
-                let _ = tx.send(self.get_payload(payload_id).ok_or(EngineApiError::PayloadUnknown));
+                let response = self.get_payload(payload_id).map(|result| {
+                    let _ = tx.send(result);
+                });
+                self.pending_response = Some(Box::pin(response));
             }
             EngineMessage::NewPayload(payload, tx) => {
-                let _ = tx.send(self.new_payload(payload));
+                let response = self.new_payload(payload).map(|result| {
+                    let _ = tx.send(result);
+                });
+                self.pending_response = Some(Box::pin(response));
             }
             EngineMessage::ForkchoiceUpdated(state, attrs, tx) => {
                 let _ = tx.send(self.fork_choice_updated(state, attrs));
@@ -108,6 +187,106 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EthConsensusEngine<
         }
     }
 
+    /// Rebuilds the payloads of all jobs that are due.
+    ///
+    /// A job that fails or that was requested and has a payload is stopped, its best payload so
+    /// far is kept until it is requested.
+    fn poll_payload_jobs(&mut self, cx: &mut Context<'_>) {
+        let finished = self
+            .payload_jobs
+            .iter_mut()
+            .filter_map(|(id, job)| {
+                let failed =
+                    job.poll(cx, &*self.client, &self.pool, &mut self.sync, &self.config).is_err();
+                (failed || (job.is_resolved() && job.best_payload().is_some())).then_some(*id)
+            })
+            .collect::<Vec<_>>();
+        for id in finished {
+            if let Some(payload) =
+                self.payload_jobs.remove(&id).and_then(PayloadJob::into_best_payload)
+            {
+                self.local_store.insert(id, payload);
+            }
+        }
+    }
+
+    /// Validates that withdrawals are given if and only if the block with the given number is
+    /// after shanghai.
+    fn validate_withdrawals_presence(
+        &self,
+        number: BlockNumber,
+        has_withdrawals: bool,
+    ) -> EngineApiResult<()> {
+        let is_shanghai = number >= self.config.shanghai_block;
+        match (is_shanghai, has_withdrawals) {
+            (true, false) => Err(EngineApiError::NoWithdrawalsPostShanghai { number }),
+            (false, true) => Err(EngineApiError::HasWithdrawalsPreShanghai { number }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Validates the payload and returns the block that needs to be executed, or the status of the
+    /// payload if it is not executed.
+    fn pre_validate_payload(
+        &self,
+        payload: ExecutionPayload,
+    ) -> EngineApiResult<Result<SealedBlock, PayloadStatus>> {
+        self.validate_withdrawals_presence(
+            payload.block_number.as_u64(),
+            payload.withdrawals.is_some(),
+        )?;
+
+        let block = match self.try_construct_block(payload) {
+            Ok(b) => b,
+            Err(err) => {
+                return Ok(Err(PayloadStatus::from_status(PayloadStatusEnum::InvalidBlockHash {
+                    validation_error: err.to_string(),
+                })))
+            }
+        };
+
+        // The block already exists in our database
+        if self.client.is_known(&block.hash())? {
+            return Ok(Err(PayloadStatus::new(PayloadStatusEnum::Valid, block.hash())))
+        }
+
+        // The parent state is not available until the sync has finished
+        if self.sync.is_syncing() {
+            return Ok(Err(PayloadStatus::from_status(PayloadStatusEnum::Syncing)))
+        }
+
+        let Some(parent) = self.client.block(BlockId::Hash(EthersH256(block.parent_hash.0)))? else {
+             // TODO: cache block for storing later
+             return Ok(Err(PayloadStatus::from_status(PayloadStatusEnum::Syncing)))
+        };
+
+        if let Some(parent_td) = self.client.header_td(&block.parent_hash)? {
+            if parent_td <= U256::from(self.config.merge_terminal_total_difficulty) {
+                return Ok(Err(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
+                    validation_error: EngineApiError::PayloadPreMerge.to_string(),
+                })))
+            }
+        }
+
+        if block.timestamp <= parent.timestamp {
+            return Ok(Err(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
+                validation_error: EngineApiError::PayloadTimestamp {
+                    invalid: block.timestamp,
+                    latest: parent.timestamp,
+                }
+                .to_string(),
+            })))
+        }
+
+        // Only blocks on top of the local head can be executed, blocks of side chains are kept
+        // until a forkchoice update makes them canonical.
+        if block.parent_hash != self.client.chain_info()?.best_hash {
+            return Ok(Err(PayloadStatus::from_status(PayloadStatusEnum::Accepted)))
+        }
+
+        Ok(Ok(block))
+    }
+
     /// Try to construct a block from given payload. Perform addition validation of `extra_data` and
     /// `base_fee_per_gas` fields.
     ///
@@ -130,6 +309,11 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EthConsensusEngine<
             .map(|tx| TransactionSigned::decode(&mut tx.as_ref()))
             .collect::<std::result::Result<Vec<_>, _>>()?;
         let transactions_root = proofs::calculate_transaction_root(transactions.iter());
+        let withdrawals: Option<Vec<Withdrawal>> = payload
+            .withdrawals
+            .map(|withdrawals| withdrawals.into_iter().map(Into::into).collect());
+        let withdrawals_root =
+            withdrawals.as_ref().map(|withdrawals| proofs::calculate_withdrawals_root(withdrawals));
         let header = Header {
             parent_hash: payload.parent_hash,
             beneficiary: payload.fee_recipient,
@@ -148,6 +332,7 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EthConsensusEngine<
             ommers_hash: EMPTY_LIST_HASH,
             difficulty: Default::default(),
             nonce: Default::default(),
+            withdrawals_root,
         };
         let header = header.seal();
 
@@ -158,84 +343,67 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> EthConsensusEngine<
             })
         }
 
-        Ok(SealedBlock { header, body: transactions, ommers: Default::default() })
+        Ok(SealedBlock { header, body: transactions, ommers: Default::default(), withdrawals })
     }
 }
 
-impl<Client: HeaderProvider + BlockProvider + StateProvider> ConsensusEngine
-    for EthConsensusEngine<Client>
+impl<Client, Pool, S> ConsensusEngine for EthConsensusEngine<Client, Pool, S>
+where
+    Client: HeaderProvider + BlockProvider + StateProviderFactory,
+    Pool: TransactionPool,
+    Pool::Transaction: IntoRecoveredTransaction,
+    S: ChainSync,
 {
-    fn get_payload(&self, payload_id: H64) -> Option<ExecutionPayload> {
-        self.local_store.get(&payload_id).cloned()
-    }
-
-    fn new_payload(&mut self, payload: ExecutionPayload) -> EngineApiResult<PayloadStatus> {
-        let block = match self.try_construct_block(payload) {
-            Ok(b) => b,
-            Err(err) => {
-                return Ok(PayloadStatus::from_status(PayloadStatusEnum::InvalidBlockHash {
-                    validation_error: err.to_string(),
-                }))
+    fn get_payload(&mut self, payload_id: H64) -> EngineApiFuture<ExecutionPayload> {
+        // Requesting the payload resolves the job, the payload is not improved anymore
+        if let Some(job) = self.payload_jobs.get_mut(&payload_id) {
+            if job.best_payload().is_none() {
+                // The state root of the first payload is not known yet, the payload is refused
+                // if it can not be computed
+                let payload = job.resolve();
+                return Box::pin(
+                    payload.map(|payload| payload.map_err(|_| EngineApiError::PayloadUnknown)),
+                )
+            }
+            if let Some(payload) =
+                self.payload_jobs.remove(&payload_id).and_then(PayloadJob::into_best_payload)
+            {
+                self.local_store.insert(payload_id, payload);
             }
-        };
-
-        // The block already exists in our database
-        if self.client.is_known(&block.hash())? {
-            return Ok(PayloadStatus::new(PayloadStatusEnum::Valid, block.hash()))
         }
+        let payload = self.local_store.get(&payload_id).cloned();
+        Box::pin(future::ready(payload.ok_or(EngineApiError::PayloadUnknown)))
+    }
 
-        let Some(parent) = self.client.block(BlockId::Hash(EthersH256(block.parent_hash.0)))? else {
-             // TODO: cache block for storing later
-             return Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing))
+    fn new_payload(&mut self, payload: ExecutionPayload) -> EngineApiFuture<PayloadStatus> {
+        let block = match self.pre_validate_payload(payload) {
+            Ok(Ok(block)) => block,
+            Ok(Err(status)) => return Box::pin(future::ok(status)),
+            Err(err) => return Box::pin(future::err(err)),
         };
 
-        if let Some(parent_td) = self.client.header_td(&block.parent_hash)? {
-            if parent_td <= U256::from(self.config.merge_terminal_total_difficulty) {
-                return Ok(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
-                    validation_error: EngineApiError::PayloadPreMerge.to_string(),
-                }))
+        let (hash, parent_hash) = (block.hash(), block.parent_hash);
+        let response = self.sync.commit_block(block).map(move |result| match result {
+            Ok(()) => Ok(PayloadStatus::new(PayloadStatusEnum::Valid, hash)),
+            // Some stages have not reached the parent yet
+            Err(reth_interfaces::Error::Consensus(consensus::Error::ParentUnknown { .. })) => {
+                Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing))
             }
-        }
-
-        if block.timestamp <= parent.timestamp {
-            return Ok(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
-                validation_error: EngineApiError::PayloadTimestamp {
-                    invalid: block.timestamp,
-                    latest: parent.timestamp,
-                }
-                .to_string(),
-            }))
-        }
-
-        let (header, body, _) = block.split();
-        let transactions = body
-            .into_iter()
-            .map(|tx| {
-                let tx_hash = tx.hash;
-                tx.into_ecrecovered().ok_or(EngineApiError::PayloadSignerRecovery { hash: tx_hash })
-            })
-            .collect::<Result<Vec<_>, EngineApiError>>()?;
-        let state_provider = SubState::new(State::new(&*self.client));
-        let config = (&self.config).into();
-        match executor::execute_and_verify_receipt(
-            &header,
-            &transactions,
-            &[],
-            &config,
-            state_provider,
-        ) {
-            Ok(_) => Ok(PayloadStatus::new(PayloadStatusEnum::Valid, header.hash())),
-            Err(err) => Ok(PayloadStatus::new(
+            Err(
+                err @ (reth_interfaces::Error::Consensus(_) | reth_interfaces::Error::Execution(_)),
+            ) => Ok(PayloadStatus::new(
                 PayloadStatusEnum::Invalid { validation_error: err.to_string() },
-                header.parent_hash, // The parent hash is already in our database hence it is valid
+                parent_hash, // The parent hash is already in our database hence it is valid
             )),
-        }
+            Err(err) => Err(err.into()),
+        });
+        Box::pin(response)
     }
 
     fn fork_choice_updated(
-        &self,
+        &mut self,
         fork_choice_state: ForkchoiceState,
-        _payload_attributes: Option<PayloadAttributes>,
+        payload_attributes: Option<PayloadAttributes>,
     ) -> EngineApiResult<ForkchoiceUpdated> {
         let ForkchoiceState { head_block_hash, finalized_block_hash, .. } = fork_choice_state;
 
@@ -245,21 +413,59 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> ConsensusEngine
             }))
         }
 
-        // Block is not known, nothing to do.
+        // Block is not known, sync to it.
         if !self.client.is_known(&head_block_hash)? {
+            self.sync.sync_to(fork_choice_state);
             return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing))
         }
 
         // The finalized block hash is not known, we are still syncing
         if !finalized_block_hash.is_zero() && !self.client.is_known(&finalized_block_hash)? {
+            self.sync.sync_to(fork_choice_state);
             return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing))
         }
 
-        // TODO: update tip
+        // The running sync continues to the new head
+        if self.sync.is_syncing() {
+            self.sync.sync_to(fork_choice_state);
+            return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing))
+        }
 
+        // The head is known, but it has not been executed yet or it is not on the local chain, e.g.
+        // an ancestor of the local head or a block of a side chain. The sync reorgs to it.
         let chain_info = self.client.chain_info()?;
-        Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
-            .with_latest_valid_hash(chain_info.best_hash))
+        if chain_info.best_hash != head_block_hash {
+            self.sync.sync_to(fork_choice_state);
+            return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing))
+        }
+
+        let mut response = ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
+            .with_latest_valid_hash(chain_info.best_hash);
+
+        // The payload attributes describe the block on top of the head
+        if let Some(attributes) = payload_attributes {
+            let Some(head) = self.client.header(&head_block_hash)? else {
+                return Ok(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing))
+            };
+            self.validate_withdrawals_presence(head.number + 1, attributes.withdrawals.is_some())?;
+            if attributes.timestamp.as_u64() <= head.timestamp {
+                return Err(EngineApiError::PayloadAttributesTimestamp {
+                    invalid: attributes.timestamp.as_u64(),
+                    latest: head.timestamp,
+                })
+            }
+
+            let payload_id = payload_id(head_block_hash, &attributes);
+            if !self.payload_jobs.contains_key(&payload_id) &&
+                !self.local_store.contains_key(&payload_id)
+            {
+                let job = PayloadJob::new(head.seal(), attributes);
+                self.payload_jobs.insert(payload_id, job);
+            }
+            response = response.with_payload_id(payload_id);
+        }
+
+        Ok(response)
     }
 
     fn exchange_transition_configuration(
@@ -307,23 +513,39 @@ impl<Client: HeaderProvider + BlockProvider + StateProvider> ConsensusEngine
     }
 }
 
-impl<Client> Future for EthConsensusEngine<Client>
+impl<Client, Pool, S> Future for EthConsensusEngine<Client, Pool, S>
 where
-    Client: HeaderProvider + BlockProvider + StateProvider + Unpin,
+    Client: HeaderProvider + BlockProvider + StateProviderFactory + Unpin,
+    Pool: TransactionPool + Unpin,
+    Pool::Transaction: IntoRecoveredTransaction,
+    S: ChainSync + Unpin,
 {
     type Output = ();
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
         loop {
-            match ready!(this.rx.poll_next_unpin(cx)) {
-                Some(msg) => this.on_message(msg),
-                None => {
+            if let Some(response) = &mut this.pending_response {
+                if response.poll_unpin(cx).is_pending() {
+                    break
+                }
+                this.pending_response = None;
+            }
+
+            match this.rx.poll_next_unpin(cx) {
+                Poll::Ready(Some(msg)) => this.on_message(msg),
+                Poll::Ready(None) => {
                     // channel closed
                     return Poll::Ready(())
                 }
+                Poll::Pending => break,
             }
         }
+
+        // The sync finishing does not end the engine
+        let _ = this.sync.poll_sync(cx);
+        this.poll_payload_jobs(cx);
+        Poll::Pending
     }
 }
 
@@ -331,16 +553,63 @@ where
 mod tests {
     use super::*;
     use assert_matches::assert_matches;
-    use reth_interfaces::test_utils::generators::random_block;
-    use reth_primitives::H256;
+    use reth_interfaces::{
+        sync::{SyncFuture, SyncStateProvider},
+        test_utils::generators::random_block,
+    };
+    use reth_primitives::{Block, H256};
     use reth_provider::test_utils::MockEthProvider;
+    use reth_transaction_pool::test_utils::testing_pool;
     use tokio::sync::mpsc::unbounded_channel;
 
+    /// A [ChainSync] that records the heads it is synced to and the blocks it commits.
+    #[derive(Debug, Default)]
+    struct TestSync {
+        syncing: bool,
+        heads: Vec<H256>,
+        committed: Vec<H256>,
+        /// Blocks that fail to execute.
+        invalid: Vec<H256>,
+        /// The state root of every block.
+        root: H256,
+    }
+
+    impl SyncStateProvider for TestSync {
+        fn is_syncing(&self) -> bool {
+            self.syncing
+        }
+    }
+
+    impl ChainSync for TestSync {
+        fn sync_to(&mut self, state: ForkchoiceState) {
+            self.heads.push(state.head_block_hash);
+        }
+
+        fn commit_block(&mut self, block: SealedBlock) -> SyncFuture<()> {
+            if self.invalid.contains(&block.hash()) {
+                let err = consensus::Error::BodyStateRootDiff {
+                    got: H256::random(),
+                    expected: block.state_root,
+                };
+                return Box::pin(future::err(err.into()))
+            }
+            self.committed.push(block.hash());
+            Box::pin(future::ok(()))
+        }
+
+        fn state_root(&mut self, _block: Block) -> SyncFuture<H256> {
+            Box::pin(future::ok(self.root))
+        }
+
+        fn poll_sync(&mut self, _cx: &mut Context<'_>) -> Poll<()> {
+            Poll::Pending
+        }
+    }
+
     mod new_payload {
         use super::*;
         use bytes::{Bytes, BytesMut};
         use reth_interfaces::test_utils::generators::random_header;
-        use reth_primitives::Block;
         use reth_rlp::DecodeError;
 
         fn transform_block<F: FnOnce(Block) -> Block>(src: SealedBlock, f: F) -> SealedBlock {
@@ -351,22 +620,25 @@ mod tests {
                 proofs::calculate_transaction_root(transformed.body.iter());
             transformed.header.ommers_hash =
                 proofs::calculate_ommers_root(transformed.ommers.iter());
+            transformed.header.withdrawals_root =
+                transformed.withdrawals.as_ref().map(|w| proofs::calculate_withdrawals_root(w));
             SealedBlock {
                 header: transformed.header.seal(),
                 body: transformed.body,
                 ommers: transformed.ommers.into_iter().map(Header::seal).collect(),
+                withdrawals: transformed.withdrawals,
             }
         }
 
         #[tokio::test]
         async fn payload_validation() {
             let (_tx, rx) = unbounded_channel();
-            let engine = EthConsensusEngine {
-                client: Arc::new(MockEthProvider::default()),
-                config: Config::default(),
-                local_store: Default::default(),
-                rx: UnboundedReceiverStream::new(rx),
-            };
+            let engine = EthConsensusEngine::new(
+                Arc::new(MockEthProvider::default()),
+                testing_pool(),
+                Config::default(),
+                rx,
+            );
 
             let block = random_block(100, Some(H256::random()), Some(3), Some(0));
 
@@ -450,12 +722,8 @@ mod tests {
         async fn payload_known() {
             let (tx, rx) = unbounded_channel();
             let client = Arc::new(MockEthProvider::default());
-            let engine = EthConsensusEngine {
-                client: client.clone(),
-                config: Config::default(),
-                local_store: Default::default(),
-                rx: UnboundedReceiverStream::new(rx),
-            };
+            let engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), Config::default(), rx);
 
             tokio::spawn(engine);
 
@@ -478,12 +746,12 @@ mod tests {
         #[tokio::test]
         async fn payload_parent_unknown() {
             let (tx, rx) = unbounded_channel();
-            let engine = EthConsensusEngine {
-                client: Arc::new(MockEthProvider::default()),
-                config: Config::default(),
-                local_store: Default::default(),
-                rx: UnboundedReceiverStream::new(rx),
-            };
+            let engine = EthConsensusEngine::new(
+                Arc::new(MockEthProvider::default()),
+                testing_pool(),
+                Config::default(),
+                rx,
+            );
 
             tokio::spawn(engine);
 
@@ -503,12 +771,8 @@ mod tests {
             let (tx, rx) = unbounded_channel();
             let config = Config::default();
             let client = Arc::new(MockEthProvider::default());
-            let engine = EthConsensusEngine {
-                client: client.clone(),
-                config: config.clone(),
-                local_store: Default::default(),
-                rx: UnboundedReceiverStream::new(rx),
-            };
+            let engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), config.clone(), rx);
 
             tokio::spawn(engine);
 
@@ -537,12 +801,8 @@ mod tests {
             let (tx, rx) = unbounded_channel();
             let config = Config::default();
             let client = Arc::new(MockEthProvider::default());
-            let engine = EthConsensusEngine {
-                client: client.clone(),
-                config: config.clone(),
-                local_store: Default::default(),
-                rx: UnboundedReceiverStream::new(rx),
-            };
+            let engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), config.clone(), rx);
 
             tokio::spawn(engine);
 
@@ -577,23 +837,215 @@ mod tests {
             assert_eq!(result.unwrap().unwrap(), expected_result);
         }
 
-        // TODO: add execution tests
+        #[tokio::test]
+        async fn payload_withdrawals() {
+            let (_tx, rx) = unbounded_channel();
+            let mut engine = EthConsensusEngine::new(
+                Arc::new(MockEthProvider::default()),
+                testing_pool(),
+                Config::default(),
+                rx,
+            );
+
+            let block = random_block(100, Some(H256::random()), None, Some(0));
+            let block_with_withdrawals = transform_block(block.clone(), |mut b| {
+                b.withdrawals = Some(vec![Withdrawal { amount: 1, ..Default::default() }]);
+                b
+            });
+
+            // Withdrawals before shanghai
+            assert_matches!(
+                engine.new_payload(block_with_withdrawals.clone().into()).await,
+                Err(EngineApiError::HasWithdrawalsPreShanghai { number: 100 })
+            );
+
+            // No withdrawals after shanghai
+            engine.config.shanghai_block = 100;
+            assert_matches!(
+                engine.new_payload(block.into()).await,
+                Err(EngineApiError::NoWithdrawalsPostShanghai { number: 100 })
+            );
+
+            // The withdrawals root is computed from the payload withdrawals
+            let constructed =
+                engine.try_construct_block(block_with_withdrawals.clone().into()).unwrap();
+            assert_eq!(constructed.hash(), block_with_withdrawals.hash());
+            assert_eq!(constructed.withdrawals, block_with_withdrawals.withdrawals);
+        }
+
+        #[tokio::test]
+        async fn payload_on_head_committed() {
+            let (_tx, rx) = unbounded_channel();
+            let config = Config::default();
+            let client = Arc::new(MockEthProvider::default());
+            let mut engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), config.clone(), rx)
+                    .with_sync(TestSync::default());
+
+            let block_at = |number, parent, timestamp| {
+                transform_block(random_block(number, Some(parent), None, Some(0)), |mut b| {
+                    b.header.timestamp = timestamp;
+                    b
+                })
+            };
+            let grandparent = transform_block(random_block(99, None, None, Some(0)), |mut b| {
+                b.header.difficulty = U256::from(config.merge_terminal_total_difficulty + 1);
+                b
+            });
+            let parent = block_at(100, grandparent.hash(), 1);
+            let block = block_at(101, parent.hash(), 2);
+            let side_block = block_at(100, grandparent.hash(), 2);
+            client.add_block(grandparent.hash(), grandparent.clone().unseal());
+            client.add_block(parent.hash(), parent.clone().unseal());
+
+            // Blocks that do not extend the local head are not executed
+            assert_eq!(
+                engine.new_payload(side_block.into()).await.unwrap(),
+                PayloadStatus::from_status(PayloadStatusEnum::Accepted)
+            );
+            assert!(engine.sync.committed.is_empty());
+
+            // Blocks that fail to execute are invalid, their parent is the latest valid block
+            let invalid = block_at(101, parent.hash(), 3);
+            engine.sync.invalid.push(invalid.hash());
+            assert_matches!(
+                engine.new_payload(invalid.into()).await.unwrap(),
+                PayloadStatus { status: PayloadStatusEnum::Invalid { .. }, latest_valid_hash }
+                    if latest_valid_hash == Some(parent.hash())
+            );
+            assert!(engine.sync.committed.is_empty());
+
+            assert_eq!(
+                engine.new_payload(block.clone().into()).await.unwrap(),
+                PayloadStatus::new(PayloadStatusEnum::Valid, block.hash())
+            );
+            assert_eq!(engine.sync.committed, vec![block.hash()]);
+
+            // Blocks are not committed while syncing
+            engine.sync.syncing = true;
+            let next = block_at(102, block.hash(), 3);
+            assert_eq!(
+                engine.new_payload(next.into()).await.unwrap(),
+                PayloadStatus::from_status(PayloadStatusEnum::Syncing)
+            );
+            assert_eq!(engine.sync.committed.len(), 1);
+        }
+
+        #[tokio::test]
+        async fn payload_on_head_without_sync() {
+            let (_tx, rx) = unbounded_channel();
+            let config = Config::default();
+            let client = Arc::new(MockEthProvider::default());
+            let mut engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), config.clone(), rx);
+
+            let parent = transform_block(random_block(100, None, None, Some(0)), |mut b| {
+                b.header.difficulty = U256::from(config.merge_terminal_total_difficulty + 1);
+                b.header.timestamp = 1;
+                b
+            });
+            let block =
+                transform_block(random_block(101, Some(parent.hash()), None, Some(0)), |mut b| {
+                    b.header.timestamp = 2;
+                    b
+                });
+            client.add_block(parent.hash(), parent.clone().unseal());
+
+            // Without a sync the block can not be executed
+            assert_eq!(
+                engine.new_payload(block.into()).await.unwrap(),
+                PayloadStatus::from_status(PayloadStatusEnum::Syncing)
+            );
+        }
+    }
+
+    // non exhaustive tests for engine_forkchoiceUpdated
+    mod fork_choice_updated {
+        use super::*;
+        use reth_interfaces::test_utils::generators::random_header;
+
+        #[tokio::test]
+        async fn head_unknown_starts_sync() {
+            let (_tx, rx) = unbounded_channel();
+            let mut engine = EthConsensusEngine::new(
+                Arc::new(MockEthProvider::default()),
+                testing_pool(),
+                Config::default(),
+                rx,
+            )
+            .with_sync(TestSync::default());
+
+            let state = ForkchoiceState { head_block_hash: H256::random(), ..Default::default() };
+            let response = engine.fork_choice_updated(state.clone(), None).unwrap();
+            assert_eq!(response, ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing));
+            assert_eq!(engine.sync.heads, vec![state.head_block_hash]);
+        }
+
+        #[tokio::test]
+        async fn head_known_while_syncing() {
+            let (_tx, rx) = unbounded_channel();
+            let client = Arc::new(MockEthProvider::default());
+            let mut engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), Config::default(), rx)
+                    .with_sync(TestSync { syncing: true, ..Default::default() });
+
+            let head = random_header(100, None);
+            client.add_header(head.hash(), head.clone().unseal());
+            let state = ForkchoiceState { head_block_hash: head.hash(), ..Default::default() };
+
+            // The running sync continues to the new head
+            let response = engine.fork_choice_updated(state.clone(), None).unwrap();
+            assert_eq!(response, ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing));
+            assert_eq!(engine.sync.heads, vec![head.hash()]);
+
+            // The head is valid once the sync has finished
+            engine.sync.syncing = false;
+            let response = engine.fork_choice_updated(state, None).unwrap();
+            assert_eq!(
+                response,
+                ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
+                    .with_latest_valid_hash(head.hash())
+            );
+            // A valid head does not start another sync
+            assert_eq!(engine.sync.heads, vec![head.hash()]);
+        }
+
+        #[tokio::test]
+        async fn head_known_not_best_starts_reorg() {
+            let (_tx, rx) = unbounded_channel();
+            let client = Arc::new(MockEthProvider::default());
+            let mut engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), Config::default(), rx)
+                    .with_sync(TestSync::default());
+
+            let ancestor = random_header(100, None);
+            let best = random_header(101, Some(ancestor.hash()));
+            client.add_header(ancestor.hash(), ancestor.clone().unseal());
+            client.add_header(best.hash(), best.clone().unseal());
+
+            // The head is an ancestor of the local head
+            let state = ForkchoiceState { head_block_hash: ancestor.hash(), ..Default::default() };
+            let response = engine.fork_choice_updated(state, None).unwrap();
+            assert_eq!(response, ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing));
+            assert_eq!(engine.sync.heads, vec![ancestor.hash()]);
+        }
     }
 
     // non exhaustive tests for engine_getPayload
-    // TODO: amend when block building is implemented
     mod get_payload {
         use super::*;
+        use reth_interfaces::test_utils::generators::random_header;
+        use reth_primitives::Address;
 
         #[tokio::test]
         async fn payload_unknown() {
             let (tx, rx) = unbounded_channel();
-            let engine = EthConsensusEngine {
-                client: Arc::new(MockEthProvider::default()),
-                config: Config::default(),
-                local_store: Default::default(),
-                rx: UnboundedReceiverStream::new(rx),
-            };
+            let engine = EthConsensusEngine::new(
+                Arc::new(MockEthProvider::default()),
+                testing_pool(),
+                Config::default(),
+                rx,
+            );
 
             tokio::spawn(engine);
 
@@ -605,6 +1057,94 @@ mod tests {
 
             assert_matches!(result_rx.await, Ok(Err(EngineApiError::PayloadUnknown)));
         }
+
+        /// Resolves the payload with the given id while polling the payload jobs of the engine.
+        async fn resolve_payload<Pool, S>(
+            engine: &mut EthConsensusEngine<MockEthProvider, Pool, S>,
+            id: H64,
+        ) -> EngineApiResult<ExecutionPayload>
+        where
+            Pool: TransactionPool,
+            Pool::Transaction: IntoRecoveredTransaction,
+            S: ChainSync,
+        {
+            let mut payload = engine.get_payload(id);
+            future::poll_fn(|cx| {
+                engine.poll_payload_jobs(cx);
+                payload.poll_unpin(cx)
+            })
+            .await
+        }
+
+        #[tokio::test]
+        async fn payload_built_on_head() {
+            let (_tx, rx) = unbounded_channel();
+            let client = Arc::new(MockEthProvider::default());
+            let root = H256::random();
+            let mut engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), Config::default(), rx)
+                    .with_sync(TestSync { root, ..Default::default() });
+
+            let head = random_header(100, None);
+            client.add_header(head.hash(), head.clone().unseal());
+
+            let state = ForkchoiceState { head_block_hash: head.hash(), ..Default::default() };
+            let attributes = PayloadAttributes {
+                timestamp: (head.timestamp + 12).into(),
+                prev_randao: H256::random(),
+                suggested_fee_recipient: Address::random(),
+                withdrawals: None,
+            };
+
+            // The timestamp must be greater than the one of the head
+            let stale = PayloadAttributes { timestamp: head.timestamp.into(), ..attributes };
+            assert_matches!(
+                engine.fork_choice_updated(state.clone(), Some(stale)),
+                Err(EngineApiError::PayloadAttributesTimestamp { .. })
+            );
+
+            let response = engine.fork_choice_updated(state, Some(attributes.clone())).unwrap();
+            let id = response.payload_id.expect("payload id");
+            assert_eq!(id, payload_id(head.hash(), &attributes));
+
+            let payload = resolve_payload(&mut engine, id).await.unwrap();
+            assert_eq!(payload.parent_hash, head.hash());
+            assert_eq!(payload.block_number.as_u64(), head.number + 1);
+            assert_eq!(payload.timestamp, attributes.timestamp);
+            assert_eq!(payload.fee_recipient, attributes.suggested_fee_recipient);
+            assert_eq!(payload.state_root, root);
+            assert!(payload.transactions.is_empty());
+
+            // The payload can be requested once it is resolved
+            assert_eq!(engine.get_payload(id).await.unwrap(), payload);
+        }
+
+        #[tokio::test]
+        async fn payload_refused_without_state_root() {
+            let (_tx, rx) = unbounded_channel();
+            let client = Arc::new(MockEthProvider::default());
+            let mut engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), Config::default(), rx);
+
+            let head = random_header(100, None);
+            client.add_header(head.hash(), head.clone().unseal());
+
+            let state = ForkchoiceState { head_block_hash: head.hash(), ..Default::default() };
+            let attributes = PayloadAttributes {
+                timestamp: (head.timestamp + 12).into(),
+                prev_randao: H256::random(),
+                suggested_fee_recipient: Address::random(),
+                withdrawals: None,
+            };
+            let response = engine.fork_choice_updated(state, Some(attributes)).unwrap();
+            let id = response.payload_id.expect("payload id");
+
+            // Without a sync the state root of the payload can not be computed
+            assert_matches!(
+                resolve_payload(&mut engine, id).await,
+                Err(EngineApiError::PayloadUnknown)
+            );
+        }
     }
 
     // https://github.com/ethereum/execution-apis/blob/main/src/engine/paris.md#specification-3
@@ -615,12 +1155,12 @@ mod tests {
         async fn terminal_td_mismatch() {
             let (tx, rx) = unbounded_channel();
             let config = Config::default();
-            let engine = EthConsensusEngine {
-                client: Arc::new(MockEthProvider::default()),
-                config: config.clone(),
-                local_store: Default::default(),
-                rx: UnboundedReceiverStream::new(rx),
-            };
+            let engine = EthConsensusEngine::new(
+                Arc::new(MockEthProvider::default()),
+                testing_pool(),
+                config.clone(),
+                rx,
+            );
 
             tokio::spawn(engine);
 
@@ -649,12 +1189,8 @@ mod tests {
             let (tx, rx) = unbounded_channel();
             let client = Arc::new(MockEthProvider::default());
             let config = Config::default();
-            let engine = EthConsensusEngine {
-                client: client.clone(),
-                config: config.clone(),
-                local_store: Default::default(),
-                rx: UnboundedReceiverStream::new(rx),
-            };
+            let engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), config.clone(), rx);
 
             tokio::spawn(engine);
 
@@ -709,12 +1245,8 @@ mod tests {
             let (tx, rx) = unbounded_channel();
             let client = Arc::new(MockEthProvider::default());
             let config = Config::default();
-            let engine = EthConsensusEngine {
-                client: client.clone(),
-                config: config.clone(),
-                local_store: Default::default(),
-                rx: UnboundedReceiverStream::new(rx),
-            };
+            let engine =
+                EthConsensusEngine::new(client.clone(), testing_pool(), config.clone(), rx);
 
             tokio::spawn(engine);
 
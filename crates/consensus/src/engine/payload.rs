@@ -0,0 +1,324 @@
+//! Local payload building for `engine_forkchoiceUpdated` calls with payload attributes.
+
+use super::EngineApiResult;
+use crate::Config;
+use futures::FutureExt;
+use reth_executor::{
+    executor,
+    revm_wrap::{State, SubState},
+};
+use reth_interfaces::{
+    executor::Error as ExecutorError,
+    sync::{ChainSync, SyncFuture},
+};
+use reth_primitives::{
+    basefee::calculate_next_block_base_fee,
+    keccak256,
+    proofs::{self, EMPTY_LIST_HASH},
+    Block, Bloom, Header, IntoRecoveredTransaction, Receipt, SealedBlock, SealedHeader,
+    TransactionSigned, Withdrawal, H256, H64,
+};
+use reth_provider::StateProviderFactory;
+use reth_rlp::Encodable;
+use reth_rpc_types::engine::{ExecutionPayload, PayloadAttributes};
+use reth_transaction_pool::{BestTransactions, TransactionPool};
+use std::{
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    sync::oneshot,
+    time::{Interval, MissedTickBehavior},
+};
+
+/// The interval in which the payload of a job is rebuilt with the best transactions of the pool.
+pub(crate) const PAYLOAD_REBUILD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Derives the id of the payload that is built on top of the parent block with the given
+/// attributes.
+///
+/// The id is the first 8 bytes of the keccak256 hash of the parent hash and the attributes, so
+/// the same attributes on top of the same parent always result in the same id.
+pub fn payload_id(parent: H256, attributes: &PayloadAttributes) -> H64 {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(parent.as_bytes());
+    buf.extend_from_slice(&attributes.timestamp.as_u64().to_be_bytes());
+    buf.extend_from_slice(attributes.prev_randao.as_bytes());
+    buf.extend_from_slice(attributes.suggested_fee_recipient.as_bytes());
+    if let Some(withdrawals) = payload_withdrawals(attributes) {
+        withdrawals.encode(&mut buf);
+    }
+    H64::from_slice(&keccak256(buf)[..8])
+}
+
+/// A job that builds a payload on top of a parent block and keeps improving it with the best
+/// transactions of the pool until it is resolved.
+///
+/// A built block only becomes the best payload once the [ChainSync] has computed its state root,
+/// so there is no payload until the state root of the first block is known.
+pub(crate) struct PayloadJob {
+    /// The block the payload is built on top of.
+    parent: SealedHeader,
+    /// The attributes of the payload.
+    attributes: PayloadAttributes,
+    /// Rebuilds the payload on every tick.
+    interval: Interval,
+    /// The best payload built so far.
+    best_payload: Option<ExecutionPayload>,
+    /// The gas used by the best payload.
+    best_gas_used: u64,
+    /// A block that is better than the best payload, waiting for its state root.
+    pending: Option<PendingBlock>,
+    /// Requests for the payload that wait for the first payload of the job, `Some` once the
+    /// payload was requested.
+    waiting: Option<Vec<oneshot::Sender<ExecutionPayload>>>,
+}
+
+/// A built block whose state root is being computed.
+struct PendingBlock {
+    /// The block without its state root.
+    block: Block,
+    /// The gas used by the transactions of the block.
+    gas_used: u64,
+    /// Resolves to the state root after the block.
+    state_root: SyncFuture<H256>,
+}
+
+impl PayloadJob {
+    /// Creates a new job that builds payloads on top of the parent block.
+    ///
+    /// The first block is built when the job is polled.
+    pub(crate) fn new(parent: SealedHeader, attributes: PayloadAttributes) -> Self {
+        let mut interval = tokio::time::interval(PAYLOAD_REBUILD_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            parent,
+            attributes,
+            interval,
+            best_payload: None,
+            best_gas_used: 0,
+            pending: None,
+            waiting: None,
+        }
+    }
+
+    /// Returns the best payload built so far.
+    pub(crate) fn best_payload(&self) -> Option<&ExecutionPayload> {
+        self.best_payload.as_ref()
+    }
+
+    /// Consumes the job and returns the best payload built so far.
+    pub(crate) fn into_best_payload(self) -> Option<ExecutionPayload> {
+        self.best_payload
+    }
+
+    /// Returns `true` if the payload was requested.
+    pub(crate) fn is_resolved(&self) -> bool {
+        self.waiting.is_some()
+    }
+
+    /// Marks the job as resolved and returns a receiver for its first payload.
+    ///
+    /// A resolved job is stopped once it has a payload. The sender is dropped if the job fails
+    /// before.
+    pub(crate) fn resolve(&mut self) -> oneshot::Receiver<ExecutionPayload> {
+        let (tx, rx) = oneshot::channel();
+        self.waiting.get_or_insert_with(Vec::new).push(tx);
+        rx
+    }
+
+    /// Rebuilds the payload on every tick of the job's interval.
+    ///
+    /// The payload is only replaced if the new one uses more gas, i.e. pays more fees to the fee
+    /// recipient.
+    pub(crate) fn poll<Client, Pool, S>(
+        &mut self,
+        cx: &mut Context<'_>,
+        client: &Client,
+        pool: &Pool,
+        sync: &mut S,
+        config: &Config,
+    ) -> EngineApiResult<()>
+    where
+        Client: StateProviderFactory,
+        Pool: TransactionPool,
+        Pool::Transaction: IntoRecoveredTransaction,
+        S: ChainSync,
+    {
+        loop {
+            if let Some(pending) = &mut self.pending {
+                let Poll::Ready(state_root) = pending.state_root.poll_unpin(cx) else {
+                    return Ok(())
+                };
+                let PendingBlock { block, gas_used, .. } =
+                    self.pending.take().expect("pending block exists");
+                let payload = ExecutionPayload::from(seal_block(block, state_root?));
+                for tx in self.waiting.iter_mut().flat_map(|waiting| waiting.drain(..)) {
+                    let _ = tx.send(payload.clone());
+                }
+                self.best_payload = Some(payload);
+                self.best_gas_used = gas_used;
+            }
+
+            if self.interval.poll_tick(cx).is_pending() {
+                return Ok(())
+            }
+
+            let (block, gas_used) =
+                build_payload(client, pool, &self.parent, &self.attributes, config)?;
+            if self.best_payload.is_none() || gas_used > self.best_gas_used {
+                let state_root = sync.state_root(block.clone());
+                self.pending = Some(PendingBlock { block, gas_used, state_root });
+            }
+        }
+    }
+}
+
+/// Builds a block on top of the parent block with the best transactions of the pool.
+///
+/// Transactions are executed on top of the latest state in the order of the pool until the gas
+/// limit of the block is reached. Transactions that do not fit into the remaining gas of the block
+/// are skipped, transactions that are rejected by the executor are skipped together with all
+/// transactions that depend on them.
+///
+/// Returns the block without its state root together with the gas used by its transactions.
+pub(crate) fn build_payload<Client, Pool>(
+    client: &Client,
+    pool: &Pool,
+    parent: &SealedHeader,
+    attributes: &PayloadAttributes,
+    config: &Config,
+) -> EngineApiResult<(Block, u64)>
+where
+    Client: StateProviderFactory,
+    Pool: TransactionPool,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    let header = payload_header(parent, attributes, config);
+    let executor_config = config.into();
+    let state = SubState::new(State::new(client.latest()?));
+    let mut evm = executor::block_evm(&header, &executor_config, state);
+
+    let mut cumulative_gas_used = 0;
+    let mut transactions = Vec::new();
+    let mut receipts = Vec::new();
+    let mut best_transactions = pool.best_transactions();
+    while let Some(pool_transaction) = best_transactions.next() {
+        // Skip the transaction if it does not fit into the block anymore, a later one might
+        if cumulative_gas_used + pool_transaction.transaction.gas_limit() > header.gas_limit {
+            continue
+        }
+
+        let transaction = pool_transaction.transaction.to_recovered_transaction();
+        let changeset =
+            match executor::execute_transaction(&mut evm, &transaction, cumulative_gas_used) {
+                Ok(changeset) => changeset,
+                // Neither the transaction nor the ones that depend on it can be included
+                Err(ExecutorError::TransactionRejected { .. }) => {
+                    best_transactions.mark_invalid(&pool_transaction);
+                    continue
+                }
+                Err(err) => return Err(reth_interfaces::Error::from(err).into()),
+            };
+
+        cumulative_gas_used = changeset.receipt.cumulative_gas_used;
+        transactions.push(transaction.into_signed());
+        receipts.push(changeset.receipt);
+    }
+
+    let block = build_block(parent, attributes, config, transactions, receipts);
+    Ok((block, cumulative_gas_used))
+}
+
+/// Returns the withdrawals of the payload attributes.
+fn payload_withdrawals(attributes: &PayloadAttributes) -> Option<Vec<Withdrawal>> {
+    attributes
+        .withdrawals
+        .as_ref()
+        .map(|withdrawals| withdrawals.iter().cloned().map(Into::into).collect())
+}
+
+/// Returns the header of the payload without any transactions and without its state root.
+fn payload_header(
+    parent: &SealedHeader,
+    attributes: &PayloadAttributes,
+    config: &Config,
+) -> Header {
+    let withdrawals_root = payload_withdrawals(attributes)
+        .map(|withdrawals| proofs::calculate_withdrawals_root(&withdrawals));
+    let base_fee_per_gas = (parent.number + 1 >= config.london_block).then(|| {
+        calculate_next_block_base_fee(
+            parent.gas_used,
+            parent.gas_limit,
+            parent.base_fee_per_gas.unwrap_or_default(),
+        )
+    });
+
+    Header {
+        parent_hash: parent.hash(),
+        ommers_hash: EMPTY_LIST_HASH,
+        beneficiary: attributes.suggested_fee_recipient,
+        number: parent.number + 1,
+        gas_limit: parent.gas_limit,
+        timestamp: attributes.timestamp.as_u64(),
+        mix_hash: attributes.prev_randao,
+        base_fee_per_gas,
+        withdrawals_root,
+        ..Default::default()
+    }
+}
+
+/// Assembles the block of the payload from the executed transactions and their receipts.
+///
+/// The state root is set once it is computed, see [seal_block].
+fn build_block(
+    parent: &SealedHeader,
+    attributes: &PayloadAttributes,
+    config: &Config,
+    transactions: Vec<TransactionSigned>,
+    receipts: Vec<Receipt>,
+) -> Block {
+    let mut header = payload_header(parent, attributes, config);
+    header.transactions_root = proofs::calculate_transaction_root(transactions.iter());
+    header.receipts_root = proofs::calculate_receipt_root(receipts.iter());
+    header.logs_bloom = receipts.iter().fold(Bloom::zero(), |bloom, receipt| bloom | receipt.bloom);
+    header.gas_used =
+        receipts.last().map(|receipt| receipt.cumulative_gas_used).unwrap_or_default();
+
+    Block {
+        header,
+        body: transactions,
+        ommers: Vec::new(),
+        withdrawals: payload_withdrawals(attributes),
+    }
+}
+
+/// Seals the block of the payload with the state root after its execution.
+fn seal_block(block: Block, state_root: H256) -> SealedBlock {
+    let Block { mut header, body, withdrawals, .. } = block;
+    header.state_root = state_root;
+    SealedBlock { header: header.seal(), body, ommers: Vec::new(), withdrawals }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Address, U64};
+
+    #[test]
+    fn payload_id_depends_on_parent_and_attributes() {
+        let attributes = PayloadAttributes {
+            timestamp: U64::from(1),
+            prev_randao: H256::random(),
+            suggested_fee_recipient: Address::random(),
+            withdrawals: None,
+        };
+        let parent = H256::random();
+        let id = payload_id(parent, &attributes);
+        assert_eq!(id, payload_id(parent, &attributes));
+        assert_ne!(id, payload_id(H256::random(), &attributes));
+
+        let with_withdrawals = PayloadAttributes { withdrawals: Some(vec![]), ..attributes };
+        assert_ne!(id, payload_id(parent, &with_withdrawals));
+    }
+}
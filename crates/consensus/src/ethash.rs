@@ -0,0 +1,444 @@
+//! Proof-of-work validation of pre-merge headers.
+//!
+//! The seal is verified with the light client algorithm of
+//! [ethash](https://github.com/ethereum/wiki/wiki/Ethash), which only needs the cache of the
+//! epoch of the block instead of the full dataset.
+
+use crate::Config;
+use reth_interfaces::consensus::Error;
+use reth_primitives::{
+    keccak256, proofs::EMPTY_LIST_HASH, BlockNumber, Header, SealedHeader, H256, U256,
+};
+use reth_rlp::Encodable;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+use tiny_keccak::{Hasher, Keccak};
+
+/// The number of blocks that share the same cache.
+const EPOCH_LENGTH: u64 = 30_000;
+/// The size of a cache item.
+const HASH_BYTES: u64 = 64;
+/// The size of the mix that is hashed with the dataset.
+const MIX_BYTES: u64 = 128;
+/// The size of the cache of the first epoch.
+const CACHE_BYTES_INIT: u64 = 1 << 24;
+/// The growth of the cache per epoch.
+const CACHE_BYTES_GROWTH: u64 = 1 << 17;
+/// The size of the dataset of the first epoch.
+const DATASET_BYTES_INIT: u64 = 1 << 30;
+/// The growth of the dataset per epoch.
+const DATASET_BYTES_GROWTH: u64 = 1 << 23;
+/// The number of rounds used to generate the cache.
+const CACHE_ROUNDS: usize = 3;
+/// The number of cache items that make up a dataset item.
+const DATASET_PARENTS: u32 = 256;
+/// The number of dataset accesses per hash.
+const ACCESSES: u32 = 64;
+/// The prime used by the FNV hash.
+const FNV_PRIME: u32 = 0x01000193;
+
+/// The lowest difficulty a proof-of-work block can have.
+const MINIMUM_DIFFICULTY: u64 = 131_072;
+/// The number of blocks after which the difficulty bomb doubles.
+const DIFFICULTY_BOMB_PERIOD: u64 = 100_000;
+
+/// The number of epoch caches that are kept, the one of the current and the previous epoch.
+const MAX_CACHES: usize = 2;
+
+/// A cache item, as little endian words.
+type Node = [u32; 16];
+
+/// Verifies the ethash seal of headers.
+///
+/// The caches of the most recent epochs are kept in memory, generating the cache of a new epoch
+/// takes a few seconds.
+#[derive(Debug, Clone, Default)]
+pub struct Ethash {
+    /// The caches of the most recently used epochs.
+    caches: Arc<Mutex<VecDeque<Arc<EpochCache>>>>,
+}
+
+impl Ethash {
+    /// Verifies that the mix hash and the nonce of the header are a valid proof-of-work for its
+    /// difficulty.
+    ///
+    /// **This is blocking**, the cache of the epoch of the header is generated if it is not known
+    /// yet.
+    pub fn verify_seal(&self, header: &SealedHeader) -> Result<(), Error> {
+        let cache = self.cache(header.number / EPOCH_LENGTH);
+        let (mix_hash, result) = cache.hashimoto(seal_hash(header), header.nonce);
+        if mix_hash != header.mix_hash {
+            return Err(Error::MixHashDiff { got: mix_hash, expected: header.mix_hash })
+        }
+
+        let target = if header.difficulty <= U256::from(1) {
+            U256::MAX
+        } else {
+            U256::MAX / header.difficulty
+        };
+        if U256::from_be_bytes(result.0) > target {
+            return Err(Error::InvalidProofOfWork)
+        }
+        Ok(())
+    }
+
+    /// Returns the cache of the epoch, generating it if necessary.
+    fn cache(&self, epoch: u64) -> Arc<EpochCache> {
+        // Hold the lock while generating, so a cache is only generated once
+        let mut caches = self.caches.lock().expect("not poisoned");
+        if let Some(cache) = caches.iter().find(|cache| cache.epoch == epoch) {
+            return cache.clone()
+        }
+
+        let cache = Arc::new(EpochCache::new(epoch));
+        if caches.len() == MAX_CACHES {
+            caches.pop_front();
+        }
+        caches.push_back(cache.clone());
+        cache
+    }
+}
+
+/// The ethash cache of an epoch.
+#[derive(Debug)]
+struct EpochCache {
+    /// The epoch of the cache.
+    epoch: u64,
+    /// The size of the full dataset of the epoch in bytes.
+    dataset_size: u64,
+    /// The cache items.
+    nodes: Vec<Node>,
+}
+
+impl EpochCache {
+    /// Generates the cache of the epoch.
+    fn new(epoch: u64) -> Self {
+        let mut seed = H256::zero();
+        for _ in 0..epoch {
+            seed = keccak256(seed);
+        }
+
+        let len = (cache_size(epoch) / HASH_BYTES) as usize;
+        let mut nodes = Vec::with_capacity(len);
+        nodes.push(to_node(&keccak512(seed.as_bytes())));
+        for i in 1..len {
+            nodes.push(hash_node(&nodes[i - 1]));
+        }
+
+        for _ in 0..CACHE_ROUNDS {
+            for i in 0..len {
+                let other = nodes[i][0] as usize % len;
+                let mut node = nodes[(i + len - 1) % len];
+                node.iter_mut().zip(nodes[other].iter()).for_each(|(word, other)| *word ^= other);
+                nodes[i] = hash_node(&node);
+            }
+        }
+
+        Self { epoch, dataset_size: dataset_size(epoch), nodes }
+    }
+
+    /// Computes the dataset item with the given index from the cache.
+    fn dataset_item(&self, index: u32) -> Node {
+        let len = self.nodes.len();
+        let mut mix = self.nodes[index as usize % len];
+        mix[0] ^= index;
+        let mut mix = hash_node(&mix);
+
+        for parent in 0..DATASET_PARENTS {
+            let parent = fnv(index ^ parent, mix[parent as usize % mix.len()]) as usize % len;
+            mix.iter_mut().zip(self.nodes[parent].iter()).for_each(|(word, other)| {
+                *word = fnv(*word, *other);
+            });
+        }
+        hash_node(&mix)
+    }
+
+    /// Returns the mix hash and the result of the proof-of-work for the seal hash and nonce of a
+    /// header.
+    fn hashimoto(&self, seal_hash: H256, nonce: u64) -> (H256, H256) {
+        let mut seed = [0u8; 40];
+        seed[..32].copy_from_slice(seal_hash.as_bytes());
+        seed[32..].copy_from_slice(&nonce.to_le_bytes());
+        let seed = keccak512(&seed);
+        let seed_node = to_node(&seed);
+
+        let mut mix = [0u32; (MIX_BYTES / 4) as usize];
+        mix[..16].copy_from_slice(&seed_node);
+        mix[16..].copy_from_slice(&seed_node);
+
+        let rows = (self.dataset_size / MIX_BYTES) as u32;
+        for i in 0..ACCESSES {
+            let row = fnv(i ^ seed_node[0], mix[i as usize % mix.len()]) % rows;
+            let first = self.dataset_item(row * 2);
+            let second = self.dataset_item(row * 2 + 1);
+            mix.iter_mut().zip(first.iter().chain(second.iter())).for_each(|(word, other)| {
+                *word = fnv(*word, *other);
+            });
+        }
+
+        let mut mix_hash = H256::zero();
+        for (chunk, out) in mix.chunks(4).zip(mix_hash.0.chunks_mut(4)) {
+            let word = fnv(fnv(fnv(chunk[0], chunk[1]), chunk[2]), chunk[3]);
+            out.copy_from_slice(&word.to_le_bytes());
+        }
+
+        let mut result = [0u8; 96];
+        result[..64].copy_from_slice(&seed);
+        result[64..].copy_from_slice(mix_hash.as_bytes());
+        (mix_hash, keccak256(result))
+    }
+}
+
+/// Validates the difficulty of a proof-of-work header against its parent.
+pub fn validate_difficulty(
+    header: &SealedHeader,
+    parent: &SealedHeader,
+    config: &Config,
+) -> Result<(), Error> {
+    let expected = calculate_difficulty(config, parent, header.timestamp);
+    if header.difficulty != expected {
+        return Err(Error::DifficultyDiff { got: header.difficulty, expected })
+    }
+    Ok(())
+}
+
+/// Calculates the difficulty of the child of the parent block with the given timestamp.
+///
+/// This implements the rules of Frontier, Homestead ([EIP-2](https://eips.ethereum.org/EIPS/eip-2))
+/// and Byzantium ([EIP-100](https://eips.ethereum.org/EIPS/eip-100)), including the delays of the
+/// difficulty bomb of the later forks.
+pub fn calculate_difficulty(config: &Config, parent: &Header, timestamp: u64) -> U256 {
+    let number = parent.number + 1;
+    let elapsed = timestamp.saturating_sub(parent.timestamp);
+    let adjustment = parent.difficulty / U256::from(2048);
+
+    // The difficulty is adjusted by `max(base - elapsed / divisor, -99)` steps
+    let adjust = |base: u64, divisor: u64| {
+        let decrease = (elapsed / divisor).min(base + 99);
+        if decrease <= base {
+            parent.difficulty.saturating_add(adjustment * U256::from(base - decrease))
+        } else {
+            parent.difficulty.saturating_sub(adjustment * U256::from(decrease - base))
+        }
+    };
+    let difficulty = if number >= config.byzantium_block {
+        adjust(if parent.ommers_hash == EMPTY_LIST_HASH { 1 } else { 2 }, 9)
+    } else if number >= config.homestead_block {
+        adjust(1, 10)
+    } else if elapsed < 13 {
+        parent.difficulty.saturating_add(adjustment)
+    } else {
+        parent.difficulty.saturating_sub(adjustment)
+    };
+    let mut difficulty = difficulty.max(U256::from(MINIMUM_DIFFICULTY));
+
+    let period = number.saturating_sub(bomb_delay(config, number)) / DIFFICULTY_BOMB_PERIOD;
+    if period > 1 {
+        difficulty = difficulty.saturating_add(U256::from(1) << (period - 2) as usize);
+    }
+    difficulty
+}
+
+/// Returns the number of blocks the difficulty bomb is delayed by at the given block.
+fn bomb_delay(config: &Config, number: BlockNumber) -> u64 {
+    if number >= config.gray_glacier_block {
+        11_400_000
+    } else if number >= config.arrow_glacier_block {
+        10_700_000
+    } else if number >= config.london_block {
+        9_700_000
+    } else if number >= config.muir_glacier_block {
+        9_000_000
+    } else if number >= config.constantinople_block {
+        5_000_000
+    } else if number >= config.byzantium_block {
+        3_000_000
+    } else {
+        0
+    }
+}
+
+/// Returns the hash of the header without the mix hash and the nonce, which is the input of the
+/// proof-of-work.
+pub fn seal_hash(header: &Header) -> H256 {
+    let base_fee = header.base_fee_per_gas.map(U256::from);
+    let fields: [&dyn Encodable; 13] = [
+        &header.parent_hash,
+        &header.ommers_hash,
+        &header.beneficiary,
+        &header.state_root,
+        &header.transactions_root,
+        &header.receipts_root,
+        &header.logs_bloom,
+        &header.difficulty,
+        &header.number,
+        &header.gas_limit,
+        &header.gas_used,
+        &header.timestamp,
+        &header.extra_data,
+    ];
+    let fields = fields.into_iter().chain(base_fee.as_ref().map(|fee| fee as &dyn Encodable));
+
+    let mut payload = Vec::new();
+    fields.for_each(|field| field.encode(&mut payload));
+    let mut out = Vec::with_capacity(payload.len() + 9);
+    reth_rlp::Header { list: true, payload_length: payload.len() }.encode(&mut out);
+    out.extend_from_slice(&payload);
+    keccak256(out)
+}
+
+/// Returns the size of the cache of the epoch in bytes.
+fn cache_size(epoch: u64) -> u64 {
+    let mut size = CACHE_BYTES_INIT + CACHE_BYTES_GROWTH * epoch - HASH_BYTES;
+    while !is_prime(size / HASH_BYTES) {
+        size -= 2 * HASH_BYTES;
+    }
+    size
+}
+
+/// Returns the size of the full dataset of the epoch in bytes.
+fn dataset_size(epoch: u64) -> u64 {
+    let mut size = DATASET_BYTES_INIT + DATASET_BYTES_GROWTH * epoch - MIX_BYTES;
+    while !is_prime(size / MIX_BYTES) {
+        size -= 2 * MIX_BYTES;
+    }
+    size
+}
+
+fn is_prime(n: u64) -> bool {
+    n >= 2 && (2..).take_while(|i| i * i <= n).all(|i| n % i != 0)
+}
+
+fn fnv(a: u32, b: u32) -> u32 {
+    a.wrapping_mul(FNV_PRIME) ^ b
+}
+
+fn keccak512(data: &[u8]) -> [u8; 64] {
+    let mut hasher = Keccak::v512();
+    hasher.update(data);
+    let mut out = [0u8; 64];
+    hasher.finalize(&mut out);
+    out
+}
+
+fn to_node(bytes: &[u8; 64]) -> Node {
+    let mut node = [0u32; 16];
+    for (word, chunk) in node.iter_mut().zip(bytes.chunks(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().expect("4 bytes"));
+    }
+    node
+}
+
+fn hash_node(node: &Node) -> Node {
+    let mut bytes = [0u8; 64];
+    for (chunk, word) in bytes.chunks_mut(4).zip(node.iter()) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    to_node(&keccak512(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::hex_literal::hex;
+
+    /// The first block of mainnet.
+    fn mainnet_block_1() -> Header {
+        Header {
+            parent_hash: hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
+                .into(),
+            ommers_hash: EMPTY_LIST_HASH,
+            beneficiary: hex!("05a56e2d52c817161883f50c441c3228cfe54d9f").into(),
+            state_root: hex!("d67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3")
+                .into(),
+            transactions_root: hex!(
+                "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+            )
+            .into(),
+            receipts_root: hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+                .into(),
+            difficulty: U256::from(0x3ff800000u64),
+            number: 1,
+            gas_limit: 5000,
+            timestamp: 0x55ba4224,
+            extra_data: hex!("476574682f76312e302e302f6c696e75782f676f312e342e32")[..].into(),
+            mix_hash: hex!("969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59")
+                .into(),
+            nonce: 0x539bd4979fef1ec4,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(cache_size(0), 16776896);
+        assert_eq!(dataset_size(0), 1073739904);
+        assert_eq!(cache_size(1), 16907456);
+        assert_eq!(dataset_size(1), 1082130304);
+    }
+
+    #[test]
+    fn mainnet_seal_hash() {
+        let header = mainnet_block_1().seal();
+        assert_eq!(
+            header.hash(),
+            H256(hex!("88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6"))
+        );
+        assert_eq!(
+            seal_hash(&header),
+            H256(hex!("85913a3057ea8bec78cd916871ca73802e77724e014dda65add3405d02240eb7"))
+        );
+    }
+
+    #[test]
+    // generating the cache of the first epoch takes too long in debug builds
+    #[ignore]
+    fn verify_mainnet_seal() {
+        let header = mainnet_block_1().seal();
+        let ethash = Ethash::default();
+        assert_eq!(ethash.verify_seal(&header), Ok(()));
+
+        let mut invalid = header.unseal();
+        invalid.nonce += 1;
+        assert!(ethash.verify_seal(&invalid.seal()).is_err());
+    }
+
+    #[test]
+    fn difficulty_adjustment() {
+        let config = Config::default();
+
+        // Frontier: the first block of mainnet is more than 13 seconds after the genesis block
+        let genesis = Header { difficulty: U256::from(0x400000000u64), ..Default::default() };
+        let block = mainnet_block_1();
+        assert_eq!(calculate_difficulty(&config, &genesis, block.timestamp), block.difficulty);
+
+        let parent = Header {
+            number: 5_000_000,
+            timestamp: 1000,
+            difficulty: U256::from(2_048_000_000u64),
+            ..Default::default()
+        };
+
+        // Byzantium: one step up for blocks within 9 seconds, one step down for each additional 9
+        let step = U256::from(1_000_000);
+        let bomb = U256::from(1) << 18;
+        assert_eq!(calculate_difficulty(&config, &parent, 1005), parent.difficulty + step + bomb);
+        assert_eq!(calculate_difficulty(&config, &parent, 1010), parent.difficulty + bomb);
+        assert_eq!(calculate_difficulty(&config, &parent, 1020), parent.difficulty - step + bomb);
+        assert_eq!(
+            calculate_difficulty(&config, &parent, 100_000),
+            parent.difficulty - step * U256::from(99) + bomb
+        );
+
+        let child = Header { number: 5_000_001, timestamp: 1005, ..Default::default() };
+        assert_eq!(
+            validate_difficulty(&child.seal(), &parent.clone().seal(), &config),
+            Err(Error::DifficultyDiff {
+                got: U256::ZERO,
+                expected: parent.difficulty + step + bomb
+            })
+        );
+    }
+}
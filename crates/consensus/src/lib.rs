@@ -11,6 +11,7 @@
 //! - `serde`: Enable serde support for configuration types.
 pub mod config;
 pub mod consensus;
+pub mod ethash;
 pub mod verification;
 
 /// Engine API module.
@@ -2,8 +2,9 @@
 use crate::{config, Config};
 use reth_interfaces::{consensus::Error, Result as RethResult};
 use reth_primitives::{
-    BlockNumber, Header, SealedBlock, SealedHeader, Transaction, TransactionSignedEcRecovered,
-    TxEip1559, TxEip2930, TxLegacy, EMPTY_OMMER_ROOT, U256,
+    basefee::calculate_next_block_base_fee, BlockNumber, Header, SealedBlock, SealedHeader,
+    Transaction, TransactionSignedEcRecovered, TxEip1559, TxEip2930, TxLegacy, EMPTY_OMMER_ROOT,
+    U256,
 };
 use reth_provider::{AccountProvider, HeaderProvider};
 use std::{
@@ -61,6 +62,16 @@ pub fn validate_header_standalone(
         // https://eips.ethereum.org/EIPS/eip-4399#using-mixhash-field-instead-of-difficulty
     }
 
+    // EIP-4895: Beacon chain push withdrawals as operations
+    // https://eips.ethereum.org/EIPS/eip-4895
+    if header.number >= config.shanghai_block {
+        if header.withdrawals_root.is_none() {
+            return Err(Error::WithdrawalsRootMissing)
+        }
+    } else if header.withdrawals_root.is_some() {
+        return Err(Error::WithdrawalsRootUnexpected)
+    }
+
     Ok(())
 }
 
@@ -179,6 +190,8 @@ pub fn validate_all_transaction_regarding_block_and_nonces<
 ///
 /// - Compares the ommer hash in the block header to the block body
 /// - Compares the transactions root in the block header to the block body
+/// - Compares the withdrawals root in the block header to the block body, and requires the
+///   withdrawals if the header has a withdrawals root
 /// - Pre-execution transaction validation
 /// - (Optionally) Compares the receipts root in the block header to the block body
 pub fn validate_block_standalone(block: &SealedBlock) -> Result<(), Error> {
@@ -203,33 +216,18 @@ pub fn validate_block_standalone(block: &SealedBlock) -> Result<(), Error> {
         })
     }
 
-    Ok(())
-}
-
-/// Calculate base fee for next block. EIP-1559 spec
-pub fn calculate_next_block_base_fee(gas_used: u64, gas_limit: u64, base_fee: u64) -> u64 {
-    let gas_target = gas_limit / config::EIP1559_ELASTICITY_MULTIPLIER;
-
-    if gas_used == gas_target {
-        return base_fee
+    // Check withdrawals root
+    if let Some(withdrawals) = &block.withdrawals {
+        let withdrawals_root = reth_primitives::proofs::calculate_withdrawals_root(withdrawals);
+        let expected = block.header.withdrawals_root.ok_or(Error::WithdrawalsRootMissing)?;
+        if withdrawals_root != expected {
+            return Err(Error::BodyWithdrawalsRootDiff { got: withdrawals_root, expected })
+        }
+    } else if block.header.withdrawals_root.is_some() {
+        return Err(Error::BodyWithdrawalsMissing)
     }
-    if gas_used > gas_target {
-        let gas_used_delta = gas_used - gas_target;
-        let base_fee_delta = std::cmp::max(
-            1,
-            base_fee as u128 * gas_used_delta as u128 /
-                gas_target as u128 /
-                config::EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR as u128,
-        );
-        base_fee + (base_fee_delta as u64)
-    } else {
-        let gas_used_delta = gas_target - gas_used;
-        let base_fee_per_gas_delta = base_fee as u128 * gas_used_delta as u128 /
-            gas_target as u128 /
-            config::EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR as u128;
 
-        base_fee.saturating_sub(base_fee_per_gas_delta as u64)
-    }
+    Ok(())
 }
 
 /// Validate block in regards to parent
@@ -362,39 +360,14 @@ pub fn full_validation<Provider: HeaderProvider + AccountProvider>(
 mod tests {
     use reth_interfaces::Result;
     use reth_primitives::{
-        hex_literal::hex, Account, Address, BlockHash, Bytes, Header, Signature, TransactionKind,
-        TransactionSigned,
+        hex_literal::hex,
+        proofs::{self, EMPTY_ROOT},
+        Account, Address, BlockHash, Bytes, Header, Signature, TransactionKind, TransactionSigned,
+        Withdrawal,
     };
 
     use super::*;
 
-    #[test]
-    fn calculate_base_fee_success() {
-        let base_fee = [
-            1000000000, 1000000000, 1000000000, 1072671875, 1059263476, 1049238967, 1049238967, 0,
-            1, 2,
-        ];
-        let gas_used = [
-            10000000, 10000000, 10000000, 9000000, 10001000, 0, 10000000, 10000000, 10000000,
-            10000000,
-        ];
-        let gas_limit = [
-            10000000, 12000000, 14000000, 10000000, 14000000, 2000000, 18000000, 18000000,
-            18000000, 18000000,
-        ];
-        let next_base_fee = [
-            1125000000, 1083333333, 1053571428, 1179939062, 1116028649, 918084097, 1063811730, 1,
-            2, 3,
-        ];
-
-        for i in 0..base_fee.len() {
-            assert_eq!(
-                next_base_fee[i],
-                calculate_next_block_base_fee(gas_used[i], gas_limit[i], base_fee[i])
-            );
-        }
-    }
-
     struct Provider {
         is_known: bool,
         parent: Option<Header>,
@@ -476,6 +449,7 @@ mod tests {
             mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
             nonce: 0x0000000000000000,
             base_fee_per_gas: 0x28f0001df.into(),
+            withdrawals_root: None,
         };
         // size: 0x9b5
 
@@ -488,7 +462,7 @@ mod tests {
         let ommers = Vec::new();
         let body = Vec::new();
 
-        (SealedBlock { header: header.seal(), body, ommers }, parent)
+        (SealedBlock { header: header.seal(), body, ommers, withdrawals: None }, parent)
     }
 
     #[test]
@@ -569,4 +543,48 @@ mod tests {
             Err(Error::TransactionNonceNotConsistent.into())
         );
     }
+
+    #[test]
+    fn withdrawals_root_presence() {
+        let (block, _) = mock_block();
+        let mut config = Config::default();
+
+        let mut header = block.header.clone().unseal();
+        header.withdrawals_root = Some(EMPTY_ROOT);
+        assert_eq!(
+            validate_header_standalone(&header.clone().seal(), &config),
+            Err(Error::WithdrawalsRootUnexpected)
+        );
+
+        config.shanghai_block = block.number;
+        assert_eq!(validate_header_standalone(&header.seal(), &config), Ok(()));
+        assert_eq!(
+            validate_header_standalone(&block.header, &config),
+            Err(Error::WithdrawalsRootMissing)
+        );
+    }
+
+    #[test]
+    fn withdrawals_root_mismatch() {
+        let (mut block, _) = mock_block();
+        let withdrawals = vec![Withdrawal { amount: 1, ..Default::default() }];
+        let withdrawals_root = proofs::calculate_withdrawals_root(&withdrawals);
+
+        let mut header = block.header.clone().unseal();
+        header.withdrawals_root = Some(EMPTY_ROOT);
+        block.header = header.seal();
+        block.withdrawals = Some(withdrawals);
+        assert_eq!(
+            validate_block_standalone(&block),
+            Err(Error::BodyWithdrawalsRootDiff { got: withdrawals_root, expected: EMPTY_ROOT })
+        );
+
+        let mut header = block.header.clone().unseal();
+        header.withdrawals_root = Some(withdrawals_root);
+        block.header = header.seal();
+        assert_eq!(validate_block_standalone(&block), Ok(()));
+
+        block.withdrawals = None;
+        assert_eq!(validate_block_standalone(&block), Err(Error::BodyWithdrawalsMissing));
+    }
 }
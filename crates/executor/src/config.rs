@@ -54,6 +54,11 @@ impl SpecUpgrades {
         block_num < self.paris
     }
 
+    /// Since Shanghai, blocks contain the withdrawals of the consensus layer.
+    pub fn has_withdrawals(&self, block_num: BlockNumber) -> bool {
+        block_num >= self.shanghai
+    }
+
     /// Ethereum mainnet spec
     pub fn new_ethereum() -> Self {
         Self {
@@ -73,7 +78,7 @@ impl SpecUpgrades {
             //arrow_glacier: 13773000,
             //gray_glacier: 15050000,
             paris: 15537394, // TheMerge,
-            shanghai: u64::MAX,
+            shanghai: 17034870,
         }
     }
 
@@ -139,6 +144,11 @@ impl SpecUpgrades {
         Self { paris: 0, ..Self::new_london_activated() }
     }
 
+    /// New shanghai enabled spec
+    pub fn new_shanghai_activated() -> Self {
+        Self { shanghai: 0, ..Self::new_paris_activated() }
+    }
+
     /// return revm_spec from spec configuration.
     pub fn revm_spec(&self, for_block: BlockNumber) -> revm::SpecId {
         match for_block {
@@ -163,6 +173,7 @@ mod tests {
     use super::SpecUpgrades;
     #[test]
     fn test_to_revm_spec() {
+        assert_eq!(SpecUpgrades::new_shanghai_activated().revm_spec(1), revm::MERGE_EOF);
         assert_eq!(SpecUpgrades::new_paris_activated().revm_spec(1), revm::MERGE);
         assert_eq!(SpecUpgrades::new_london_activated().revm_spec(1), revm::LONDON);
         assert_eq!(SpecUpgrades::new_berlin_activated().revm_spec(1), revm::BERLIN);
@@ -181,6 +192,7 @@ mod tests {
     #[test]
     fn test_eth_spec() {
         let spec = SpecUpgrades::new_ethereum();
+        assert_eq!(spec.revm_spec(17034870 + 10), revm::MERGE_EOF);
         assert_eq!(spec.revm_spec(15537394 + 10), revm::MERGE);
         assert_eq!(spec.revm_spec(15537394 - 10), revm::LONDON);
         assert_eq!(spec.revm_spec(12244000 + 10), revm::BERLIN);
@@ -8,7 +8,7 @@ use reth_db::{models::AccountBeforeTx, tables, transaction::DbTxMut, Error as Db
 use reth_interfaces::executor::Error;
 use reth_primitives::{
     bloom::logs_bloom, Account, Address, Bloom, Header, Log, Receipt, TransactionSignedEcRecovered,
-    H160, H256, U256,
+    Withdrawal, H160, H256, U256,
 };
 use reth_provider::StateProvider;
 use revm::{
@@ -109,7 +109,7 @@ pub struct AccountChangeSet {
 pub struct ExecutionResult {
     /// Transaction changeest contraining [Receipt], changed [Accounts][Account] and Storages.
     pub changesets: Vec<TransactionChangeSet>,
-    /// Block reward if present. It represent changeset for block reward slot in
+    /// Block reward and withdrawals if present. It represent changeset for block reward slot in
     /// [tables::AccountChangeSet] .
     pub block_reward: Option<BTreeMap<Address, AccountInfoChangeSet>>,
 }
@@ -250,10 +250,11 @@ pub fn execute_and_verify_receipt<DB: StateProvider>(
     header: &Header,
     transactions: &[TransactionSignedEcRecovered],
     ommers: &[Header],
+    withdrawals: &[Withdrawal],
     config: &Config,
     db: SubState<DB>,
 ) -> Result<ExecutionResult, Error> {
-    let transaction_change_set = execute(header, transactions, ommers, config, db)?;
+    let transaction_change_set = execute(header, transactions, ommers, withdrawals, config, db)?;
 
     let receipts_iter =
         transaction_change_set.changesets.iter().map(|changeset| &changeset.receipt);
@@ -294,25 +295,17 @@ pub fn verify_receipt<'a>(
 
 /// Verify block. Execute all transaction and compare results.
 /// Returns ChangeSet on transaction granularity.
-/// NOTE: If block reward is still active (Before Paris/Merge) we would return
-/// additional TransactionStatechangeset for account that receives the reward.
+/// NOTE: If block reward is still active (Before Paris/Merge) or the block contains withdrawals
+/// (After Shanghai) we would return additional changeset for the accounts that receive the balance.
 pub fn execute<DB: StateProvider>(
     header: &Header,
     transactions: &[TransactionSignedEcRecovered],
     ommers: &[Header],
+    withdrawals: &[Withdrawal],
     config: &Config,
     db: SubState<DB>,
 ) -> Result<ExecutionResult, Error> {
-    let mut evm = EVM::new();
-    evm.database(db);
-
-    let spec_id = config.spec_upgrades.revm_spec(header.number);
-    evm.env.cfg.chain_id = config.chain_id;
-    evm.env.cfg.spec_id = config.spec_upgrades.revm_spec(header.number);
-    evm.env.cfg.perf_all_precompiles_have_balance = false;
-    evm.env.cfg.perf_analyse_created_bytecodes = AnalysisKind::Raw;
-
-    revm_wrap::fill_block_env(&mut evm.env.block, header, spec_id >= SpecId::MERGE);
+    let mut evm = block_evm(header, config, db);
     let mut cumulative_gas_used = 0;
     // output of verification
     let mut changesets = Vec::with_capacity(transactions.len());
@@ -328,82 +321,139 @@ pub fn execute<DB: StateProvider>(
             })
         }
 
-        // Fill revm structure.
-        revm_wrap::fill_tx_env(&mut evm.env.tx, transaction);
+        let changeset = execute_transaction(&mut evm, transaction, cumulative_gas_used)?;
+        cumulative_gas_used = changeset.receipt.cumulative_gas_used;
+        changesets.push(changeset);
+    }
 
-        // Execute transaction.
-        let out = evm.transact();
+    // Check if gas used matches the value set in header.
+    if header.gas_used != cumulative_gas_used {
+        return Err(Error::BlockGasUsed { got: cumulative_gas_used, expected: header.gas_used })
+    }
 
-        // Useful for debugging
-        // let out = evm.inspect(revm::inspectors::CustomPrintTracer::default());
-        // tracing::trace!(target:"evm","Executing transaction {:?}, \n:{out:?}: {:?}
-        // \nENV:{:?}",transaction.hash(),transaction,evm.env);
+    let mut db = evm.db.expect("It is set by block_evm");
+    let block_reward = block_reward_changeset(header, ommers, withdrawals, &mut db, config)?;
 
-        let (revm::ExecutionResult { exit_reason, gas_used, logs, .. }, state) = out;
+    Ok(ExecutionResult { changesets, block_reward })
+}
 
-        // Fatal internal error.
-        if exit_reason == revm::Return::FatalExternalError {
-            return Err(Error::ExecutionFatalError)
-        }
+/// Create the [EVM] that executes the transactions of the block with the given header on top of
+/// the given state.
+pub fn block_evm<DB: StateProvider>(
+    header: &Header,
+    config: &Config,
+    db: SubState<DB>,
+) -> EVM<SubState<DB>> {
+    let mut evm = EVM::new();
+    evm.database(db);
 
-        // Success flag was added in `EIP-658: Embedding transaction status code in receipts`.
-        // TODO for verification (exit_reason): some error should return EVM error as the block with
-        // that transaction can have consensus error that would make block invalid.
-        let is_success = match exit_reason {
-            revm::return_ok!() => true,
-            revm::return_revert!() => false,
-            _ => false,
-            //e => return Err(Error::EVMError { error_code: e as u32 }),
-        };
+    let spec_id = config.spec_upgrades.revm_spec(header.number);
+    evm.env.cfg.chain_id = config.chain_id;
+    evm.env.cfg.spec_id = spec_id;
+    evm.env.cfg.perf_all_precompiles_have_balance = false;
+    evm.env.cfg.perf_analyse_created_bytecodes = AnalysisKind::Raw;
 
-        // Add spend gas.
-        cumulative_gas_used += gas_used;
+    revm_wrap::fill_block_env(&mut evm.env.block, header, spec_id >= SpecId::MERGE);
+    evm
+}
 
-        // Transform logs to reth format.
-        let logs: Vec<Log> = logs
-            .into_iter()
-            .map(|l| Log {
-                address: H160(l.address.0),
-                topics: l.topics.into_iter().map(|h| H256(h.0)).collect(),
-                data: l.data,
-            })
-            .collect();
-
-        // commit state
-        let (changeset, new_bytecodes) = commit_changes(evm.db().unwrap(), state);
-
-        // Push transaction changeset and calculte header bloom filter for receipt.
-        changesets.push(TransactionChangeSet {
-            receipt: Receipt {
-                tx_type: transaction.tx_type(),
-                success: is_success,
-                cumulative_gas_used,
-                bloom: logs_bloom(logs.iter()),
-                logs,
-            },
-            changeset,
-            new_bytecodes,
-        })
+/// Execute the transaction with the [EVM] of the block and commit its changes to the state of the
+/// [EVM].
+///
+/// `cumulative_gas_used` is the gas used by the transactions of the block executed before this
+/// one. The gas used by this transaction is the difference to the cumulative gas used of the
+/// returned receipt. Transactions that are rejected before execution, e.g. because of an invalid
+/// nonce, return [Error::TransactionRejected] and do not change the state.
+pub fn execute_transaction<DB: StateProvider>(
+    evm: &mut EVM<SubState<DB>>,
+    transaction: &TransactionSignedEcRecovered,
+    cumulative_gas_used: u64,
+) -> Result<TransactionChangeSet, Error> {
+    // Fill revm structure.
+    revm_wrap::fill_tx_env(&mut evm.env.tx, transaction);
+
+    // Execute transaction.
+    let out = evm.transact();
+
+    // Useful for debugging
+    // let out = evm.inspect(revm::inspectors::CustomPrintTracer::default());
+    // tracing::trace!(target:"evm","Executing transaction {:?}, \n:{out:?}: {:?}
+    // \nENV:{:?}",transaction.hash(),transaction,evm.env);
+
+    let (revm::ExecutionResult { exit_reason, gas_used, logs, .. }, state) = out;
+
+    match exit_reason {
+        // Fatal internal error.
+        revm::Return::FatalExternalError => return Err(Error::ExecutionFatalError),
+        // The transaction can not be included in the block.
+        revm::Return::PrevrandaoNotSet |
+        revm::Return::GasMaxFeeGreaterThanPriorityFee |
+        revm::Return::GasPriceLessThenBasefee |
+        revm::Return::CallerGasLimitMoreThenBlock |
+        revm::Return::RejectCallerWithCode |
+        revm::Return::InvalidChainId |
+        revm::Return::NonceTooHigh |
+        revm::Return::NonceTooLow |
+        revm::Return::LackOfFundForGasLimit |
+        revm::Return::OverflowPayment => {
+            return Err(Error::TransactionRejected { reason: format!("{exit_reason:?}") })
+        }
+        _ => {}
     }
 
-    // Check if gas used matches the value set in header.
-    if header.gas_used != cumulative_gas_used {
-        return Err(Error::BlockGasUsed { got: cumulative_gas_used, expected: header.gas_used })
-    }
+    // Success flag was added in `EIP-658: Embedding transaction status code in receipts`.
+    // TODO for verification (exit_reason): some error should return EVM error as the block with
+    // that transaction can have consensus error that would make block invalid.
+    let is_success = match exit_reason {
+        revm::return_ok!() => true,
+        revm::return_revert!() => false,
+        _ => false,
+        //e => return Err(Error::EVMError { error_code: e as u32 }),
+    };
 
-    let mut db = evm.db.expect("It is set at the start of the function");
-    let block_reward = block_reward_changeset(header, ommers, &mut db, config)?;
+    // Add spend gas.
+    let cumulative_gas_used = cumulative_gas_used + gas_used;
 
-    Ok(ExecutionResult { changesets, block_reward })
+    // Transform logs to reth format.
+    let logs: Vec<Log> = logs
+        .into_iter()
+        .map(|l| Log {
+            address: H160(l.address.0),
+            topics: l.topics.into_iter().map(|h| H256(h.0)).collect(),
+            data: l.data,
+        })
+        .collect();
+
+    // commit state
+    let (changeset, new_bytecodes) = commit_changes(evm.db().unwrap(), state);
+
+    // Return transaction changeset and calculte header bloom filter for receipt.
+    Ok(TransactionChangeSet {
+        receipt: Receipt {
+            tx_type: transaction.tx_type(),
+            success: is_success,
+            cumulative_gas_used,
+            bloom: logs_bloom(logs.iter()),
+            logs,
+        },
+        changeset,
+        new_bytecodes,
+    })
 }
 
 /// Calculate Block reward changeset
+///
+/// The changeset contains the block and ommer rewards before Paris and the withdrawals since
+/// Shanghai. It is `None` if no balance was increased.
 pub fn block_reward_changeset<DB: StateProvider>(
     header: &Header,
     ommers: &[Header],
+    withdrawals: &[Withdrawal],
     db: &mut SubState<DB>,
     config: &Config,
 ) -> Result<Option<BTreeMap<H160, AccountInfoChangeSet>>, Error> {
+    let mut balance_increments: BTreeMap<H160, U256> = BTreeMap::new();
+
     // NOTE: Related to Ethereum reward change, for other network this is probably going to be moved
     // to config.
 
@@ -412,15 +462,14 @@ pub fn block_reward_changeset<DB: StateProvider>(
     // of the accounts of the beneficiary address of the block and each ommer by a certain
     // amount. We raise the block’s beneficiary account by Rblock; for each ommer, we raise the
     // block’s beneficiary by an additional 1/32 of the block reward and the beneficiary of the
-    // ommer gets rewarded depending on the blocknumber. Formally we define the function Ω:
-    match header.number {
+    // ommer gets rewarded depending on the blocknumber. Formally we define the function Ω:
+    let reward = match header.number {
         n if n >= config.spec_upgrades.paris => None,
         n if n >= config.spec_upgrades.petersburg => Some(WEI_2ETH),
         n if n >= config.spec_upgrades.byzantium => Some(WEI_3ETH),
         _ => Some(WEI_5ETH),
-    }
-    .map(|reward| -> Result<_, _> {
-        let mut reward_beneficiaries: BTreeMap<H160, u128> = BTreeMap::new();
+    };
+    if let Some(reward) = reward {
         // Calculate Uncle reward
         // OpenEthereum code: https://github.com/openethereum/openethereum/blob/6c2d392d867b058ff867c4373e40850ca3f96969/crates/ethcore/src/ethereum/ethash.rs#L319-L333
         for ommer in ommers {
@@ -430,35 +479,52 @@ pub fn block_reward_changeset<DB: StateProvider>(
             // (i.e. two ommers with the same beneficiary address or an ommer with the
             // same beneficiary address as the present block), additions are applied
             // cumulatively
-            *reward_beneficiaries.entry(ommer.beneficiary).or_default() += ommer_reward;
+            *balance_increments.entry(ommer.beneficiary).or_default() += U256::from(ommer_reward);
         }
         // insert main block reward
-        *reward_beneficiaries.entry(header.beneficiary).or_default() +=
-            reward + (reward >> 5) * ommers.len() as u128;
-
-        // apply block rewards to beneficiaries (Main block and ommers);
-        reward_beneficiaries
-            .into_iter()
-            .map(|(beneficiary, reward)| -> Result<_, _> {
-                let changeset = db
-                    .basic(beneficiary)
-                    .map_err(|_| Error::ProviderError)?
-                    // if account is present append `Changed` changeset for block reward
-                    .map(|acc| {
-                        let old = to_reth_acc(&acc);
-                        let mut new = old;
-                        new.balance += U256::from(reward);
-                        AccountInfoChangeSet::Changed { new, old }
-                    })
-                    // if account is not present append `Created` changeset
-                    .unwrap_or(AccountInfoChangeSet::Created {
-                        new: Account { nonce: 0, balance: U256::from(reward), bytecode_hash: None },
-                    });
-                Ok((beneficiary, changeset))
-            })
-            .collect::<Result<BTreeMap<_, _>, _>>()
-    })
-    .transpose()
+        *balance_increments.entry(header.beneficiary).or_default() +=
+            U256::from(reward + (reward >> 5) * ommers.len() as u128);
+    }
+
+    // Withdrawals are applied after the transactions and the rewards, they are not transactions
+    // and do not consume any gas. See https://eips.ethereum.org/EIPS/eip-4895
+    let has_withdrawals =
+        config.spec_upgrades.has_withdrawals(header.number) && !withdrawals.is_empty();
+    if has_withdrawals {
+        for withdrawal in withdrawals {
+            *balance_increments.entry(withdrawal.address).or_default() += withdrawal.amount_wei();
+        }
+    }
+
+    // The rewards and withdrawals get their own transition, even if none of them changes a balance.
+    if reward.is_none() && !has_withdrawals {
+        return Ok(None)
+    }
+    // Withdrawals with a zero amount do not touch the account.
+    balance_increments.retain(|_, increment| *increment != U256::ZERO);
+
+    // apply block rewards and withdrawals to the beneficiaries
+    balance_increments
+        .into_iter()
+        .map(|(beneficiary, increment)| -> Result<_, _> {
+            let changeset = db
+                .basic(beneficiary)
+                .map_err(|_| Error::ProviderError)?
+                // if account is present append `Changed` changeset for block reward
+                .map(|acc| {
+                    let old = to_reth_acc(&acc);
+                    let mut new = old;
+                    new.balance += increment;
+                    AccountInfoChangeSet::Changed { new, old }
+                })
+                // if account is not present append `Created` changeset
+                .unwrap_or(AccountInfoChangeSet::Created {
+                    new: Account { nonce: 0, balance: increment, bytecode_hash: None },
+                });
+            Ok((beneficiary, changeset))
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()
+        .map(Some)
 }
 
 #[cfg(test)]
@@ -473,8 +539,8 @@ mod tests {
         transaction::DbTx,
     };
     use reth_primitives::{
-        hex_literal::hex, keccak256, Account, Address, Bytes, SealedBlock, StorageKey, H160, H256,
-        U256,
+        hex_literal::hex, keccak256, Account, Address, Bytes, SealedBlock, StorageKey, GWEI_TO_WEI,
+        H160, H256, U256,
     };
     use reth_provider::{AccountProvider, BlockHashProvider, StateProvider};
     use reth_rlp::Decodable;
@@ -585,7 +651,8 @@ mod tests {
 
         // execute chain and verify receipts
         let out =
-            execute_and_verify_receipt(&block.header, &transactions, &ommers, &config, db).unwrap();
+            execute_and_verify_receipt(&block.header, &transactions, &ommers, &[], &config, db)
+                .unwrap();
 
         assert_eq!(out.changesets.len(), 1, "Should executed one transaction");
 
@@ -664,6 +731,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn withdrawals_changeset() {
+        let existing = H160(hex!("1000000000000000000000000000000000000000"));
+        let created = H160(hex!("2000000000000000000000000000000000000000"));
+        let mut db = StateProviderTest::default();
+        let existing_info = Account { balance: U256::from(1), nonce: 1, bytecode_hash: None };
+        db.insert_account(existing, existing_info, None, HashMap::new());
+        let mut db = SubState::new(State::new(db));
+
+        let withdrawal = |address, amount| Withdrawal { address, amount, ..Default::default() };
+        let withdrawals = [withdrawal(existing, 1), withdrawal(created, 2), withdrawal(created, 0)];
+        let header = Header::default();
+
+        // Withdrawals are ignored before shanghai
+        let mut config = Config::new_ethereum();
+        config.spec_upgrades = SpecUpgrades::new_paris_activated();
+        let changeset =
+            block_reward_changeset(&header, &[], &withdrawals, &mut db, &config).unwrap();
+        assert_eq!(changeset, None);
+
+        config.spec_upgrades = SpecUpgrades::new_shanghai_activated();
+        let changeset =
+            block_reward_changeset(&header, &[], &withdrawals, &mut db, &config).unwrap();
+        let mut existing_new_info = existing_info;
+        existing_new_info.balance += U256::from(GWEI_TO_WEI);
+        assert_eq!(
+            changeset,
+            Some(BTreeMap::from([
+                (
+                    existing,
+                    AccountInfoChangeSet::Changed { new: existing_new_info, old: existing_info }
+                ),
+                (
+                    created,
+                    AccountInfoChangeSet::Created {
+                        new: Account {
+                            nonce: 0,
+                            balance: U256::from(2 * GWEI_TO_WEI),
+                            bytecode_hash: None
+                        }
+                    }
+                )
+            ]))
+        );
+
+        // Withdrawals that do not change any balance still get their own transition
+        let changeset =
+            block_reward_changeset(&header, &[], &[withdrawal(created, 0)], &mut db, &config)
+                .unwrap();
+        assert_eq!(changeset, Some(BTreeMap::new()));
+    }
+
     #[test]
     fn apply_account_info_changeset() {
         let db: Arc<Env<WriteMap>> = test_utils::create_test_db(EnvKind::RW);
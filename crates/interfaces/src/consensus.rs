@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use reth_primitives::{BlockHash, BlockNumber, SealedBlock, SealedHeader, H256};
+use reth_primitives::{BlockHash, BlockNumber, SealedBlock, SealedHeader, H256, U256};
 use tokio::sync::watch::Receiver;
 
 /// Re-export forkchoice state
@@ -12,6 +12,10 @@ pub trait Consensus: Send + Sync {
     /// Get a receiver for the fork choice state
     fn fork_choice_state(&self) -> Receiver<ForkchoiceState>;
 
+    /// Notifies the receivers of [Consensus::fork_choice_state] about the latest fork choice
+    /// state.
+    fn notify_fork_choice_state(&self, state: ForkchoiceState);
+
     /// Validate if header is correct and follows consensus specification.
     ///
     /// **This should not be called for the genesis block**.
@@ -46,6 +50,8 @@ pub enum Error {
     BodyTransactionRootDiff { got: H256, expected: H256 },
     #[error("Block receipts root ({got:?}) is different then expected: ({expected:?}).")]
     BodyReceiptsRootDiff { got: H256, expected: H256 },
+    #[error("Block state root ({got:?}) is different then expected: ({expected:?}).")]
+    BodyStateRootDiff { got: H256, expected: H256 },
     #[error("Block with [hash:{hash:?},number: {number:}] is already known.")]
     BlockKnown { hash: BlockHash, number: BlockNumber },
     #[error("Block parent [hash:{hash:?}] is not known.")]
@@ -104,4 +110,18 @@ pub enum Error {
     TheMergeOmmerRootIsNotEmpty,
     #[error("Mix hash after merge is not zero")]
     TheMergeMixHashIsNotZero,
+    #[error("Withdrawals root missing after shanghai.")]
+    WithdrawalsRootMissing,
+    #[error("Withdrawals root is present before shanghai.")]
+    WithdrawalsRootUnexpected,
+    #[error("Block withdrawals root ({got:?}) is different then expected: ({expected:?}).")]
+    BodyWithdrawalsRootDiff { got: H256, expected: H256 },
+    #[error("Block withdrawals are missing although the header has a withdrawals root.")]
+    BodyWithdrawalsMissing,
+    #[error("Block difficulty ({got:?}) is different then expected: ({expected:?}).")]
+    DifficultyDiff { got: U256, expected: U256 },
+    #[error("Block mix hash ({got:?}) is different then expected: ({expected:?}).")]
+    MixHashDiff { got: H256, expected: H256 },
+    #[error("Block proof-of-work does not satisfy the difficulty.")]
+    InvalidProofOfWork,
 }
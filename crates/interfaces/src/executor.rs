@@ -38,6 +38,8 @@ pub enum Error {
     BlockGasUsed { got: u64, expected: u64 },
     #[error("Revm error {error_code}")]
     EVMError { error_code: u32 },
+    #[error("Transaction was rejected before execution: {reason}")]
+    TransactionRejected { reason: String },
     #[error("Provider error")]
     ProviderError,
 }
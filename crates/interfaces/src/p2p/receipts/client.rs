@@ -0,0 +1,11 @@
+use crate::p2p::{downloader::DownloadClient, error::PeerRequestResult};
+use async_trait::async_trait;
+use reth_primitives::{Receipt, H256};
+
+/// A client capable of downloading the receipts of blocks.
+#[async_trait]
+#[auto_impl::auto_impl(&, Arc, Box)]
+pub trait ReceiptsClient: DownloadClient {
+    /// Fetches the receipts of the requested blocks.
+    async fn get_receipts(&self, hashes: Vec<H256>) -> PeerRequestResult<Vec<Vec<Receipt>>>;
+}
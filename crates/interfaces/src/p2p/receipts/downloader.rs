@@ -0,0 +1,31 @@
+use crate::p2p::downloader::DownloadStream;
+use reth_primitives::{Receipt, SealedHeader};
+
+/// The receipts of a block, ordered by transaction index.
+#[derive(PartialEq, Eq, Debug)]
+pub struct BlockReceipts {
+    /// The header of the block.
+    pub header: SealedHeader,
+    /// The receipts of the block.
+    pub receipts: Vec<Receipt>,
+}
+
+/// A downloader capable of fetching the receipts of blocks from their headers.
+///
+/// A downloader represents a distinct strategy for submitting requests to download receipts,
+/// while a [ReceiptsClient](super::client::ReceiptsClient) represents a client capable of
+/// fulfilling these requests.
+pub trait ReceiptsDownloader: Send + Sync {
+    /// Download the receipts of the given headers.
+    ///
+    /// The returned stream will always emit receipts in the order they were requested and every
+    /// emitted item is validated against the receipts root of its header.
+    ///
+    /// It is *not* guaranteed that all the requested receipts are fetched: the downloader may
+    /// close the stream before the entire range has been fetched for any reason.
+    fn receipts_stream<'a, 'b, I>(&'a self, headers: I) -> DownloadStream<'a, BlockReceipts>
+    where
+        I: IntoIterator<Item = &'b SealedHeader>,
+        <I as IntoIterator>::IntoIter: Send + 'b,
+        'b: 'a;
+}
@@ -0,0 +1,5 @@
+/// Traits and types for receipt clients.
+pub mod client;
+
+/// Receipt downloaders.
+pub mod downloader;
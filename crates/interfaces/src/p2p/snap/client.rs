@@ -0,0 +1,28 @@
+use crate::p2p::{downloader::DownloadClient, error::PeerRequestResult};
+use async_trait::async_trait;
+use reth_eth_wire::{
+    AccountRange, ByteCodes, GetAccountRange, GetByteCodes, GetStorageRanges, GetTrieNodes,
+    StorageRanges, TrieNodes,
+};
+
+/// A client capable of downloading the state of a recent block over the `snap` protocol.
+///
+/// The request ids of the requests are ignored, they are assigned by the client.
+#[async_trait]
+#[auto_impl::auto_impl(&, Arc, Box)]
+pub trait SnapClient: DownloadClient {
+    /// Fetches a range of accounts of the state trie.
+    async fn get_account_range(&self, request: GetAccountRange) -> PeerRequestResult<AccountRange>;
+
+    /// Fetches the storage slots of accounts of the state trie.
+    async fn get_storage_ranges(
+        &self,
+        request: GetStorageRanges,
+    ) -> PeerRequestResult<StorageRanges>;
+
+    /// Fetches contract bytecodes by their hashes.
+    async fn get_byte_codes(&self, request: GetByteCodes) -> PeerRequestResult<ByteCodes>;
+
+    /// Fetches trie nodes of the state trie by their paths.
+    async fn get_trie_nodes(&self, request: GetTrieNodes) -> PeerRequestResult<TrieNodes>;
+}
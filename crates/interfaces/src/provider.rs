@@ -1,4 +1,4 @@
-use reth_primitives::{BlockHash, BlockNumber};
+use reth_primitives::{Address, BlockHash, BlockNumber, TransitionId, H256};
 
 /// KV error type. They are using u32 to represent error code.
 #[allow(missing_docs)]
@@ -12,4 +12,14 @@ pub enum Error {
     BlockBody { block_number: BlockNumber, block_hash: BlockHash },
     #[error("Block transition does not exist for block #{block_number} ({block_hash:?})")]
     BlockTransition { block_number: BlockNumber, block_hash: BlockHash },
+    #[error("Total difficulty does not exist for block #{block_number} ({block_hash:?})")]
+    TotalDifficulty { block_number: BlockNumber, block_hash: BlockHash },
+    #[error("Account {address:?} is indexed at transition {transition_id} but has no changeset")]
+    AccountChangeset { transition_id: TransitionId, address: Address },
+    #[error("Storage {storage_key:?} of account {address:?} is indexed at transition {transition_id} but has no changeset")]
+    StorageChangeset { transition_id: TransitionId, address: Address, storage_key: H256 },
+    #[error("History of stage {stage} is indexed up to block #{indexed}, behind the executed block #{executed}")]
+    HistoryIndexBehind { stage: &'static str, indexed: BlockNumber, executed: BlockNumber },
+    #[error("Failed to compute the state root: {reason}")]
+    StateRoot { reason: String },
 }
@@ -1,6 +1,15 @@
 //! Traits used when interacting with the sync status of the network.
 
-use reth_primitives::BlockNumber;
+use crate::consensus::{self, ForkchoiceState};
+use reth_primitives::{Block, BlockNumber, SealedBlock, H256};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The future returned by the [ChainSync] for work that runs outside of the caller's task.
+pub type SyncFuture<T> = Pin<Box<dyn Future<Output = crate::Result<T>> + Send>>;
 
 /// A type that provides information about whether the node is currently syncing and the network is
 /// currently serving syncing related requests.
@@ -28,6 +37,37 @@ pub trait SyncStateUpdater: SyncStateProvider {
     fn update_sync_state(&self, state: SyncState);
 }
 
+/// The chain sync that is driven by the consensus layer.
+///
+/// The consensus layer starts a sync to every head that is not the local head. Blocks that extend
+/// the local head are committed directly instead while the chain is not syncing.
+pub trait ChainSync: SyncStateProvider {
+    /// Starts syncing the chain to the head of the given fork choice state.
+    ///
+    /// If the head is a known block that is not the local head, the chain is reorged to it. If a
+    /// sync is already running, it continues to the new head.
+    fn sync_to(&mut self, state: ForkchoiceState);
+
+    /// Executes the block on top of the local head, verifies its state root and commits it.
+    ///
+    /// The returned future resolves once the block is committed. It resolves to
+    /// [consensus::Error::ParentUnknown] if the block does not extend the local head, and to
+    /// [Error::Consensus][crate::Error::Consensus] or [Error::Execution][crate::Error::Execution]
+    /// if the block is invalid, in which case nothing is committed. Must not be called while the
+    /// chain is syncing.
+    fn commit_block(&mut self, block: SealedBlock) -> SyncFuture<()>;
+
+    /// Executes the block on top of the local head and computes the state root after it, without
+    /// committing anything.
+    ///
+    /// The state root in the header of the block is ignored. Fails like
+    /// [ChainSync::commit_block] otherwise.
+    fn state_root(&mut self, block: Block) -> SyncFuture<H256>;
+
+    /// Drives the running sync, returns [Poll::Ready] once it has finished.
+    fn poll_sync(&mut self, cx: &mut Context<'_>) -> Poll<()>;
+}
+
 /// The state the network is currently in when it comes to synchronization.
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum SyncState {
@@ -75,3 +115,34 @@ impl SyncStateProvider for NoopSyncStateUpdate {
 impl SyncStateUpdater for NoopSyncStateUpdate {
     fn update_sync_state(&self, _state: SyncState) {}
 }
+
+/// A [ChainSync] implementation that does nothing.
+///
+/// Blocks are never executed or committed, their parent is always considered unknown.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct NoopChainSync;
+
+impl SyncStateProvider for NoopChainSync {
+    fn is_syncing(&self) -> bool {
+        false
+    }
+}
+
+impl ChainSync for NoopChainSync {
+    fn sync_to(&mut self, _state: ForkchoiceState) {}
+
+    fn commit_block(&mut self, block: SealedBlock) -> SyncFuture<()> {
+        let err = consensus::Error::ParentUnknown { hash: block.parent_hash };
+        Box::pin(futures::future::ready(Err(err.into())))
+    }
+
+    fn state_root(&mut self, block: Block) -> SyncFuture<H256> {
+        let err = consensus::Error::ParentUnknown { hash: block.parent_hash };
+        Box::pin(futures::future::ready(Err(err.into())))
+    }
+
+    fn poll_sync(&mut self, _cx: &mut Context<'_>) -> Poll<()> {
+        Poll::Pending
+    }
+}
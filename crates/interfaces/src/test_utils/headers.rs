@@ -248,6 +248,10 @@ impl Consensus for TestConsensus {
         self.channel.1.clone()
     }
 
+    fn notify_fork_choice_state(&self, state: ForkchoiceState) {
+        self.channel.0.send(state).expect("updating fork choice state failed");
+    }
+
     fn validate_header(
         &self,
         _header: &SealedHeader,
@@ -40,6 +40,18 @@ pub static GOERLI_BOOTNODES : [&str; 7] = [
     "enode://d2b720352e8216c9efc470091aa91ddafc53e222b32780f505c817ceef69e01d5b0b0797b69db254c586f493872352f5a022b4d8479a00fc92ec55f9ad46a27e@88.99.70.182:30303",
 ];
 
+/// Ethereum Foundation DNS node list for mainnet, see <https://github.com/ethereum/discv4-dns-lists>
+pub static MAINNET_DNS_NETWORK: &str =
+    "enrtree://AKA3AM6LPBYEUDMVNU3BSVQJ5AD45Y7YPOHJLEF6W26QOE4VTUDPE@all.mainnet.ethdisco.net";
+
+/// Ethereum Foundation DNS node list for goerli, see <https://github.com/ethereum/discv4-dns-lists>
+pub static GOERLI_DNS_NETWORK: &str =
+    "enrtree://AKA3AM6LPBYEUDMVNU3BSVQJ5AD45Y7YPOHJLEF6W26QOE4VTUDPE@all.goerli.ethdisco.net";
+
+/// Ethereum Foundation DNS node list for sepolia, see <https://github.com/ethereum/discv4-dns-lists>
+pub static SEPOLIA_DNS_NETWORK: &str =
+    "enrtree://AKA3AM6LPBYEUDMVNU3BSVQJ5AD45Y7YPOHJLEF6W26QOE4VTUDPE@all.sepolia.ethdisco.net";
+
 /// Returns parsed mainnet nodes
 pub fn mainnet_nodes() -> Vec<NodeRecord> {
     parse_nodes(&MAINNET_BOOTNODES[..])
@@ -0,0 +1,108 @@
+//! A set of configuration parameters to tune the discv5 service.
+
+use bytes::{Bytes, BytesMut};
+use discv5::{Discv5ConfigBuilder as InnerConfigBuilder, Enr};
+use reth_rlp::Encodable;
+use std::{collections::HashMap, time::Duration};
+
+/// Configuration parameters of the discv5 service.
+#[derive(Clone, Debug)]
+pub struct Discv5Config {
+    /// The configuration of the underlying [discv5::Discv5] instance.
+    pub discv5_config: discv5::Discv5Config,
+    /// Nodes to boot from.
+    pub bootstrap_nodes: Vec<Enr>,
+    /// Whether to automatically lookup random nodes.
+    pub enable_lookup: bool,
+    /// The rate at which lookups should be triggered.
+    pub lookup_interval: Duration,
+    /// Additional pairs to include in the local [`Enr`] <https://eips.ethereum.org/EIPS/eip-868>
+    pub additional_eip868_rlp_pairs: HashMap<Vec<u8>, Bytes>,
+}
+
+impl Discv5Config {
+    /// Returns a new default builder instance
+    pub fn builder() -> Discv5ConfigBuilder {
+        Default::default()
+    }
+
+    /// Add another key value pair to include in the ENR
+    pub fn add_eip868_pair(&mut self, key: impl AsRef<[u8]>, value: impl Encodable) -> &mut Self {
+        let mut buf = BytesMut::new();
+        value.encode(&mut buf);
+        self.add_eip868_rlp_pair(key, buf.freeze())
+    }
+
+    /// Add another key value pair to include in the ENR
+    pub fn add_eip868_rlp_pair(&mut self, key: impl AsRef<[u8]>, rlp: Bytes) -> &mut Self {
+        self.additional_eip868_rlp_pairs.insert(key.as_ref().to_vec(), rlp);
+        self
+    }
+}
+
+impl Default for Discv5Config {
+    fn default() -> Self {
+        Self {
+            discv5_config: InnerConfigBuilder::new().build(),
+            bootstrap_nodes: Default::default(),
+            enable_lookup: true,
+            lookup_interval: Duration::from_secs(20),
+            additional_eip868_rlp_pairs: Default::default(),
+        }
+    }
+}
+
+/// Builder type for [`Discv5Config`]
+#[derive(Debug, Default)]
+pub struct Discv5ConfigBuilder {
+    config: Discv5Config,
+}
+
+impl Discv5ConfigBuilder {
+    /// Sets the configuration of the underlying [discv5::Discv5] instance.
+    pub fn discv5_config(&mut self, discv5_config: discv5::Discv5Config) -> &mut Self {
+        self.config.discv5_config = discv5_config;
+        self
+    }
+
+    /// Whether to automatically lookup
+    pub fn enable_lookup(&mut self, enable_lookup: bool) -> &mut Self {
+        self.config.enable_lookup = enable_lookup;
+        self
+    }
+
+    /// Sets the lookup interval duration.
+    pub fn lookup_interval(&mut self, lookup_interval: Duration) -> &mut Self {
+        self.config.lookup_interval = lookup_interval;
+        self
+    }
+
+    /// Add another key value pair to include in the ENR
+    pub fn add_eip868_pair(&mut self, key: impl AsRef<[u8]>, value: impl Encodable) -> &mut Self {
+        self.config.add_eip868_pair(key, value);
+        self
+    }
+
+    /// Add another key value pair to include in the ENR
+    pub fn add_eip868_rlp_pair(&mut self, key: impl AsRef<[u8]>, rlp: Bytes) -> &mut Self {
+        self.config.add_eip868_rlp_pair(key, rlp);
+        self
+    }
+
+    /// Adds a boot node
+    pub fn add_boot_node(&mut self, node: Enr) -> &mut Self {
+        self.config.bootstrap_nodes.push(node);
+        self
+    }
+
+    /// Adds multiple boot nodes
+    pub fn add_boot_nodes(&mut self, nodes: impl IntoIterator<Item = Enr>) -> &mut Self {
+        self.config.bootstrap_nodes.extend(nodes);
+        self
+    }
+
+    /// Returns the configured [`Discv5Config`]
+    pub fn build(&self) -> Discv5Config {
+        self.config.clone()
+    }
+}
@@ -0,0 +1,21 @@
+//! Error types that can occur in this crate.
+
+/// Errors that can occur when starting or interacting with the discv5 service.
+#[derive(Debug, thiserror::Error)]
+pub enum Discv5Error {
+    /// The secret key can not be used for the local ENR.
+    #[error("Invalid secret key")]
+    InvalidSecretKey,
+    /// Failed to bind the UDP socket.
+    #[error("Failed to bind UDP socket: {0}")]
+    Bind(std::io::Error),
+    /// Failed to build the local ENR.
+    #[error("Failed to build local ENR: {0}")]
+    Enr(String),
+    /// Failed to create the discv5 service.
+    #[error("Failed to create discv5 service: {0}")]
+    Init(&'static str),
+    /// Failed to start the discv5 service.
+    #[error("Failed to start discv5 service: {0:?}")]
+    Start(discv5::Discv5Error),
+}
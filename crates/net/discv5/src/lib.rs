@@ -0,0 +1,413 @@
+#![warn(missing_docs, unused_crate_dependencies)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Discovery v5 support: <https://github.com/ethereum/devp2p/blob/master/discv5/discv5.md>
+//!
+//! The wire protocol, including the encrypted sessions, the `WHOAREYOU` handshake and the
+//! kademlia table, is implemented by the [discv5] crate. This crate wraps it in a [`Discv5`] and
+//! [`Discv5Service`] pair, analogous to `reth_discv4`: the service periodically looks up random
+//! nodes via topic-free `FINDNODE` requests and turns all ENRs it learns about into
+//! [`DiscoveryUpdate`]s, the (optional) [`Discv5`] serves as the frontend to interact with the
+//! service.
+use crate::error::Discv5Error;
+use discv5::{
+    enr::{CombinedKey, EnrBuilder, EnrPublicKey, NodeId},
+    Discv5Event, Enr,
+};
+use reth_discv4::DiscoveryUpdate;
+use reth_primitives::{keccak256, ForkId, NodeRecord, PeerId};
+use reth_rlp::{Decodable, Encodable};
+use secp256k1::SecretKey;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt, io,
+    net::{IpAddr, SocketAddr, UdpSocket},
+    sync::Arc,
+};
+use tokio::{
+    sync::{mpsc, mpsc::error::TrySendError},
+    task::JoinHandle,
+    time::Interval,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, trace};
+
+pub mod error;
+
+mod config;
+pub use config::{Discv5Config, Discv5ConfigBuilder};
+
+/// The default port for discv5 via UDP
+///
+/// Note: this differs from the discv4 port, so that both protocols can run side by side, and from
+/// the port `9000` that consensus clients use for their discv5 service.
+pub const DEFAULT_DISCOVERY_V5_PORT: u16 = 9200;
+
+/// The frontend to interact with the discv5 service.
+#[derive(Clone)]
+pub struct Discv5 {
+    /// The address the UDP socket is bound to.
+    local_addr: SocketAddr,
+    /// The shared discv5 instance that is driven by the [`Discv5Service`].
+    inner: Arc<discv5::Discv5>,
+}
+
+// === impl Discv5 ===
+
+impl Discv5 {
+    /// Binds a new UDP socket and creates the discv5 service.
+    ///
+    /// The local ENR advertises the UDP port the socket is bound to and the TCP port of the
+    /// `local_node_record`, together with all configured EIP-868 pairs. The IP of the
+    /// `local_node_record` is only advertised if it is specified, otherwise it is left out until
+    /// discv5 learns the external address of the node from its peers.
+    ///
+    /// NOTE: The service is not yet spawned, see [`Discv5Service::spawn`].
+    pub async fn start(
+        local_address: SocketAddr,
+        local_node_record: NodeRecord,
+        secret_key: SecretKey,
+        config: Discv5Config,
+    ) -> Result<(Self, Discv5Service), Discv5Error> {
+        let enr_key = CombinedKey::secp256k1_from_bytes(&mut secret_key.secret_bytes())
+            .map_err(|_| Discv5Error::InvalidSecretKey)?;
+
+        // discv5 binds its own socket and doesn't expose it, so an unspecified port is resolved
+        // beforehand to advertise the port that is actually bound
+        let local_address = resolve_port(local_address).map_err(Discv5Error::Bind)?;
+
+        let local_enr = {
+            let mut builder = EnrBuilder::new("v4");
+            if !local_node_record.address.is_unspecified() {
+                builder.ip(local_node_record.address);
+            }
+            if local_node_record.address.is_ipv4() {
+                builder.udp4(local_address.port());
+                builder.tcp4(local_node_record.tcp_port);
+            } else {
+                builder.udp6(local_address.port());
+                builder.tcp6(local_node_record.tcp_port);
+            }
+
+            for (key, val) in config.additional_eip868_rlp_pairs.iter() {
+                builder.add_value_rlp(key, val.clone());
+            }
+
+            builder.build(&enr_key).map_err(|err| Discv5Error::Enr(err.to_string()))?
+        };
+
+        let mut discv5 = discv5::Discv5::new(local_enr, enr_key, config.discv5_config)
+            .map_err(Discv5Error::Init)?;
+        for enr in config.bootstrap_nodes {
+            if let Err(err) = discv5.add_enr(enr) {
+                debug!(target : "discv5", %err, "Failed to add boot node");
+            }
+        }
+
+        discv5.start(local_address).await.map_err(Discv5Error::Start)?;
+        let events = discv5.event_stream().await.map_err(Discv5Error::Start)?;
+        trace!(target : "discv5", ?local_address, "started discv5");
+
+        let discv5 = Arc::new(discv5);
+        let service = Discv5Service {
+            discv5: Arc::clone(&discv5),
+            events,
+            lookup_interval: config
+                .enable_lookup
+                .then(|| tokio::time::interval(config.lookup_interval)),
+            nodes: Default::default(),
+            update_listeners: Default::default(),
+        };
+        Ok((Self { local_addr: local_address, inner: discv5 }, service))
+    }
+
+    /// Returns the address of the UDP socket.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Returns the current local ENR of the node.
+    pub fn local_enr(&self) -> Enr {
+        self.inner.local_enr()
+    }
+
+    /// Adds the peer and id to the ban list.
+    ///
+    /// This will prevent any future inclusion in the table
+    pub fn ban(&self, peer_id: PeerId, ip: IpAddr) {
+        self.inner.ban_node(&node_id(peer_id), None);
+        self.inner.ban_ip(ip, None);
+    }
+
+    /// Adds the ip to the ban list.
+    ///
+    /// This will prevent any future inclusion in the table
+    pub fn ban_ip(&self, ip: IpAddr) {
+        self.inner.ban_ip(ip, None);
+    }
+
+    /// Sets the pair in the EIP-868 [`Enr`] of the node.
+    ///
+    /// If the key already exists, this will update it.
+    pub fn set_eip868_rlp(&self, key: &str, value: impl Encodable) {
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        if let Err(err) = self.inner.enr_insert(key, &buf) {
+            debug!(target : "discv5", key, ?err, "Failed to update local ENR");
+        }
+    }
+}
+
+impl fmt::Debug for Discv5 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Discv5")
+            .field("local_addr", &self.local_addr)
+            .field("local_enr", &self.local_enr())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Drives the lookups of the discv5 instance and reports all discovered nodes.
+pub struct Discv5Service {
+    /// The shared discv5 instance.
+    discv5: Arc<discv5::Discv5>,
+    /// All events emitted by the discv5 instance.
+    events: mpsc::Receiver<Discv5Event>,
+    /// The interval in which random nodes are looked up, if lookups are enabled.
+    lookup_interval: Option<Interval>,
+    /// All nodes that were reported, with their last known fork id.
+    nodes: HashMap<NodeId, (NodeRecord, Option<ForkId>)>,
+    /// All subscribers for table updates
+    update_listeners: Vec<mpsc::Sender<DiscoveryUpdate>>,
+}
+
+// === impl Discv5Service ===
+
+impl Discv5Service {
+    /// Spawns this service onto a new task
+    ///
+    /// Note: requires a running runtime
+    pub fn spawn(mut self) -> JoinHandle<()> {
+        tokio::task::spawn(async move {
+            loop {
+                tokio::select! {
+                    event = self.events.recv() => match event {
+                        Some(event) => self.on_event(event),
+                        None => {
+                            debug!(target : "discv5", "discv5 event stream closed");
+                            break
+                        }
+                    },
+                    _ = tick(&mut self.lookup_interval) => self.lookup(NodeId::random()),
+                }
+            }
+        })
+    }
+
+    /// Creates a new channel for [`DiscoveryUpdate`]s
+    pub fn update_stream(&mut self) -> ReceiverStream<DiscoveryUpdate> {
+        let (tx, rx) = mpsc::channel(512);
+        self.update_listeners.push(tx);
+        ReceiverStream::new(rx)
+    }
+
+    /// Looks up the nodes closest to the given target in the DHT.
+    ///
+    /// All ENRs that are learned during the lookup are reported via
+    /// [`Discv5Event::Discovered`].
+    pub fn lookup(&self, target: NodeId) {
+        let discv5 = Arc::clone(&self.discv5);
+        tokio::task::spawn(async move {
+            match discv5.find_node(target).await {
+                Ok(nodes) => {
+                    trace!(target : "discv5", ?target, found = nodes.len(), "lookup finished")
+                }
+                Err(err) => trace!(target : "discv5", ?target, ?err, "lookup failed"),
+            }
+        });
+    }
+
+    fn on_event(&mut self, event: Discv5Event) {
+        match event {
+            Discv5Event::Discovered(enr) |
+            Discv5Event::EnrAdded { enr, .. } |
+            Discv5Event::SessionEstablished(enr, _) => self.on_enr(enr),
+            Discv5Event::NodeInserted { replaced: Some(node_id), .. } => {
+                if let Some((record, _)) = self.nodes.remove(&node_id) {
+                    self.notify(DiscoveryUpdate::Removed(record.id))
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Reports the node of the ENR if it wasn't reported yet or if its fork id changed.
+    fn on_enr(&mut self, enr: Enr) {
+        let Some(record) = enr_to_node_record(&enr) else {
+            trace!(target : "discv5", ?enr, "skipping ENR without TCP socket");
+            return
+        };
+        let fork_id = eth_fork_id(&enr);
+
+        let mut updates = Vec::new();
+        match self.nodes.entry(enr.node_id()) {
+            Entry::Vacant(entry) => {
+                entry.insert((record, fork_id));
+                updates.push(DiscoveryUpdate::Added(record));
+                updates.extend(fork_id.map(|fork_id| DiscoveryUpdate::EnrForkId(record, fork_id)));
+            }
+            Entry::Occupied(mut entry) => {
+                if fork_id.is_some() && entry.get().1 != fork_id {
+                    entry.get_mut().1 = fork_id;
+                    updates
+                        .extend(fork_id.map(|fork_id| DiscoveryUpdate::EnrForkId(record, fork_id)));
+                }
+            }
+        }
+
+        for update in updates {
+            self.notify(update);
+        }
+    }
+
+    fn notify(&mut self, update: DiscoveryUpdate) {
+        self.update_listeners.retain_mut(|listener| match listener.try_send(update.clone()) {
+            Ok(()) => true,
+            Err(err) => match err {
+                TrySendError::Full(_) => true,
+                TrySendError::Closed(_) => false,
+            },
+        });
+    }
+}
+
+impl fmt::Debug for Discv5Service {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Discv5Service").field("nodes", &self.nodes.len()).finish_non_exhaustive()
+    }
+}
+
+/// Completes on the next tick of the interval, or never if there is no interval.
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Resolves port `0` of the address to the port the OS assigns when binding to it.
+fn resolve_port(addr: SocketAddr) -> io::Result<SocketAddr> {
+    if addr.port() != 0 {
+        return Ok(addr)
+    }
+    UdpSocket::bind(addr)?.local_addr()
+}
+
+/// Converts the [`Enr`] into a [`NodeRecord`].
+///
+/// Returns `None` if the ENR does not advertise a TCP socket, since such nodes can't be connected
+/// to via RLPx. IPv4 sockets are preferred over IPv6 sockets.
+pub fn enr_to_node_record(enr: &Enr) -> Option<NodeRecord> {
+    let id = PeerId::from_slice(&enr.public_key().encode_uncompressed());
+    let (address, tcp_port, udp_port) = match (enr.ip4(), enr.tcp4()) {
+        (Some(ip), Some(tcp_port)) => (IpAddr::from(ip), tcp_port, enr.udp4().unwrap_or(tcp_port)),
+        _ => {
+            let (ip, tcp_port) = (enr.ip6()?, enr.tcp6()?);
+            (IpAddr::from(ip), tcp_port, enr.udp6().unwrap_or(tcp_port))
+        }
+    };
+    Some(NodeRecord { address, tcp_port, udp_port, id })
+}
+
+/// Returns the [`ForkId`] of the `eth` entry of the [`Enr`], if set.
+///
+/// See also <https://github.com/ethereum/go-ethereum/blob/9244d5cd61f3ea5a7645fdf2a1a96d53421e412f/eth/protocols/eth/discovery.go#L36>
+pub fn eth_fork_id(enr: &Enr) -> Option<ForkId> {
+    let mut maybe_fork_id = enr.get("eth")?;
+    ForkId::decode(&mut maybe_fork_id).ok()
+}
+
+/// Returns the discv5 [`NodeId`] of the peer, which is the keccak256 hash of its public key.
+fn node_id(peer_id: PeerId) -> NodeId {
+    NodeId::new(&keccak256(peer_id).0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use rand::thread_rng;
+    use reth_primitives::{ForkHash, H256};
+    use secp256k1::SECP256K1;
+    use std::net::{Ipv4Addr, SocketAddrV4};
+
+    fn enr(secret_key: &SecretKey, fork_id: Option<ForkId>) -> Enr {
+        let key = CombinedKey::secp256k1_from_bytes(&mut secret_key.secret_bytes()).unwrap();
+        let mut builder = EnrBuilder::new("v4");
+        builder.ip4(Ipv4Addr::LOCALHOST).udp4(30304).tcp4(30303);
+        if let Some(fork_id) = fork_id {
+            let mut buf = BytesMut::new();
+            fork_id.encode(&mut buf);
+            builder.add_value_rlp("eth", buf.freeze());
+        }
+        builder.build(&key).unwrap()
+    }
+
+    #[test]
+    fn test_enr_to_node_record() {
+        let (secret_key, _) = SECP256K1.generate_keypair(&mut thread_rng());
+        let record = enr_to_node_record(&enr(&secret_key, None)).unwrap();
+
+        let expected = NodeRecord::from_secret_key(
+            SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 30303)),
+            &secret_key,
+        );
+        assert_eq!(record.id, expected.id);
+        assert_eq!(record.tcp_addr(), expected.tcp_addr());
+        assert_eq!(record.udp_port, 30304);
+        assert_eq!(node_id(record.id), enr(&secret_key, None).node_id());
+    }
+
+    #[test]
+    fn test_eth_fork_id() {
+        let (secret_key, _) = SECP256K1.generate_keypair(&mut thread_rng());
+        assert_eq!(eth_fork_id(&enr(&secret_key, None)), None);
+
+        let fork_id = ForkId { hash: ForkHash(H256::random()[..4].try_into().unwrap()), next: 1 };
+        assert_eq!(eth_fork_id(&enr(&secret_key, Some(fork_id))), Some(fork_id));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_start() {
+        let (secret_key, _) = SECP256K1.generate_keypair(&mut thread_rng());
+        let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0));
+        let record = NodeRecord::from_secret_key(addr, &secret_key);
+        let (discv5, mut service) =
+            Discv5::start(addr, record, secret_key, Default::default()).await.unwrap();
+        let _updates = service.update_stream();
+        let _handle = service.spawn();
+
+        let local_enr = discv5.local_enr();
+        assert_eq!(enr_to_node_record(&local_enr).unwrap().id, record.id);
+        assert_ne!(discv5.local_addr().port(), 0);
+        assert_eq!(local_enr.udp4(), Some(discv5.local_addr().port()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_start_unspecified_address() {
+        let (secret_key, _) = SECP256K1.generate_keypair(&mut thread_rng());
+        let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+        let record = NodeRecord::from_secret_key(addr, &secret_key);
+        let (discv5, _service) =
+            Discv5::start(addr, record, secret_key, Default::default()).await.unwrap();
+
+        // the address is unknown, so no IP is advertised
+        assert_eq!(discv5.local_enr().ip4(), None);
+    }
+}
@@ -0,0 +1,34 @@
+//! A set of configuration parameters to tune the DNS discovery service.
+
+use crate::tree::LinkEntry;
+use std::{collections::HashSet, time::Duration};
+
+/// Settings for the [DnsDiscoveryService](crate::DnsDiscoveryService).
+#[derive(Clone, Debug)]
+pub struct DnsDiscoveryConfig {
+    /// Timeout for DNS lookups.
+    ///
+    /// Default: 5s
+    pub lookup_timeout: Duration,
+    /// The rate at which trees should be re-checked for updates.
+    ///
+    /// Default: 30min
+    pub recheck_interval: Duration,
+    /// The maximum number of lookups of a tree that are in flight at the same time.
+    ///
+    /// Default: 10
+    pub max_concurrent_requests: usize,
+    /// Links to the DNS networks to bootstrap.
+    pub bootstrap_dns_networks: Option<HashSet<LinkEntry>>,
+}
+
+impl Default for DnsDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            lookup_timeout: Duration::from_secs(5),
+            recheck_interval: Duration::from_secs(60 * 30),
+            max_concurrent_requests: 10,
+            bootstrap_dns_networks: Some(Default::default()),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Error types that can occur in this crate.
+
+use crate::tree::TreeRootEntry;
+
+/// Errors that can occur when parsing a [`DnsEntry`](crate::tree::DnsEntry).
+#[derive(Debug, thiserror::Error)]
+pub enum ParseDnsEntryError {
+    /// The entry does not start with any of the known prefixes.
+    #[error("Unknown entry: {0}")]
+    UnknownEntry(String),
+    /// A required field of the entry is missing.
+    #[error("Field {0} not found")]
+    FieldNotFound(&'static str),
+    /// A base64 encoded value could not be decoded.
+    #[error("Base64 decoding failed: {0}")]
+    Base64DecodeError(String),
+    /// A base32 encoded value could not be decoded.
+    #[error("Base32 decoding failed: {0}")]
+    Base32DecodeError(String),
+    /// The ENR of a node entry is invalid.
+    #[error("Invalid ENR: {0}")]
+    InvalidEnr(String),
+    /// A child hash of a branch entry is invalid.
+    #[error("Invalid child hash in branch: {0}")]
+    InvalidChildHash(String),
+    /// Any other error.
+    #[error("{0}")]
+    Other(String),
+}
+
+/// Errors that can occur when looking up an entry of a tree.
+#[derive(Debug, thiserror::Error)]
+pub enum LookupError {
+    /// The resolved entry is invalid.
+    #[error(transparent)]
+    Parse(#[from] ParseDnsEntryError),
+    /// The signature of the root does not match the public key of the link.
+    #[error("Failed to verify root {0}")]
+    InvalidRoot(TreeRootEntry),
+    /// The request did not finish in time.
+    #[error("Request timed out")]
+    RequestTimedOut,
+    /// No TXT record exists for the queried name.
+    #[error("Entry not found: {0}")]
+    EntryNotFound(String),
+    /// The hash of the resolved entry does not match the queried hash.
+    #[error("Hash mismatch, expected {expected}")]
+    HashMismatch {
+        /// The hash that was queried.
+        expected: String,
+    },
+}
@@ -0,0 +1,489 @@
+#![warn(missing_docs, unused_crate_dependencies)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Implementation of [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459) Node Discovery via DNS.
+//!
+//! The [`DnsDiscoveryService`] resolves the signed root of every tree it is asked to sync, walks
+//! all branches of the tree and reports every node record it finds via
+//! [`DnsDiscoveryService::node_record_stream`]. Links to other trees are followed. The
+//! [`DnsDiscoveryHandle`] serves as the frontend to request new trees.
+//!
+//! DNS lookups are performed by a [`Resolver`], which can be swapped for the in-memory
+//! [`MapResolver`] in tests.
+
+use crate::{
+    error::{LookupError, ParseDnsEntryError},
+    tree::{hash_entry, DnsEntry, LinkEntry, TreeRootEntry},
+};
+use enr::Enr;
+use futures::{stream, StreamExt};
+use reth_primitives::{ForkId, NodeRecord};
+use secp256k1::SecretKey;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, mpsc::error::TrySendError},
+    task::JoinHandle,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, trace};
+
+pub mod config;
+pub mod error;
+pub mod resolver;
+pub mod tree;
+
+pub use config::DnsDiscoveryConfig;
+pub use resolver::{DnsResolver, MapResolver, Resolver};
+
+/// A node that was discovered via DNS.
+#[derive(Debug, Clone)]
+pub struct DnsNodeRecordUpdate {
+    /// The discovered node.
+    pub node_record: NodeRecord,
+    /// The [`ForkId`] of the `eth` entry of the node's [`Enr`], if set.
+    pub fork_id: Option<ForkId>,
+    /// The [`Enr`] of the node.
+    pub enr: Enr<SecretKey>,
+}
+
+/// The frontend to interact with the [`DnsDiscoveryService`].
+#[derive(Debug, Clone)]
+pub struct DnsDiscoveryHandle {
+    to_service: mpsc::UnboundedSender<DnsDiscoveryCommand>,
+}
+
+impl DnsDiscoveryHandle {
+    /// Starts syncing the tree of the given `enrtree://<key>@<domain>` link.
+    pub fn sync_tree(&self, link: &str) -> Result<(), ParseDnsEntryError> {
+        self.sync_tree_with_link(link.parse()?);
+        Ok(())
+    }
+
+    /// Starts syncing the tree of the given [`LinkEntry`].
+    pub fn sync_tree_with_link(&self, link: LinkEntry) {
+        let _ = self.to_service.send(DnsDiscoveryCommand::SyncTree(link));
+    }
+}
+
+/// Syncs the configured trees and reports all discovered nodes.
+pub struct DnsDiscoveryService<R: Resolver = DnsResolver> {
+    /// Sender half of the command channel, used to queue linked trees.
+    command_tx: mpsc::UnboundedSender<DnsDiscoveryCommand>,
+    /// Receiver half of the command channel.
+    command_rx: mpsc::UnboundedReceiver<DnsDiscoveryCommand>,
+    /// All subscribers of discovered nodes.
+    node_record_listeners: Vec<mpsc::Sender<DnsNodeRecordUpdate>>,
+    /// All trees that are synced.
+    trees: HashMap<LinkEntry, SyncTree>,
+    /// The resolver used for all lookups.
+    resolver: Arc<R>,
+    /// The settings of the service.
+    config: DnsDiscoveryConfig,
+}
+
+impl<R: Resolver> DnsDiscoveryService<R> {
+    /// Creates a new service and the handle to interact with it.
+    pub fn new_pair(resolver: Arc<R>, config: DnsDiscoveryConfig) -> (Self, DnsDiscoveryHandle) {
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        let handle = DnsDiscoveryHandle { to_service: command_tx.clone() };
+        let service = Self {
+            command_tx,
+            command_rx,
+            node_record_listeners: Default::default(),
+            trees: Default::default(),
+            resolver,
+            config,
+        };
+        (service, handle)
+    }
+
+    /// Creates a new channel for [`DnsNodeRecordUpdate`]s
+    pub fn node_record_stream(&mut self) -> ReceiverStream<DnsNodeRecordUpdate> {
+        let (tx, rx) = mpsc::channel(256);
+        self.node_record_listeners.push(tx);
+        ReceiverStream::new(rx)
+    }
+
+    /// Spawns the service onto a new task.
+    ///
+    /// This first syncs all bootstrap trees and then re-checks all trees in the configured
+    /// interval.
+    pub fn spawn(mut self) -> JoinHandle<()> {
+        tokio::task::spawn(async move {
+            for link in self.config.bootstrap_dns_networks.take().unwrap_or_default() {
+                self.sync_tree(link).await;
+            }
+
+            let mut recheck = tokio::time::interval(self.config.recheck_interval);
+            // the first tick completes immediately
+            recheck.tick().await;
+
+            loop {
+                tokio::select! {
+                    cmd = self.command_rx.recv() => match cmd {
+                        Some(DnsDiscoveryCommand::SyncTree(link)) => self.sync_tree(link).await,
+                        // unreachable, since the service holds a sender
+                        None => break,
+                    },
+                    _ = recheck.tick() => {
+                        let links = self.trees.keys().cloned().collect::<Vec<_>>();
+                        for link in links {
+                            self.sync_tree(link).await;
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Resolves the root of the tree and walks it, if the root changed since the last sync.
+    async fn sync_tree(&mut self, link: LinkEntry) {
+        let root = match lookup_root(&*self.resolver, &link, self.config.lookup_timeout).await {
+            Ok(root) => root,
+            Err(err) => {
+                debug!(target: "disc::dns", %err, %link, "failed to resolve tree root");
+                return
+            }
+        };
+
+        let tree = self.trees.entry(link.clone()).or_default();
+        if tree.is_synced(&root) {
+            trace!(target: "disc::dns", %link, seq = root.sequence_number, "tree unchanged");
+            return
+        }
+        if let Some(last) =
+            tree.root.as_ref().filter(|last| root.sequence_number < last.sequence_number)
+        {
+            debug!(target: "disc::dns", %link, seq = root.sequence_number, last_seq = last.sequence_number, "ignoring outdated tree root");
+            return
+        }
+        trace!(target: "disc::dns", %link, seq = root.sequence_number, "syncing tree");
+
+        let mut complete = true;
+        let mut pending =
+            vec![(root.enr_root.clone(), SubTree::Enr), (root.link_root.clone(), SubTree::Link)];
+        // walks the tree level by level, leaves are content addressed so leaves that were resolved
+        // by a previous sync are skipped. Branches are always resolved, so that the leaves that
+        // failed to resolve during a previous sync are retried.
+        while !pending.is_empty() {
+            let tree = self.trees.get_mut(&link).expect("tree exists");
+            pending.retain(|(hash, _)| !tree.resolved.contains(hash));
+
+            let resolver = Arc::clone(&self.resolver);
+            let domain = link.domain.clone();
+            let timeout = self.config.lookup_timeout;
+            let entries = stream::iter(pending.drain(..))
+                .map(|(hash, kind)| {
+                    let resolver = Arc::clone(&resolver);
+                    let domain = domain.clone();
+                    async move {
+                        let entry = lookup_entry(&*resolver, &domain, &hash, timeout).await;
+                        (hash, kind, entry)
+                    }
+                })
+                .buffer_unordered(self.config.max_concurrent_requests)
+                .collect::<Vec<_>>()
+                .await;
+
+            for (hash, kind, entry) in entries {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(err) => {
+                        debug!(target: "disc::dns", %err, %hash, %link, "failed to resolve entry");
+                        complete = false;
+                        continue
+                    }
+                };
+
+                match (entry, kind) {
+                    (DnsEntry::Branch(branch), kind) => {
+                        pending.extend(branch.children.into_iter().map(|child| (child, kind)));
+                    }
+                    (DnsEntry::Node(node), SubTree::Enr) => {
+                        self.trees.get_mut(&link).expect("tree exists").resolved.insert(hash);
+                        self.notify(DnsNodeRecordUpdate {
+                            node_record: node.node_record,
+                            fork_id: node.fork_id,
+                            enr: node.enr,
+                        });
+                    }
+                    (DnsEntry::Link(linked), SubTree::Link) => {
+                        self.trees.get_mut(&link).expect("tree exists").resolved.insert(hash);
+                        if !self.trees.contains_key(&linked) {
+                            let _ = self.command_tx.send(DnsDiscoveryCommand::SyncTree(linked));
+                        }
+                    }
+                    (entry, _) => {
+                        debug!(target: "disc::dns", %entry, %hash, %link, "unexpected entry in tree");
+                    }
+                }
+            }
+        }
+
+        let tree = self.trees.get_mut(&link).expect("tree exists");
+        tree.root = Some(root);
+        tree.complete = complete;
+    }
+
+    /// Sends the update to all listeners.
+    ///
+    /// Removes listeners that are closed.
+    fn notify(&mut self, update: DnsNodeRecordUpdate) {
+        self.node_record_listeners.retain_mut(|listener| match listener.try_send(update.clone()) {
+            Ok(()) => true,
+            Err(err) => match err {
+                TrySendError::Full(_) => true,
+                TrySendError::Closed(_) => false,
+            },
+        });
+    }
+}
+
+impl<R: Resolver> fmt::Debug for DnsDiscoveryService<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DnsDiscoveryService")
+            .field("trees", &self.trees.len())
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The state of a synced tree.
+#[derive(Debug, Default)]
+struct SyncTree {
+    /// The root of the last sync.
+    root: Option<TreeRootEntry>,
+    /// Whether all entries could be resolved during the last sync.
+    complete: bool,
+    /// Hashes of all leaf entries that were resolved.
+    resolved: HashSet<String>,
+}
+
+impl SyncTree {
+    /// Returns true if the tree was completely synced with the given root.
+    fn is_synced(&self, root: &TreeRootEntry) -> bool {
+        self.complete && self.root.as_ref() == Some(root)
+    }
+}
+
+/// The subtrees of a tree.
+#[derive(Debug, Clone, Copy)]
+enum SubTree {
+    /// The subtree that contains node records.
+    Enr,
+    /// The subtree that contains links to other trees.
+    Link,
+}
+
+/// Commands that can be sent to the [`DnsDiscoveryService`].
+enum DnsDiscoveryCommand {
+    /// Sync the tree of the link.
+    SyncTree(LinkEntry),
+}
+
+/// Resolves the root of the linked tree and verifies its signature.
+async fn lookup_root<R: Resolver + ?Sized>(
+    resolver: &R,
+    link: &LinkEntry,
+    timeout: Duration,
+) -> Result<TreeRootEntry, LookupError> {
+    let txt = lookup_txt(resolver, &link.domain, timeout).await?;
+    let root: TreeRootEntry = txt.parse()?;
+    if !root.verify(&link.pubkey) {
+        return Err(LookupError::InvalidRoot(root))
+    }
+    Ok(root)
+}
+
+/// Resolves the entry with the given hash and verifies that its content matches the hash.
+async fn lookup_entry<R: Resolver + ?Sized>(
+    resolver: &R,
+    domain: &str,
+    hash: &str,
+    timeout: Duration,
+) -> Result<DnsEntry, LookupError> {
+    let txt = lookup_txt(resolver, &format!("{hash}.{domain}"), timeout).await?;
+    if !hash_entry(&txt).eq_ignore_ascii_case(hash) {
+        return Err(LookupError::HashMismatch { expected: hash.to_string() })
+    }
+    Ok(txt.parse()?)
+}
+
+async fn lookup_txt<R: Resolver + ?Sized>(
+    resolver: &R,
+    query: &str,
+    timeout: Duration,
+) -> Result<String, LookupError> {
+    tokio::time::timeout(timeout, resolver.lookup_txt(query))
+        .await
+        .map_err(|_| LookupError::RequestTimedOut)?
+        .ok_or_else(|| LookupError::EntryNotFound(query.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::{BranchEntry, NodeEntry};
+    use enr::EnrBuilder;
+    use rand::thread_rng;
+    use secp256k1::{PublicKey, SECP256K1};
+    use std::net::Ipv4Addr;
+
+    const DOMAIN: &str = "nodes.example.org";
+
+    /// Publishes a tree with the given nodes and links and returns its link.
+    fn publish_tree(
+        resolver: &MapResolver,
+        domain: &str,
+        nodes: &[Enr<SecretKey>],
+        links: &[LinkEntry],
+        seq: u64,
+    ) -> LinkEntry {
+        let secret_key = SecretKey::new(&mut thread_rng());
+        publish_signed_tree(resolver, domain, nodes, links, seq, &secret_key)
+    }
+
+    /// Publishes a tree signed with the given key and returns its link.
+    fn publish_signed_tree(
+        resolver: &MapResolver,
+        domain: &str,
+        nodes: &[Enr<SecretKey>],
+        links: &[LinkEntry],
+        seq: u64,
+        secret_key: &SecretKey,
+    ) -> LinkEntry {
+        let pubkey = PublicKey::from_secret_key(SECP256K1, secret_key);
+        let mut publish = |entry: String| {
+            let hash = hash_entry(&entry);
+            resolver.insert(format!("{hash}.{domain}"), entry);
+            hash
+        };
+
+        let children =
+            nodes.iter().map(|enr| publish(NodeEntry::new(enr.clone()).unwrap().to_string()));
+        let enr_root = publish(BranchEntry { children: children.collect() }.to_string());
+        let children = links.iter().map(|link| publish(link.to_string()));
+        let link_root = publish(BranchEntry { children: children.collect() }.to_string());
+
+        let mut root =
+            TreeRootEntry { enr_root, link_root, sequence_number: seq, signature: vec![] };
+        root.sign(secret_key);
+        resolver.insert(domain, root.to_string());
+
+        LinkEntry { domain: domain.to_string(), pubkey }
+    }
+
+    fn enr(tcp_port: u16) -> Enr<SecretKey> {
+        let secret_key = SecretKey::new(&mut thread_rng());
+        EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::LOCALHOST)
+            .tcp4(tcp_port)
+            .udp4(tcp_port)
+            .build(&secret_key)
+            .unwrap()
+    }
+
+    fn service(resolver: Arc<MapResolver>) -> DnsDiscoveryService<MapResolver> {
+        DnsDiscoveryService::new_pair(resolver, Default::default()).0
+    }
+
+    #[tokio::test]
+    async fn sync_tree() {
+        let resolver = Arc::new(MapResolver::default());
+        let nodes = vec![enr(30303), enr(30304), enr(30305)];
+        let link = publish_tree(&resolver, DOMAIN, &nodes, &[], 1);
+
+        let mut service = service(Arc::clone(&resolver));
+        let mut updates = service.node_record_stream();
+        service.sync_tree(link.clone()).await;
+
+        let mut ports = HashSet::new();
+        for _ in 0..nodes.len() {
+            let update = updates.next().await.unwrap();
+            assert_eq!(update.node_record.address, Ipv4Addr::LOCALHOST);
+            ports.insert(update.node_record.tcp_port);
+        }
+        assert_eq!(ports, HashSet::from([30303, 30304, 30305]));
+        assert!(service.trees[&link].complete);
+
+        // unchanged tree is not walked again
+        service.sync_tree(link).await;
+        assert!(tokio::time::timeout(Duration::from_millis(50), updates.next()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_outdated_root() {
+        let resolver = Arc::new(MapResolver::default());
+        let secret_key = SecretKey::new(&mut thread_rng());
+        let link = publish_signed_tree(&resolver, DOMAIN, &[enr(30303)], &[], 2, &secret_key);
+
+        let mut service = service(Arc::clone(&resolver));
+        let mut updates = service.node_record_stream();
+        service.sync_tree(link.clone()).await;
+        assert_eq!(updates.next().await.unwrap().node_record.tcp_port, 30303);
+
+        // a root with a lower sequence number is not synced
+        publish_signed_tree(&resolver, DOMAIN, &[enr(30304)], &[], 1, &secret_key);
+        service.sync_tree(link.clone()).await;
+        assert!(tokio::time::timeout(Duration::from_millis(50), updates.next()).await.is_err());
+        assert_eq!(service.trees[&link].root.as_ref().unwrap().sequence_number, 2);
+    }
+
+    #[tokio::test]
+    async fn follows_links() {
+        let resolver = Arc::new(MapResolver::default());
+        let linked = publish_tree(&resolver, "linked.example.org", &[enr(30303)], &[], 1);
+        let link = publish_tree(&resolver, DOMAIN, &[], &[linked.clone()], 1);
+
+        let (mut service, _handle) =
+            DnsDiscoveryService::new_pair(Arc::clone(&resolver), Default::default());
+        let mut updates = service.node_record_stream();
+        service.sync_tree(link).await;
+
+        let Some(DnsDiscoveryCommand::SyncTree(next)) = service.command_rx.recv().await else {
+            panic!("expected linked tree")
+        };
+        assert_eq!(next, linked);
+        service.sync_tree(next).await;
+        assert_eq!(updates.next().await.unwrap().node_record.tcp_port, 30303);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_root() {
+        let resolver = Arc::new(MapResolver::default());
+        let mut link = publish_tree(&resolver, DOMAIN, &[enr(30303)], &[], 1);
+        link.pubkey = PublicKey::from_secret_key(SECP256K1, &SecretKey::new(&mut thread_rng()));
+
+        let err = lookup_root(&*resolver, &link, Duration::from_secs(1)).await.unwrap_err();
+        assert!(matches!(err, LookupError::InvalidRoot(_)));
+
+        let mut service = service(resolver);
+        service.sync_tree(link.clone()).await;
+        assert!(!service.trees.contains_key(&link));
+    }
+
+    #[tokio::test]
+    async fn spawned_service_syncs_bootstrap_networks() {
+        let resolver = Arc::new(MapResolver::default());
+        let link = publish_tree(&resolver, DOMAIN, &[enr(30303)], &[], 1);
+
+        let config = DnsDiscoveryConfig {
+            bootstrap_dns_networks: Some(HashSet::from([link])),
+            ..Default::default()
+        };
+        let (mut service, _handle) = DnsDiscoveryService::new_pair(resolver, config);
+        let mut updates = service.node_record_stream();
+        let _service = service.spawn();
+
+        assert_eq!(updates.next().await.unwrap().node_record.tcp_port, 30303);
+    }
+}
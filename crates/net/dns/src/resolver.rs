@@ -0,0 +1,85 @@
+//! Perform DNS lookups
+
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use tracing::trace;
+pub use trust_dns_resolver::{error::ResolveError, TokioAsyncResolver};
+
+/// A type that can lookup DNS entries
+#[async_trait]
+pub trait Resolver: Send + Sync + 'static {
+    /// Performs a textual lookup and returns the first text
+    async fn lookup_txt(&self, query: &str) -> Option<String>;
+}
+
+#[async_trait]
+impl Resolver for TokioAsyncResolver {
+    async fn lookup_txt(&self, query: &str) -> Option<String> {
+        // See: [AsyncResolver::txt_lookup]
+        // > *hint* queries that end with a '.' are fully qualified names and are cheaper lookups
+        let fqn = if query.ends_with('.') { query.to_string() } else { format!("{query}.") };
+        match self.txt_lookup(fqn).await {
+            Err(err) => {
+                trace!(target: "disc::dns", %err, ?query, "dns lookup failed");
+                None
+            }
+            Ok(lookup) => {
+                let txt = lookup.into_iter().next()?;
+                // a TXT record may be split into multiple strings of at most 255 bytes
+                let entry: Vec<u8> =
+                    txt.txt_data().iter().flat_map(|data| data.iter()).copied().collect();
+                String::from_utf8(entry).ok()
+            }
+        }
+    }
+}
+
+/// A [Resolver] that uses the system's DNS configuration.
+#[derive(Clone)]
+pub struct DnsResolver(TokioAsyncResolver);
+
+impl DnsResolver {
+    /// Creates a new resolver from the system configuration, e.g. `/etc/resolv.conf` on unix.
+    pub fn from_system_conf() -> Result<Self, ResolveError> {
+        TokioAsyncResolver::tokio_from_system_conf().map(Self)
+    }
+}
+
+impl std::fmt::Debug for DnsResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DnsResolver").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl Resolver for DnsResolver {
+    async fn lookup_txt(&self, query: &str) -> Option<String> {
+        Resolver::lookup_txt(&self.0, query).await
+    }
+}
+
+/// An in-memory [Resolver] that maps names to TXT entries.
+///
+/// This is intended for testing or for serving a tree that is not published via DNS.
+#[derive(Debug, Default)]
+pub struct MapResolver(RwLock<HashMap<String, String>>);
+
+impl MapResolver {
+    /// Inserts the entry for the name, returning the previous entry, if any.
+    pub fn insert(&self, name: impl Into<String>, entry: impl Into<String>) -> Option<String> {
+        self.0.write().insert(name.into(), entry.into())
+    }
+
+    /// Removes the entry of the name.
+    pub fn remove(&self, name: &str) -> Option<String> {
+        self.0.write().remove(name)
+    }
+}
+
+#[async_trait]
+impl Resolver for MapResolver {
+    async fn lookup_txt(&self, query: &str) -> Option<String> {
+        self.0.read().get(query).cloned()
+    }
+}
@@ -0,0 +1,366 @@
+//! Support for the entries of an [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459) merkle tree.
+//!
+//! A tree is published as TXT records of a domain:
+//!
+//! ```text
+//! <domain>         enrtree-root:v1 e=<enr-root> l=<link-root> seq=<sequence-number> sig=<signature>
+//! <hash>.<domain>  enrtree-branch:<h₁>,<h₂>,...,<hₙ>
+//! <hash>.<domain>  enr:<node-record>
+//! <hash>.<domain>  enrtree://<key>@<fqdn>
+//! ```
+//!
+//! where `<hash>` is the base32 encoding of the first 16 bytes of the keccak256 hash of the entry.
+
+use crate::error::ParseDnsEntryError;
+use data_encoding::{BASE32_NOPAD, BASE64URL_NOPAD};
+use enr::Enr;
+use reth_primitives::{keccak256, ForkId, NodeRecord, PeerId};
+use reth_rlp::Decodable;
+use secp256k1::{ecdsa::Signature, Message, PublicKey, SecretKey, SECP256K1};
+use std::{fmt, net::IpAddr, str::FromStr};
+
+/// Prefix of the root entry.
+pub const ROOT_V1_PREFIX: &str = "enrtree-root:v1";
+/// Prefix of a link entry.
+pub const LINK_PREFIX: &str = "enrtree://";
+/// Prefix of a branch entry.
+pub const BRANCH_PREFIX: &str = "enrtree-branch:";
+/// Prefix of a node entry.
+pub const ENR_PREFIX: &str = "enr:";
+
+/// Returns the hash under which the entry is published: the base32 encoding of the first 16 bytes
+/// of the keccak256 hash of the entry.
+pub fn hash_entry(entry: &str) -> String {
+    BASE32_NOPAD.encode(&keccak256(entry.as_bytes())[..16])
+}
+
+/// Any entry of a tree.
+#[derive(Debug, Clone)]
+pub enum DnsEntry {
+    /// The signed root of the tree.
+    Root(TreeRootEntry),
+    /// A link to another tree.
+    Link(LinkEntry),
+    /// An intermediate node of the tree.
+    Branch(BranchEntry),
+    /// A leaf with the record of a node.
+    Node(NodeEntry),
+}
+
+impl FromStr for DnsEntry {
+    type Err = ParseDnsEntryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with(ROOT_V1_PREFIX) {
+            s.parse().map(DnsEntry::Root)
+        } else if s.starts_with(LINK_PREFIX) {
+            s.parse().map(DnsEntry::Link)
+        } else if s.starts_with(BRANCH_PREFIX) {
+            s.parse().map(DnsEntry::Branch)
+        } else if s.starts_with(ENR_PREFIX) {
+            s.parse().map(DnsEntry::Node)
+        } else {
+            Err(ParseDnsEntryError::UnknownEntry(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for DnsEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DnsEntry::Root(entry) => entry.fmt(f),
+            DnsEntry::Link(entry) => entry.fmt(f),
+            DnsEntry::Branch(entry) => entry.fmt(f),
+            DnsEntry::Node(entry) => entry.fmt(f),
+        }
+    }
+}
+
+/// The signed root of a tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeRootEntry {
+    /// The root hash of the subtree that contains the node records.
+    pub enr_root: String,
+    /// The root hash of the subtree that contains the links to other trees.
+    pub link_root: String,
+    /// Incremented on every update of the tree.
+    pub sequence_number: u64,
+    /// The 65 byte `[R || S || V]` signature of the keccak256 hash of the [content](Self::content).
+    pub signature: Vec<u8>,
+}
+
+impl TreeRootEntry {
+    /// Returns the signed part of the entry.
+    pub fn content(&self) -> String {
+        format!(
+            "{} e={} l={} seq={}",
+            ROOT_V1_PREFIX, self.enr_root, self.link_root, self.sequence_number
+        )
+    }
+
+    /// Signs the entry with the given key.
+    pub fn sign(&mut self, key: &SecretKey) {
+        let msg = Message::from_slice(keccak256(self.content().as_bytes()).as_bytes())
+            .expect("is correct MESSAGE_SIZE; qed");
+        let (rec, sig) = SECP256K1.sign_ecdsa_recoverable(&msg, key).serialize_compact();
+        let mut signature = sig.to_vec();
+        signature.push(rec.to_i32() as u8);
+        self.signature = signature;
+    }
+
+    /// Returns true if the entry was signed by the given key.
+    pub fn verify(&self, pubkey: &PublicKey) -> bool {
+        if self.signature.len() != 65 {
+            return false
+        }
+        let Ok(sig) = Signature::from_compact(&self.signature[..64]) else { return false };
+        let msg = Message::from_slice(keccak256(self.content().as_bytes()).as_bytes())
+            .expect("is correct MESSAGE_SIZE; qed");
+        SECP256K1.verify_ecdsa(&msg, &sig, pubkey).is_ok()
+    }
+}
+
+impl FromStr for TreeRootEntry {
+    type Err = ParseDnsEntryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix(ROOT_V1_PREFIX)
+            .ok_or_else(|| ParseDnsEntryError::UnknownEntry(s.to_string()))?;
+
+        let mut fields = s.split_whitespace();
+        let mut field = |name: &'static str| {
+            fields
+                .next()
+                .and_then(|field| field.strip_prefix(name))
+                .and_then(|field| field.strip_prefix('='))
+                .ok_or(ParseDnsEntryError::FieldNotFound(name))
+        };
+
+        let enr_root = field("e")?.to_string();
+        let link_root = field("l")?.to_string();
+        let sequence_number = field("seq")?
+            .parse()
+            .map_err(|err| ParseDnsEntryError::Other(format!("invalid sequence number: {err}")))?;
+        let signature = BASE64URL_NOPAD
+            .decode(field("sig")?.as_bytes())
+            .map_err(|err| ParseDnsEntryError::Base64DecodeError(err.to_string()))?;
+
+        Ok(Self { enr_root, link_root, sequence_number, signature })
+    }
+}
+
+impl fmt::Display for TreeRootEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} sig={}", self.content(), BASE64URL_NOPAD.encode(&self.signature))
+    }
+}
+
+/// A link to another tree, `enrtree://<key>@<domain>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LinkEntry {
+    /// The domain of the linked tree.
+    pub domain: String,
+    /// The key the root of the linked tree is signed with.
+    pub pubkey: PublicKey,
+}
+
+impl FromStr for LinkEntry {
+    type Err = ParseDnsEntryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix(LINK_PREFIX)
+            .ok_or_else(|| ParseDnsEntryError::UnknownEntry(s.to_string()))?;
+        let (pubkey, domain) =
+            s.split_once('@').ok_or(ParseDnsEntryError::FieldNotFound("domain"))?;
+        if domain.is_empty() {
+            return Err(ParseDnsEntryError::FieldNotFound("domain"))
+        }
+        let pubkey = BASE32_NOPAD
+            .decode(pubkey.as_bytes())
+            .map_err(|err| ParseDnsEntryError::Base32DecodeError(err.to_string()))?;
+        let pubkey = PublicKey::from_slice(&pubkey)
+            .map_err(|err| ParseDnsEntryError::Other(format!("invalid public key: {err}")))?;
+
+        Ok(Self { domain: domain.to_string(), pubkey })
+    }
+}
+
+impl fmt::Display for LinkEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}@{}",
+            LINK_PREFIX,
+            BASE32_NOPAD.encode(&self.pubkey.serialize()),
+            self.domain
+        )
+    }
+}
+
+/// An intermediate node of a tree, `enrtree-branch:<h₁>,<h₂>,...,<hₙ>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchEntry {
+    /// The hashes of the child entries.
+    pub children: Vec<String>,
+}
+
+impl FromStr for BranchEntry {
+    type Err = ParseDnsEntryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix(BRANCH_PREFIX)
+            .ok_or_else(|| ParseDnsEntryError::UnknownEntry(s.to_string()))?;
+
+        let children = s
+            .trim()
+            .split(',')
+            .filter(|child| !child.is_empty())
+            .map(|child| {
+                // every hash is the base32 encoding of 16 bytes
+                let valid = BASE32_NOPAD
+                    .decode(child.as_bytes())
+                    .map(|hash| hash.len() == 16)
+                    .unwrap_or_default();
+                if valid {
+                    Ok(child.to_string())
+                } else {
+                    Err(ParseDnsEntryError::InvalidChildHash(child.to_string()))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { children })
+    }
+}
+
+impl fmt::Display for BranchEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", BRANCH_PREFIX, self.children.join(","))
+    }
+}
+
+/// A leaf of a tree that contains the record of a node, `enr:<node-record>`.
+#[derive(Debug, Clone)]
+pub struct NodeEntry {
+    /// The record of the node.
+    pub enr: Enr<SecretKey>,
+    /// The [`NodeRecord`] of the node, derived from the [`Enr`].
+    pub node_record: NodeRecord,
+    /// The [`ForkId`] of the `eth` entry of the [`Enr`], if set.
+    pub fork_id: Option<ForkId>,
+}
+
+impl NodeEntry {
+    /// Creates the entry for the given [`Enr`].
+    ///
+    /// Returns an error if the [`Enr`] does not advertise an IP and port.
+    pub fn new(enr: Enr<SecretKey>) -> Result<Self, ParseDnsEntryError> {
+        let node_record = enr_to_node_record(&enr)
+            .ok_or_else(|| ParseDnsEntryError::InvalidEnr("missing ip or port".to_string()))?;
+        let fork_id = enr.get("eth").and_then(|mut rlp| ForkId::decode(&mut rlp).ok());
+        Ok(Self { enr, node_record, fork_id })
+    }
+}
+
+impl FromStr for NodeEntry {
+    type Err = ParseDnsEntryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.starts_with(ENR_PREFIX) {
+            return Err(ParseDnsEntryError::UnknownEntry(s.to_string()))
+        }
+        Self::new(s.parse().map_err(ParseDnsEntryError::InvalidEnr)?)
+    }
+}
+
+impl fmt::Display for NodeEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.enr.to_base64().fmt(f)
+    }
+}
+
+/// Converts the [`Enr`] into a [`NodeRecord`].
+///
+/// Prefers the IPv4 endpoint and falls back to the UDP port if no TCP port is set.
+fn enr_to_node_record(enr: &Enr<SecretKey>) -> Option<NodeRecord> {
+    let id = PeerId::from_slice(&enr.public_key().serialize_uncompressed()[1..]);
+    let (address, udp_port, tcp_port) = match enr.ip4() {
+        Some(ip) => (IpAddr::from(ip), enr.udp4(), enr.tcp4()),
+        None => (IpAddr::from(enr.ip6()?), enr.udp6(), enr.tcp6()),
+    };
+    let tcp_port = tcp_port.or(udp_port)?;
+    let udp_port = udp_port.unwrap_or(tcp_port);
+    Some(NodeRecord { address, tcp_port, udp_port, id })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_root_entry() {
+        let s = "enrtree-root:v1 e=QFT4PBCRX4XQCV3VUYJ6BTCEPU l=JGUFMSAGI7KZYB3P7IZW4S5Y3A seq=3 sig=3FmXuVwpa8Y7OstZTx9PIb1mt8FrW7VpDOFv4AaGCsZ2EIHmhraWhe4NxYhQDlw5MjeFXYMbJjsPeKlHzmJREQE";
+        let root: TreeRootEntry = s.parse().unwrap();
+        assert_eq!(root.enr_root, "QFT4PBCRX4XQCV3VUYJ6BTCEPU");
+        assert_eq!(root.link_root, "JGUFMSAGI7KZYB3P7IZW4S5Y3A");
+        assert_eq!(root.sequence_number, 3);
+        assert_eq!(root.signature.len(), 65);
+        assert_eq!(root.to_string(), s);
+    }
+
+    #[test]
+    fn sign_root_entry() {
+        let (secret_key, pubkey) = SECP256K1.generate_keypair(&mut rand::thread_rng());
+        let mut root = TreeRootEntry {
+            enr_root: "QFT4PBCRX4XQCV3VUYJ6BTCEPU".to_string(),
+            link_root: "JGUFMSAGI7KZYB3P7IZW4S5Y3A".to_string(),
+            sequence_number: 1,
+            signature: vec![],
+        };
+        assert!(!root.verify(&pubkey));
+        root.sign(&secret_key);
+        assert!(root.verify(&pubkey));
+
+        root.sequence_number += 1;
+        assert!(!root.verify(&pubkey));
+    }
+
+    #[test]
+    fn parse_link_entry() {
+        let s = "enrtree://AKA3AM6LPBYEUDMVNU3BSVQJ5AD45Y7YPOHJLEF6W26QOE4VTUDPE@all.mainnet.ethdisco.net";
+        let link: LinkEntry = s.parse().unwrap();
+        assert_eq!(link.domain, "all.mainnet.ethdisco.net");
+        assert_eq!(link.to_string(), s);
+
+        assert!("enrtree://AKA3AM6LPBYEUDMVNU3BSVQJ5AD45Y7YPOHJLEF6W26QOE4VTUDPE"
+            .parse::<LinkEntry>()
+            .is_err());
+    }
+
+    #[test]
+    fn parse_branch_entry() {
+        let s = "enrtree-branch:2XS2367YHAXJFGLZHVAWLQD4ZY,H4FHT4B454P6UXFD7JCYQ5PWDY,MHTDO6TMUBRIA2XWG5LUDACK24";
+        let branch: BranchEntry = s.parse().unwrap();
+        assert_eq!(branch.children.len(), 3);
+        assert_eq!(branch.to_string(), s);
+
+        assert!("enrtree-branch:1".parse::<BranchEntry>().is_err());
+    }
+
+    #[test]
+    fn parse_node_entry() {
+        // <https://eips.ethereum.org/EIPS/eip-778>
+        let s = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+        let node: NodeEntry = s.parse().unwrap();
+        assert_eq!(node.node_record.address, IpAddr::from([127, 0, 0, 1]));
+        assert_eq!(node.node_record.udp_port, 30303);
+        assert_eq!(node.node_record.tcp_port, 30303);
+        assert_eq!(node.fork_id, None);
+        assert_eq!(node.to_string(), s);
+
+        assert!(matches!(s.parse::<DnsEntry>(), Ok(DnsEntry::Node(_))));
+    }
+}
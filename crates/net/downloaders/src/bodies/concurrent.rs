@@ -173,6 +173,7 @@ where
                     header: header.clone(),
                     body: body.transactions,
                     ommers: body.ommers.into_iter().map(|header| header.seal()).collect(),
+                    withdrawals: body.withdrawals,
                 };
 
                 // This ensures that the TxRoot and OmmersRoot from the header match the
@@ -265,6 +266,7 @@ mod tests {
                                     header,
                                     body: body.transactions,
                                     ommers: body.ommers.into_iter().map(|o| o.seal()).collect(),
+                                    withdrawals: body.withdrawals,
                                 })
                             }
                         })
@@ -305,11 +307,7 @@ mod tests {
                         retries_left.fetch_sub(1, Ordering::SeqCst);
                         Err(RequestError::Timeout)
                     } else {
-                        Ok((
-                            PeerId::default(),
-                            vec![BlockBody { transactions: vec![], ommers: vec![] }],
-                        )
-                            .into())
+                        Ok((PeerId::default(), vec![BlockBody::default()]).into())
                     }
                 }
             })),
@@ -339,11 +337,7 @@ mod tests {
                         retries_left.fetch_sub(1, Ordering::SeqCst);
                         Err(RequestError::Timeout)
                     } else {
-                        Ok((
-                            PeerId::default(),
-                            vec![BlockBody { transactions: vec![], ommers: vec![] }],
-                        )
-                            .into())
+                        Ok((PeerId::default(), vec![BlockBody::default()]).into())
                     }
                 }
             })),
@@ -416,6 +410,7 @@ mod tests {
                                     header,
                                     body: body.transactions,
                                     ommers: body.ommers.into_iter().map(|o| o.seal()).collect(),
+                                    withdrawals: body.withdrawals,
                                 })
                             }
                         })
@@ -1,26 +1,37 @@
-use futures::{stream::Stream, FutureExt};
+use futures::{
+    ready,
+    stream::{FuturesUnordered, Stream},
+    FutureExt, StreamExt,
+};
 use reth_interfaces::{
     consensus::Consensus,
     p2p::{
         downloader::{DownloadStream, Downloader},
-        error::{DownloadError, DownloadResult, PeerRequestResult},
+        error::{DownloadError, DownloadResult, PeerRequestResult, RequestError},
         headers::{
             client::{BlockHeaders, HeadersClient, HeadersRequest},
-            downloader::{validate_header_download, HeaderDownloader},
+            downloader::{ensure_parent, validate_header_download, HeaderDownloader},
         },
     },
 };
-use reth_primitives::{HeadersDirection, SealedHeader, H256};
+use reth_primitives::{
+    BlockHashOrNumber, BlockNumber, HeadersDirection, PeerId, SealedHeader, H256,
+};
 use std::{
     borrow::Borrow,
-    collections::VecDeque,
+    cmp::Reverse,
+    collections::{BTreeMap, VecDeque},
     future::Future,
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
 };
 
-/// Download headers in batches
+/// Download headers in reverse, from the tip down to the local head.
+///
+/// The tip is requested by hash first. Once its number is known, the remaining range is split
+/// into segments of `batch_size` headers which are requested by number, with up to `concurrency`
+/// requests in flight at the same time. Responses are validated and reassembled in order.
 #[derive(Debug)]
 pub struct LinearDownloader<C, H> {
     /// The consensus client
@@ -31,6 +42,8 @@ pub struct LinearDownloader<C, H> {
     pub batch_size: u64,
     /// The number of retries for downloading
     pub request_retries: usize,
+    /// The maximum number of requests to send concurrently.
+    pub concurrency: usize,
 }
 
 impl<C, H> Downloader for LinearDownloader<C, H>
@@ -67,21 +80,35 @@ impl<C: Consensus, H: HeadersClient> Clone for LinearDownloader<C, H> {
             client: Arc::clone(&self.client),
             batch_size: self.batch_size,
             request_retries: self.request_retries,
+            concurrency: self.concurrency,
         }
     }
 }
 
 impl<C: Consensus, H: HeadersClient> LinearDownloader<C, H> {
     fn new_download(&self, head: SealedHeader, tip: H256) -> HeadersDownload<C, H> {
+        // the tip is requested first, by hash, since its number is unknown
+        let tip_request = SegmentRequest {
+            request: HeadersRequest {
+                start: tip.into(),
+                limit: self.batch_size,
+                direction: HeadersDirection::Falling,
+            },
+            retries: 0,
+        };
         HeadersDownload {
             head,
-            tip,
-            buffered: VecDeque::default(),
-            request: Default::default(),
+            queued_headers: VecDeque::default(),
+            next_request_block_number: None,
+            pending_requests: VecDeque::from([tip_request]),
+            in_progress: FuturesUnordered::default(),
+            buffered_segments: BTreeMap::default(),
             consensus: Arc::clone(&self.consensus),
             request_retries: self.request_retries,
             batch_size: self.batch_size,
+            concurrency: self.concurrency.max(1),
             client: Arc::clone(&self.client),
+            reached_head: false,
             encountered_error: false,
         }
     }
@@ -89,48 +116,60 @@ impl<C: Consensus, H: HeadersClient> LinearDownloader<C, H> {
 
 type HeadersFut = Pin<Box<dyn Future<Output = PeerRequestResult<BlockHeaders>> + Send>>;
 
-/// A retryable future that returns a list of [`BlockHeaders`] on success.
-struct HeadersRequestFuture {
+/// A request for a segment of headers and the number of times it was already retried.
+#[derive(Debug)]
+struct SegmentRequest {
     request: HeadersRequest,
-    fut: HeadersFut,
     retries: usize,
-    max_retries: usize,
 }
 
-impl HeadersRequestFuture {
-    /// Returns true if the request can be retried.
-    fn is_retryable(&self) -> bool {
-        self.retries < self.max_retries
-    }
-
-    /// Increments the retry counter and returns whether the request can still be retried.
-    fn inc_err(&mut self) -> bool {
-        self.retries += 1;
-        self.is_retryable()
-    }
+/// A future that returns a list of [`BlockHeaders`] for a [`SegmentRequest`].
+struct HeadersRequestFuture {
+    request: SegmentRequest,
+    fut: HeadersFut,
 }
 
 impl Future for HeadersRequestFuture {
-    type Output = PeerRequestResult<BlockHeaders>;
+    type Output = (SegmentRequest, PeerRequestResult<BlockHeaders>);
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.get_mut().fut.poll_unpin(cx)
+        let this = self.get_mut();
+        let response = ready!(this.fut.poll_unpin(cx));
+        let request =
+            SegmentRequest { request: this.request.request.clone(), retries: this.request.retries };
+        Poll::Ready((request, response))
     }
 }
 
+/// A validated segment of headers that waits until it can be connected to the downloaded chain.
+struct HeadersSegment {
+    /// The request this segment was downloaded for
+    request: SegmentRequest,
+    /// The peer that sent the segment
+    peer_id: PeerId,
+    /// The headers in descending order
+    headers: Vec<SealedHeader>,
+}
+
 /// An in progress headers download.
 pub struct HeadersDownload<C, H> {
     /// The local head of the chain.
     head: SealedHeader,
-    /// Tip to start syncing from
-    tip: H256,
-    /// Buffered results
-    buffered: VecDeque<SealedHeader>,
-    /// Contains the request that's currently in progress.
+    /// Validated headers in descending order that are ready to be returned.
     ///
-    /// TODO(mattsse): this could be converted into a `FuturesOrdered` where batching is done via
-    /// `skip` so we don't actually need to know the start hash
-    request: Option<HeadersRequestFuture>,
+    /// The lowest header is held back until it was validated against its parent.
+    queued_headers: VecDeque<SealedHeader>,
+    /// The highest block number of the next segment to request.
+    ///
+    /// This is `None` until the tip was received.
+    next_request_block_number: Option<BlockNumber>,
+    /// Requests that are ready to be sent: the initial tip request and the segments that need to
+    /// be requested again.
+    pending_requests: VecDeque<SegmentRequest>,
+    /// Requests that are currently in flight.
+    in_progress: FuturesUnordered<HeadersRequestFuture>,
+    /// Segments that arrived out of order, keyed by their highest block number.
+    buffered_segments: BTreeMap<BlockNumber, HeadersSegment>,
     /// Downloader used to issue new requests.
     consensus: Arc<C>,
     /// Downloader used to issue new requests.
@@ -139,6 +178,10 @@ pub struct HeadersDownload<C, H> {
     batch_size: u64,
     /// The number of retries for downloading
     request_retries: usize,
+    /// The maximum number of segments that are in flight or buffered.
+    concurrency: usize,
+    /// Whether the downloaded headers are connected to the local head.
+    reached_head: bool,
     /// Flag whether the stream encountered an error
     encountered_error: bool,
 }
@@ -148,146 +191,247 @@ where
     C: Consensus + 'static,
     H: HeadersClient + 'static,
 {
-    /// Returns the first header from the vector of buffered headers
-    fn earliest_header(&self) -> Option<&SealedHeader> {
-        self.buffered.back()
-    }
-
-    /// Check if any more requests should be dispatched
-    fn has_reached_head(&self) -> bool {
-        self.earliest_header().map(|h| h.hash() == self.head.hash()).unwrap_or_default()
-    }
-
     /// Check if the stream has terminated
     fn has_terminated(&self) -> bool {
-        self.has_reached_head() || self.encountered_error
+        (self.reached_head && self.queued_headers.is_empty()) || self.encountered_error
     }
 
-    /// Returns the start hash for a new request.
-    fn request_start(&self) -> H256 {
-        self.earliest_header().map_or(self.tip, |h| h.parent_hash)
+    /// Returns `true` if another segment can be requested without exceeding the buffer limits.
+    ///
+    /// At most `concurrency` segments are in flight or buffered, and new segments are only
+    /// requested while at most `concurrency * batch_size` validated headers are queued.
+    fn has_capacity(&self) -> bool {
+        self.in_progress.len() + self.buffered_segments.len() < self.concurrency &&
+            self.queued_headers.len() <= self.concurrency * self.batch_size as usize
     }
 
-    /// Get the headers request to dispatch
-    fn headers_request(&self) -> HeadersRequest {
-        HeadersRequest {
-            start: self.request_start().into(),
-            limit: self.batch_size,
-            direction: HeadersDirection::Rising,
+    /// Returns the request for the next segment below the already requested range, if any.
+    fn next_segment_request(&mut self) -> Option<SegmentRequest> {
+        let start = self.next_request_block_number?;
+        if start <= self.head.number {
+            return None
         }
+        let limit = self.batch_size.min(start - self.head.number);
+        self.next_request_block_number = Some(start - limit);
+        let request =
+            HeadersRequest { start: start.into(), limit, direction: HeadersDirection::Falling };
+        Some(SegmentRequest { request, retries: 0 })
     }
 
-    /// Pop header from the buffer
-    fn pop_header_from_buffer(&mut self) -> Option<SealedHeader> {
-        if self.buffered.len() > 1 {
-            self.buffered.pop_front()
-        } else {
-            None
+    /// Sends out requests until `concurrency` requests are in flight.
+    ///
+    /// Pending requests are sent first, since the downloaded chain can't make progress until the
+    /// segments they cover are received.
+    fn dispatch_requests(&mut self) {
+        while self.in_progress.len() < self.concurrency {
+            let request = match self.pending_requests.pop_front() {
+                Some(request) => request,
+                None if self.has_capacity() => match self.next_segment_request() {
+                    Some(request) => request,
+                    None => break,
+                },
+                None => break,
+            };
+            tracing::trace!(
+                target: "downloaders::headers",
+                "Requesting headers {:?}",
+                request.request
+            );
+            let client = Arc::clone(&self.client);
+            let req = request.request.clone();
+            self.in_progress.push(HeadersRequestFuture {
+                request,
+                fut: Box::pin(async move { client.get_headers(req).await }),
+            });
         }
     }
 
-    /// Insert the header into buffer
-    fn push_header_into_buffer(&mut self, header: SealedHeader) {
-        self.buffered.push_back(header);
-    }
-
-    /// Get a current future or instantiate a new one
-    fn get_or_init_fut(&mut self) -> HeadersRequestFuture {
-        match self.request.take() {
-            None => {
-                // queue in the first request
-                let client = Arc::clone(&self.client);
-                let req = self.headers_request();
-                tracing::trace!(
-                    target: "downloaders::headers",
-                    "Requesting headers {req:?}"
-                );
-                HeadersRequestFuture {
-                    request: req.clone(),
-                    fut: Box::pin(async move { client.get_headers(req).await }),
-                    retries: 0,
-                    max_retries: self.request_retries,
-                }
-            }
-            Some(fut) => fut,
-        }
+    /// Validate whether the header is valid in relation to it's parent
+    ///
+    /// Returns and `Err` if the header does not conform to consensus rules.
+    #[allow(clippy::result_large_err)]
+    fn validate(&self, header: &SealedHeader, parent: &SealedHeader) -> DownloadResult<()> {
+        validate_header_download(&self.consensus, header, parent)?;
+        Ok(())
     }
 
-    /// Tries to fuse the future with a new request.
+    /// Handles a failed request or an invalid segment by queueing the request again.
     ///
-    /// Returns an `Err` if the request exhausted all retries
-    fn try_fuse_request_fut(&self, fut: &mut HeadersRequestFuture) -> Result<(), ()> {
-        if !fut.inc_err() {
-            return Err(())
+    /// The peer that sent the invalid response is penalized, so the retry is likely to be served
+    /// by another peer.
+    ///
+    /// Returns an `Err` if the request exhausted all retries.
+    #[allow(clippy::result_large_err)]
+    fn on_segment_error(
+        &mut self,
+        mut request: SegmentRequest,
+        peer_id: Option<PeerId>,
+        error: DownloadError,
+    ) -> DownloadResult<()> {
+        tracing::error!(
+            target: "downloaders::headers", request = ?request.request, ?error, "Error processing header response",
+        );
+        // Penalize the peer for bad response
+        if let Some(peer_id) = peer_id {
+            tracing::trace!(target: "downloaders::headers", ?peer_id, ?error, "Penalizing peer");
+            self.client.report_bad_message(peer_id);
+        }
+        request.retries += 1;
+        if request.retries >= self.request_retries {
+            tracing::trace!(
+                target: "downloaders::headers",
+                "Ran out of retries, terminating stream"
+            );
+            return Err(error)
         }
         tracing::trace!(
             target : "downloaders::headers",
-            "Retrying future attempt: {}/{}",
-            fut.retries,
-            fut.max_retries
+            "Retrying request attempt: {}/{}",
+            request.retries,
+            self.request_retries
         );
-        let req = self.headers_request();
-        fut.request = req.clone();
-        let client = Arc::clone(&self.client);
-        fut.fut = Box::pin(async move { client.get_headers(req).await });
+        self.pending_requests.push_front(request);
         Ok(())
     }
 
-    /// Validate whether the header is valid in relation to it's parent
-    ///
-    /// Returns and `Err` if the header does not conform to consensus rules.
+    /// Validates the response of a request and buffers the headers in case of success.
     #[allow(clippy::result_large_err)]
-    fn validate(&self, header: &SealedHeader, parent: &SealedHeader) -> DownloadResult<()> {
-        validate_header_download(&self.consensus, header, parent)?;
-        Ok(())
+    fn on_headers_response(
+        &mut self,
+        request: SegmentRequest,
+        response: PeerRequestResult<BlockHeaders>,
+    ) -> DownloadResult<()> {
+        let peer_id = response.as_ref().map(|res| res.peer_id()).ok();
+        match self.process_header_response(&request.request, response) {
+            Ok(Some(headers)) => {
+                let peer_id = peer_id.expect("is ok response; qed");
+                self.buffer_segment(HeadersSegment { request, peer_id, headers });
+                Ok(())
+            }
+            Ok(None) => Ok(()),
+            Err(error) => self.on_segment_error(request, peer_id, error),
+        }
     }
 
+    /// Validates the headers of the response among themselves.
+    ///
+    /// Returns the headers above the local head in descending order, if any.
     #[allow(clippy::result_large_err)]
     fn process_header_response(
         &mut self,
+        request: &HeadersRequest,
         response: PeerRequestResult<BlockHeaders>,
-    ) -> DownloadResult<()> {
-        match response {
-            Ok(res) => {
-                let mut headers = res.1 .0;
-                headers.sort_unstable_by_key(|h| h.number);
+    ) -> DownloadResult<Option<Vec<SealedHeader>>> {
+        // most likely a noop, because this error
+        // would've been handled by the fetcher internally
+        let (_, BlockHeaders(headers)) = response?.split();
+        if headers.is_empty() {
+            return Err(DownloadError::EmptyResponse)
+        }
 
-                if headers.is_empty() {
-                    return Err(DownloadError::EmptyResponse)
-                }
+        let mut headers = headers.into_iter().map(|h| h.seal()).collect::<Vec<_>>();
+        headers.sort_unstable_by_key(|h| Reverse(h.number));
+        headers.truncate(request.limit as usize);
 
-                // Iterate headers in reverse
-                for parent in headers.into_iter().rev() {
-                    let parent = parent.seal();
-
-                    if self.head.hash() == parent.hash() {
-                        // We've reached the target, stop buffering headers
-                        self.push_header_into_buffer(parent);
-                        break
-                    }
-
-                    if let Some(header) = self.earliest_header() {
-                        // Proceed to insert. If there is a validation error re-queue
-                        // the future.
-                        self.validate(header, &parent)?;
-                    } else if parent.hash() != self.tip {
-                        // The buffer is empty and the first header does not match the
-                        // tip, requeue the future
-                        return Err(DownloadError::InvalidTip {
-                            received: parent.hash(),
-                            expected: self.tip,
-                        })
-                    }
-
-                    // Record new parent
-                    self.push_header_into_buffer(parent);
-                }
-                Ok(())
+        let highest = &headers[0];
+        match request.start {
+            BlockHashOrNumber::Hash(hash) if highest.hash() != hash => {
+                return Err(DownloadError::InvalidTip { received: highest.hash(), expected: hash })
             }
-            // most likely a noop, because this error
-            // would've been handled by the fetcher internally
-            Err(err) => Err(err.into()),
+            BlockHashOrNumber::Number(number) if highest.number != number => {
+                return Err(RequestError::BadResponse.into())
+            }
+            _ => {}
+        }
+
+        for pair in headers.windows(2) {
+            self.validate(&pair[0], &pair[1])?;
         }
+
+        if highest.hash() == self.head.hash() {
+            // The tip is the local head, nothing to download
+            self.reached_head = true;
+            return Ok(None)
+        }
+        if highest.number <= self.head.number {
+            // The tip is not a descendant of the local head
+            ensure_parent(highest, &self.head)?;
+        }
+
+        // We've reached the target, stop buffering headers
+        headers.retain(|h| h.number > self.head.number);
+
+        let lowest = headers.last().expect("not empty; qed");
+        if matches!(request.start, BlockHashOrNumber::Hash(_)) {
+            // Continue below the tip
+            self.next_request_block_number = Some(lowest.number - 1);
+        } else if (headers.len() as u64) < request.limit {
+            // The peer returned fewer headers than requested, request the rest separately
+            let request = HeadersRequest {
+                start: (lowest.number - 1).into(),
+                limit: request.limit - headers.len() as u64,
+                direction: HeadersDirection::Falling,
+            };
+            self.pending_requests.push_back(SegmentRequest { request, retries: 0 });
+        }
+
+        Ok(Some(headers))
+    }
+
+    /// Buffers the segment until it can be connected to the downloaded chain.
+    fn buffer_segment(&mut self, segment: HeadersSegment) {
+        if matches!(segment.request.request.start, BlockHashOrNumber::Hash(_)) {
+            // The tip segment is the start of the downloaded chain
+            self.queued_headers.extend(segment.headers);
+        } else {
+            self.buffered_segments.insert(segment.headers[0].number, segment);
+        }
+    }
+
+    /// Connects buffered segments to the downloaded chain, in order.
+    ///
+    /// Returns an `Err` if the downloaded chain doesn't connect to the local head or a segment
+    /// exhausted all of its retries.
+    #[allow(clippy::result_large_err)]
+    fn connect_segments(&mut self) -> DownloadResult<()> {
+        while !self.reached_head {
+            let Some(lowest) = self.queued_headers.back() else { break };
+            if lowest.number == self.head.number + 1 {
+                self.validate(lowest, &self.head)?;
+                self.reached_head = true;
+                break
+            }
+
+            let parent_number = lowest.number - 1;
+            let Some(segment) = self.buffered_segments.remove(&parent_number) else { break };
+            let lowest = self.queued_headers.back().expect("exists; qed");
+            if let Err(error) = self.validate(lowest, &segment.headers[0]) {
+                // The segment doesn't belong to the downloaded chain
+                self.on_segment_error(segment.request, Some(segment.peer_id), error)?;
+                break
+            }
+            self.queued_headers.extend(segment.headers);
+        }
+        Ok(())
+    }
+
+    /// Pop header from the queue
+    fn pop_header(&mut self) -> Option<SealedHeader> {
+        if self.reached_head || self.queued_headers.len() > 1 {
+            self.queued_headers.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Terminates the stream with the given error.
+    fn on_error(&mut self, error: DownloadError) -> Poll<Option<DownloadResult<SealedHeader>>> {
+        self.queued_headers.clear();
+        self.pending_requests.clear();
+        self.buffered_segments.clear();
+        self.in_progress = FuturesUnordered::default();
+        self.encountered_error = true;
+        Poll::Ready(Some(Err(error)))
     }
 }
 
@@ -298,12 +442,12 @@ where
 {
     type Item = DownloadResult<SealedHeader>;
 
-    /// Linear header downloader implemented as a [Stream]. The downloader sends header
-    /// requests until the head is reached and buffers the responses. If the request future
-    /// is still pending, the downloader will return a buffered header if any is available.
+    /// Reverse header downloader implemented as a [Stream]. The downloader keeps up to
+    /// `concurrency` requests for disjoint segments in flight until the head is reached. The
+    /// responses are validated and buffered until they can be connected to the downloaded chain,
+    /// headers are returned in descending order.
     ///
-    /// Internally, the stream is terminated if the `done` flag has been set and there are no
-    /// more headers available in the buffer.
+    /// Internally, the stream is terminated once all headers down to the head have been returned.
     ///
     /// Upon encountering an error, the downloader will attempt to retry the failed request.
     /// If the number of retries is exhausted, the downloader will stream an error,
@@ -312,63 +456,34 @@ where
         let this = self.get_mut();
 
         loop {
-            // Drain any buffered element except the head
-            if let Some(header) = this.pop_header_from_buffer() {
-                return Poll::Ready(Some(Ok(header)))
-            }
-
             // We've reached the head or encountered an error, terminate the stream
             if this.has_terminated() {
                 return Poll::Ready(None)
             }
 
-            // Initialize the future
-            let mut fut = this.get_or_init_fut();
-            match fut.poll_unpin(cx) {
-                Poll::Ready(result) => {
-                    let peer_id = result.as_ref().map(|res| res.peer_id()).ok();
-                    // Process the response, buffering the headers
-                    // in case of successful validation
-                    match this.process_header_response(result) {
-                        Ok(_) => {
-                            // The buffer has been updated, check if we need
-                            // to dispatch another request
-                            if !this.has_reached_head() {
-                                // Create a new request
-                                this.request = Some(this.get_or_init_fut());
-                            }
-                        }
-                        Err(error) => {
-                            tracing::error!(
-                                target: "downloaders::headers", request = ?fut.request, ?error, "Error processing header response",
-                            );
-                            // Penalize the peer for bad response
-                            if let Some(peer_id) = peer_id {
-                                tracing::trace!(target: "downloaders::headers", ?peer_id, ?error, "Penalizing peer");
-                                this.client.report_bad_message(peer_id);
-                            }
-                            // Response is invalid, attempt to retry
-                            if this.try_fuse_request_fut(&mut fut).is_err() {
-                                tracing::trace!(
-                                    target: "downloaders::headers",
-                                    "Ran out of retries, terminating stream"
-                                );
-                                // We exhausted all of the retries. Stream must terminate
-                                this.buffered.clear();
-                                this.encountered_error = true;
-                                return Poll::Ready(Some(Err(error)))
-                            }
-                            // Reset the future
-                            this.request = Some(fut);
-                        }
-                    };
-                }
-                Poll::Pending => {
-                    // Set the future back to state
-                    this.request = Some(fut);
-                    return Poll::Pending
+            this.dispatch_requests();
+
+            // Process all ready responses
+            let mut progress = false;
+            while let Poll::Ready(Some((request, response))) = this.in_progress.poll_next_unpin(cx)
+            {
+                progress = true;
+                if let Err(error) = this.on_headers_response(request, response) {
+                    return this.on_error(error)
                 }
             }
+
+            if let Err(error) = this.connect_segments() {
+                return this.on_error(error)
+            }
+
+            if let Some(header) = this.pop_header() {
+                return Poll::Ready(Some(Ok(header)))
+            }
+
+            if !progress && !this.has_terminated() {
+                return Poll::Pending
+            }
         }
     }
 }
@@ -381,11 +496,13 @@ pub struct LinearDownloadBuilder {
     batch_size: u64,
     /// The number of retries for downloading
     request_retries: usize,
+    /// The maximum number of requests to send concurrently
+    concurrency: usize,
 }
 
 impl Default for LinearDownloadBuilder {
     fn default() -> Self {
-        Self { batch_size: 100, request_retries: 5 }
+        Self { batch_size: 100, request_retries: 5, concurrency: 5 }
     }
 }
 
@@ -402,6 +519,12 @@ impl LinearDownloadBuilder {
         self
     }
 
+    /// Set the maximum number of requests to send concurrently
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
     /// Build [LinearDownloader] with provided consensus
     /// and header client implementations
     pub fn build<C: Consensus, H: HeadersClient>(
@@ -414,6 +537,7 @@ impl LinearDownloadBuilder {
             client,
             batch_size: self.batch_size,
             request_retries: self.request_retries,
+            concurrency: self.concurrency,
         }
     }
 }
@@ -423,8 +547,18 @@ mod tests {
     use super::*;
     use futures::TryStreamExt;
     use once_cell::sync::Lazy;
-    use reth_interfaces::test_utils::{TestConsensus, TestHeadersClient};
-    use reth_primitives::SealedHeader;
+    use reth_interfaces::{
+        p2p::downloader::DownloadClient,
+        test_utils::{TestConsensus, TestHeadersClient},
+    };
+    use reth_primitives::{Header, SealedHeader};
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        },
+    };
 
     static CONSENSUS: Lazy<Arc<TestConsensus>> = Lazy::new(|| Arc::new(TestConsensus::default()));
 
@@ -504,7 +638,129 @@ mod tests {
         assert_eq!(headers[0], p0);
         assert_eq!(headers[1], p1);
         assert_eq!(headers[2], p2);
-        // stream has to poll twice because of the batch size
-        assert_eq!(client.request_attempts(), 2);
+        // the tip response already connects to the head
+        assert_eq!(client.request_attempts(), 1);
+    }
+
+    /// A client that serves requests from a chain, like a peer would.
+    #[derive(Debug, Default)]
+    struct ChainHeadersClient {
+        /// The canonical chain, indexed by block number
+        chain: Vec<Header>,
+        /// Responses that are sent once instead of the canonical headers, keyed by start number
+        bad_responses: Mutex<HashMap<BlockNumber, Vec<Header>>>,
+        request_attempts: AtomicU64,
+    }
+
+    impl ChainHeadersClient {
+        fn request_attempts(&self) -> u64 {
+            self.request_attempts.load(Ordering::SeqCst)
+        }
+    }
+
+    impl DownloadClient for ChainHeadersClient {
+        fn report_bad_message(&self, _peer_id: PeerId) {}
+    }
+
+    #[async_trait::async_trait]
+    impl HeadersClient for ChainHeadersClient {
+        async fn get_headers(&self, request: HeadersRequest) -> PeerRequestResult<BlockHeaders> {
+            self.request_attempts.fetch_add(1, Ordering::SeqCst);
+            let start = match request.start {
+                BlockHashOrNumber::Hash(hash) => {
+                    self.chain.iter().position(|h| h.hash_slow() == hash).unwrap() as u64
+                }
+                BlockHashOrNumber::Number(number) => number,
+            };
+            let headers = match self.bad_responses.lock().unwrap().remove(&start) {
+                Some(headers) => headers,
+                None => self.chain[..=start as usize]
+                    .iter()
+                    .rev()
+                    .take(request.limit as usize)
+                    .cloned()
+                    .collect(),
+            };
+            Ok((PeerId::random(), BlockHeaders(headers)).into())
+        }
+    }
+
+    fn chain(len: usize) -> Vec<SealedHeader> {
+        let mut chain = vec![SealedHeader::default()];
+        for _ in 1..len {
+            chain.push(child_header(chain.last().unwrap()));
+        }
+        chain
+    }
+
+    #[tokio::test]
+    async fn download_segments() {
+        let chain = chain(51);
+        let client = Arc::new(ChainHeadersClient {
+            chain: chain.iter().map(|h| h.as_ref().clone()).collect(),
+            ..Default::default()
+        });
+        let downloader = LinearDownloadBuilder::default()
+            .batch_size(10)
+            .concurrency(3)
+            .build(CONSENSUS.clone(), Arc::clone(&client));
+
+        let head = chain[0].clone();
+        let tip = chain.last().unwrap().hash();
+        let headers = downloader.stream(head, tip).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(headers, chain[1..].iter().rev().cloned().collect::<Vec<_>>());
+        // one request for the tip and four segments below it
+        assert_eq!(client.request_attempts(), 5);
+    }
+
+    #[tokio::test]
+    async fn download_retries_bad_segment() {
+        let chain = chain(51);
+        // a segment of a fork that doesn't connect to the chain above it
+        let mut fork = vec![chain[20].clone()];
+        for _ in 21..=30 {
+            let mut child = child_header(fork.last().unwrap()).unseal();
+            child.gas_limit += 1;
+            fork.push(child.seal());
+        }
+        let bad_segment = fork[1..].iter().rev().map(|h| h.as_ref().clone()).collect();
+        let client = Arc::new(ChainHeadersClient {
+            chain: chain.iter().map(|h| h.as_ref().clone()).collect(),
+            bad_responses: Mutex::new(HashMap::from([(30, bad_segment)])),
+            ..Default::default()
+        });
+        let downloader = LinearDownloadBuilder::default()
+            .batch_size(10)
+            .concurrency(3)
+            .build(CONSENSUS.clone(), Arc::clone(&client));
+
+        let head = chain[0].clone();
+        let tip = chain.last().unwrap().hash();
+        let headers = downloader.stream(head, tip).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(headers, chain[1..].iter().rev().cloned().collect::<Vec<_>>());
+        // the bad segment was requested again
+        assert_eq!(client.request_attempts(), 6);
+    }
+
+    #[tokio::test]
+    async fn download_partial_segment() {
+        let chain = chain(21);
+        // the peer only returns half of the segment
+        let partial = chain[6..=10].iter().rev().map(|h| h.as_ref().clone()).collect();
+        let client = Arc::new(ChainHeadersClient {
+            chain: chain.iter().map(|h| h.as_ref().clone()).collect(),
+            bad_responses: Mutex::new(HashMap::from([(10, partial)])),
+            ..Default::default()
+        });
+        let downloader = LinearDownloadBuilder::default()
+            .batch_size(10)
+            .build(CONSENSUS.clone(), Arc::clone(&client));
+
+        let head = chain[0].clone();
+        let tip = chain.last().unwrap().hash();
+        let headers = downloader.stream(head, tip).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(headers, chain[1..].iter().rev().cloned().collect::<Vec<_>>());
+        // the rest of the segment was requested separately
+        assert_eq!(client.request_attempts(), 3);
     }
 }
@@ -13,5 +13,8 @@ pub mod bodies;
 /// The collection of alhgorithms for downloading block headers.
 pub mod headers;
 
+/// The collection of algorithms for downloading receipts.
+pub mod receipts;
+
 #[cfg(test)]
 mod test_utils;
@@ -0,0 +1,270 @@
+use backon::{ExponentialBackoff, Retryable};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use reth_interfaces::{
+    consensus,
+    p2p::{
+        downloader::DownloadStream,
+        error::{DownloadError, DownloadResult, RequestError},
+        receipts::{
+            client::ReceiptsClient,
+            downloader::{BlockReceipts, ReceiptsDownloader},
+        },
+    },
+};
+use reth_primitives::{
+    proofs::{calculate_receipt_root, EMPTY_ROOT},
+    SealedHeader,
+};
+use std::sync::Arc;
+
+/// Downloads receipts in batches.
+///
+/// All blocks in a batch are fetched at the same time.
+#[derive(Debug)]
+pub struct ConcurrentDownloader<Client> {
+    /// The receipts client
+    client: Arc<Client>,
+    /// The number of retries for each request.
+    retries: usize,
+    /// The batch size per one request
+    batch_size: usize,
+    /// The maximum number of requests to send concurrently.
+    concurrency: usize,
+}
+
+impl<Client> ReceiptsDownloader for ConcurrentDownloader<Client>
+where
+    Client: ReceiptsClient,
+{
+    fn receipts_stream<'a, 'b, I>(&'a self, headers: I) -> DownloadStream<'a, BlockReceipts>
+    where
+        I: IntoIterator<Item = &'b SealedHeader>,
+        <I as IntoIterator>::IntoIter: Send + 'b,
+        'b: 'a,
+    {
+        Box::pin(
+            stream::iter(headers.into_iter())
+                .chunks(self.batch_size)
+                .map(move |headers| {
+                    (move || self.fetch_receipts(headers.clone()))
+                        .retry(ExponentialBackoff::default().with_max_times(self.retries))
+                })
+                .buffered(self.concurrency)
+                .map_ok(|receipts| stream::iter(receipts.into_iter()).map(Ok))
+                .try_flatten(),
+        )
+    }
+}
+
+impl<Client> ConcurrentDownloader<Client>
+where
+    Client: ReceiptsClient,
+{
+    /// Create a new concurrent downloader instance.
+    pub fn new(client: Arc<Client>) -> Self {
+        Self { client, retries: 3, batch_size: 100, concurrency: 5 }
+    }
+
+    /// Set the number of blocks to fetch the receipts of at the same time.
+    ///
+    /// Defaults to 100.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set the maximum number of requests to send concurrently.
+    ///
+    /// Defaults to 5.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Set the number of times to retry receipt fetch requests.
+    ///
+    /// Defaults to 3.
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Given a batch of headers, this function proceeds to:
+    /// 1. Filter for all the headers with a non-empty receipts root
+    /// 2. Return early with empty receipts, if there were no such headers, else..
+    /// 3. Request the receipts for these headers from a peer chosen by the network client
+    /// 4. Validate the receipts of every block against the receipts root of its header
+    ///
+    /// NB: Like bodies, this assumes that peers respond with receipts in the order that they were
+    /// requested. Responses that do not match the receipts root cause the peer to get penalized.
+    async fn fetch_receipts(
+        &self,
+        headers: Vec<&SealedHeader>,
+    ) -> DownloadResult<Vec<BlockReceipts>> {
+        let headers_to_download = headers
+            .iter()
+            .filter(|h| h.receipts_root != EMPTY_ROOT)
+            .map(|h| h.hash())
+            .collect::<Vec<_>>();
+        if headers_to_download.is_empty() {
+            tracing::trace!(target: "downloaders::receipts", len = headers.len(), "Nothing to download");
+            return Ok(headers
+                .into_iter()
+                .cloned()
+                .map(|header| BlockReceipts { header, receipts: Vec::new() })
+                .collect());
+        }
+
+        // The receipts might exceed a max response size limit set by peer. We need to keep
+        // retrying until we finish downloading all of the requested receipts
+        let mut responses = Vec::with_capacity(headers_to_download.len());
+        while responses.len() != headers_to_download.len() {
+            let request: Vec<_> =
+                headers_to_download.iter().skip(responses.len()).cloned().collect();
+            let request_len = request.len();
+            tracing::trace!(target: "downloaders::receipts", request_len, "Requesting receipts");
+            let (peer_id, receipts) = self.client.get_receipts(request.clone()).await?.split();
+
+            if receipts.is_empty() {
+                tracing::error!(
+                    target: "downloaders::receipts", ?peer_id, ?request, "Received empty response. Penalizing peer"
+                );
+                self.client.report_bad_message(peer_id);
+                // return error and trigger new retry
+                return Err(DownloadError::RequestError(RequestError::BadResponse))
+            }
+
+            tracing::trace!(
+                target: "downloaders::receipts", request_len, response_len = receipts.len(), ?peer_id, "Received receipts"
+            );
+            receipts.into_iter().take(request_len).for_each(|r| responses.push((peer_id, r)));
+        }
+
+        let mut receipts = responses.into_iter();
+        let mut results = Vec::with_capacity(headers.len());
+        for header in headers.into_iter().cloned() {
+            if header.receipts_root == EMPTY_ROOT {
+                results.push(BlockReceipts { header, receipts: Vec::new() });
+                continue
+            }
+
+            // The receipts must be present since we requested them for all blocks with a
+            // non-empty receipts root at this point
+            let (peer_id, block_receipts) = receipts.next().expect("download logic failed");
+
+            let root = calculate_receipt_root(block_receipts.iter());
+            if root != header.receipts_root {
+                tracing::trace!(
+                    target: "downloaders::receipts", ?peer_id, header = ?header.hash(), got = ?root, expected = ?header.receipts_root, "Penalizing peer"
+                );
+                self.client.report_bad_message(peer_id);
+                return Err(DownloadError::BlockValidation {
+                    hash: header.hash(),
+                    error: consensus::Error::BodyReceiptsRootDiff {
+                        got: root,
+                        expected: header.receipts_root,
+                    },
+                });
+            }
+
+            results.push(BlockReceipts { header, receipts: block_receipts });
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{generate_receipts, TestReceiptsClient};
+    use assert_matches::assert_matches;
+    use reth_primitives::{PeerId, Receipt, H256};
+    use std::time::Duration;
+
+    /// Checks that the receipts are emitted in order of block number and that empty blocks are
+    /// not requested
+    #[tokio::test]
+    async fn emits_receipts_in_order() {
+        let (headers, receipts) = generate_receipts(0..20);
+
+        let downloader =
+            ConcurrentDownloader::new(Arc::new(TestReceiptsClient::new(|hashes: Vec<H256>| {
+                let mut receipts = receipts.clone();
+                async move {
+                    // Simulate that the request for this (random) block takes 0-100ms
+                    tokio::time::sleep(Duration::from_millis(hashes[0].to_low_u64_be() % 100))
+                        .await;
+
+                    Ok((
+                        PeerId::default(),
+                        hashes
+                            .into_iter()
+                            .map(|hash| {
+                                let receipts = receipts.remove(&hash).unwrap();
+                                assert!(!receipts.is_empty(), "requested an empty block");
+                                receipts
+                            })
+                            .collect(),
+                    )
+                        .into())
+                }
+            })))
+            .with_batch_size(3);
+
+        assert_matches!(
+            downloader.receipts_stream(headers.iter()).try_collect::<Vec<_>>().await,
+            Ok(responses) => {
+                assert_eq!(
+                    responses,
+                    headers
+                        .into_iter()
+                        .map(|header| {
+                            let receipts = receipts[&header.hash()].clone();
+                            BlockReceipts { header, receipts }
+                        })
+                        .collect::<Vec<_>>()
+                );
+            }
+        );
+    }
+
+    /// Checks that receipts that don't match the receipts root are rejected
+    #[tokio::test]
+    async fn rejects_invalid_receipts_root() {
+        let (headers, _) = generate_receipts(1..3);
+
+        let downloader = ConcurrentDownloader::new(Arc::new(TestReceiptsClient::new(
+            |hashes: Vec<H256>| async move {
+                let receipts = hashes.iter().map(|_| vec![Receipt::default()]).collect();
+                Ok((PeerId::default(), receipts).into())
+            },
+        )))
+        .with_retries(0);
+
+        assert_matches!(
+            downloader.receipts_stream(headers.iter()).next().await,
+            Some(Err(DownloadError::BlockValidation {
+                error: consensus::Error::BodyReceiptsRootDiff { .. },
+                ..
+            }))
+        );
+    }
+
+    /// Checks that an empty response is treated as a bad response
+    #[tokio::test]
+    async fn rejects_empty_response() {
+        let (headers, _) = generate_receipts(1..3);
+
+        let downloader =
+            ConcurrentDownloader::new(Arc::new(TestReceiptsClient::new(|_: Vec<H256>| async {
+                Ok((PeerId::default(), vec![]).into())
+            })))
+            .with_retries(0);
+
+        assert_matches!(
+            downloader.receipts_stream(headers.iter()).next().await,
+            Some(Err(DownloadError::RequestError(RequestError::BadResponse)))
+        );
+    }
+}
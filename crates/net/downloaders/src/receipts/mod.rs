@@ -0,0 +1,2 @@
+/// A naive concurrent downloader.
+pub mod concurrent;
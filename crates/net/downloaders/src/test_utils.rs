@@ -3,10 +3,13 @@
 use async_trait::async_trait;
 use reth_eth_wire::BlockBody;
 use reth_interfaces::{
-    p2p::{bodies::client::BodiesClient, downloader::DownloadClient, error::PeerRequestResult},
-    test_utils::generators::random_block_range,
+    p2p::{
+        bodies::client::BodiesClient, downloader::DownloadClient, error::PeerRequestResult,
+        receipts::client::ReceiptsClient,
+    },
+    test_utils::generators::{random_block_range, random_header_range},
 };
-use reth_primitives::{PeerId, SealedHeader, H256};
+use reth_primitives::{proofs::calculate_receipt_root, PeerId, Receipt, SealedHeader, H256};
 use std::{
     collections::HashMap,
     fmt::{Debug, Formatter},
@@ -30,6 +33,7 @@ pub(crate) fn generate_bodies(
                 BlockBody {
                     transactions: block.body,
                     ommers: block.ommers.into_iter().map(|header| header.unseal()).collect(),
+                    withdrawals: block.withdrawals,
                 },
             )
         })
@@ -38,6 +42,28 @@ pub(crate) fn generate_bodies(
     (headers, bodies)
 }
 
+/// Generate a set of headers and the receipts of their blocks, mapped by block hash
+pub(crate) fn generate_receipts(
+    rng: std::ops::Range<u64>,
+) -> (Vec<SealedHeader>, HashMap<H256, Vec<Receipt>>) {
+    let mut receipts = HashMap::new();
+    let headers = random_header_range(rng, H256::zero())
+        .into_iter()
+        .map(|header| {
+            let block_receipts = (0..header.number % 3)
+                .map(|i| Receipt { cumulative_gas_used: 21_000 * (i + 1), ..Default::default() })
+                .collect::<Vec<_>>();
+            let mut header = header.unseal();
+            header.receipts_root = calculate_receipt_root(block_receipts.iter());
+            let header = header.seal();
+            receipts.insert(header.hash(), block_receipts);
+            header
+        })
+        .collect();
+
+    (headers, receipts)
+}
+
 /// A [BodiesClient] for testing.
 pub(crate) struct TestBodiesClient<F>(pub(crate) Arc<Mutex<F>>);
 
@@ -70,3 +96,36 @@ where
         (f)(hash).await
     }
 }
+
+/// A [ReceiptsClient] for testing.
+pub(crate) struct TestReceiptsClient<F>(pub(crate) Arc<Mutex<F>>);
+
+impl<F> Debug for TestReceiptsClient<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestReceiptsClient").finish_non_exhaustive()
+    }
+}
+
+impl<F> TestReceiptsClient<F> {
+    pub(crate) fn new(f: F) -> Self {
+        Self(Arc::new(Mutex::new(f)))
+    }
+}
+
+impl<F: Send + Sync> DownloadClient for TestReceiptsClient<F> {
+    fn report_bad_message(&self, _peer_id: PeerId) {
+        // noop
+    }
+}
+
+#[async_trait]
+impl<F, Fut> ReceiptsClient for TestReceiptsClient<F>
+where
+    F: FnMut(Vec<H256>) -> Fut + Send + Sync,
+    Fut: Future<Output = PeerRequestResult<Vec<Vec<Receipt>>>> + Send,
+{
+    async fn get_receipts(&self, hashes: Vec<H256>) -> PeerRequestResult<Vec<Vec<Receipt>>> {
+        let f = &mut *self.0.lock().await;
+        (f)(hashes).await
+    }
+}
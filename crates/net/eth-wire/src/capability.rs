@@ -1,6 +1,9 @@
 //! All capability related types
 
-use crate::{version::ParseVersionError, EthMessage, EthVersion};
+use crate::{
+    version::ParseVersionError, EthMessage, EthVersion, SnapMessageID, SNAP_PROTOCOL_NAME,
+    SNAP_PROTOCOL_VERSION,
+};
 use bytes::{BufMut, Bytes};
 use reth_rlp::{Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable};
 use serde::{Deserialize, Serialize};
@@ -27,7 +30,7 @@ pub enum CapabilityMessage {
 
 /// A message indicating a supported capability and capability version.
 #[derive(
-    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
+    Clone, Debug, PartialEq, Eq, Hash, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
 )]
 pub struct Capability {
     /// The name of the subprotocol
@@ -53,6 +56,41 @@ impl Capability {
     pub fn is_eth_v67(&self) -> bool {
         self.name == "eth" && self.version == 67
     }
+
+    /// Returns the `snap/1` capability.
+    pub fn snap() -> Self {
+        Self::new(SNAP_PROTOCOL_NAME.into(), SNAP_PROTOCOL_VERSION)
+    }
+
+    /// Whether this is snap v1.
+    #[inline]
+    pub fn is_snap_v1(&self) -> bool {
+        self.name == SNAP_PROTOCOL_NAME && self.version == SNAP_PROTOCOL_VERSION
+    }
+}
+
+/// A sub-protocol that can be multiplexed over a `p2p` connection next to `eth`, e.g. `snap`.
+///
+/// Message ids of the shared capabilities are assigned in consecutive ranges, so the number of
+/// messages of every capability other than `eth` must be known to negotiate it with a peer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Protocol {
+    /// The capability the protocol is advertised with.
+    pub cap: Capability,
+    /// The number of messages of the protocol.
+    pub messages: u8,
+}
+
+impl Protocol {
+    /// Create a new `Protocol` for the given capability with the given number of messages.
+    pub fn new(cap: Capability, messages: u8) -> Self {
+        Self { cap, messages }
+    }
+
+    /// Returns the `snap/1` protocol, see [`SnapMessage`](crate::SnapMessage).
+    pub fn snap() -> Self {
+        Self::new(Capability::snap(), SnapMessageID::COUNT)
+    }
 }
 
 /// Represents all capabilities of a node.
@@ -62,6 +100,7 @@ pub struct Capabilities {
     inner: Vec<Capability>,
     eth_66: bool,
     eth_67: bool,
+    snap_1: bool,
 }
 
 impl Capabilities {
@@ -94,6 +133,12 @@ impl Capabilities {
     pub fn supports_eth_v67(&self) -> bool {
         self.eth_67
     }
+
+    /// Whether this peer supports snap v1 protocol.
+    #[inline]
+    pub fn supports_snap_v1(&self) -> bool {
+        self.snap_1
+    }
 }
 
 impl From<Vec<Capability>> for Capabilities {
@@ -101,6 +146,7 @@ impl From<Vec<Capability>> for Capabilities {
         Self {
             eth_66: value.iter().any(Capability::is_eth_v66),
             eth_67: value.iter().any(Capability::is_eth_v67),
+            snap_1: value.iter().any(Capability::is_snap_v1),
             inner: value,
         }
     }
@@ -119,6 +165,7 @@ impl Decodable for Capabilities {
         Ok(Self {
             eth_66: inner.iter().any(Capability::is_eth_v66),
             eth_67: inner.iter().any(Capability::is_eth_v67),
+            snap_1: inner.iter().any(Capability::is_snap_v1),
             inner,
         })
     }
@@ -131,6 +178,9 @@ pub enum SharedCapability {
     /// The `eth` capability.
     Eth { version: EthVersion, offset: u8 },
 
+    /// A capability of a registered [`Protocol`] other than `eth`.
+    Protocol { name: SmolStr, version: u8, offset: u8, messages: u8 },
+
     /// An unknown capability.
     UnknownCapability { name: SmolStr, version: u8, offset: u8 },
 }
@@ -144,10 +194,27 @@ impl SharedCapability {
         }
     }
 
+    /// Creates a new [`SharedCapability`] for the given registered protocol at the given offset.
+    ///
+    /// The number of messages of `eth` is always determined by its version.
+    pub(crate) fn from_protocol(
+        protocol: &Protocol,
+        offset: u8,
+    ) -> Result<Self, SharedCapabilityError> {
+        let Protocol { cap, messages } = protocol;
+        match Self::new(&cap.name, cap.version as u8, offset)? {
+            Self::UnknownCapability { name, version, offset } => {
+                Ok(Self::Protocol { name, version, offset, messages: *messages })
+            }
+            eth => Ok(eth),
+        }
+    }
+
     /// Returns the name of the capability.
     pub fn name(&self) -> &str {
         match self {
             SharedCapability::Eth { .. } => "eth",
+            SharedCapability::Protocol { name, .. } => name,
             SharedCapability::UnknownCapability { name, .. } => name,
         }
     }
@@ -156,6 +223,7 @@ impl SharedCapability {
     pub fn version(&self) -> u8 {
         match self {
             SharedCapability::Eth { version, .. } => *version as u8,
+            SharedCapability::Protocol { version, .. } => *version,
             SharedCapability::UnknownCapability { version, .. } => *version,
         }
     }
@@ -164,6 +232,7 @@ impl SharedCapability {
     pub fn offset(&self) -> u8 {
         match self {
             SharedCapability::Eth { offset, .. } => *offset,
+            SharedCapability::Protocol { offset, .. } => *offset,
             SharedCapability::UnknownCapability { offset, .. } => *offset,
         }
     }
@@ -172,6 +241,7 @@ impl SharedCapability {
     pub fn num_messages(&self) -> Result<u8, SharedCapabilityError> {
         match self {
             SharedCapability::Eth { version, .. } => Ok(version.total_messages()),
+            SharedCapability::Protocol { messages, .. } => Ok(*messages),
             _ => Err(SharedCapabilityError::UnknownCapability),
         }
     }
@@ -209,4 +279,18 @@ mod tests {
         assert_eq!(capability.version(), 66);
         assert_eq!(capability, SharedCapability::Eth { version: EthVersion::Eth66, offset: 0 });
     }
+
+    #[test]
+    fn from_protocol() {
+        let snap = Protocol::new(Capability::new("snap".into(), 1), 8);
+        let capability = SharedCapability::from_protocol(&snap, 0x1d).unwrap();
+
+        assert_eq!(capability.name(), "snap");
+        assert_eq!(capability.offset(), 0x1d);
+        assert_eq!(capability.num_messages().unwrap(), 8);
+
+        let eth = Protocol::new(EthVersion::Eth67.into(), 0);
+        let capability = SharedCapability::from_protocol(&eth, 0x10).unwrap();
+        assert_eq!(capability, SharedCapability::Eth { version: EthVersion::Eth67, offset: 0x10 });
+    }
 }
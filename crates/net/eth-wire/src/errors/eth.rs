@@ -1,5 +1,5 @@
 //! Error handling for (`EthStream`)[crate::EthStream]
-use crate::{errors::P2PStreamError, DisconnectReason};
+use crate::{errors::P2PStreamError, DisconnectReason, EthMessageID};
 use reth_primitives::{Chain, ValidationError, H256};
 use std::io;
 
@@ -13,6 +13,8 @@ pub enum EthStreamError {
     EthHandshakeError(#[from] EthHandshakeError),
     #[error("message size ({0}) exceeds max length (10MB)")]
     MessageTooBig(usize),
+    #[error("message id {1:?} is invalid for eth/{0}")]
+    EthInvalidMessageError(u8, EthMessageID),
 }
 
 // === impl EthStreamError ===
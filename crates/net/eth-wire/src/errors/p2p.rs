@@ -35,6 +35,12 @@ pub enum P2PStreamError {
     PingBeforeHandshake,
     #[error("too many messages buffered before sending")]
     SendBufferFull,
+    #[error("too many messages buffered for a multiplexed protocol")]
+    ProtocolBufferFull,
+    #[error("message id {0} is not a message of the protocol")]
+    UnknownProtocolMessageId(u8),
+    #[error("connection closed")]
+    ConnectionClosed,
     #[error("disconnected")]
     Disconnected(DisconnectReason),
     #[error("unknown disconnect reason: {0}")]
@@ -66,6 +72,8 @@ pub enum P2PHandshakeError {
     NonHelloMessageInHandshake,
     #[error("no capabilities shared with peer")]
     NoSharedCapabilities,
+    #[error("eth capability not shared with peer")]
+    NoSharedEthCapability,
     #[error("no response received when sending out handshake")]
     NoResponse,
     #[error("handshake timed out")]
@@ -1,6 +1,6 @@
 #![allow(dead_code, unreachable_pub, missing_docs, unused_variables)]
 use crate::{
-    capability::{Capability, SharedCapability},
+    capability::{Capability, Protocol, SharedCapability},
     errors::{P2PHandshakeError, P2PStreamError},
     pinger::{Pinger, PingerEvent},
     DisconnectReason, HelloMessage,
@@ -12,12 +12,13 @@ use pin_project::pin_project;
 use reth_rlp::{Decodable, DecodeError, Encodable, EMPTY_LIST_CODE};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeSet, HashMap, VecDeque},
+    collections::{BTreeMap, HashSet, VecDeque},
     io,
     pin::Pin,
     task::{ready, Context, Poll},
     time::Duration,
 };
+use tokio::sync::mpsc;
 use tokio_stream::Stream;
 
 /// [`MAX_PAYLOAD_SIZE`] is the maximum size of an uncompressed message payload.
@@ -57,12 +58,24 @@ const MAX_P2P_CAPACITY: usize = 64;
 pub struct UnauthedP2PStream<S> {
     #[pin]
     inner: S,
+    /// The sub-protocols other than `eth` that can be multiplexed over the stream.
+    protocols: Vec<Protocol>,
 }
 
 impl<S> UnauthedP2PStream<S> {
     /// Create a new `UnauthedP2PStream` from a type `S` which implements `Stream` and `Sink`.
     pub fn new(inner: S) -> Self {
-        Self { inner }
+        Self { inner, protocols: Vec::new() }
+    }
+
+    /// Registers a sub-protocol that is multiplexed over the [`P2PStream`] if it is shared with
+    /// the peer.
+    ///
+    /// The capability of the protocol must also be advertised in the `Hello` message of the
+    /// handshake.
+    pub fn with_protocol(mut self, protocol: Protocol) -> Self {
+        self.protocols.push(protocol);
+        self
     }
 }
 
@@ -131,11 +144,23 @@ where
             })
         }
 
-        // determine shared capabilities (currently returns only one capability)
-        let capability =
-            set_capability_offsets(hello.capabilities, their_hello.capabilities.clone())?;
-
-        let stream = P2PStream::new(self.inner, capability);
+        // determine shared capabilities, `eth` is the primary capability of the stream if it is
+        // shared and all other capabilities are multiplexed over it
+        let mut shared_capabilities = set_capability_offsets(
+            hello.capabilities,
+            their_hello.capabilities.clone(),
+            &self.protocols,
+        )?;
+        let primary = shared_capabilities
+            .iter()
+            .position(|capability| matches!(capability, SharedCapability::Eth { .. }))
+            .ok_or(P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedEthCapability))?;
+        let capability = shared_capabilities.remove(primary);
+
+        let mut stream = P2PStream::new(self.inner, capability);
+        for capability in shared_capabilities {
+            stream.add_protocol(capability);
+        }
 
         Ok((stream, their_hello))
     }
@@ -161,6 +186,9 @@ pub struct P2PStream<S> {
     /// The supported capability for this stream.
     shared_capability: SharedCapability,
 
+    /// The sub-protocols that are multiplexed over this stream next to its capability.
+    protocols: Vec<MultiplexedProtocol>,
+
     /// Outgoing messages buffered for sending to the underlying stream.
     outgoing_messages: VecDeque<Bytes>,
 
@@ -180,6 +208,7 @@ impl<S> P2PStream<S> {
             decoder: snap::raw::Decoder::new(),
             pinger: Pinger::new(PING_INTERVAL, PING_TIMEOUT),
             shared_capability: capability,
+            protocols: Vec::new(),
             outgoing_messages: VecDeque::new(),
             disconnecting: false,
         }
@@ -190,6 +219,32 @@ impl<S> P2PStream<S> {
         &self.shared_capability
     }
 
+    /// Returns all capabilities shared with the peer, including the ones of the multiplexed
+    /// sub-protocols.
+    pub fn shared_capabilities(&self) -> impl Iterator<Item = &SharedCapability> + '_ {
+        std::iter::once(&self.shared_capability)
+            .chain(self.protocols.iter().map(|protocol| &protocol.capability))
+    }
+
+    /// Multiplexes the sub-protocol of the given capability over this stream.
+    ///
+    /// Messages of the capability are no longer yielded by this stream but by the
+    /// [`ProtocolStream`] of the capability, see [`P2PStream::take_protocol_stream`].
+    pub(crate) fn add_protocol(&mut self, capability: SharedCapability) {
+        self.protocols.push(MultiplexedProtocol::new(capability));
+    }
+
+    /// Takes the [`ProtocolStream`] of the multiplexed sub-protocol with the given name.
+    ///
+    /// Returns `None` if the capability is not shared with the peer, is the primary capability
+    /// of this stream, or if its stream was already taken.
+    pub fn take_protocol_stream(&mut self, name: &str) -> Option<ProtocolStream> {
+        self.protocols
+            .iter_mut()
+            .find(|protocol| protocol.capability.name() == name)
+            .and_then(|protocol| protocol.stream.take())
+    }
+
     /// Returns `true` if the connection is about to disconnect.
     pub fn is_disconnecting(&self) -> bool {
         self.disconnecting
@@ -394,6 +449,16 @@ where
                     //  * `eth/67` is reserved message IDs 0x10 - 0x19.
                     //  * `qrs/65` is reserved message IDs 0x1a - 0x21.
                     //
+                    if let Some(protocol) =
+                        this.protocols.iter().find(|protocol| protocol.contains(id))
+                    {
+                        decompress_buf[0] = id - protocol.capability.offset();
+                        if let Err(err) = protocol.on_message(decompress_buf) {
+                            return Poll::Ready(Some(Err(err)))
+                        }
+                        continue
+                    }
+
                     decompress_buf[0] = bytes[0] - this.shared_capability.offset();
 
                     return Poll::Ready(Some(Ok(decompress_buf)))
@@ -447,17 +512,11 @@ where
             "Compressing message",
         );
 
-        let mut compressed = BytesMut::zeroed(1 + snap::raw::max_compress_len(item.len() - 1));
-        let compressed_size = this.encoder.compress(&item[1..], &mut compressed[1..])?;
-
-        // truncate the compressed buffer to the actual compressed size (plus one for the message
-        // id)
-        compressed.truncate(compressed_size + 1);
-
         // all messages sent in this stream are subprotocol messages, so we need to switch the
         // message id based on the offset
-        compressed[0] = item[0] + this.shared_capability.offset();
-        this.outgoing_messages.push_back(compressed.freeze());
+        let compressed =
+            compress_message(this.encoder, &item, item[0] + this.shared_capability.offset())?;
+        this.outgoing_messages.push_back(compressed);
 
         Ok(())
     }
@@ -470,12 +529,22 @@ where
             match ready!(this.inner.as_mut().poll_flush(cx)) {
                 Err(err) => return Poll::Ready(Err(err.into())),
                 Ok(()) => {
-                    if let Some(message) = this.outgoing_messages.pop_front() {
-                        if let Err(err) = this.inner.as_mut().start_send(message) {
-                            return Poll::Ready(Err(err.into()))
-                        }
-                    } else {
+                    // queue in the messages of the multiplexed protocols while the buffer has
+                    // capacity, so they are sent interleaved with the messages of this stream
+                    if !*this.disconnecting {
+                        queue_protocol_messages(
+                            this.protocols,
+                            this.encoder,
+                            this.outgoing_messages,
+                            cx,
+                        )?;
+                    }
+
+                    let Some(message) = this.outgoing_messages.pop_front() else {
                         return Poll::Ready(Ok(()))
+                    };
+                    if let Err(err) = this.inner.as_mut().start_send(message) {
+                        return Poll::Ready(Err(err.into()))
                     }
                 }
             }
@@ -489,20 +558,159 @@ where
     }
 }
 
+/// A sub-protocol that is multiplexed over a [`P2PStream`].
+#[derive(Debug)]
+struct MultiplexedProtocol {
+    /// The shared capability of the protocol.
+    capability: SharedCapability,
+    /// The number of messages of the protocol.
+    messages: u8,
+    /// Sends the incoming messages of the protocol to its [`ProtocolStream`].
+    to_protocol: mpsc::Sender<BytesMut>,
+    /// Receives the outgoing messages of the protocol from its [`ProtocolStream`].
+    from_protocol: mpsc::Receiver<Bytes>,
+    /// The stream of the protocol, until it is taken.
+    stream: Option<ProtocolStream>,
+}
+
+impl MultiplexedProtocol {
+    fn new(capability: SharedCapability) -> Self {
+        // unknown capabilities do not have a message id range, so they never receive messages
+        let messages = capability.num_messages().unwrap_or_default();
+        let (to_protocol, from_wire) = mpsc::channel(MAX_P2P_CAPACITY);
+        let (to_wire, from_protocol) = mpsc::channel(MAX_P2P_CAPACITY);
+        let stream =
+            ProtocolStream { capability: capability.clone(), messages, from_wire, to_wire };
+        Self { capability, messages, to_protocol, from_protocol, stream: Some(stream) }
+    }
+
+    /// Whether the message id is in the message id range of the protocol.
+    fn contains(&self, id: u8) -> bool {
+        let offset = self.capability.offset();
+        id >= offset && id - offset < self.messages
+    }
+
+    /// Forwards an incoming message, with its id relative to the offset of the protocol.
+    ///
+    /// Messages are dropped while the [`ProtocolStream`] was not taken or after it was dropped.
+    /// Returns an error if the [`ProtocolStream`] does not keep up with the incoming messages.
+    fn on_message(&self, msg: BytesMut) -> Result<(), P2PStreamError> {
+        if self.stream.is_some() {
+            tracing::trace!(
+                capability=%self.capability.name(),
+                "Dropping message of protocol without stream",
+            );
+            return Ok(())
+        }
+
+        match self.to_protocol.try_send(msg) {
+            Ok(()) => Ok(()),
+            Err(mpsc::error::TrySendError::Full(_)) => Err(P2PStreamError::ProtocolBufferFull),
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                tracing::trace!(
+                    capability=%self.capability.name(),
+                    "Dropping message of protocol without stream",
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A stream of the messages of a sub-protocol that is multiplexed over a [`P2PStream`].
+///
+/// Message ids are relative to the offset of the capability, so the first message of the
+/// protocol always has the id `0`. The stream ends once the [`P2PStream`] is dropped.
+#[derive(Debug)]
+pub struct ProtocolStream {
+    /// The shared capability of the protocol.
+    capability: SharedCapability,
+    /// The number of messages of the protocol.
+    messages: u8,
+    /// Receives the incoming messages of the protocol.
+    from_wire: mpsc::Receiver<BytesMut>,
+    /// Sends the outgoing messages of the protocol to the [`P2PStream`].
+    to_wire: mpsc::Sender<Bytes>,
+}
+
+impl ProtocolStream {
+    /// Returns the shared capability of the protocol.
+    pub fn capability(&self) -> &SharedCapability {
+        &self.capability
+    }
+
+    /// Queues in a message of the protocol to be sent by the [`P2PStream`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message id is not a message of the protocol, if too many messages
+    /// are buffered or if the [`P2PStream`] was dropped.
+    pub fn send(&self, msg: Bytes) -> Result<(), P2PStreamError> {
+        let id = *msg.first().ok_or(P2PStreamError::EmptyProtocolMessage)?;
+        if id >= self.messages {
+            return Err(P2PStreamError::UnknownProtocolMessageId(id))
+        }
+
+        self.to_wire.try_send(msg).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(_) => P2PStreamError::SendBufferFull,
+            mpsc::error::TrySendError::Closed(_) => P2PStreamError::ConnectionClosed,
+        })
+    }
+}
+
+impl Stream for ProtocolStream {
+    type Item = BytesMut;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().from_wire.poll_recv(cx)
+    }
+}
+
+/// Compresses the payload of the message with snappy and sets the given message id.
+fn compress_message(
+    encoder: &mut snap::raw::Encoder,
+    msg: &[u8],
+    id: u8,
+) -> Result<Bytes, snap::Error> {
+    let mut compressed = BytesMut::zeroed(1 + snap::raw::max_compress_len(msg.len() - 1));
+    let compressed_size = encoder.compress(&msg[1..], &mut compressed[1..])?;
+
+    // truncate the compressed buffer to the actual compressed size (plus one for the message id)
+    compressed.truncate(compressed_size + 1);
+    compressed[0] = id;
+    Ok(compressed.freeze())
+}
+
+/// Queues in the outgoing messages of the multiplexed protocols until the buffer is full.
+fn queue_protocol_messages(
+    protocols: &mut [MultiplexedProtocol],
+    encoder: &mut snap::raw::Encoder,
+    outgoing_messages: &mut VecDeque<Bytes>,
+    cx: &mut Context<'_>,
+) -> Result<(), snap::Error> {
+    for protocol in protocols {
+        while outgoing_messages.len() < MAX_P2P_CAPACITY {
+            let Poll::Ready(Some(msg)) = protocol.from_protocol.poll_recv(cx) else { break };
+            let id = msg[0] + protocol.capability.offset();
+            outgoing_messages.push_back(compress_message(encoder, &msg, id)?);
+        }
+    }
+    Ok(())
+}
+
 /// Determines the offsets for each shared capability between the input list of peer
 /// capabilities and the input list of locally supported capabilities.
 ///
-/// Currently only `eth` versions 66 and 67 are supported.
+/// Besides `eth`, only the capabilities of the given protocols are supported, because the number
+/// of messages of a capability must be known to determine the offsets of the following ones. The
+/// returned capabilities are ordered by their offset.
 pub fn set_capability_offsets(
     local_capabilities: Vec<Capability>,
     peer_capabilities: Vec<Capability>,
-) -> Result<SharedCapability, P2PStreamError> {
+    protocols: &[Protocol],
+) -> Result<Vec<SharedCapability>, P2PStreamError> {
     // find intersection of capabilities
-    let our_capabilities_map =
-        local_capabilities.into_iter().map(|c| (c.name, c.version)).collect::<HashMap<_, _>>();
-
-    // map of capability name to version
-    let mut shared_capabilities = HashMap::new();
+    let our_capabilities = local_capabilities.into_iter().collect::<HashSet<_>>();
 
     // The `Ord` implementation for capability names should be equivalent to geth (and every other
     // client), since geth uses golang's default string comparison, which orders strings
@@ -516,18 +724,16 @@ pub fn set_capability_offsets(
     //
     // The `Ord` implementation for `SmolStr` (used here) currently delegates to rust's `Ord`
     // implementation for `str`, which also orders strings lexicographically.
-    let mut shared_capability_names = BTreeSet::new();
+    let mut shared_capabilities = BTreeMap::new();
 
     // find highest shared version of each shared capability
     for capability in peer_capabilities {
-        // if this is Some, we share this capability
-        if let Some(version) = our_capabilities_map.get(&capability.name) {
+        // we share this capability if we support the same version
+        if our_capabilities.contains(&capability) {
             // If multiple versions are shared of the same (equal name) capability, the numerically
             // highest wins, others are ignored
-            if capability.version <= *version {
-                shared_capabilities.insert(capability.name.clone(), capability.version);
-                shared_capability_names.insert(capability.name);
-            }
+            let version = shared_capabilities.entry(capability.name).or_insert(capability.version);
+            *version = capability.version.max(*version);
         }
     }
 
@@ -546,36 +752,34 @@ pub fn set_capability_offsets(
     // "p2p" capability) and given to each shared (equal-version, equal-name) capability in
     // alphabetic order.
     let mut offset = MAX_RESERVED_MESSAGE_ID + 1;
-    for name in shared_capability_names {
-        let version = shared_capabilities.get(&name).unwrap();
-
-        let shared_capability = SharedCapability::new(&name, *version as u8, offset)?;
+    for (name, version) in shared_capabilities {
+        let protocol = protocols
+            .iter()
+            .find(|protocol| protocol.cap.name == name && protocol.cap.version == version);
+        let shared_capability = match protocol {
+            Some(protocol) => SharedCapability::from_protocol(protocol, offset)?,
+            None => SharedCapability::new(&name, version as u8, offset)?,
+        };
 
         match shared_capability {
             SharedCapability::UnknownCapability { .. } => {
-                // Capabilities which are not shared are ignored
+                // Capabilities without a registered protocol are ignored
                 tracing::warn!("unknown capability: name={:?}, version={}", name, version,);
             }
-            SharedCapability::Eth { .. } => {
-                shared_with_offsets.push(shared_capability.clone());
-
+            SharedCapability::Eth { .. } | SharedCapability::Protocol { .. } => {
                 // increment the offset if the capability is known
                 offset += shared_capability.num_messages()?;
+
+                shared_with_offsets.push(shared_capability);
             }
         }
     }
 
-    // TODO: support multiple capabilities - we would need a new Stream type to go on top of
-    // `P2PStream` containing its capability. `P2PStream` would still send pings and handle
-    // pongs, but instead contain a map of capabilities to their respective stream / channel.
-    // Each channel would be responsible for containing the offset for that stream and would
-    // only increment / decrement message IDs.
-    // NOTE: since the `P2PStream` currently only supports one capability, we set the
-    // capability with the lowest offset.
-    Ok(shared_with_offsets
-        .first()
-        .ok_or(P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedCapabilities))?
-        .clone())
+    if shared_with_offsets.is_empty() {
+        return Err(P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedCapabilities))
+    }
+
+    Ok(shared_with_offsets)
 }
 
 /// This represents only the reserved `p2p` subprotocol messages.
@@ -851,6 +1055,155 @@ mod tests {
         handle.await.unwrap();
     }
 
+    fn snap_protocol() -> Protocol {
+        Protocol::new(Capability::new("snap".into(), 1), 8)
+    }
+
+    #[test]
+    fn test_capability_offsets() {
+        let local = vec![
+            EthVersion::Eth66.into(),
+            EthVersion::Eth67.into(),
+            Capability::new("snap".into(), 1),
+            Capability::new("qrs".into(), 1),
+        ];
+        let peer = vec![
+            Capability::new("snap".into(), 1),
+            EthVersion::Eth67.into(),
+            EthVersion::Eth66.into(),
+            Capability::new("qrs".into(), 1),
+        ];
+
+        // `qrs` is not registered, so its number of messages is unknown and it is ignored
+        let shared = set_capability_offsets(local, peer, &[snap_protocol()]).unwrap();
+        assert_eq!(
+            shared,
+            vec![
+                SharedCapability::Eth { version: EthVersion::Eth67, offset: 0x10 },
+                SharedCapability::Protocol {
+                    name: "snap".into(),
+                    version: 1,
+                    offset: 0x10 + EthVersion::Eth67.total_messages(),
+                    messages: 8
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multiplex_protocol() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let snap_hello = || {
+            let (mut hello, _) = eth_hello();
+            hello.capabilities.push(snap_protocol().cap);
+            hello
+        };
+
+        let handle = tokio::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let stream = crate::PassthroughCodec::default().framed(incoming);
+
+            let (mut p2p_stream, _) = UnauthedP2PStream::new(stream)
+                .with_protocol(snap_protocol())
+                .handshake(snap_hello())
+                .await
+                .unwrap();
+            let snap = p2p_stream.take_protocol_stream("snap").unwrap();
+            assert!(p2p_stream.take_protocol_stream("snap").is_none());
+
+            // messages outside of the protocol's message id range are rejected
+            assert!(matches!(
+                snap.send(Bytes::from_static(&[0x08, 0xc0])),
+                Err(P2PStreamError::UnknownProtocolMessageId(0x08))
+            ));
+
+            snap.send(Bytes::from_static(&[0x01, 0xc0])).unwrap();
+            p2p_stream.flush().await.unwrap();
+            p2p_stream.send(Bytes::from_static(&[0x00, 0xc0])).await.unwrap();
+        });
+
+        let outgoing = TcpStream::connect(local_addr).await.unwrap();
+        let sink = crate::PassthroughCodec::default().framed(outgoing);
+
+        let (mut p2p_stream, _) = UnauthedP2PStream::new(sink)
+            .with_protocol(snap_protocol())
+            .handshake(snap_hello())
+            .await
+            .unwrap();
+        assert_eq!(p2p_stream.shared_capabilities().count(), 2);
+        let mut snap = p2p_stream.take_protocol_stream("snap").unwrap();
+
+        // the `eth` message is yielded by the stream itself, the `snap` message that was sent
+        // before it is forwarded to the protocol stream
+        let eth_msg = p2p_stream.next().await.unwrap().unwrap();
+        assert_eq!(eth_msg.as_ref(), &[0x00, 0xc0]);
+        let snap_msg = snap.next().await.unwrap();
+        assert_eq!(snap_msg.as_ref(), &[0x01, 0xc0]);
+
+        handle.await.unwrap();
+    }
+
+    #[test]
+    fn test_protocol_buffer_full() {
+        let capability = SharedCapability::from_protocol(&snap_protocol(), 0x21).unwrap();
+        let mut protocol = MultiplexedProtocol::new(capability);
+
+        // messages are dropped until the protocol stream is taken
+        protocol.on_message(BytesMut::from(&[0x00, 0xc0][..])).unwrap();
+        let mut stream = protocol.stream.take().unwrap();
+        assert!(stream.from_wire.try_recv().is_err());
+
+        for _ in 0..MAX_P2P_CAPACITY {
+            protocol.on_message(BytesMut::from(&[0x00, 0xc0][..])).unwrap();
+        }
+        assert!(matches!(
+            protocol.on_message(BytesMut::from(&[0x00, 0xc0][..])),
+            Err(P2PStreamError::ProtocolBufferFull)
+        ));
+
+        // messages are dropped once the protocol stream is dropped
+        drop(stream);
+        protocol.on_message(BytesMut::from(&[0x00, 0xc0][..])).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_eth_not_shared() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let snap_hello = || {
+            let (mut hello, _) = eth_hello();
+            hello.capabilities = vec![snap_protocol().cap];
+            hello
+        };
+
+        let handle = tokio::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let stream = crate::PassthroughCodec::default().framed(incoming);
+            let _ = UnauthedP2PStream::new(stream)
+                .with_protocol(snap_protocol())
+                .handshake(snap_hello())
+                .await;
+        });
+
+        let outgoing = TcpStream::connect(local_addr).await.unwrap();
+        let sink = crate::PassthroughCodec::default().framed(outgoing);
+
+        let err = UnauthedP2PStream::new(sink)
+            .with_protocol(snap_protocol())
+            .handshake(snap_hello())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedEthCapability)
+        ));
+
+        handle.await.unwrap();
+    }
+
     #[test]
     fn snappy_decode_encode_ping() {
         let snappy_ping = b"\x02\x01\0\xc0";
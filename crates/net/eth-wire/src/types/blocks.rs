@@ -1,8 +1,14 @@
 //! Implements the `GetBlockHeaders`, `GetBlockBodies`, `BlockHeaders`, and `BlockBodies` message
 //! types.
 use super::RawBlockBody;
-use reth_primitives::{BlockHashOrNumber, Header, HeadersDirection, TransactionSigned, H256};
-use reth_rlp::{RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper};
+use bytes::BufMut;
+use reth_primitives::{
+    BlockHashOrNumber, Header, HeadersDirection, TransactionSigned, Withdrawal, H256,
+};
+use reth_rlp::{
+    Decodable, DecodeError, Encodable, RlpDecodable, RlpDecodableWrapper, RlpEncodable,
+    RlpEncodableWrapper,
+};
 use serde::{Deserialize, Serialize};
 
 /// A request for a peer to return block headers starting at the requested block.
@@ -82,14 +88,17 @@ impl From<Vec<H256>> for GetBlockBodies {
 
 // TODO(onbjerg): We should have this type in primitives
 /// A response to [`GetBlockBodies`], containing bodies if any bodies were found.
-#[derive(
-    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
-)]
+///
+/// The body is encoded as the list `[transactions, ommers, withdrawals]`, where the withdrawals
+/// are only appended since Shanghai.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct BlockBody {
     /// Transactions in the block
     pub transactions: Vec<TransactionSigned>,
     /// Uncle headers for the given block
     pub ommers: Vec<Header>,
+    /// Withdrawals in the block, present since Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
 }
 
 impl BlockBody {
@@ -99,7 +108,55 @@ impl BlockBody {
             header: header.clone(),
             transactions: self.transactions.clone(),
             ommers: self.ommers.clone(),
+            withdrawals: self.withdrawals.clone(),
+        }
+    }
+
+    fn payload_length(&self) -> usize {
+        let withdrawals = self.withdrawals.as_deref().map(reth_rlp::list_length::<Withdrawal, _>);
+        reth_rlp::list_length::<TransactionSigned, _>(&self.transactions) +
+            reth_rlp::list_length::<Header, _>(&self.ommers) +
+            withdrawals.unwrap_or_default()
+    }
+}
+
+impl Encodable for BlockBody {
+    fn encode(&self, out: &mut dyn BufMut) {
+        reth_rlp::Header { list: true, payload_length: self.payload_length() }.encode(out);
+        reth_rlp::encode_list::<TransactionSigned, _>(&self.transactions, out);
+        reth_rlp::encode_list::<Header, _>(&self.ommers, out);
+        if let Some(withdrawals) = &self.withdrawals {
+            reth_rlp::encode_list::<Withdrawal, _>(withdrawals, out);
+        }
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + reth_rlp::length_of_length(payload_length)
+    }
+}
+
+impl Decodable for BlockBody {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let rlp_head = reth_rlp::Header::decode(buf)?;
+        if !rlp_head.list {
+            return Err(DecodeError::UnexpectedString)
+        }
+        let started_len = buf.len();
+        let transactions = Decodable::decode(buf)?;
+        let ommers = Decodable::decode(buf)?;
+        let mut withdrawals = None;
+        if started_len - buf.len() < rlp_head.payload_length {
+            withdrawals = Some(Decodable::decode(buf)?);
         }
+        let consumed = started_len - buf.len();
+        if consumed != rlp_head.payload_length {
+            return Err(DecodeError::ListLengthMismatch {
+                expected: rlp_head.payload_length,
+                got: consumed,
+            })
+        }
+        Ok(Self { transactions, ommers, withdrawals })
     }
 }
 
@@ -134,8 +191,8 @@ mod test {
     };
     use hex_literal::hex;
     use reth_primitives::{
-        BlockHashOrNumber, Header, Signature, Transaction, TransactionKind, TransactionSigned,
-        TxLegacy, U256,
+        Address, BlockHashOrNumber, Header, Signature, Transaction, TransactionKind,
+        TransactionSigned, TxLegacy, Withdrawal, U256,
     };
     use reth_rlp::{Decodable, Encodable};
     use std::str::FromStr;
@@ -296,6 +353,7 @@ mod test {
                     mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                     nonce: 0x0000000000000000u64,
                     base_fee_per_gas: None,
+                    withdrawals_root: None,
                 },
             ]),
         }.encode(&mut data);
@@ -326,6 +384,7 @@ mod test {
                     mix_hash: hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                     nonce: 0x0000000000000000u64,
                     base_fee_per_gas: None,
+                    withdrawals_root: None,
                 },
             ]),
         };
@@ -437,8 +496,10 @@ mod test {
     hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                             nonce: 0x0000000000000000u64,
                             base_fee_per_gas: None,
+                            withdrawals_root: None,
                         },
                     ],
+                    withdrawals: None,
                 }
             ]),
         };
@@ -519,12 +580,42 @@ mod test {
     hex!("0000000000000000000000000000000000000000000000000000000000000000").into(),
                             nonce: 0x0000000000000000u64,
                             base_fee_per_gas: None,
+                            withdrawals_root: None,
                         },
                     ],
+                    withdrawals: None,
                 }
             ]),
         };
         let result = RequestPair::decode(&mut &data[..]).unwrap();
         assert_eq!(result, expected);
     }
+    #[test]
+    fn block_body_withdrawals_roundtrip() {
+        let body = BlockBody {
+            ommers: vec![Header::default()],
+            withdrawals: Some(vec![Withdrawal {
+                index: 1,
+                validator_index: 2,
+                address: Address::random(),
+                amount: 3,
+            }]),
+            ..Default::default()
+        };
+        let mut data = vec![];
+        body.encode(&mut data);
+        assert_eq!(data.len(), body.length());
+        assert_eq!(BlockBody::decode(&mut &data[..]).unwrap(), body);
+
+        // an empty withdrawals list must not be confused with a pre-Shanghai body
+        let body = BlockBody { withdrawals: Some(vec![]), ..Default::default() };
+        let mut data = vec![];
+        body.encode(&mut data);
+        assert_eq!(BlockBody::decode(&mut &data[..]).unwrap(), body);
+
+        let body = BlockBody::default();
+        let mut data = vec![];
+        body.encode(&mut data);
+        assert_eq!(BlockBody::decode(&mut &data[..]).unwrap().withdrawals, None);
+    }
 }
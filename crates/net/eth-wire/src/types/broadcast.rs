@@ -1,6 +1,10 @@
 //! Types for broadcasting new data.
-use reth_primitives::{Header, TransactionSigned, H256, U128};
-use reth_rlp::{RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper};
+use bytes::BufMut;
+use reth_primitives::{Header, TransactionSigned, Withdrawal, H256, U128};
+use reth_rlp::{
+    Decodable, DecodeError, Encodable, RlpDecodable, RlpDecodableWrapper, RlpEncodable,
+    RlpEncodableWrapper,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -59,10 +63,8 @@ impl From<NewBlockHashes> for Vec<BlockHashNumber> {
     }
 }
 
-/// A block body, including transactions and uncle headers.
-#[derive(
-    Debug, Clone, PartialEq, Eq, Default, RlpEncodable, RlpDecodable, Serialize, Deserialize,
-)]
+/// A block body, including transactions, uncle headers and, since Shanghai, withdrawals.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct RawBlockBody {
     /// This block's header
     pub header: Header,
@@ -70,6 +72,60 @@ pub struct RawBlockBody {
     pub transactions: Vec<TransactionSigned>,
     /// Uncle block headers.
     pub ommers: Vec<Header>,
+    /// Withdrawals in this block, present since Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+impl RawBlockBody {
+    fn payload_length(&self) -> usize {
+        let withdrawals = self.withdrawals.as_deref().map(reth_rlp::list_length::<Withdrawal, _>);
+        self.header.length() +
+            reth_rlp::list_length::<TransactionSigned, _>(&self.transactions) +
+            reth_rlp::list_length::<Header, _>(&self.ommers) +
+            withdrawals.unwrap_or_default()
+    }
+}
+
+impl Encodable for RawBlockBody {
+    fn encode(&self, out: &mut dyn BufMut) {
+        reth_rlp::Header { list: true, payload_length: self.payload_length() }.encode(out);
+        self.header.encode(out);
+        reth_rlp::encode_list::<TransactionSigned, _>(&self.transactions, out);
+        reth_rlp::encode_list::<Header, _>(&self.ommers, out);
+        if let Some(withdrawals) = &self.withdrawals {
+            reth_rlp::encode_list::<Withdrawal, _>(withdrawals, out);
+        }
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + reth_rlp::length_of_length(payload_length)
+    }
+}
+
+impl Decodable for RawBlockBody {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let rlp_head = reth_rlp::Header::decode(buf)?;
+        if !rlp_head.list {
+            return Err(DecodeError::UnexpectedString)
+        }
+        let started_len = buf.len();
+        let header = Decodable::decode(buf)?;
+        let transactions = Decodable::decode(buf)?;
+        let ommers = Decodable::decode(buf)?;
+        let mut withdrawals = None;
+        if started_len - buf.len() < rlp_head.payload_length {
+            withdrawals = Some(Decodable::decode(buf)?);
+        }
+        let consumed = started_len - buf.len();
+        if consumed != rlp_head.payload_length {
+            return Err(DecodeError::ListLengthMismatch {
+                expected: rlp_head.payload_length,
+                got: consumed,
+            })
+        }
+        Ok(Self { header, transactions, ommers, withdrawals })
+    }
 }
 
 /// A new block with the current total difficulty, which includes the difficulty of the returned
@@ -0,0 +1,440 @@
+//! Implements the `snap/1` protocol messages, see <https://github.com/ethereum/devp2p/blob/master/caps/snap.md>
+use bytes::{Buf, BufMut, Bytes};
+use reth_primitives::H256;
+use reth_rlp::{Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable};
+use serde::{Deserialize, Serialize};
+
+/// The name of the `snap` capability.
+pub const SNAP_PROTOCOL_NAME: &str = "snap";
+
+/// The version of the `snap` capability.
+pub const SNAP_PROTOCOL_VERSION: usize = 1;
+
+/// A request for the accounts of the state trie with the given root, starting at the given hash.
+#[derive(
+    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
+)]
+pub struct GetAccountRange {
+    /// The id of the request.
+    pub request_id: u64,
+    /// The root hash of the account trie to serve.
+    pub root_hash: H256,
+    /// The account hash of the first account to retrieve.
+    pub starting_hash: H256,
+    /// The account hash after which to stop serving data.
+    pub limit_hash: H256,
+    /// The soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// An account of an [`AccountRange`] response.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize)]
+pub struct AccountData {
+    /// The hash of the account address.
+    pub hash: H256,
+    /// The account in the slim RLP format, which omits the empty storage root and code hash.
+    pub body: RawRlp,
+}
+
+/// An RLP encoded item that is embedded in a message as-is, instead of as an RLP string.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawRlp(pub Bytes);
+
+impl Encodable for RawRlp {
+    fn encode(&self, out: &mut dyn BufMut) {
+        out.put_slice(&self.0)
+    }
+
+    fn length(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl Decodable for RawRlp {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let mut payload = *buf;
+        let header = reth_rlp::Header::decode(&mut payload)?;
+        let len = buf.len() - payload.len() + header.payload_length;
+        if buf.len() < len {
+            return Err(DecodeError::InputTooShort)
+        }
+        let raw = Bytes::copy_from_slice(&buf[..len]);
+        buf.advance(len);
+        Ok(Self(raw))
+    }
+}
+
+/// The response to [`GetAccountRange`], containing consecutive accounts and the merkle proofs of
+/// the range boundaries.
+#[derive(
+    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
+)]
+pub struct AccountRange {
+    /// The id of the request this is a response to.
+    pub request_id: u64,
+    /// The accounts of the range, ordered by their hash.
+    pub accounts: Vec<AccountData>,
+    /// The trie nodes proving the first and last account of the range.
+    pub proof: Vec<Bytes>,
+}
+
+/// A request for the storage slots of the given accounts in the state trie with the given root.
+#[derive(
+    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
+)]
+pub struct GetStorageRanges {
+    /// The id of the request.
+    pub request_id: u64,
+    /// The root hash of the account trie to serve.
+    pub root_hash: H256,
+    /// The hashes of the accounts whose storage to retrieve.
+    pub account_hashes: Vec<H256>,
+    /// The storage slot hash of the first slot to retrieve, empty to start at the first slot.
+    ///
+    /// Only applies to the first account.
+    pub starting_hash: Bytes,
+    /// The storage slot hash after which to stop serving data, empty to serve all slots.
+    ///
+    /// Only applies to the last account.
+    pub limit_hash: Bytes,
+    /// The soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// A storage slot of a [`StorageRanges`] response.
+#[derive(
+    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
+)]
+pub struct StorageData {
+    /// The hash of the storage slot key.
+    pub hash: H256,
+    /// The RLP encoded value of the storage slot.
+    pub data: Bytes,
+}
+
+/// The response to [`GetStorageRanges`], containing the storage slots of consecutive accounts.
+#[derive(
+    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
+)]
+pub struct StorageRanges {
+    /// The id of the request this is a response to.
+    pub request_id: u64,
+    /// The storage slots of each served account, ordered by their hash.
+    pub slots: Vec<Vec<StorageData>>,
+    /// The trie nodes proving the last slot range if it is incomplete.
+    pub proof: Vec<Bytes>,
+}
+
+/// A request for contract bytecodes by their hashes.
+#[derive(
+    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
+)]
+pub struct GetByteCodes {
+    /// The id of the request.
+    pub request_id: u64,
+    /// The code hashes of the bytecodes to retrieve.
+    pub hashes: Vec<H256>,
+    /// The soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetByteCodes`].
+///
+/// Not all bytecodes are guaranteed to be returned by the peer, but the returned ones are in the
+/// order of the request.
+#[derive(
+    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
+)]
+pub struct ByteCodes {
+    /// The id of the request this is a response to.
+    pub request_id: u64,
+    /// The requested bytecodes.
+    pub codes: Vec<Bytes>,
+}
+
+/// A request for trie nodes of the state trie with the given root by their paths.
+#[derive(
+    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
+)]
+pub struct GetTrieNodes {
+    /// The id of the request.
+    pub request_id: u64,
+    /// The root hash of the account trie to serve.
+    pub root_hash: H256,
+    /// The path sets of the nodes to retrieve.
+    ///
+    /// The first path of each set is the path of an account trie node, the following paths are
+    /// paths of storage trie nodes of that account.
+    pub paths: Vec<Vec<Bytes>>,
+    /// The soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetTrieNodes`].
+///
+/// Not all nodes are guaranteed to be returned by the peer, but the returned ones are in the order
+/// of the request.
+#[derive(
+    Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Serialize, Deserialize, Default,
+)]
+pub struct TrieNodes {
+    /// The id of the request this is a response to.
+    pub request_id: u64,
+    /// The requested trie nodes.
+    pub nodes: Vec<Bytes>,
+}
+
+/// Represents a message of the `snap/1` protocol.
+///
+/// All messages are request-response pairs that carry their request id as the first field.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum SnapMessage {
+    GetAccountRange(GetAccountRange),
+    AccountRange(AccountRange),
+    GetStorageRanges(GetStorageRanges),
+    StorageRanges(StorageRanges),
+    GetByteCodes(GetByteCodes),
+    ByteCodes(ByteCodes),
+    GetTrieNodes(GetTrieNodes),
+    TrieNodes(TrieNodes),
+}
+
+impl SnapMessage {
+    /// Returns the message's ID.
+    pub fn message_id(&self) -> SnapMessageID {
+        match self {
+            SnapMessage::GetAccountRange(_) => SnapMessageID::GetAccountRange,
+            SnapMessage::AccountRange(_) => SnapMessageID::AccountRange,
+            SnapMessage::GetStorageRanges(_) => SnapMessageID::GetStorageRanges,
+            SnapMessage::StorageRanges(_) => SnapMessageID::StorageRanges,
+            SnapMessage::GetByteCodes(_) => SnapMessageID::GetByteCodes,
+            SnapMessage::ByteCodes(_) => SnapMessageID::ByteCodes,
+            SnapMessage::GetTrieNodes(_) => SnapMessageID::GetTrieNodes,
+            SnapMessage::TrieNodes(_) => SnapMessageID::TrieNodes,
+        }
+    }
+
+    /// Returns the request id of the message.
+    pub fn request_id(&self) -> u64 {
+        match self {
+            SnapMessage::GetAccountRange(msg) => msg.request_id,
+            SnapMessage::AccountRange(msg) => msg.request_id,
+            SnapMessage::GetStorageRanges(msg) => msg.request_id,
+            SnapMessage::StorageRanges(msg) => msg.request_id,
+            SnapMessage::GetByteCodes(msg) => msg.request_id,
+            SnapMessage::ByteCodes(msg) => msg.request_id,
+            SnapMessage::GetTrieNodes(msg) => msg.request_id,
+            SnapMessage::TrieNodes(msg) => msg.request_id,
+        }
+    }
+}
+
+/// Encodes the message id as a single byte, followed by the RLP encoded message.
+impl Encodable for SnapMessage {
+    fn encode(&self, out: &mut dyn BufMut) {
+        out.put_u8(self.message_id() as u8);
+        match self {
+            SnapMessage::GetAccountRange(msg) => msg.encode(out),
+            SnapMessage::AccountRange(msg) => msg.encode(out),
+            SnapMessage::GetStorageRanges(msg) => msg.encode(out),
+            SnapMessage::StorageRanges(msg) => msg.encode(out),
+            SnapMessage::GetByteCodes(msg) => msg.encode(out),
+            SnapMessage::ByteCodes(msg) => msg.encode(out),
+            SnapMessage::GetTrieNodes(msg) => msg.encode(out),
+            SnapMessage::TrieNodes(msg) => msg.encode(out),
+        }
+    }
+
+    fn length(&self) -> usize {
+        let payload_len = match self {
+            SnapMessage::GetAccountRange(msg) => msg.length(),
+            SnapMessage::AccountRange(msg) => msg.length(),
+            SnapMessage::GetStorageRanges(msg) => msg.length(),
+            SnapMessage::StorageRanges(msg) => msg.length(),
+            SnapMessage::GetByteCodes(msg) => msg.length(),
+            SnapMessage::ByteCodes(msg) => msg.length(),
+            SnapMessage::GetTrieNodes(msg) => msg.length(),
+            SnapMessage::TrieNodes(msg) => msg.length(),
+        };
+        payload_len + 1
+    }
+}
+
+/// Decodes a message from bytes, using the first byte to determine the message type.
+impl Decodable for SnapMessage {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let id = *buf.first().ok_or(DecodeError::InputTooShort)?;
+        let id = SnapMessageID::try_from(id)
+            .map_err(|_| DecodeError::Custom("Invalid snap message ID"))?;
+        buf.advance(1);
+        let message = match id {
+            SnapMessageID::GetAccountRange => {
+                SnapMessage::GetAccountRange(GetAccountRange::decode(buf)?)
+            }
+            SnapMessageID::AccountRange => SnapMessage::AccountRange(AccountRange::decode(buf)?),
+            SnapMessageID::GetStorageRanges => {
+                SnapMessage::GetStorageRanges(GetStorageRanges::decode(buf)?)
+            }
+            SnapMessageID::StorageRanges => SnapMessage::StorageRanges(StorageRanges::decode(buf)?),
+            SnapMessageID::GetByteCodes => SnapMessage::GetByteCodes(GetByteCodes::decode(buf)?),
+            SnapMessageID::ByteCodes => SnapMessage::ByteCodes(ByteCodes::decode(buf)?),
+            SnapMessageID::GetTrieNodes => SnapMessage::GetTrieNodes(GetTrieNodes::decode(buf)?),
+            SnapMessageID::TrieNodes => SnapMessage::TrieNodes(TrieNodes::decode(buf)?),
+        };
+        Ok(message)
+    }
+}
+
+/// Represents message IDs for `snap/1` protocol messages, relative to the offset of the
+/// capability.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum SnapMessageID {
+    GetAccountRange = 0x00,
+    AccountRange = 0x01,
+    GetStorageRanges = 0x02,
+    StorageRanges = 0x03,
+    GetByteCodes = 0x04,
+    ByteCodes = 0x05,
+    GetTrieNodes = 0x06,
+    TrieNodes = 0x07,
+}
+
+impl SnapMessageID {
+    /// The number of messages of the `snap/1` protocol.
+    pub const COUNT: u8 = SnapMessageID::TrieNodes as u8 + 1;
+}
+
+impl TryFrom<u8> for SnapMessageID {
+    type Error = &'static str;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(SnapMessageID::GetAccountRange),
+            0x01 => Ok(SnapMessageID::AccountRange),
+            0x02 => Ok(SnapMessageID::GetStorageRanges),
+            0x03 => Ok(SnapMessageID::StorageRanges),
+            0x04 => Ok(SnapMessageID::GetByteCodes),
+            0x05 => Ok(SnapMessageID::ByteCodes),
+            0x06 => Ok(SnapMessageID::GetTrieNodes),
+            0x07 => Ok(SnapMessageID::TrieNodes),
+            _ => Err("Invalid snap message ID"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex_literal::hex;
+
+    fn roundtrip(message: SnapMessage) {
+        let mut buf = Vec::new();
+        message.encode(&mut buf);
+        assert_eq!(buf.len(), message.length());
+        assert_eq!(buf[0], message.message_id() as u8);
+        assert_eq!(SnapMessage::decode(&mut &buf[..]).unwrap(), message);
+    }
+
+    #[test]
+    fn encode_get_byte_codes() {
+        let message = SnapMessage::GetByteCodes(GetByteCodes {
+            request_id: 1111,
+            hashes: vec![
+                hex!("00000000000000000000000000000000000000000000000000000000deadc0de").into()
+            ],
+            response_bytes: 1024,
+        });
+        let mut buf = Vec::new();
+        message.encode(&mut buf);
+        assert_eq!(
+            buf,
+            hex!("04e8820457e1a000000000000000000000000000000000000000000000000000000000deadc0de820400")
+        );
+        roundtrip(message);
+    }
+
+    #[test]
+    fn roundtrip_snap_messages() {
+        roundtrip(SnapMessage::GetAccountRange(GetAccountRange {
+            request_id: 1,
+            root_hash: H256::random(),
+            starting_hash: H256::zero(),
+            limit_hash: H256::repeat_byte(0xff),
+            response_bytes: 512 * 1024,
+        }));
+        roundtrip(SnapMessage::AccountRange(AccountRange {
+            request_id: 1,
+            accounts: vec![AccountData {
+                hash: H256::random(),
+                body: RawRlp(Bytes::from_static(&[0xc0])),
+            }],
+            proof: vec![Bytes::from_static(&hex!("deadc0de"))],
+        }));
+        roundtrip(SnapMessage::GetStorageRanges(GetStorageRanges {
+            request_id: 2,
+            root_hash: H256::random(),
+            account_hashes: vec![H256::random(), H256::random()],
+            starting_hash: Bytes::new(),
+            limit_hash: Bytes::new(),
+            response_bytes: 512 * 1024,
+        }));
+        roundtrip(SnapMessage::StorageRanges(StorageRanges {
+            request_id: 2,
+            slots: vec![
+                vec![StorageData { hash: H256::random(), data: Bytes::from_static(&[0x01]) }],
+                vec![],
+            ],
+            proof: vec![],
+        }));
+        roundtrip(SnapMessage::ByteCodes(ByteCodes {
+            request_id: 3,
+            codes: vec![Bytes::from_static(&hex!("6080604052"))],
+        }));
+        roundtrip(SnapMessage::GetTrieNodes(GetTrieNodes {
+            request_id: 4,
+            root_hash: H256::random(),
+            paths: vec![vec![Bytes::from_static(&[0x01])], vec![Bytes::new(), Bytes::new()]],
+            response_bytes: 512 * 1024,
+        }));
+        roundtrip(SnapMessage::TrieNodes(TrieNodes {
+            request_id: 4,
+            nodes: vec![Bytes::from_static(&hex!("deadc0de"))],
+        }));
+    }
+
+    // An `AccountRange` in the format geth sends it, with the slim account embedded as a list
+    #[test]
+    fn decode_account_range() {
+        let data = hex!("01f201efeea05380c7b7ae81a58eb98d9c78de4a1fd7fd9535fc953ed2be602daaa41767312acc01880de0b6b3a76400008080c0");
+        let message = SnapMessage::decode(&mut &data[..]).unwrap();
+        assert_eq!(
+            message,
+            SnapMessage::AccountRange(AccountRange {
+                request_id: 1,
+                accounts: vec![AccountData {
+                    hash: hex!("5380c7b7ae81a58eb98d9c78de4a1fd7fd9535fc953ed2be602daaa41767312a")
+                        .into(),
+                    body: RawRlp(Bytes::from_static(&hex!("cc01880de0b6b3a76400008080"))),
+                }],
+                proof: vec![],
+            })
+        );
+
+        let mut buf = Vec::new();
+        message.encode(&mut buf);
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    fn decode_raw_rlp_too_short() {
+        assert!(RawRlp::decode(&mut &hex!("cc0188")[..]).is_err());
+    }
+
+    #[test]
+    fn decode_invalid_message_id() {
+        assert!(SnapMessage::decode(&mut &hex!("08c0")[..]).is_err());
+    }
+}
@@ -8,8 +8,10 @@ use crate::{
     NetworkHandle, NetworkManager,
 };
 use reth_discv4::{Discv4Config, Discv4ConfigBuilder, DEFAULT_DISCOVERY_PORT};
+use reth_discv5::{Discv5Config, Discv5ConfigBuilder, DEFAULT_DISCOVERY_V5_PORT};
+use reth_dns_discovery::DnsDiscoveryConfig;
 use reth_primitives::{Chain, ForkFilter, Hardfork, NodeRecord, PeerId, H256, MAINNET_GENESIS};
-use reth_provider::{BlockProvider, HeaderProvider};
+use reth_provider::{BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory};
 use reth_tasks::TaskExecutor;
 use secp256k1::{SecretKey, SECP256K1};
 use std::{
@@ -26,7 +28,7 @@ mod __reexport {
 }
 pub use __reexport::*;
 use reth_ecies::util::pk2id;
-use reth_eth_wire::{HelloMessage, Status};
+use reth_eth_wire::{capability::Capability, HelloMessage, Status};
 
 /// Convenience function to create a new random [`SecretKey`]
 pub fn rng_secret_key() -> SecretKey {
@@ -45,6 +47,12 @@ pub struct NetworkConfig<C> {
     pub discovery_v4_config: Option<Discv4Config>,
     /// Address to use for discovery
     pub discovery_addr: SocketAddr,
+    /// How to set up discovery v5, if enabled.
+    pub discovery_v5_config: Option<Discv5Config>,
+    /// Address to use for discovery v5
+    pub discovery_v5_addr: SocketAddr,
+    /// How to set up DNS discovery, if enabled.
+    pub dns_discovery_config: Option<DnsDiscoveryConfig>,
     /// Address to listen for incoming connections
     pub listener_addr: SocketAddr,
     /// How to instantiate peer manager.
@@ -93,6 +101,12 @@ impl<C> NetworkConfig<C> {
         self
     }
 
+    /// Sets the config to use for the discovery v5 protocol.
+    pub fn set_discovery_v5(mut self, discovery_config: Discv5Config) -> Self {
+        self.discovery_v5_config = Some(discovery_config);
+        self
+    }
+
     /// Sets the address for the incoming connection listener.
     pub fn set_listener_addr(mut self, listener_addr: SocketAddr) -> Self {
         self.listener_addr = listener_addr;
@@ -102,7 +116,7 @@ impl<C> NetworkConfig<C> {
 
 impl<C> NetworkConfig<C>
 where
-    C: BlockProvider + HeaderProvider + 'static,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + StateProviderFactory + 'static,
 {
     /// Starts the networking stack given a [NetworkConfig] and returns a handle to the network.
     pub async fn start_network(self) -> Result<NetworkHandle, NetworkError> {
@@ -130,6 +144,12 @@ pub struct NetworkConfigBuilder<C> {
     boot_nodes: HashSet<NodeRecord>,
     /// Address to use for discovery
     discovery_addr: Option<SocketAddr>,
+    /// How to set up discovery v5, disabled by default.
+    discovery_v5_builder: Option<Discv5ConfigBuilder>,
+    /// Address to use for discovery v5
+    discovery_v5_addr: Option<SocketAddr>,
+    /// How to set up DNS discovery, disabled by default.
+    dns_discovery_config: Option<DnsDiscoveryConfig>,
     /// Listener for incoming connections
     listener_addr: Option<SocketAddr>,
     /// How to instantiate peer manager.
@@ -154,6 +174,8 @@ pub struct NetworkConfigBuilder<C> {
     fork_filter: Option<ForkFilter>,
     /// Head used to start set for the fork filter
     head: Option<u64>,
+    /// Whether to advertise the `snap` capability to request state from peers.
+    snap_client: bool,
 }
 
 // === impl NetworkConfigBuilder ===
@@ -167,6 +189,9 @@ impl<C> NetworkConfigBuilder<C> {
             discovery_v4_builder: Some(Default::default()),
             boot_nodes: Default::default(),
             discovery_addr: None,
+            discovery_v5_builder: None,
+            discovery_v5_addr: None,
+            dns_discovery_config: None,
             listener_addr: None,
             peers_config: None,
             sessions_config: None,
@@ -179,6 +204,7 @@ impl<C> NetworkConfigBuilder<C> {
             hello_message: None,
             fork_filter: None,
             head: None,
+            snap_client: false,
         }
     }
 
@@ -256,6 +282,15 @@ impl<C> NetworkConfigBuilder<C> {
         self
     }
 
+    /// Sets the [`NetworkMode`] of the network.
+    ///
+    /// Block propagation is only enabled in [`NetworkMode::Work`], see also
+    /// [`ProofOfWorkBlockImport`](crate::import::ProofOfWorkBlockImport).
+    pub fn network_mode(mut self, network_mode: NetworkMode) -> Self {
+        self.network_mode = network_mode;
+        self
+    }
+
     /// Sets the socket address the network will listen on
     pub fn listener_addr(mut self, listener_addr: SocketAddr) -> Self {
         self.listener_addr = Some(listener_addr);
@@ -274,12 +309,48 @@ impl<C> NetworkConfigBuilder<C> {
         self
     }
 
+    /// Sets the socket address the discovery v5 network will listen on
+    pub fn discovery_v5_addr(mut self, discovery_v5_addr: SocketAddr) -> Self {
+        self.discovery_v5_addr = Some(discovery_v5_addr);
+        self
+    }
+
+    /// Enables discv5 with the given config, next to discv4 if not disabled.
+    pub fn discovery_v5(mut self, builder: Discv5ConfigBuilder) -> Self {
+        self.discovery_v5_builder = Some(builder);
+        self
+    }
+
+    /// Enables DNS discovery with the given config.
+    ///
+    /// If no DNS networks to bootstrap are configured, the node list of the chain is used.
+    pub fn dns_discovery(mut self, config: DnsDiscoveryConfig) -> Self {
+        self.dns_discovery_config = Some(config);
+        self
+    }
+
+    /// Disables DNS discovery.
+    pub fn disable_dns_discovery(mut self) -> Self {
+        self.dns_discovery_config = None;
+        self
+    }
+
     /// Sets the boot nodes.
     pub fn boot_nodes(mut self, nodes: impl IntoIterator<Item = NodeRecord>) -> Self {
         self.boot_nodes = nodes.into_iter().collect();
         self
     }
 
+    /// Advertises the `snap` capability, so state can be downloaded from peers with the
+    /// [`SnapClient`](reth_interfaces::p2p::snap::client::SnapClient) of the network.
+    ///
+    /// The node only acts as a client: `snap` requests of peers are answered with empty
+    /// responses, which tells them that the requested state is not available.
+    pub fn enable_snap_client(mut self) -> Self {
+        self.snap_client = true;
+        self
+    }
+
     /// Sets the discovery service off on true.
     pub fn set_discovery(mut self, disable_discovery: bool) -> Self {
         if disable_discovery {
@@ -291,6 +362,8 @@ impl<C> NetworkConfigBuilder<C> {
     /// disables discovery.
     pub fn disable_discovery(&mut self) {
         self.discovery_v4_builder = None;
+        self.discovery_v5_builder = None;
+        self.dns_discovery_config = None;
     }
 
     /// Consumes the type and creates the actual [`NetworkConfig`]
@@ -302,6 +375,9 @@ impl<C> NetworkConfigBuilder<C> {
             discovery_v4_builder,
             boot_nodes,
             discovery_addr,
+            discovery_v5_builder,
+            discovery_v5_addr,
+            dns_discovery_config,
             listener_addr,
             peers_config,
             sessions_config,
@@ -314,6 +390,7 @@ impl<C> NetworkConfigBuilder<C> {
             hello_message,
             fork_filter,
             head,
+            snap_client,
         } = self;
 
         let listener_addr = listener_addr.unwrap_or_else(|| {
@@ -323,12 +400,24 @@ impl<C> NetworkConfigBuilder<C> {
         let mut hello_message =
             hello_message.unwrap_or_else(|| HelloMessage::builder(peer_id).build());
         hello_message.port = listener_addr.port();
+        if snap_client && !hello_message.capabilities.contains(&Capability::snap()) {
+            hello_message.capabilities.push(Capability::snap());
+        }
+
+        // bootstrap DNS discovery from the node list of the chain if none are configured
+        let dns_discovery_config = dns_discovery_config.map(|mut config| {
+            if config.bootstrap_dns_networks.as_ref().map_or(true, |networks| networks.is_empty()) {
+                config.bootstrap_dns_networks = dns_network(&chain)
+                    .map(|network| HashSet::from([network.parse().expect("valid link")]));
+            }
+            config
+        });
 
         // get the fork filter
         let fork_filter = fork_filter.unwrap_or_else(|| {
             let head = head.unwrap_or_default();
             // TODO(mattsse): this should be chain agnostic: <https://github.com/paradigmxyz/reth/issues/485>
-            ForkFilter::new(head, genesis_hash, Hardfork::all_forks())
+            ForkFilter::new(head, genesis_hash, Hardfork::all_fork_id_values())
         });
 
         NetworkConfig {
@@ -339,6 +428,11 @@ impl<C> NetworkConfigBuilder<C> {
             discovery_addr: discovery_addr.unwrap_or_else(|| {
                 SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, DEFAULT_DISCOVERY_PORT))
             }),
+            discovery_v5_config: discovery_v5_builder.map(|builder| builder.build()),
+            discovery_v5_addr: discovery_v5_addr.unwrap_or_else(|| {
+                SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, DEFAULT_DISCOVERY_V5_PORT))
+            }),
+            dns_discovery_config,
             listener_addr,
             peers_config: peers_config.unwrap_or_default(),
             sessions_config: sessions_config.unwrap_or_default(),
@@ -354,6 +448,17 @@ impl<C> NetworkConfigBuilder<C> {
     }
 }
 
+/// Returns the Ethereum Foundation DNS node list of the chain, if there is one.
+fn dns_network(chain: &Chain) -> Option<&'static str> {
+    use reth_primitives::rpc::Chain as NamedChain;
+    match chain {
+        Chain::Named(NamedChain::Mainnet) => Some(MAINNET_DNS_NETWORK),
+        Chain::Named(NamedChain::Goerli) => Some(GOERLI_DNS_NETWORK),
+        Chain::Named(NamedChain::Sepolia) => Some(SEPOLIA_DNS_NETWORK),
+        _ => None,
+    }
+}
+
 /// Describes the mode of the network wrt. POS or POW.
 ///
 /// This affects block propagation in the `eth` sub-protocol [EIP-3675](https://eips.ethereum.org/EIPS/eip-3675#devp2p)
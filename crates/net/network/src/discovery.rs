@@ -3,19 +3,28 @@
 use crate::error::NetworkError;
 use futures::StreamExt;
 use reth_discv4::{DiscoveryUpdate, Discv4, Discv4Config};
+use reth_discv5::{Discv5, Discv5Config};
+use reth_dns_discovery::{
+    DnsDiscoveryConfig, DnsDiscoveryHandle, DnsDiscoveryService, DnsNodeRecordUpdate, DnsResolver,
+};
 use reth_primitives::{ForkId, NodeRecord, PeerId};
 use secp256k1::SecretKey;
 use std::{
     collections::{hash_map::Entry, HashMap, VecDeque},
     net::{IpAddr, SocketAddr},
+    sync::Arc,
     task::{Context, Poll},
 };
 use tokio::task::JoinHandle;
 use tokio_stream::wrappers::ReceiverStream;
 
-/// An abstraction over the configured discovery protocol.
+/// An abstraction over the configured discovery protocols.
 ///
 /// Listens for new discovered nodes and emits events for discovered nodes and their address.
+///
+/// Both discv4 and discv5 can be enabled at the same time, in which case the nodes discovered by
+/// either protocol are reported. Nodes discovered via DNS are reported as well and are added to
+/// the discv4 table, if enabled.
 pub struct Discovery {
     /// All nodes discovered via discovery protocol.
     ///
@@ -27,21 +36,37 @@ pub struct Discovery {
     discv4: Option<Discv4>,
     /// All KAD table updates from the discv4 service.
     discv4_updates: Option<ReceiverStream<DiscoveryUpdate>>,
+    /// Handler to interact with the Discovery v5 service
+    discv5: Option<Discv5>,
+    /// All discovered nodes from the discv5 service.
+    discv5_updates: Option<ReceiverStream<DiscoveryUpdate>>,
+    /// Handler to interact with the DNS discovery service
+    _dns_discovery: Option<DnsDiscoveryHandle>,
+    /// All nodes discovered via DNS.
+    dns_discovery_updates: Option<ReceiverStream<DnsNodeRecordUpdate>>,
     /// Events buffered until polled.
     queued_events: VecDeque<DiscoveryEvent>,
     /// The handle to the spawned discv4 service
     _discv4_service: Option<JoinHandle<()>>,
+    /// The handle to the spawned discv5 service
+    _discv5_service: Option<JoinHandle<()>>,
+    /// The handle to the spawned DNS discovery service
+    _dns_disc_service: Option<JoinHandle<()>>,
 }
 
 impl Discovery {
-    /// Spawns the discovery service.
+    /// Spawns the discovery services.
     ///
-    /// This will spawn the [`reth_discv4::Discv4Service`] onto a new task and establish a listener
-    /// channel to receive all discovered nodes.
+    /// This will spawn the [`reth_discv4::Discv4Service`], the [`reth_discv5::Discv5Service`] and
+    /// the [`DnsDiscoveryService`], if configured, onto new tasks and establish listener channels
+    /// to receive all discovered nodes.
     pub async fn new(
         discovery_addr: SocketAddr,
         sk: SecretKey,
         discv4_config: Option<Discv4Config>,
+        discovery_v5_addr: SocketAddr,
+        discv5_config: Option<Discv5Config>,
+        dns_discovery_config: Option<DnsDiscoveryConfig>,
     ) -> Result<Self, NetworkError> {
         let local_enr = NodeRecord::from_secret_key(discovery_addr, &sk);
         let (discv4, discv4_updates, _discv4_service) = if let Some(disc_config) = discv4_config {
@@ -57,36 +82,76 @@ impl Discovery {
             (None, None, None)
         };
 
+        let (discv5, discv5_updates, _discv5_service) = if let Some(disc_config) = discv5_config {
+            let (discv5, mut discv5_service) =
+                Discv5::start(discovery_v5_addr, local_enr, sk, disc_config)
+                    .await
+                    .map_err(NetworkError::Discv5)?;
+            let discv5_updates = discv5_service.update_stream();
+            // spawn the service
+            let _discv5_service = discv5_service.spawn();
+            (Some(discv5), Some(discv5_updates), Some(_discv5_service))
+        } else {
+            (None, None, None)
+        };
+
+        let (_dns_discovery, dns_discovery_updates, _dns_disc_service) =
+            if let Some(dns_config) = dns_discovery_config {
+                let resolver = DnsResolver::from_system_conf()?;
+                let (mut service, dns_disc) =
+                    DnsDiscoveryService::new_pair(Arc::new(resolver), dns_config);
+                let dns_discovery_updates = service.node_record_stream();
+                let dns_disc_service = service.spawn();
+                (Some(dns_disc), Some(dns_discovery_updates), Some(dns_disc_service))
+            } else {
+                (None, None, None)
+            };
+
         Ok(Self {
             local_enr,
             discv4,
             discv4_updates,
             _discv4_service,
+            discv5,
+            discv5_updates,
+            _discv5_service,
+            _dns_discovery,
+            dns_discovery_updates,
+            _dns_disc_service,
             discovered_nodes: Default::default(),
             queued_events: Default::default(),
         })
     }
 
-    /// Updates the `eth:ForkId` field in discv4.
+    /// Updates the `eth:ForkId` field in discv4 and discv5.
     #[allow(unused)]
     pub(crate) fn update_fork_id(&self, fork_id: ForkId) {
         if let Some(discv4) = &self.discv4 {
             discv4.set_eip868_rlp("eth".as_bytes().to_vec(), fork_id)
         }
+        if let Some(discv5) = &self.discv5 {
+            discv5.set_eip868_rlp("eth", fork_id)
+        }
     }
 
-    /// Bans the [`IpAddr`] in the discovery service.
+    /// Bans the [`IpAddr`] in the discovery services.
     pub(crate) fn ban_ip(&self, ip: IpAddr) {
         if let Some(discv4) = &self.discv4 {
             discv4.ban_ip(ip)
         }
+        if let Some(discv5) = &self.discv5 {
+            discv5.ban_ip(ip)
+        }
     }
 
-    /// Bans the [`PeerId`] and [`IpAddr`] in the discovery service.
+    /// Bans the [`PeerId`] and [`IpAddr`] in the discovery services.
     pub(crate) fn ban(&self, peer_id: PeerId, ip: IpAddr) {
         if let Some(discv4) = &self.discv4 {
             discv4.ban(peer_id, ip)
         }
+        if let Some(discv5) = &self.discv5 {
+            discv5.ban(peer_id, ip)
+        }
     }
 
     /// Returns the id with which the local identifies itself in the network
@@ -94,7 +159,22 @@ impl Discovery {
         self.local_enr.id
     }
 
-    fn on_discv4_update(&mut self, update: DiscoveryUpdate) {
+    /// Processes a node discovered via DNS.
+    ///
+    /// The node is also added to the discv4 table, so that it's used for lookups.
+    fn on_dns_update(&mut self, update: DnsNodeRecordUpdate) {
+        let DnsNodeRecordUpdate { node_record, fork_id, .. } = update;
+        if let Some(discv4) = &self.discv4 {
+            discv4.add_node(node_record);
+        }
+        self.on_discovery_update(DiscoveryUpdate::Added(node_record));
+        if let Some(fork_id) = fork_id {
+            self.on_discovery_update(DiscoveryUpdate::EnrForkId(node_record, fork_id));
+        }
+    }
+
+    /// Processes an update of either the discv4 or the discv5 service.
+    fn on_discovery_update(&mut self, update: DiscoveryUpdate) {
         match update {
             DiscoveryUpdate::Added(node) => {
                 let id = node.id;
@@ -115,7 +195,7 @@ impl Discovery {
             }
             DiscoveryUpdate::Batch(updates) => {
                 for update in updates {
-                    self.on_discv4_update(update);
+                    self.on_discovery_update(update);
                 }
             }
         }
@@ -128,11 +208,21 @@ impl Discovery {
                 return Poll::Ready(event)
             }
 
-            // drain the update stream
+            // drain the update streams
             while let Some(Poll::Ready(Some(update))) =
                 self.discv4_updates.as_mut().map(|disc_updates| disc_updates.poll_next_unpin(cx))
             {
-                self.on_discv4_update(update)
+                self.on_discovery_update(update)
+            }
+            while let Some(Poll::Ready(Some(update))) =
+                self.discv5_updates.as_mut().map(|disc_updates| disc_updates.poll_next_unpin(cx))
+            {
+                self.on_discovery_update(update)
+            }
+            while let Some(Poll::Ready(Some(update))) =
+                self.dns_discovery_updates.as_mut().map(|updates| updates.poll_next_unpin(cx))
+            {
+                self.on_dns_update(update)
             }
 
             if self.queued_events.is_empty() {
@@ -158,8 +248,14 @@ impl Discovery {
             },
             discv4: Default::default(),
             discv4_updates: Default::default(),
+            discv5: Default::default(),
+            discv5_updates: Default::default(),
+            _dns_discovery: Default::default(),
+            dns_discovery_updates: Default::default(),
             queued_events: Default::default(),
             _discv4_service: Default::default(),
+            _discv5_service: Default::default(),
+            _dns_disc_service: Default::default(),
         }
     }
 }
@@ -184,7 +280,33 @@ mod tests {
         let mut rng = thread_rng();
         let (secret_key, _) = SECP256K1.generate_keypair(&mut rng);
         let discovery_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
-        let _discovery =
-            Discovery::new(discovery_addr, secret_key, Default::default()).await.unwrap();
+        let _discovery = Discovery::new(
+            discovery_addr,
+            secret_key,
+            Default::default(),
+            discovery_addr,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_discovery_v5_setup() {
+        let mut rng = thread_rng();
+        let (secret_key, _) = SECP256K1.generate_keypair(&mut rng);
+        let discovery_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+        let discovery_v5_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0));
+        let _discovery = Discovery::new(
+            discovery_addr,
+            secret_key,
+            Default::default(),
+            discovery_v5_addr,
+            Some(Default::default()),
+            None,
+        )
+        .await
+        .unwrap();
     }
 }
@@ -16,6 +16,12 @@ pub enum NetworkError {
     /// IO error when creating the discovery service
     #[error("Failed to launch discovery service: {0}")]
     Discovery(std::io::Error),
+    /// Error when creating the discv5 service
+    #[error("Failed to launch discv5 service: {0}")]
+    Discv5(reth_discv5::error::Discv5Error),
+    /// Error when creating the DNS resolver of the DNS discovery service
+    #[error("Failed to configure DNS resolver: {0}")]
+    DnsResolver(#[from] reth_dns_discovery::resolver::ResolveError),
 }
 
 /// Abstraction over errors that can lead to a failed session
@@ -83,6 +89,7 @@ impl SessionError for EthStreamError {
                 matches!(
                     err,
                     P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedCapabilities) |
+                        P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedEthCapability) |
                         P2PStreamError::HandshakeError(P2PHandshakeError::HelloNotInHandshake) |
                         P2PStreamError::HandshakeError(
                             P2PHandshakeError::NonHelloMessageInHandshake
@@ -108,6 +115,7 @@ impl SessionError for EthStreamError {
                 )
             }
             EthStreamError::EthHandshakeError(err) => !matches!(err, EthHandshakeError::NoResponse),
+            EthStreamError::EthInvalidMessageError(..) => true,
             _ => false,
         }
     }
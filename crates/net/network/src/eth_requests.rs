@@ -8,7 +8,10 @@ use reth_eth_wire::{
 };
 use reth_interfaces::p2p::error::RequestResult;
 use reth_primitives::{rpc, BlockHashOrNumber, Header, HeadersDirection, PeerId, U256};
-use reth_provider::{BlockProvider, HeaderProvider};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProvider, StateProviderFactory,
+};
+use reth_rlp::Encodable;
 use std::{
     borrow::Borrow,
     future::Future,
@@ -33,6 +36,16 @@ const MAX_HEADERS_SERVE: usize = 1024;
 /// SOFT_RESPONSE_LIMIT.
 const MAX_BODIES_SERVE: usize = 1024;
 
+/// Maximum number of block receipts to serve.
+///
+/// Used to limit lookups.
+const MAX_RECEIPTS_SERVE: usize = 1024;
+
+/// Maximum number of state entries to serve for `GetNodeData`.
+///
+/// Used to limit lookups.
+const MAX_NODE_DATA_SERVE: usize = 1024;
+
 /// Estimated size in bytes of an RLP encoded body.
 // TODO: check 24kb blocksize assumption
 const APPROX_BODY_SIZE: usize = 24 * 1024;
@@ -72,7 +85,7 @@ impl<C> EthRequestHandler<C> {
 
 impl<C> EthRequestHandler<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + StateProviderFactory,
 {
     /// Returns the list of requested heders
     fn get_headers_response(&self, request: GetBlockHeaders) -> Vec<Header> {
@@ -164,7 +177,11 @@ where
             if let Some(block) =
                 self.client.block(rpc::BlockId::Hash(rpc::H256(hash.0))).unwrap_or_default()
             {
-                let body = BlockBody { transactions: block.body, ommers: block.ommers };
+                let body = BlockBody {
+                    transactions: block.body,
+                    ommers: block.ommers,
+                    withdrawals: block.withdrawals,
+                };
 
                 bodies.push(body);
 
@@ -184,6 +201,74 @@ where
 
         let _ = response.send(Ok(BlockBodies(bodies)));
     }
+
+    fn on_receipts_request(
+        &mut self,
+        _peer_id: PeerId,
+        request: GetReceipts,
+        response: oneshot::Sender<RequestResult<Receipts>>,
+    ) {
+        let mut receipts = Vec::new();
+
+        let mut total_bytes = 0;
+
+        for hash in request.0 {
+            if let Some(block_receipts) =
+                self.client.receipts_by_block(hash.into()).unwrap_or_default()
+            {
+                total_bytes += block_receipts.length();
+
+                receipts.push(block_receipts);
+
+                if total_bytes > SOFT_RESPONSE_LIMIT {
+                    break
+                }
+
+                if receipts.len() >= MAX_RECEIPTS_SERVE {
+                    break
+                }
+            } else {
+                break
+            }
+        }
+
+        let _ = response.send(Ok(Receipts(receipts)));
+    }
+
+    /// Serves the contract codes of the requested hashes.
+    ///
+    /// Trie nodes are not stored by hash, so only contract codes can be served. Unknown hashes are
+    /// skipped, which is allowed by the protocol.
+    fn on_node_data_request(
+        &mut self,
+        _peer_id: PeerId,
+        request: GetNodeData,
+        response: oneshot::Sender<RequestResult<NodeData>>,
+    ) {
+        let mut data = Vec::new();
+
+        if let Ok(state) = self.client.latest() {
+            let mut total_bytes = 0;
+
+            for hash in request.0 {
+                if let Some(code) = state.bytecode_by_hash(hash).unwrap_or_default() {
+                    total_bytes += code.len();
+
+                    data.push(code.0);
+
+                    if total_bytes > SOFT_RESPONSE_LIMIT {
+                        break
+                    }
+
+                    if data.len() >= MAX_NODE_DATA_SERVE {
+                        break
+                    }
+                }
+            }
+        }
+
+        let _ = response.send(Ok(NodeData(data)));
+    }
 }
 
 /// An endless future.
@@ -191,7 +276,7 @@ where
 /// This should be spawned or used as part of `tokio::select!`.
 impl<C> Future for EthRequestHandler<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + StateProviderFactory,
 {
     type Output = ();
 
@@ -209,8 +294,12 @@ where
                     IncomingEthRequest::GetBlockBodies { peer_id, request, response } => {
                         this.on_bodies_request(peer_id, request, response)
                     }
-                    IncomingEthRequest::GetNodeData { .. } => {}
-                    IncomingEthRequest::GetReceipts { .. } => {}
+                    IncomingEthRequest::GetNodeData { peer_id, request, response } => {
+                        this.on_node_data_request(peer_id, request, response)
+                    }
+                    IncomingEthRequest::GetReceipts { peer_id, request, response } => {
+                        this.on_receipts_request(peer_id, request, response)
+                    }
                 },
             }
         }
@@ -270,3 +359,126 @@ pub enum IncomingEthRequest {
         response: oneshot::Sender<RequestResult<Receipts>>,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peers::PeersManager;
+    use reth_primitives::{keccak256, Address, Bytes, Receipt, H256};
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use tokio::sync::mpsc;
+
+    fn request_handler(client: MockEthProvider) -> EthRequestHandler<MockEthProvider> {
+        let (_, rx) = mpsc::unbounded_channel();
+        EthRequestHandler::new(Arc::new(client), PeersManager::default().handle(), rx)
+    }
+
+    /// Adds the given receipts to the client under random block hashes and returns the hashes.
+    fn add_receipts(client: &MockEthProvider, blocks: Vec<Vec<Receipt>>) -> Vec<H256> {
+        blocks
+            .into_iter()
+            .map(|receipts| {
+                let hash = H256::random();
+                client.add_receipts(hash, receipts);
+                hash
+            })
+            .collect()
+    }
+
+    /// Adds accounts with the given contract codes to the client and returns the code hashes.
+    fn add_codes(client: &MockEthProvider, codes: Vec<Vec<u8>>) -> Vec<H256> {
+        codes
+            .into_iter()
+            .map(|code| {
+                let code = Bytes::from(code);
+                let hash = keccak256(&code);
+                client.add_account(
+                    Address::random(),
+                    ExtendedAccount::new(0, U256::ZERO).with_bytecode(code),
+                );
+                hash
+            })
+            .collect()
+    }
+
+    async fn receipts(
+        handler: &mut EthRequestHandler<MockEthProvider>,
+        hashes: Vec<H256>,
+    ) -> Vec<Vec<Receipt>> {
+        let (tx, rx) = oneshot::channel();
+        handler.on_receipts_request(PeerId::random(), GetReceipts(hashes), tx);
+        rx.await.unwrap().unwrap().0
+    }
+
+    async fn node_data(
+        handler: &mut EthRequestHandler<MockEthProvider>,
+        hashes: Vec<H256>,
+    ) -> Vec<bytes::Bytes> {
+        let (tx, rx) = oneshot::channel();
+        handler.on_node_data_request(PeerId::random(), GetNodeData(hashes), tx);
+        rx.await.unwrap().unwrap().0
+    }
+
+    #[tokio::test]
+    async fn test_receipts_request() {
+        let client = MockEthProvider::default();
+        let block_receipts = vec![
+            vec![Receipt { cumulative_gas_used: 21_000, ..Default::default() }],
+            vec![],
+            vec![
+                Receipt { cumulative_gas_used: 21_000, ..Default::default() },
+                Receipt { cumulative_gas_used: 42_000, ..Default::default() },
+            ],
+        ];
+        let hashes = add_receipts(&client, block_receipts.clone());
+        let mut handler = request_handler(client);
+
+        assert_eq!(receipts(&mut handler, hashes.clone()).await, block_receipts);
+
+        // The response ends at the first unknown block
+        let request = vec![hashes[0], H256::random(), hashes[2]];
+        assert_eq!(receipts(&mut handler, request).await, block_receipts[..1].to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_receipts_request_limits() {
+        let client = MockEthProvider::default();
+        let hashes = add_receipts(&client, vec![vec![Receipt::default()]; MAX_RECEIPTS_SERVE + 10]);
+        let mut handler = request_handler(client);
+        assert_eq!(receipts(&mut handler, hashes).await.len(), MAX_RECEIPTS_SERVE);
+
+        // Blocks of more than half of the soft limit, only the first two blocks are served
+        let client = MockEthProvider::default();
+        let receipts_per_block = SOFT_RESPONSE_LIMIT / 2 / Receipt::default().length() + 1;
+        let hashes = add_receipts(&client, vec![vec![Receipt::default(); receipts_per_block]; 4]);
+        let mut handler = request_handler(client);
+        assert_eq!(receipts(&mut handler, hashes).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_node_data_request() {
+        let client = MockEthProvider::default();
+        let hashes = add_codes(&client, vec![vec![1], vec![2, 2]]);
+        let mut handler = request_handler(client);
+
+        // Unknown hashes are skipped
+        let request = vec![hashes[0], H256::random(), hashes[1]];
+        assert_eq!(node_data(&mut handler, request).await, vec![vec![1], vec![2, 2]]);
+    }
+
+    #[tokio::test]
+    async fn test_node_data_request_limits() {
+        let client = MockEthProvider::default();
+        let codes = (0..MAX_NODE_DATA_SERVE + 10).map(|i| i.to_be_bytes().to_vec()).collect();
+        let hashes = add_codes(&client, codes);
+        let mut handler = request_handler(client);
+        assert_eq!(node_data(&mut handler, hashes).await.len(), MAX_NODE_DATA_SERVE);
+
+        // Codes of more than half of the soft limit, only the first two codes are served
+        let client = MockEthProvider::default();
+        let codes = (0..4u8).map(|i| vec![i; SOFT_RESPONSE_LIMIT / 2 + 1]).collect();
+        let hashes = add_codes(&client, codes);
+        let mut handler = request_handler(client);
+        assert_eq!(node_data(&mut handler, hashes).await.len(), 2);
+    }
+}
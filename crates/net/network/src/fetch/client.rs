@@ -1,23 +1,29 @@
 //! A client implementation that can interact with the network and download data.
 
 use crate::{
-    fetch::DownloadRequest,
+    fetch::{DownloadRequest, FetchRequest},
+    message::SnapRequest,
     peers::{PeersHandle, ReputationChangeKind},
 };
-use reth_eth_wire::{BlockBody, BlockHeaders};
+use reth_eth_wire::{
+    AccountRange, BlockBody, BlockHeaders, ByteCodes, GetAccountRange, GetByteCodes,
+    GetStorageRanges, GetTrieNodes, StorageRanges, TrieNodes,
+};
 use reth_interfaces::p2p::{
     bodies::client::BodiesClient,
     downloader::DownloadClient,
     error::PeerRequestResult,
     headers::client::{HeadersClient, HeadersRequest},
+    receipts::client::ReceiptsClient,
+    snap::client::SnapClient,
 };
-use reth_primitives::{PeerId, WithPeerId, H256};
+use reth_primitives::{PeerId, Receipt, WithPeerId, H256};
 use tokio::sync::{mpsc::UnboundedSender, oneshot};
 
 /// Front-end API for fetching data from the network.
 ///
-/// Following diagram illustrates how a request, See [`HeadersClient::get_headers`] and
-/// [`BodiesClient::get_block_bodies`] is handled internally.
+/// Following diagram illustrates how a request, See [`HeadersClient::get_headers`],
+/// [`BodiesClient::get_block_bodies`] and [`ReceiptsClient::get_receipts`] is handled internally.
 #[cfg_attr(doc, aquamarine::aquamarine)]
 /// ```mermaid
 /// sequenceDiagram
@@ -55,7 +61,7 @@ use tokio::sync::{mpsc::UnboundedSender, oneshot};
 #[derive(Debug)]
 pub struct FetchClient {
     /// Sender half of the request channel.
-    pub(crate) request_tx: UnboundedSender<DownloadRequest>,
+    pub(crate) request_tx: UnboundedSender<FetchRequest>,
     /// The handle to the peers
     pub(crate) peers_handle: PeersHandle,
 }
@@ -71,7 +77,7 @@ impl HeadersClient for FetchClient {
     /// Sends a `GetBlockHeaders` request to an available peer.
     async fn get_headers(&self, request: HeadersRequest) -> PeerRequestResult<BlockHeaders> {
         let (response, rx) = oneshot::channel();
-        self.request_tx.send(DownloadRequest::GetBlockHeaders { request, response })?;
+        self.request_tx.send(DownloadRequest::GetBlockHeaders { request, response }.into())?;
         rx.await?.map(WithPeerId::transform)
     }
 }
@@ -80,7 +86,50 @@ impl HeadersClient for FetchClient {
 impl BodiesClient for FetchClient {
     async fn get_block_bodies(&self, request: Vec<H256>) -> PeerRequestResult<Vec<BlockBody>> {
         let (response, rx) = oneshot::channel();
-        self.request_tx.send(DownloadRequest::GetBlockBodies { request, response })?;
+        self.request_tx.send(DownloadRequest::GetBlockBodies { request, response }.into())?;
+        rx.await?
+    }
+}
+
+#[async_trait::async_trait]
+impl ReceiptsClient for FetchClient {
+    async fn get_receipts(&self, request: Vec<H256>) -> PeerRequestResult<Vec<Vec<Receipt>>> {
+        let (response, rx) = oneshot::channel();
+        self.request_tx.send(DownloadRequest::GetReceipts { request, response }.into())?;
+        rx.await?
+    }
+}
+
+#[async_trait::async_trait]
+impl SnapClient for FetchClient {
+    async fn get_account_range(&self, request: GetAccountRange) -> PeerRequestResult<AccountRange> {
+        let (response, rx) = oneshot::channel();
+        let request = SnapRequest::GetAccountRange { request, response };
+        self.request_tx.send(FetchRequest::Snap(request))?;
+        rx.await?
+    }
+
+    async fn get_storage_ranges(
+        &self,
+        request: GetStorageRanges,
+    ) -> PeerRequestResult<StorageRanges> {
+        let (response, rx) = oneshot::channel();
+        let request = SnapRequest::GetStorageRanges { request, response };
+        self.request_tx.send(FetchRequest::Snap(request))?;
+        rx.await?
+    }
+
+    async fn get_byte_codes(&self, request: GetByteCodes) -> PeerRequestResult<ByteCodes> {
+        let (response, rx) = oneshot::channel();
+        let request = SnapRequest::GetByteCodes { request, response };
+        self.request_tx.send(FetchRequest::Snap(request))?;
+        rx.await?
+    }
+
+    async fn get_trie_nodes(&self, request: GetTrieNodes) -> PeerRequestResult<TrieNodes> {
+        let (response, rx) = oneshot::channel();
+        let request = SnapRequest::GetTrieNodes { request, response };
+        self.request_tx.send(FetchRequest::Snap(request))?;
         rx.await?
     }
 }
@@ -1,20 +1,27 @@
 //! Fetch data from the network.
 
-use crate::{message::BlockRequest, peers::PeersHandle};
+use crate::{
+    message::{BlockRequest, SnapRequest},
+    peers::PeersHandle,
+};
 use futures::StreamExt;
 use linked_hash_map::LinkedHashMap;
-use reth_eth_wire::{BlockBody, GetBlockBodies, GetBlockHeaders};
+use reth_eth_wire::{BlockBody, GetBlockBodies, GetBlockHeaders, GetReceipts};
 use reth_interfaces::p2p::{
     error::{PeerRequestResult, RequestError, RequestResult},
     headers::client::HeadersRequest,
 };
-use reth_primitives::{Header, PeerId, H256};
+use reth_primitives::{Header, PeerId, Receipt, H256};
+use reth_rlp::Encodable;
 use std::{
+    cmp::Ordering,
     collections::{HashMap, VecDeque},
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
 use tokio::sync::{mpsc, mpsc::UnboundedSender, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::trace;
 
 mod client;
 use crate::peers::ReputationChangeKind;
@@ -33,16 +40,23 @@ pub struct StateFetcher {
     /// Currently active [`GetBlockBodies`] requests
     inflight_bodies_requests:
         HashMap<PeerId, Request<Vec<H256>, PeerRequestResult<Vec<BlockBody>>>>,
+    /// Currently active [`GetReceipts`] requests
+    inflight_receipts_requests:
+        HashMap<PeerId, Request<Vec<H256>, PeerRequestResult<Vec<Vec<Receipt>>>>>,
     /// The list of _available_ peers for requests.
     peers: LinkedHashMap<PeerId, Peer>,
     /// The handle to the peers manager
     peers_handle: PeersHandle,
     /// Requests queued for processing
     queued_requests: VecDeque<DownloadRequest>,
+    /// `snap` requests queued for processing
+    ///
+    /// These are queued separately because they're not bound to the idle state of a peer.
+    queued_snap_requests: VecDeque<SnapRequest>,
     /// Receiver for new incoming download requests
-    download_requests_rx: UnboundedReceiverStream<DownloadRequest>,
+    download_requests_rx: UnboundedReceiverStream<FetchRequest>,
     /// Sender for download requests, used to detach a [`FetchClient`]
-    download_requests_tx: UnboundedSender<DownloadRequest>,
+    download_requests_tx: UnboundedSender<FetchRequest>,
 }
 
 // === impl StateSyncer ===
@@ -53,17 +67,34 @@ impl StateFetcher {
         Self {
             inflight_headers_requests: Default::default(),
             inflight_bodies_requests: Default::default(),
+            inflight_receipts_requests: Default::default(),
             peers: Default::default(),
             peers_handle,
             queued_requests: Default::default(),
+            queued_snap_requests: Default::default(),
             download_requests_rx: UnboundedReceiverStream::new(download_requests_rx),
             download_requests_tx,
         }
     }
 
     /// Invoked when connected to a new peer.
-    pub(crate) fn new_active_peer(&mut self, peer_id: PeerId, best_hash: H256, best_number: u64) {
-        self.peers.insert(peer_id, Peer { state: PeerState::Idle, best_hash, best_number });
+    pub(crate) fn new_active_peer(
+        &mut self,
+        peer_id: PeerId,
+        best_hash: H256,
+        best_number: u64,
+        supports_snap: bool,
+    ) {
+        self.peers.insert(
+            peer_id,
+            Peer {
+                state: PeerState::Idle,
+                best_hash,
+                best_number,
+                supports_snap,
+                stats: Default::default(),
+            },
+        );
     }
 
     /// Removes the peer from the peer list, after which it is no longer available for future
@@ -80,6 +111,9 @@ impl StateFetcher {
         if let Some(req) = self.inflight_bodies_requests.remove(peer) {
             let _ = req.response.send(Err(RequestError::ConnectionDropped));
         }
+        if let Some(req) = self.inflight_receipts_requests.remove(peer) {
+            let _ = req.response.send(Err(RequestError::ConnectionDropped));
+        }
     }
 
     /// Updates the block information for the peer.
@@ -103,11 +137,38 @@ impl StateFetcher {
         }
     }
 
-    /// Returns the _next_ idle peer that's ready to accept a request.
+    /// Returns the best idle peer that's ready to accept a request.
+    ///
+    /// Peers are ranked by their [`PeerStats`], peers that haven't served a request yet are
+    /// preferred so that they get sampled. Among equally ranked peers the one that was yielded
+    /// least recently is returned.
     /// Once a peer has been yielded, it will be moved to the end of the map
     fn next_peer(&mut self) -> Option<PeerId> {
-        let peer =
-            self.peers.iter().find_map(|(peer_id, peer)| peer.state.is_idle().then_some(*peer_id));
+        let mut best: Option<(&PeerId, &Peer)> = None;
+        for (peer_id, peer) in self.peers.iter().filter(|(_, peer)| peer.state.is_idle()) {
+            match best {
+                Some((_, best_peer)) if peer.stats.rank(&best_peer.stats) != Ordering::Greater => {}
+                _ => best = Some((peer_id, peer)),
+            }
+        }
+        let peer = best.map(|(peer_id, _)| *peer_id);
+        if let Some(peer_id) = peer {
+            // Move to end of the map
+            self.peers.get_refresh(&peer_id);
+        }
+        peer
+    }
+
+    /// Returns the _next_ peer that supports the `snap` protocol and is not about to be
+    /// disconnected.
+    ///
+    /// Unlike [`Self::next_peer`] this does not require the peer to be idle, since `snap` requests
+    /// are tracked by the session. Once a peer has been yielded, it will be moved to the end of the
+    /// map.
+    fn next_snap_peer(&mut self) -> Option<PeerId> {
+        let peer = self.peers.iter().find_map(|(peer_id, peer)| {
+            (peer.supports_snap && !matches!(peer.state, PeerState::Closing)).then_some(*peer_id)
+        });
         if let Some(peer_id) = peer {
             // Move to end of the map
             self.peers.get_refresh(&peer_id);
@@ -117,9 +178,19 @@ impl StateFetcher {
 
     /// Returns the next action to return
     fn poll_action(&mut self) -> PollAction {
+        if !self.queued_snap_requests.is_empty() {
+            if let Some(peer_id) = self.next_snap_peer() {
+                let request = self.queued_snap_requests.pop_front().expect("not empty; qed");
+                return PollAction::Ready(FetchAction::SnapRequest { peer_id, request })
+            }
+        }
+
         // we only check and not pop here since we don't know yet whether a peer is available.
         if self.queued_requests.is_empty() {
-            return PollAction::NoRequests
+            if self.queued_snap_requests.is_empty() {
+                return PollAction::NoRequests
+            }
+            return PollAction::NoPeersAvailable
         }
 
         let peer_id = if let Some(peer_id) = self.next_peer() {
@@ -147,9 +218,12 @@ impl StateFetcher {
             loop {
                 // poll incoming requests
                 match self.download_requests_rx.poll_next_unpin(cx) {
-                    Poll::Ready(Some(request)) => {
+                    Poll::Ready(Some(FetchRequest::Block(request))) => {
                         self.queued_requests.push_back(request);
                     }
+                    Poll::Ready(Some(FetchRequest::Snap(request))) => {
+                        self.queued_snap_requests.push_back(request);
+                    }
                     Poll::Ready(None) => {
                         unreachable!("channel can't close")
                     }
@@ -157,7 +231,9 @@ impl StateFetcher {
                 }
             }
 
-            if self.queued_requests.is_empty() || no_peers_available {
+            if (self.queued_requests.is_empty() && self.queued_snap_requests.is_empty()) ||
+                no_peers_available
+            {
                 return Poll::Pending
             }
         }
@@ -172,9 +248,10 @@ impl StateFetcher {
             peer.state = req.peer_state();
         }
 
+        let started = Instant::now();
         match req {
             DownloadRequest::GetBlockHeaders { request, response } => {
-                let inflight = Request { request: request.clone(), response };
+                let inflight = Request { request: request.clone(), response, started };
                 self.inflight_headers_requests.insert(peer_id, inflight);
                 let HeadersRequest { start, limit, direction } = request;
                 BlockRequest::GetBlockHeaders(GetBlockHeaders {
@@ -185,10 +262,15 @@ impl StateFetcher {
                 })
             }
             DownloadRequest::GetBlockBodies { request, response } => {
-                let inflight = Request { request: request.clone(), response };
+                let inflight = Request { request: request.clone(), response, started };
                 self.inflight_bodies_requests.insert(peer_id, inflight);
                 BlockRequest::GetBlockBodies(GetBlockBodies(request))
             }
+            DownloadRequest::GetReceipts { request, response } => {
+                let inflight = Request { request: request.clone(), response, started };
+                self.inflight_receipts_requests.insert(peer_id, inflight);
+                BlockRequest::GetReceipts(GetReceipts(request))
+            }
         }
     }
 
@@ -207,27 +289,12 @@ impl StateFetcher {
         peer_id: PeerId,
         res: RequestResult<Vec<Header>>,
     ) -> Option<BlockResponseOutcome> {
-        let is_error = res.is_err();
+        let mut reputation_change = None;
         if let Some(resp) = self.inflight_headers_requests.remove(&peer_id) {
+            reputation_change = self.on_request_outcome(peer_id, resp.started, &res);
             let _ = resp.response.send(res.map(|h| (peer_id, h).into()));
         }
-
-        if is_error {
-            // if the response was erroneous we want to report the peer.
-            return Some(BlockResponseOutcome::BadResponse(
-                peer_id,
-                ReputationChangeKind::BadMessage,
-            ))
-        }
-
-        if let Some(peer) = self.peers.get_mut(&peer_id) {
-            // If the peer is still ready to be accept new requests, we try to send a followup
-            // request immediately.
-            if peer.state.on_request_finished() {
-                return self.followup_request(peer_id)
-            }
-        }
-        None
+        self.on_response_outcome(peer_id, reputation_change)
     }
 
     /// Called on a `GetBlockBodies` response from a peer
@@ -236,14 +303,93 @@ impl StateFetcher {
         peer_id: PeerId,
         res: RequestResult<Vec<BlockBody>>,
     ) -> Option<BlockResponseOutcome> {
+        let mut reputation_change = None;
         if let Some(resp) = self.inflight_bodies_requests.remove(&peer_id) {
+            reputation_change = self.on_request_outcome(peer_id, resp.started, &res);
             let _ = resp.response.send(res.map(|b| (peer_id, b).into()));
         }
-        if let Some(peer) = self.peers.get_mut(&peer_id) {
-            if peer.state.on_request_finished() {
-                return self.followup_request(peer_id)
+        self.on_response_outcome(peer_id, reputation_change)
+    }
+
+    /// Called on a `GetReceipts` response from a peer
+    pub(crate) fn on_receipts_response(
+        &mut self,
+        peer_id: PeerId,
+        res: RequestResult<Vec<Vec<Receipt>>>,
+    ) -> Option<BlockResponseOutcome> {
+        let mut reputation_change = None;
+        if let Some(resp) = self.inflight_receipts_requests.remove(&peer_id) {
+            reputation_change = self.on_request_outcome(peer_id, resp.started, &res);
+            let _ = resp.response.send(res.map(|r| (peer_id, r).into()));
+        }
+        self.on_response_outcome(peer_id, reputation_change)
+    }
+
+    /// Records the outcome of a request that was sent to the peer at `started` in the peer's
+    /// [`PeerStats`].
+    ///
+    /// Returns the reputation change that should be applied to the peer, if any.
+    fn on_request_outcome<T: Encodable>(
+        &mut self,
+        peer_id: PeerId,
+        started: Instant,
+        res: &RequestResult<Vec<T>>,
+    ) -> Option<ReputationChangeKind> {
+        let peer = self.peers.get_mut(&peer_id)?;
+        let elapsed = started.elapsed();
+        match res {
+            Ok(items) => {
+                let bytes = items.iter().map(Encodable::length).sum();
+                peer.stats.on_success(elapsed, bytes);
+                None
+            }
+            Err(err) => {
+                peer.stats.on_failure(elapsed, err);
+                trace!(
+                    target: "net::fetch",
+                    ?peer_id,
+                    %err,
+                    successes = peer.stats.successes,
+                    failures = peer.stats.failures,
+                    "request failed"
+                );
+                match err {
+                    RequestError::Timeout => {
+                        // only penalize peers that keep timing out, a single timeout can be caused
+                        // by a temporary hiccup
+                        (peer.stats.consecutive_timeouts >= MAX_CONSECUTIVE_TIMEOUTS)
+                            .then_some(ReputationChangeKind::Timeout)
+                    }
+                    RequestError::BadResponse => Some(ReputationChangeKind::BadMessage),
+                    RequestError::UnsupportedCapability => Some(ReputationChangeKind::BadProtocol),
+                    // the connection itself is already penalized when the session closes
+                    RequestError::ConnectionDropped |
+                    RequestError::ChannelClosed |
+                    RequestError::NotConnected => None,
+                }
             }
         }
+    }
+
+    /// Marks the peer as ready again after a response and determines the outcome.
+    fn on_response_outcome(
+        &mut self,
+        peer_id: PeerId,
+        reputation_change: Option<ReputationChangeKind>,
+    ) -> Option<BlockResponseOutcome> {
+        let peer = self.peers.get_mut(&peer_id)?;
+        // If the peer is still ready to be accept new requests, we try to send a followup
+        // request immediately.
+        let is_ready = peer.state.on_request_finished();
+
+        if let Some(reputation_change) = reputation_change {
+            // if the response was erroneous we want to report the peer.
+            return Some(BlockResponseOutcome::BadResponse(peer_id, reputation_change))
+        }
+
+        if is_ready {
+            return self.followup_request(peer_id)
+        }
         None
     }
 
@@ -271,6 +417,96 @@ struct Peer {
     best_hash: H256,
     /// Tracks the best number of the peer.
     best_number: u64,
+    /// Whether the peer shares the `snap` protocol.
+    supports_snap: bool,
+    /// How well the peer served requests so far.
+    stats: PeerStats,
+}
+
+/// The number of consecutive timeouts after which a peer's reputation is lowered on every
+/// additional timeout.
+const MAX_CONSECUTIVE_TIMEOUTS: u32 = 3;
+
+/// The maximum number of times the latency of a peer is doubled for its consecutive failures.
+const MAX_FAILURE_PENALTY_SHIFT: u32 = 16;
+
+/// Tracks how fast and reliable a peer responds to requests.
+#[derive(Debug, Default)]
+struct PeerStats {
+    /// Exponentially weighted moving average of the response latency.
+    latency: Option<Duration>,
+    /// Exponentially weighted moving average of the download rate in bytes/sec.
+    throughput: Option<f64>,
+    /// Total number of successful requests.
+    successes: u64,
+    /// Total number of failed requests.
+    failures: u64,
+    /// Number of failed requests since the last successful request.
+    consecutive_failures: u32,
+    /// Number of timed out requests since the last response.
+    consecutive_timeouts: u32,
+}
+
+// === impl PeerStats ===
+
+impl PeerStats {
+    /// Records a successful response of `bytes` size that arrived `elapsed` after the request.
+    fn on_success(&mut self, elapsed: Duration, bytes: usize) {
+        self.successes += 1;
+        self.consecutive_failures = 0;
+        self.consecutive_timeouts = 0;
+        self.record_latency(elapsed);
+        let secs = elapsed.as_secs_f64();
+        if secs > 0.0 {
+            let rate = bytes as f64 / secs;
+            self.throughput = Some(match self.throughput {
+                Some(throughput) => (throughput * 3.0 + rate) / 4.0,
+                None => rate,
+            });
+        }
+    }
+
+    /// Records a failed request.
+    fn on_failure(&mut self, elapsed: Duration, err: &RequestError) {
+        self.failures += 1;
+        self.consecutive_failures += 1;
+        if matches!(err, RequestError::Timeout) {
+            self.consecutive_timeouts += 1;
+        } else {
+            self.consecutive_timeouts = 0;
+        }
+        // the request took at least this long, even if it did not yield a usable response
+        self.record_latency(elapsed);
+    }
+
+    fn record_latency(&mut self, elapsed: Duration) {
+        self.latency = Some(match self.latency {
+            Some(latency) => (latency * 3 + elapsed) / 4,
+            None => elapsed,
+        });
+    }
+
+    /// Returns the latency of the peer, doubled for every request that failed since the last
+    /// successful one.
+    ///
+    /// This way a fast peer that failed once is still preferred over a much slower peer, while
+    /// peers that keep failing are only chosen if no better peer is available.
+    fn weighted_latency(&self) -> Option<Duration> {
+        let penalty = 1u32 << self.consecutive_failures.min(MAX_FAILURE_PENALTY_SHIFT);
+        self.latency.map(|latency| latency.saturating_mul(penalty))
+    }
+
+    /// Compares the stats of two peers, returns [`Ordering::Greater`] if `self` is the better
+    /// choice for the next request.
+    ///
+    /// Peers with a lower [weighted latency](Self::weighted_latency) are preferred, then peers
+    /// with a higher throughput.
+    fn rank(&self, other: &Self) -> Ordering {
+        other
+            .weighted_latency()
+            .cmp(&self.weighted_latency())
+            .then_with(|| self.throughput.partial_cmp(&other.throughput).unwrap_or(Ordering::Equal))
+    }
 }
 
 /// Tracks the state of an individual peer
@@ -281,6 +517,8 @@ enum PeerState {
     GetBlockHeaders,
     /// Peer is handling a `GetBlockBodies` request.
     GetBlockBodies,
+    /// Peer is handling a `GetReceipts` request.
+    GetReceipts,
     /// Peer session is about to close
     Closing,
 }
@@ -315,9 +553,25 @@ struct Request<Req, Resp> {
     #[allow(unused)]
     request: Req,
     response: oneshot::Sender<Resp>,
+    /// When the request was sent to the peer.
+    started: Instant,
 }
 
 /// Requests that can be sent to the Syncer from a [`FetchClient`]
+pub(crate) enum FetchRequest {
+    /// An eth request that is sent to an idle peer
+    Block(DownloadRequest),
+    /// A `snap` request that is sent to a peer that supports the protocol
+    Snap(SnapRequest),
+}
+
+impl From<DownloadRequest> for FetchRequest {
+    fn from(request: DownloadRequest) -> Self {
+        FetchRequest::Block(request)
+    }
+}
+
+/// Eth requests that are queued until a peer is idle
 pub(crate) enum DownloadRequest {
     /// Download the requested headers and send response through channel
     GetBlockHeaders {
@@ -329,6 +583,11 @@ pub(crate) enum DownloadRequest {
         request: Vec<H256>,
         response: oneshot::Sender<PeerRequestResult<Vec<BlockBody>>>,
     },
+    /// Download the requested receipts and send response through channel
+    GetReceipts {
+        request: Vec<H256>,
+        response: oneshot::Sender<PeerRequestResult<Vec<Vec<Receipt>>>>,
+    },
 }
 
 // === impl DownloadRequest ===
@@ -339,6 +598,7 @@ impl DownloadRequest {
         match self {
             DownloadRequest::GetBlockHeaders { .. } => PeerState::GetBlockHeaders,
             DownloadRequest::GetBlockBodies { .. } => PeerState::GetBlockBodies,
+            DownloadRequest::GetReceipts { .. } => PeerState::GetReceipts,
         }
     }
 }
@@ -352,6 +612,13 @@ pub(crate) enum FetchAction {
         /// The request to send
         request: BlockRequest,
     },
+    /// Dispatch a `snap` request to the given peer.
+    SnapRequest {
+        /// The targeted recipient for the request
+        peer_id: PeerId,
+        /// The request to send
+        request: SnapRequest,
+    },
 }
 
 /// Outcome of a processed response.
@@ -398,9 +665,9 @@ mod tests {
         let peer1 = H512::random();
         let peer2 = H512::random();
         let peer3 = H512::random();
-        fetcher.new_active_peer(peer1, H256::random(), 1);
-        fetcher.new_active_peer(peer2, H256::random(), 2);
-        fetcher.new_active_peer(peer3, H256::random(), 3);
+        fetcher.new_active_peer(peer1, H256::random(), 1, false);
+        fetcher.new_active_peer(peer2, H256::random(), 2, false);
+        fetcher.new_active_peer(peer3, H256::random(), 3, false);
         let next_peer = fetcher.next_peer();
         // Must get peer1 as our first peer
         assert_eq!(next_peer, Some(peer1));
@@ -408,4 +675,149 @@ mod tests {
         assert_eq!(&peer1, fetcher.peers.back().unwrap().0);
         assert_eq!(fetcher.next_peer(), Some(peer2));
     }
+
+    #[tokio::test]
+    async fn test_snap_peer_rotation() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle());
+        let peer1 = H512::random();
+        let peer2 = H512::random();
+        let peer3 = H512::random();
+        fetcher.new_active_peer(peer1, H256::random(), 1, false);
+        fetcher.new_active_peer(peer2, H256::random(), 2, true);
+        fetcher.new_active_peer(peer3, H256::random(), 3, true);
+
+        // peers without snap support are skipped
+        assert_eq!(fetcher.next_snap_peer(), Some(peer2));
+        assert_eq!(fetcher.next_snap_peer(), Some(peer3));
+
+        // busy peers still accept snap requests, closing peers don't
+        fetcher.peers.get_mut(&peer2).unwrap().state = PeerState::GetBlockHeaders;
+        fetcher.on_pending_disconnect(&peer3);
+        assert_eq!(fetcher.next_snap_peer(), Some(peer2));
+        assert_eq!(fetcher.next_snap_peer(), Some(peer2));
+    }
+
+    #[tokio::test]
+    async fn test_prefer_fast_reliable_peers() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle());
+        let peer1 = H512::random();
+        let peer2 = H512::random();
+        let peer3 = H512::random();
+        fetcher.new_active_peer(peer1, H256::random(), 1, false);
+        fetcher.new_active_peer(peer2, H256::random(), 2, false);
+        fetcher.new_active_peer(peer3, H256::random(), 3, false);
+
+        fetcher.peers.get_mut(&peer1).unwrap().stats.on_success(Duration::from_millis(500), 1024);
+        fetcher.peers.get_mut(&peer2).unwrap().stats.on_success(Duration::from_millis(50), 1024);
+        fetcher
+            .peers
+            .get_mut(&peer3)
+            .unwrap()
+            .stats
+            .on_failure(Duration::from_millis(40), &RequestError::BadResponse);
+
+        // the failure doubles the latency of the fast peer
+        assert_eq!(fetcher.next_peer(), Some(peer2));
+
+        // only idle peers are selected, a single failure doesn't rule out a fast peer
+        fetcher.peers.get_mut(&peer2).unwrap().state = PeerState::GetBlockHeaders;
+        assert_eq!(fetcher.next_peer(), Some(peer3));
+
+        // until it keeps failing
+        for _ in 0..3 {
+            fetcher
+                .peers
+                .get_mut(&peer3)
+                .unwrap()
+                .stats
+                .on_failure(Duration::from_millis(40), &RequestError::BadResponse);
+        }
+        assert_eq!(fetcher.next_peer(), Some(peer1));
+
+        // peers that haven't served any requests yet are sampled first
+        let peer4 = H512::random();
+        fetcher.new_active_peer(peer4, H256::random(), 4, false);
+        assert_eq!(fetcher.next_peer(), Some(peer4));
+    }
+
+    #[tokio::test]
+    async fn test_penalize_failed_requests() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle());
+        let peer_id = H512::random();
+        fetcher.new_active_peer(peer_id, H256::random(), 1, false);
+
+        let request =
+            HeadersRequest { start: 0u64.into(), limit: 1, direction: Default::default() };
+        let (tx, _rx) = oneshot::channel();
+        fetcher
+            .queued_requests
+            .push_back(DownloadRequest::GetBlockHeaders { request: request.clone(), response: tx });
+        let _ = fetcher.poll_action();
+        assert!(matches!(
+            fetcher.on_block_headers_response(peer_id, Err(RequestError::BadResponse)),
+            Some(BlockResponseOutcome::BadResponse(id, ReputationChangeKind::BadMessage))
+                if id == peer_id
+        ));
+
+        // a dropped connection is not a protocol violation
+        let (tx, _rx) = oneshot::channel();
+        fetcher
+            .queued_requests
+            .push_back(DownloadRequest::GetBlockHeaders { request, response: tx });
+        let _ = fetcher.poll_action();
+        assert!(fetcher
+            .on_block_headers_response(peer_id, Err(RequestError::ConnectionDropped))
+            .is_none());
+        assert!(fetcher.peers.get(&peer_id).unwrap().state.is_idle());
+    }
+
+    #[tokio::test]
+    async fn test_penalize_repeated_timeouts() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle());
+        let peer_id = H512::random();
+        fetcher.new_active_peer(peer_id, H256::random(), 1, false);
+
+        let request =
+            HeadersRequest { start: 0u64.into(), limit: 1, direction: Default::default() };
+        for i in 1..=MAX_CONSECUTIVE_TIMEOUTS + 1 {
+            let (tx, _rx) = oneshot::channel();
+            fetcher.queued_requests.push_back(DownloadRequest::GetBlockHeaders {
+                request: request.clone(),
+                response: tx,
+            });
+            assert!(matches!(
+                fetcher.poll_action(),
+                PollAction::Ready(FetchAction::BlockRequest { peer_id: id, .. }) if id == peer_id
+            ));
+
+            let outcome = fetcher.on_block_headers_response(peer_id, Err(RequestError::Timeout));
+            if i < MAX_CONSECUTIVE_TIMEOUTS {
+                assert!(outcome.is_none());
+            } else {
+                assert!(matches!(
+                    outcome,
+                    Some(BlockResponseOutcome::BadResponse(id, ReputationChangeKind::Timeout))
+                        if id == peer_id
+                ));
+            }
+            // the peer is available again
+            assert!(fetcher.peers.get(&peer_id).unwrap().state.is_idle());
+        }
+
+        // a successful response resets the timeouts
+        let (tx, _rx) = oneshot::channel();
+        fetcher
+            .queued_requests
+            .push_back(DownloadRequest::GetBlockHeaders { request, response: tx });
+        let _ = fetcher.poll_action();
+        assert!(fetcher.on_block_headers_response(peer_id, Ok(vec![Header::default()])).is_none());
+        let stats = &fetcher.peers.get(&peer_id).unwrap().stats;
+        assert_eq!(stats.consecutive_timeouts, 0);
+        assert_eq!(stats.successes, 1);
+        assert_eq!(stats.failures, MAX_CONSECUTIVE_TIMEOUTS as u64 + 1);
+    }
 }
@@ -1,6 +1,19 @@
+//! Import of blocks that are broadcast by peers via `NewBlock` messages.
+
 use crate::message::NewBlockMessage;
-use reth_primitives::PeerId;
+use reth_consensus::{
+    ethash::{validate_difficulty, Ethash},
+    verification::{
+        validate_block_standalone, validate_header_regarding_parent, validate_header_standalone,
+    },
+    Config,
+};
+use reth_interfaces::{consensus, sync::ChainSync, Error as ImportError};
+use reth_primitives::{PeerId, SealedBlock, SealedHeader};
+use reth_provider::HeaderProvider;
 use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+use tracing::{trace, warn};
 
 /// Abstraction over block import.
 pub trait BlockImport: Send + Sync {
@@ -18,6 +31,7 @@ pub trait BlockImport: Send + Sync {
 }
 
 /// Outcome of the [`BlockImport`]'s block handling.
+#[derive(Debug)]
 pub struct BlockImportOutcome {
     /// Sender of the `NewBlock` message.
     pub peer: PeerId,
@@ -48,6 +62,9 @@ pub enum BlockImportError {
     /// Consensus error
     #[error(transparent)]
     Consensus(#[from] reth_interfaces::consensus::Error),
+    /// The block failed to execute
+    #[error(transparent)]
+    Execution(#[from] reth_interfaces::executor::Error),
 }
 
 /// An implementation of `BlockImport` used in Proof-of-Stake consensus that does nothing.
@@ -64,3 +81,334 @@ impl BlockImport for ProofOfStakeBlockImport {
         Poll::Pending
     }
 }
+
+/// An implementation of `BlockImport` used in Proof-of-Work consensus.
+///
+/// Incoming blocks are validated standalone and against their parent, including the difficulty,
+/// see [validate_header_standalone], [validate_block_standalone],
+/// [validate_header_regarding_parent] and [validate_difficulty]. The ethash seal is then verified
+/// on a blocking task, after which the block is reported as [BlockValidation::ValidHeader], so that
+/// it is relayed to a fraction of the peers via `NewBlock`.
+///
+/// The block is then committed via the [ChainSync], which executes it. Once it is imported, it is
+/// reported as [BlockValidation::ValidBlock], so that it is announced to the remaining peers via
+/// `NewBlockHashes`.
+///
+/// Blocks whose parent is unknown, or that do not extend the local head, are ignored, as is every
+/// block while the chain is syncing.
+#[derive(Debug)]
+pub struct ProofOfWorkBlockImport<Client, S> {
+    /// The chain configuration the blocks are validated against.
+    config: Config,
+    /// The client used to look up the parents of the blocks.
+    client: Client,
+    /// The chain sync that executes and commits the blocks.
+    sync: S,
+    /// Verifies the seals of the blocks.
+    ethash: Ethash,
+    /// Sender half for the results of the tasks that verify and import the blocks.
+    events_tx: mpsc::UnboundedSender<ImportEvent>,
+    /// Results of the tasks that verify and import the blocks.
+    events: mpsc::UnboundedReceiver<ImportEvent>,
+}
+
+impl<Client, S> ProofOfWorkBlockImport<Client, S>
+where
+    Client: HeaderProvider,
+    S: ChainSync,
+{
+    /// Creates a new instance that validates blocks against the given chain configuration and
+    /// imports them via the given [ChainSync].
+    pub fn new(config: Config, client: Client, sync: S) -> Self {
+        let (events_tx, events) = mpsc::unbounded_channel();
+        Self { config, client, sync, ethash: Default::default(), events_tx, events }
+    }
+
+    /// Validates the block standalone and against its parent.
+    ///
+    /// Returns `Ok(None)` if the parent of the block is not known.
+    fn validate(&self, block: &NewBlockMessage) -> Result<Option<SealedBlock>, BlockImportError> {
+        let header = SealedHeader::new(block.block.block.header.clone(), block.hash);
+        validate_header_standalone(&header, &self.config)?;
+
+        let block = SealedBlock {
+            header,
+            body: block.block.block.transactions.clone(),
+            ommers: block.block.block.ommers.iter().cloned().map(|ommer| ommer.seal()).collect(),
+            withdrawals: block.block.block.withdrawals.clone(),
+        };
+        validate_block_standalone(&block)?;
+
+        let parent = match self.client.header(&block.parent_hash) {
+            Ok(Some(parent)) => SealedHeader::new(parent, block.parent_hash),
+            Ok(None) => {
+                trace!(target: "net::block_import", hash = ?block.hash(), parent = ?block.parent_hash, "Ignoring block with unknown parent");
+                return Ok(None)
+            }
+            Err(error) => {
+                warn!(target: "net::block_import", ?error, "Failed to look up parent");
+                return Ok(None)
+            }
+        };
+        validate_header_regarding_parent(&parent, &block.header, &self.config)?;
+        validate_difficulty(&block.header, &parent, &self.config)?;
+        Ok(Some(block))
+    }
+
+    /// Commits the block whose header was verified, unless the chain is syncing.
+    fn import(&mut self, peer: PeerId, block: NewBlockMessage, sealed: SealedBlock) {
+        if self.sync.is_syncing() {
+            trace!(target: "net::block_import", hash = ?block.hash, "Not importing block while syncing");
+            return
+        }
+
+        let commit = self.sync.commit_block(sealed);
+        let events = self.events_tx.clone();
+        tokio::spawn(async move {
+            let result = match commit.await {
+                Ok(()) => Ok(BlockValidation::ValidBlock { block }),
+                Err(ImportError::Consensus(consensus::Error::ParentUnknown { .. })) => {
+                    trace!(target: "net::block_import", hash = ?block.hash, "Block does not extend the local head");
+                    return
+                }
+                Err(ImportError::Consensus(err)) => Err(err.into()),
+                Err(ImportError::Execution(err)) => Err(err.into()),
+                Err(error) => {
+                    warn!(target: "net::block_import", hash = ?block.hash, ?error, "Failed to import block");
+                    return
+                }
+            };
+            let _ = events.send(ImportEvent::Outcome(BlockImportOutcome { peer, result }));
+        });
+    }
+}
+
+impl<Client, S> BlockImport for ProofOfWorkBlockImport<Client, S>
+where
+    Client: HeaderProvider,
+    S: ChainSync,
+{
+    fn on_new_block(&mut self, peer_id: PeerId, incoming_block: NewBlockMessage) {
+        let block = match self.validate(&incoming_block) {
+            Ok(Some(block)) => block,
+            Ok(None) => return,
+            Err(err) => {
+                let outcome = BlockImportOutcome { peer: peer_id, result: Err(err) };
+                let _ = self.events_tx.send(ImportEvent::Outcome(outcome));
+                return
+            }
+        };
+
+        // Generating the cache of a new epoch takes a while
+        let ethash = self.ethash.clone();
+        let events = self.events_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let event = match ethash.verify_seal(&block.header) {
+                Ok(()) => {
+                    ImportEvent::Verified { peer: peer_id, block: incoming_block, sealed: block }
+                }
+                Err(err) => ImportEvent::Outcome(BlockImportOutcome {
+                    peer: peer_id,
+                    result: Err(err.into()),
+                }),
+            };
+            let _ = events.send(event);
+        });
+    }
+
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<BlockImportOutcome> {
+        // The channel can not be closed, it holds a sender itself
+        let Poll::Ready(Some(event)) = self.events.poll_recv(cx) else { return Poll::Pending };
+        match event {
+            ImportEvent::Verified { peer, block, sealed } => {
+                self.import(peer, block.clone(), sealed);
+                Poll::Ready(BlockImportOutcome {
+                    peer,
+                    result: Ok(BlockValidation::ValidHeader { block }),
+                })
+            }
+            ImportEvent::Outcome(outcome) => Poll::Ready(outcome),
+        }
+    }
+}
+
+/// Results of the tasks spawned by the [ProofOfWorkBlockImport].
+#[derive(Debug)]
+enum ImportEvent {
+    /// The seal of the block is valid.
+    Verified {
+        /// The sender of the block.
+        peer: PeerId,
+        /// The received block.
+        block: NewBlockMessage,
+        /// The validated block.
+        sealed: SealedBlock,
+    },
+    /// The block is invalid or was imported.
+    Outcome(BlockImportOutcome),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{future::poll_fn, poll};
+    use reth_eth_wire::{NewBlock, RawBlockBody};
+    use reth_interfaces::{
+        consensus::ForkchoiceState,
+        sync::{NoopChainSync, SyncFuture, SyncStateProvider},
+    };
+    use reth_primitives::{Block, Header, H256, U256};
+    use reth_provider::test_utils::MockEthProvider;
+    use std::sync::Arc;
+
+    /// A [ChainSync] that commits every block.
+    #[derive(Debug, Default)]
+    struct TestSync {
+        committed: Vec<H256>,
+    }
+
+    impl SyncStateProvider for TestSync {
+        fn is_syncing(&self) -> bool {
+            false
+        }
+    }
+
+    impl ChainSync for TestSync {
+        fn sync_to(&mut self, _state: ForkchoiceState) {}
+
+        fn commit_block(&mut self, block: SealedBlock) -> SyncFuture<()> {
+            self.committed.push(block.hash());
+            Box::pin(futures::future::ready(Ok(())))
+        }
+
+        fn state_root(&mut self, _block: Block) -> SyncFuture<H256> {
+            unreachable!("blocks are only committed")
+        }
+
+        fn poll_sync(&mut self, _cx: &mut Context<'_>) -> Poll<()> {
+            Poll::Pending
+        }
+    }
+
+    fn new_block(header: Header) -> NewBlockMessage {
+        let hash = header.hash_slow();
+        let block = RawBlockBody { header, ..Default::default() };
+        NewBlockMessage { hash, block: Arc::new(NewBlock { block, td: Default::default() }) }
+    }
+
+    fn new_import<S: ChainSync>(sync: S) -> ProofOfWorkBlockImport<MockEthProvider, S> {
+        ProofOfWorkBlockImport::new(Config::default(), MockEthProvider::default(), sync)
+    }
+
+    fn parent() -> Header {
+        Header {
+            gas_limit: 5000,
+            difficulty: U256::from(0x400000000u64),
+            timestamp: 1000,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn verified_block_is_imported() {
+        let mut import = new_import(TestSync::default());
+        let peer = PeerId::random();
+        let block = new_block(Header { number: 1, ..Default::default() });
+        let sealed = SealedBlock {
+            header: SealedHeader::new(block.block.block.header.clone(), block.hash),
+            ..Default::default()
+        };
+        import
+            .events_tx
+            .send(ImportEvent::Verified { peer, block: block.clone(), sealed })
+            .unwrap();
+
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert_eq!(outcome.peer, peer);
+        assert!(matches!(
+            outcome.result,
+            Ok(BlockValidation::ValidHeader { block: ref header_block }) if header_block.hash == block.hash
+        ));
+        assert_eq!(import.sync.committed, vec![block.hash]);
+
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(
+            outcome.result,
+            Ok(BlockValidation::ValidBlock { block: ref valid_block }) if valid_block.hash == block.hash
+        ));
+    }
+
+    #[tokio::test]
+    async fn block_not_extending_head_is_not_imported() {
+        let mut import = new_import(NoopChainSync);
+        let peer = PeerId::random();
+        let block = new_block(Header { number: 1, ..Default::default() });
+        let sealed = SealedBlock {
+            header: SealedHeader::new(block.block.block.header.clone(), block.hash),
+            ..Default::default()
+        };
+        import.events_tx.send(ImportEvent::Verified { peer, block, sealed }).unwrap();
+
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(outcome.result, Ok(BlockValidation::ValidHeader { .. })));
+        tokio::task::yield_now().await;
+        assert!(poll!(poll_fn(|cx| import.poll(cx))).is_pending());
+    }
+
+    #[tokio::test]
+    async fn block_with_unknown_parent_is_ignored() {
+        let mut import = new_import(NoopChainSync);
+        let block = new_block(Header { number: 1, ..Default::default() });
+        import.on_new_block(PeerId::random(), block);
+        assert!(poll!(poll_fn(|cx| import.poll(cx))).is_pending());
+    }
+
+    #[tokio::test]
+    async fn invalid_difficulty_is_rejected() {
+        let mut import = new_import(NoopChainSync);
+        let parent = parent();
+        let parent_hash = parent.hash_slow();
+        import.client.add_header(parent_hash, parent.clone());
+
+        let header = Header {
+            parent_hash,
+            number: 1,
+            gas_limit: 5000,
+            timestamp: parent.timestamp + 20,
+            difficulty: parent.difficulty,
+            ..Default::default()
+        };
+        import.on_new_block(PeerId::random(), new_block(header));
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(
+            outcome.result,
+            Err(BlockImportError::Consensus(consensus::Error::DifficultyDiff { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn invalid_block_is_rejected() {
+        let mut import = new_import(NoopChainSync);
+        let peer = PeerId::random();
+
+        import.on_new_block(
+            peer,
+            new_block(Header { number: 1, extra_data: vec![0; 33].into(), ..Default::default() }),
+        );
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(
+            outcome.result,
+            Err(BlockImportError::Consensus(consensus::Error::ExtraDataExceedsMax { .. }))
+        ));
+
+        import.on_new_block(
+            peer,
+            new_block(Header { number: 1, ommers_hash: H256::zero(), ..Default::default() }),
+        );
+        let outcome = poll_fn(|cx| import.poll(cx)).await;
+        assert!(matches!(
+            outcome.result,
+            Err(BlockImportError::Consensus(consensus::Error::BodyOmmersHashDiff { .. }))
+        ));
+    }
+}
@@ -120,7 +120,7 @@ mod discovery;
 pub mod error;
 pub mod eth_requests;
 mod fetch;
-mod import;
+pub mod import;
 mod listener;
 mod manager;
 mod message;
@@ -136,7 +136,7 @@ pub use builder::NetworkBuilder;
 pub use config::{NetworkConfig, NetworkConfigBuilder};
 pub use fetch::FetchClient;
 pub use manager::{NetworkEvent, NetworkManager};
-pub use message::PeerRequest;
+pub use message::{NewBlockMessage, PeerRequest, SnapRequest};
 pub use network::NetworkHandle;
 pub use peers::PeersConfig;
 
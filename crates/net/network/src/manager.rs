@@ -150,6 +150,9 @@ where
             secret_key,
             mut discovery_v4_config,
             discovery_addr,
+            mut discovery_v5_config,
+            discovery_v5_addr,
+            dns_discovery_config,
             listener_addr,
             peers_config,
             sessions_config,
@@ -177,7 +180,20 @@ where
             disc_config
         });
 
-        let discovery = Discovery::new(discovery_addr, secret_key, discovery_v4_config).await?;
+        discovery_v5_config = discovery_v5_config.map(|mut disc_config| {
+            disc_config.add_eip868_pair("eth", status.forkid);
+            disc_config
+        });
+
+        let discovery = Discovery::new(
+            discovery_addr,
+            secret_key,
+            discovery_v4_config,
+            discovery_v5_addr,
+            discovery_v5_config,
+            dns_discovery_config,
+        )
+        .await?;
         // need to retrieve the addr here since provided port could be `0`
         let local_peer_id = discovery.local_id();
 
@@ -441,7 +457,7 @@ where
                     msg,
                 });
             }
-            PeerMessage::SendTransactions(_) => {
+            PeerMessage::SendTransactions(_) | PeerMessage::SnapRequest(_) => {
                 unreachable!("Not emitted by session")
             }
             PeerMessage::Other(other) => {
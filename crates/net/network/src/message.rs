@@ -5,12 +5,13 @@
 
 use futures::FutureExt;
 use reth_eth_wire::{
-    capability::RawCapabilityMessage, message::RequestPair, BlockBodies, BlockBody, BlockHeaders,
-    EthMessage, GetBlockBodies, GetBlockHeaders, GetNodeData, GetPooledTransactions, GetReceipts,
+    capability::RawCapabilityMessage, message::RequestPair, AccountRange, BlockBodies, BlockBody,
+    BlockHeaders, ByteCodes, EthMessage, GetAccountRange, GetBlockBodies, GetBlockHeaders,
+    GetByteCodes, GetNodeData, GetPooledTransactions, GetReceipts, GetStorageRanges, GetTrieNodes,
     NewBlock, NewBlockHashes, NewPooledTransactionHashes, NodeData, PooledTransactions, Receipts,
-    SharedTransactions, Transactions,
+    SharedTransactions, SnapMessage, StorageRanges, Transactions, TrieNodes,
 };
-use reth_interfaces::p2p::error::{RequestError, RequestResult};
+use reth_interfaces::p2p::error::{PeerRequestResult, RequestError, RequestResult};
 use reth_primitives::{Header, PeerId, Receipt, TransactionSigned, H256};
 use std::{
     fmt,
@@ -53,6 +54,8 @@ pub enum PeerMessage {
     PooledTransactions(NewPooledTransactionHashes),
     /// All `eth` request variants.
     EthRequest(PeerRequest),
+    /// All `snap` request variants.
+    SnapRequest(SnapRequest),
     /// Other than eth namespace message
     #[allow(unused)]
     Other(RawCapabilityMessage),
@@ -65,6 +68,7 @@ pub enum PeerMessage {
 pub enum BlockRequest {
     GetBlockHeaders(GetBlockHeaders),
     GetBlockBodies(GetBlockBodies),
+    GetReceipts(GetReceipts),
 }
 
 /// Protocol related request messages that expect a response
@@ -146,6 +150,100 @@ impl PeerRequest {
     }
 }
 
+/// Requests of the `snap` protocol that expect a response.
+///
+/// Unlike [`PeerRequest`], the response includes the peer that served it, because `snap` requests
+/// are not tracked per peer by the network.
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names, missing_docs)]
+pub enum SnapRequest {
+    /// Request a range of accounts from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetAccountRange {
+        request: GetAccountRange,
+        response: oneshot::Sender<PeerRequestResult<AccountRange>>,
+    },
+    /// Request the storage slots of accounts from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetStorageRanges {
+        request: GetStorageRanges,
+        response: oneshot::Sender<PeerRequestResult<StorageRanges>>,
+    },
+    /// Request contract bytecodes from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetByteCodes { request: GetByteCodes, response: oneshot::Sender<PeerRequestResult<ByteCodes>> },
+    /// Request trie nodes from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetTrieNodes { request: GetTrieNodes, response: oneshot::Sender<PeerRequestResult<TrieNodes>> },
+}
+
+// === impl SnapRequest ===
+
+impl SnapRequest {
+    /// Invoked if we received a response which does not match the request
+    pub(crate) fn send_bad_response(self) {
+        self.send_err_response(RequestError::BadResponse)
+    }
+
+    /// Send an error back to the receiver.
+    pub(crate) fn send_err_response(self, err: RequestError) {
+        let _ = match self {
+            SnapRequest::GetAccountRange { response, .. } => response.send(Err(err)).ok(),
+            SnapRequest::GetStorageRanges { response, .. } => response.send(Err(err)).ok(),
+            SnapRequest::GetByteCodes { response, .. } => response.send(Err(err)).ok(),
+            SnapRequest::GetTrieNodes { response, .. } => response.send(Err(err)).ok(),
+        };
+    }
+
+    /// Sends the response of the peer back to the receiver.
+    ///
+    /// Returns `false` and sends [`RequestError::BadResponse`] if the response does not match the
+    /// request.
+    pub(crate) fn send_response(self, peer_id: PeerId, msg: SnapMessage) -> bool {
+        let _ = match (self, msg) {
+            (SnapRequest::GetAccountRange { response, .. }, SnapMessage::AccountRange(msg)) => {
+                response.send(Ok((peer_id, msg).into())).ok()
+            }
+            (SnapRequest::GetStorageRanges { response, .. }, SnapMessage::StorageRanges(msg)) => {
+                response.send(Ok((peer_id, msg).into())).ok()
+            }
+            (SnapRequest::GetByteCodes { response, .. }, SnapMessage::ByteCodes(msg)) => {
+                response.send(Ok((peer_id, msg).into())).ok()
+            }
+            (SnapRequest::GetTrieNodes { response, .. }, SnapMessage::TrieNodes(msg)) => {
+                response.send(Ok((peer_id, msg).into())).ok()
+            }
+            (request, _) => {
+                request.send_bad_response();
+                return false
+            }
+        };
+        true
+    }
+
+    /// Returns the [`SnapMessage`] for this type with the given request id.
+    pub fn create_request_message(&self, request_id: u64) -> SnapMessage {
+        match self {
+            SnapRequest::GetAccountRange { request, .. } => {
+                SnapMessage::GetAccountRange(GetAccountRange { request_id, ..request.clone() })
+            }
+            SnapRequest::GetStorageRanges { request, .. } => {
+                SnapMessage::GetStorageRanges(GetStorageRanges { request_id, ..request.clone() })
+            }
+            SnapRequest::GetByteCodes { request, .. } => {
+                SnapMessage::GetByteCodes(GetByteCodes { request_id, ..request.clone() })
+            }
+            SnapRequest::GetTrieNodes { request, .. } => {
+                SnapMessage::GetTrieNodes(GetTrieNodes { request_id, ..request.clone() })
+            }
+        }
+    }
+}
+
 /// Corresponding variant for [`PeerRequest`].
 #[derive(Debug)]
 pub enum PeerResponse {
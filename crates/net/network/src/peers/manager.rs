@@ -10,12 +10,14 @@ use futures::StreamExt;
 use reth_eth_wire::{errors::EthStreamError, DisconnectReason};
 use reth_net_common::ban_list::BanList;
 use reth_primitives::{ForkId, NodeRecord, PeerId};
+#[cfg(feature = "serde")]
+use std::path::Path;
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     fmt::Display,
     net::{IpAddr, SocketAddr},
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use thiserror::Error;
 use tokio::{
@@ -61,6 +63,14 @@ impl PeersHandle {
 
         rx.await.unwrap_or(None)
     }
+
+    /// Returns all known peers in a form that can be persisted, see [`PersistedPeer`].
+    pub async fn persisted_peers(&self) -> Vec<PersistedPeer> {
+        let (tx, rx) = oneshot::channel();
+        self.send(PeerCommand::GetPersistedPeers(tx));
+
+        rx.await.unwrap_or_default()
+    }
 }
 
 /// Maintains the state of _all_ the peers known to the network.
@@ -104,11 +114,12 @@ impl PeersManager {
             refill_slots_interval,
             connection_info,
             reputation_weights,
-            ban_list,
+            mut ban_list,
             ban_duration,
             backoff_durations,
             trusted_nodes,
             connect_trusted_nodes_only,
+            persisted_peers,
             ..
         } = config;
         let (manager_tx, handle_rx) = mpsc::unbounded_channel();
@@ -117,12 +128,39 @@ impl PeersManager {
         // We use half of the interval to decrease the max duration to `150%` in worst case
         let unban_interval = ban_duration.min(backoff_durations.low) / 2;
 
-        let mut peers = HashMap::with_capacity(trusted_nodes.len());
+        let mut peers = HashMap::with_capacity(trusted_nodes.len() + persisted_peers.len());
 
         for NodeRecord { address, tcp_port, udp_port: _, id } in trusted_nodes {
             peers.entry(id).or_insert_with(|| Peer::trusted(SocketAddr::from((address, tcp_port))));
         }
 
+        // restore the peers of a previous run, configured peers take precedence. Peers are only
+        // trusted if they are part of the current trusted nodes, so their kind is not restored.
+        let unix_now = unix_timestamp(SystemTime::now());
+        for persisted in persisted_peers {
+            let PersistedPeer {
+                peer_id, addr, reputation, fork_id, last_seen, banned_until, ..
+            } = persisted;
+            if peers.contains_key(&peer_id) || ban_list.is_banned(&peer_id, &addr.ip()) {
+                continue
+            }
+
+            let mut peer = Peer::new(addr);
+            peer.reputation = reputation;
+            peer.fork_id = fork_id;
+            peer.last_seen = last_seen.map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+            match banned_until.filter(|until| *until > unix_now) {
+                Some(until) => ban_list.ban_peer_until(
+                    peer_id,
+                    std::time::Instant::now() + Duration::from_secs(until - unix_now),
+                ),
+                // the ban expired while the node was offline
+                None if peer.is_banned() => peer.unban(),
+                None => {}
+            }
+            peers.insert(peer_id, peer);
+        }
+
         Self {
             peers,
             manager_tx,
@@ -232,9 +270,12 @@ impl PeersManager {
                     return
                 }
                 value.state = PeerConnectionState::In;
+                value.last_seen = Some(SystemTime::now());
             }
             Entry::Vacant(entry) => {
-                entry.insert(Peer::with_state(addr, PeerConnectionState::In));
+                let mut peer = Peer::with_state(addr, PeerConnectionState::In);
+                peer.last_seen = Some(SystemTime::now());
+                entry.insert(peer);
                 self.queued_actions.push_back(PeerAction::PeerAdded(peer_id));
             }
         }
@@ -319,7 +360,9 @@ impl PeersManager {
                     entry.remove();
                     self.queued_actions.push_back(PeerAction::PeerRemoved(peer_id));
                 } else {
-                    entry.get_mut().state = PeerConnectionState::Idle;
+                    let peer = entry.get_mut();
+                    peer.state = PeerConnectionState::Idle;
+                    peer.last_seen = Some(SystemTime::now());
                     return
                 }
             }
@@ -339,6 +382,9 @@ impl PeersManager {
         peer_id: &PeerId,
         err: &EthStreamError,
     ) {
+        if let Some(peer) = self.peers.get_mut(peer_id) {
+            peer.last_seen = Some(SystemTime::now());
+        }
         self.on_connection_failure(remote_addr, peer_id, err, ReputationChangeKind::Dropped)
     }
 
@@ -494,6 +540,34 @@ impl PeersManager {
         peer.kind = PeerKind::Basic;
     }
 
+    /// Returns all known peers in a form that can be persisted.
+    ///
+    /// Peers that are scheduled for removal are skipped. Connected peers are considered seen now.
+    pub(crate) fn persisted_peers(&self) -> Vec<PersistedPeer> {
+        let now = SystemTime::now();
+        let instant_now = std::time::Instant::now();
+        self.peers
+            .iter()
+            .filter(|(_, peer)| !peer.remove_after_disconnect)
+            .map(|(peer_id, peer)| {
+                let last_seen = if peer.state.is_connected() { Some(now) } else { peer.last_seen };
+                let banned_until = self
+                    .ban_list
+                    .peer_banned_until(peer_id)
+                    .map(|until| now + until.saturating_duration_since(instant_now));
+                PersistedPeer {
+                    peer_id: *peer_id,
+                    addr: peer.addr,
+                    kind: peer.kind,
+                    reputation: peer.reputation,
+                    fork_id: peer.fork_id,
+                    last_seen: last_seen.map(unix_timestamp),
+                    banned_until: banned_until.map(unix_timestamp),
+                }
+            })
+            .collect()
+    }
+
     /// Returns the idle peer with the highest reputation.
     ///
     /// Peers that are `trusted`, see [PeerKind], are prioritized as long as they're not currently
@@ -589,6 +663,9 @@ impl PeersManager {
                     PeerCommand::GetPeer(peer, tx) => {
                         let _ = tx.send(self.peers.get(&peer).cloned());
                     }
+                    PeerCommand::GetPersistedPeers(tx) => {
+                        let _ = tx.send(self.persisted_peers());
+                    }
                 }
             }
 
@@ -696,6 +773,8 @@ pub struct Peer {
     remove_after_disconnect: bool,
     /// The kind of peer
     kind: PeerKind,
+    /// When a session with the peer was last active, if ever.
+    last_seen: Option<SystemTime>,
 }
 
 // === impl Peer ===
@@ -717,6 +796,7 @@ impl Peer {
             fork_id: None,
             remove_after_disconnect: false,
             kind: Default::default(),
+            last_seen: None,
         }
     }
 
@@ -823,6 +903,8 @@ impl PeerConnectionState {
 
 /// Represents the kind of peer
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub enum PeerKind {
     /// Basic peer kind.
     #[default]
@@ -843,6 +925,39 @@ pub(crate) enum PeerCommand {
     ReputationChange(PeerId, ReputationChangeKind),
     /// Get information about a peer
     GetPeer(PeerId, oneshot::Sender<Option<Peer>>),
+    /// Get all known peers in a form that can be persisted
+    GetPersistedPeers(oneshot::Sender<Vec<PersistedPeer>>),
+}
+
+/// A known peer in a form that can be persisted across restarts.
+///
+/// All timestamps are seconds since the unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PersistedPeer {
+    /// The id of the peer.
+    pub peer_id: PeerId,
+    /// Where to reach the peer.
+    pub addr: SocketAddr,
+    /// The kind of peer.
+    ///
+    /// This is informational only, restored peers are trusted if they are configured as trusted
+    /// nodes.
+    pub kind: PeerKind,
+    /// The reputation of the peer.
+    pub reputation: i32,
+    /// The [`ForkId`] that the peer announced via discovery.
+    pub fork_id: Option<ForkId>,
+    /// When a session with the peer was last active, if ever.
+    pub last_seen: Option<u64>,
+    /// Until when the peer is banned or backed off, if it is.
+    pub banned_until: Option<u64>,
+}
+
+/// Returns the seconds since the unix epoch.
+fn unix_timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
 }
 
 /// Actions the peer manager can trigger.
@@ -901,6 +1016,9 @@ pub struct PeersConfig {
     pub trusted_nodes: HashSet<NodeRecord>,
     /// Connect to trusted nodes only?
     pub connect_trusted_nodes_only: bool,
+    /// Peers of a previous run to restore.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub persisted_peers: Vec<PersistedPeer>,
 }
 
 impl Default for PeersConfig {
@@ -915,6 +1033,7 @@ impl Default for PeersConfig {
             backoff_durations: Default::default(),
             trusted_nodes: Default::default(),
             connect_trusted_nodes_only: false,
+            persisted_peers: Default::default(),
         }
     }
 }
@@ -959,6 +1078,45 @@ impl PeersConfig {
         self.connect_trusted_nodes_only = trusted_only;
         self
     }
+
+    /// Peers of a previous run to restore, see [`PeersHandle::persisted_peers`].
+    pub fn with_persisted_peers(mut self, peers: Vec<PersistedPeer>) -> Self {
+        self.persisted_peers = peers;
+        self
+    }
+
+    /// Restores the peers of a previous run from the given file, see [`write_persisted_peers`].
+    ///
+    /// A missing file is not an error, e.g. on the first run.
+    #[cfg(feature = "serde")]
+    pub fn with_persisted_peers_file(self, path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(self)
+        }
+        let peers = serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(path)?))?;
+        debug!(target : "net::peers", file = %path.display(), "loaded persisted peers");
+        Ok(self.with_persisted_peers(peers))
+    }
+}
+
+/// Writes the peers as JSON to the given file, so they can be restored via
+/// [`PeersConfig::with_persisted_peers_file`].
+#[cfg(feature = "serde")]
+pub fn write_persisted_peers(
+    path: impl AsRef<Path>,
+    peers: &[PersistedPeer],
+) -> std::io::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // write to a temporary file first, so a crash never leaves a partially written file behind
+    let tmp = path.with_extension("new");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp)?);
+    serde_json::to_writer(&mut file, peers)?;
+    file.into_inner()?.sync_all()?;
+    std::fs::rename(tmp, path)
 }
 
 /// The durations to use when a backoff should be applied to a peer.
@@ -1022,10 +1180,14 @@ impl Display for InboundConnectionError {
 #[cfg(test)]
 mod test {
     use super::PeersManager;
+    #[cfg(feature = "serde")]
+    use crate::peers::manager::write_persisted_peers;
     use crate::{
+        error::BackoffKind,
         peers::{
             manager::{ConnectionInfo, PeerBackoffDurations, PeerConnectionState},
-            PeerAction, ReputationChangeKind,
+            reputation::BANNED_REPUTATION,
+            PeerAction, PeerKind, PersistedPeer, ReputationChangeKind,
         },
         session::PendingSessionHandshakeError,
         PeersConfig,
@@ -1658,4 +1820,91 @@ mod test {
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn test_persist_and_restore_peers() {
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let backoff_peer = PeerId::random();
+        let backoff_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8009);
+
+        let mut peers = PeersManager::default();
+        peers.add_trusted_peer(peer, socket_addr);
+        peers.add_peer(backoff_peer, backoff_addr);
+        peers.apply_reputation_change(&peer, ReputationChangeKind::Timeout);
+        peers.backoff_peer(backoff_peer, BackoffKind::High);
+
+        let persisted = peers.persisted_peers();
+        assert_eq!(persisted.len(), 2);
+        let persisted_peer = persisted.iter().find(|p| p.peer_id == peer).unwrap();
+        assert_eq!(persisted_peer.addr, socket_addr);
+        assert_eq!(persisted_peer.kind, PeerKind::Trusted);
+        assert_eq!(persisted_peer.reputation, peers.peers[&peer].reputation);
+        assert!(persisted_peer.banned_until.is_none());
+        let persisted_backoff = persisted.iter().find(|p| p.peer_id == backoff_peer).unwrap();
+        assert!(persisted_backoff.banned_until.is_some());
+
+        let restored =
+            PeersManager::new(PeersConfig::default().with_persisted_peers(persisted.clone()));
+        let restored_peer = &restored.peers[&peer];
+        assert_eq!(restored_peer.addr, socket_addr);
+        assert_eq!(restored_peer.reputation, peers.peers[&peer].reputation);
+        assert!(restored.peers.contains_key(&backoff_peer));
+        assert!(restored.ban_list.is_banned_peer(&backoff_peer));
+
+        // trust is not restored, only configured trusted nodes are trusted
+        assert_eq!(restored_peer.kind, PeerKind::Basic);
+        let trusted = NodeRecord::new(socket_addr, peer);
+        let restored = PeersManager::new(
+            PeersConfig::default()
+                .with_trusted_nodes(HashSet::from([trusted]))
+                .with_persisted_peers(persisted),
+        );
+        assert_eq!(restored.peers[&peer].kind, PeerKind::Trusted);
+    }
+
+    #[tokio::test]
+    async fn test_restore_expired_ban() {
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let persisted = PersistedPeer {
+            peer_id: peer,
+            addr: socket_addr,
+            kind: PeerKind::Basic,
+            reputation: BANNED_REPUTATION - 1,
+            fork_id: None,
+            last_seen: Some(1),
+            banned_until: Some(1),
+        };
+
+        let mut peers =
+            PeersManager::new(PeersConfig::default().with_persisted_peers(vec![persisted]));
+        assert!(!peers.ban_list.is_banned_peer(&peer));
+        assert!(!peers.peers[&peer].is_banned());
+
+        match event!(peers) {
+            PeerAction::Connect { peer_id, remote_addr } => {
+                assert_eq!(peer_id, peer);
+                assert_eq!(remote_addr, socket_addr);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_persisted_peers_file() {
+        let mut peers = PeersManager::default();
+        peers.add_peer(PeerId::random(), SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8008));
+        let persisted = peers.persisted_peers();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("known-peers.json");
+        let config = PeersConfig::default().with_persisted_peers_file(&path).unwrap();
+        assert!(config.persisted_peers.is_empty());
+
+        write_persisted_peers(&path, &persisted).unwrap();
+        let config = PeersConfig::default().with_persisted_peers_file(&path).unwrap();
+        assert_eq!(config.persisted_peers, persisted);
+    }
 }
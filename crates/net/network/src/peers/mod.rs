@@ -4,5 +4,7 @@ mod manager;
 mod reputation;
 
 pub(crate) use manager::{InboundConnectionError, PeerAction, PeersManager};
-pub use manager::{PeerKind, PeersConfig, PeersHandle};
+#[cfg(feature = "serde")]
+pub use manager::write_persisted_peers;
+pub use manager::{PeerKind, PeersConfig, PeersHandle, PersistedPeer};
 pub use reputation::{ReputationChangeKind, ReputationChangeWeights};
@@ -1,7 +1,9 @@
 //! Represents an established session.
 
 use crate::{
-    message::{NewBlockMessage, PeerMessage, PeerRequest, PeerResponse, PeerResponseResult},
+    message::{
+        NewBlockMessage, PeerMessage, PeerRequest, PeerResponse, PeerResponseResult, SnapRequest,
+    },
     session::{
         handle::{ActiveSessionMessage, SessionCommand},
         SessionId,
@@ -14,11 +16,13 @@ use reth_eth_wire::{
     capability::Capabilities,
     errors::{EthHandshakeError, EthStreamError, P2PStreamError},
     message::{EthBroadcastMessage, RequestPair},
-    DisconnectReason, EthMessage, EthStream, P2PStream,
+    AccountRange, ByteCodes, DisconnectReason, EthMessage, EthStream, EthVersion, P2PStream,
+    ProtocolStream, SnapMessage, StorageRanges, TrieNodes,
 };
 use reth_interfaces::p2p::error::RequestError;
 use reth_net_common::bandwidth_meter::MeteredStream;
 use reth_primitives::PeerId;
+use reth_rlp::{Decodable, Encodable};
 use std::{
     collections::VecDeque,
     future::Future,
@@ -49,6 +53,8 @@ pub(crate) struct ActiveSession {
     pub(crate) next_id: u64,
     /// The underlying connection.
     pub(crate) conn: EthStream<P2PStream<ECIESStream<MeteredStream<TcpStream>>>>,
+    /// The `snap` protocol multiplexed over the connection, if it is shared with the peer.
+    pub(crate) snap: Option<ProtocolStream>,
     /// Identifier of the node we're connected to.
     pub(crate) remote_peer_id: PeerId,
     /// The address we're connected to.
@@ -65,6 +71,8 @@ pub(crate) struct ActiveSession {
     pub(crate) request_tx: Fuse<ReceiverStream<PeerRequest>>,
     /// All requests sent to the remote peer we're waiting on a response
     pub(crate) inflight_requests: FnvHashMap<u64, InflightRequest>,
+    /// All `snap` requests sent to the remote peer we're waiting on a response
+    pub(crate) inflight_snap_requests: FnvHashMap<u64, InflightRequest<SnapRequest>>,
     /// All requests that were sent by the remote peer.
     pub(crate) received_requests: Vec<ReceivedRequest>,
     /// Buffered messages that should be handled and sent to the peer.
@@ -88,6 +96,11 @@ impl ActiveSession {
         id
     }
 
+    /// Returns the negotiated version of the `eth` protocol.
+    fn eth_version(&self) -> u8 {
+        self.conn.inner().shared_capability().version()
+    }
+
     /// Handle a message read from the connection.
     ///
     /// Returns an error if the message is considered to be in violation of the protocol.
@@ -173,6 +186,15 @@ impl ActiveSession {
             EthMessage::PooledTransactions(resp) => {
                 on_response!(self, resp, GetPooledTransactions);
             }
+            msg @ (EthMessage::GetNodeData(_) | EthMessage::NodeData(_))
+                if self.eth_version() >= EthVersion::Eth67 as u8 =>
+            {
+                // `GetNodeData` and `NodeData` were removed in eth/67
+                return Some((
+                    EthStreamError::EthInvalidMessageError(self.eth_version(), msg.message_id()),
+                    msg,
+                ))
+            }
             EthMessage::GetNodeData(req) => {
                 on_request!(req, NodeData, GetNodeData);
             }
@@ -215,6 +237,10 @@ impl ActiveSession {
                 let deadline = self.request_deadline();
                 self.on_peer_request(req, deadline);
             }
+            PeerMessage::SnapRequest(req) => {
+                let deadline = self.request_deadline();
+                self.on_snap_request(req, deadline);
+            }
             PeerMessage::SendTransactions(msg) => {
                 self.queued_outgoing.push_back(EthBroadcastMessage::Transactions(msg).into());
             }
@@ -227,6 +253,66 @@ impl ActiveSession {
         }
     }
 
+    /// Sends a `snap` request to the peer.
+    fn on_snap_request(&mut self, request: SnapRequest, deadline: Instant) {
+        let Some(snap) = self.snap.as_ref() else {
+            request.send_err_response(RequestError::UnsupportedCapability);
+            return
+        };
+
+        let request_id = self.next_id();
+        let msg = request.create_request_message(request_id);
+        let mut buf = Vec::with_capacity(msg.length());
+        msg.encode(&mut buf);
+        if let Err(err) = snap.send(buf.into()) {
+            debug!(target: "net::session", ?err, remote_peer_id=?self.remote_peer_id, "failed to send snap request");
+            request.send_err_response(RequestError::ConnectionDropped);
+            return
+        }
+
+        let req = InflightRequest { request, deadline };
+        self.inflight_snap_requests.insert(request_id, req);
+    }
+
+    /// Handle a `snap` message read from the connection.
+    ///
+    /// The node does not serve state over `snap`, it only advertises the protocol as a client, see
+    /// [`NetworkConfigBuilder::enable_snap_client`](crate::NetworkConfigBuilder::enable_snap_client).
+    /// Requests are answered with empty responses, which tells the peer that the requested state
+    /// root is not available.
+    fn on_incoming_snap(&mut self, msg: SnapMessage) {
+        let request_id = msg.request_id();
+        let response = match msg {
+            SnapMessage::GetAccountRange(_) => {
+                SnapMessage::AccountRange(AccountRange { request_id, ..Default::default() })
+            }
+            SnapMessage::GetStorageRanges(_) => {
+                SnapMessage::StorageRanges(StorageRanges { request_id, ..Default::default() })
+            }
+            SnapMessage::GetByteCodes(_) => {
+                SnapMessage::ByteCodes(ByteCodes { request_id, ..Default::default() })
+            }
+            SnapMessage::GetTrieNodes(_) => {
+                SnapMessage::TrieNodes(TrieNodes { request_id, ..Default::default() })
+            }
+            msg => {
+                match self.inflight_snap_requests.remove(&request_id) {
+                    Some(req) if req.request.send_response(self.remote_peer_id, msg) => {}
+                    _ => self.on_bad_message(),
+                }
+                return
+            }
+        };
+
+        if let Some(snap) = self.snap.as_ref() {
+            let mut buf = Vec::with_capacity(response.length());
+            response.encode(&mut buf);
+            if let Err(err) = snap.send(buf.into()) {
+                debug!(target: "net::session", ?err, remote_peer_id=?self.remote_peer_id, "failed to send snap response");
+            }
+        }
+    }
+
     /// Returns the deadline timestamp at which the request times out
     fn request_deadline(&self) -> Instant {
         Instant::now() + self.request_timeout
@@ -324,6 +410,17 @@ impl ActiveSession {
             let req = self.inflight_requests.remove(&id).expect("exists; qed");
             req.request.send_err_response(RequestError::Timeout);
         }
+
+        let timedout = self
+            .inflight_snap_requests
+            .iter()
+            .filter_map(|(id, req)| (now > req.deadline).then_some(*id))
+            .collect::<Vec<_>>();
+        for id in timedout {
+            warn!(target: "net::session", ?id, remote_peer_id=?self.remote_peer_id, "timed out outgoing snap request");
+            let req = self.inflight_snap_requests.remove(&id).expect("exists; qed");
+            req.request.send_err_response(RequestError::Timeout);
+        }
     }
 }
 
@@ -453,6 +550,21 @@ impl Future for ActiveSession {
                 }
             }
 
+            // `snap` messages are read after the `eth` messages, so they are not starved by them
+            while let Some(Poll::Ready(Some(bytes))) =
+                this.snap.as_mut().map(|snap| snap.poll_next_unpin(cx))
+            {
+                progress = true;
+                match SnapMessage::decode(&mut &bytes[..]) {
+                    Ok(msg) => this.on_incoming_snap(msg),
+                    Err(err) => {
+                        error!(target: "net::session", ?err, remote_peer_id=?this.remote_peer_id, "received invalid snap message");
+                        this.close_on_error(P2PStreamError::from(err).into());
+                        return Poll::Ready(())
+                    }
+                }
+            }
+
             if !progress {
                 if this.timeout_interval.poll_tick(cx).is_ready() {
                     // check for timed out requests
@@ -477,8 +589,8 @@ pub(crate) struct ReceivedRequest {
 }
 
 /// A request that waits for a response from the peer
-pub(crate) struct InflightRequest {
-    request: PeerRequest,
+pub(crate) struct InflightRequest<R = PeerRequest> {
+    request: R,
     deadline: Instant,
 }
 
@@ -511,7 +623,7 @@ mod tests {
     };
     use reth_ecies::util::pk2id;
     use reth_eth_wire::{
-        EthVersion, HelloMessage, NewPooledTransactionHashes, ProtocolVersion, Status,
+        EthVersion, GetNodeData, HelloMessage, NewPooledTransactionHashes, ProtocolVersion, Status,
         StatusBuilder, UnauthedEthStream, UnauthedP2PStream,
     };
     use reth_net_common::bandwidth_meter::BandwidthMeter;
@@ -628,6 +740,8 @@ mod tests {
                         to_session: self.active_session_tx.clone(),
                         request_tx: ReceiverStream::new(messages_rx).fuse(),
                         inflight_requests: Default::default(),
+                        inflight_snap_requests: Default::default(),
+                        snap: None,
                         conn,
                         queued_outgoing: Default::default(),
                         received_requests: Default::default(),
@@ -778,4 +892,30 @@ mod tests {
 
         rx.await.unwrap();
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reject_node_data_eth67() {
+        let mut builder = SessionBuilder::default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let fut = builder.with_client_stream(local_addr, move |mut client_stream| async move {
+            let request = RequestPair { request_id: 0, message: GetNodeData(vec![]) };
+            client_stream.send(EthMessage::GetNodeData(request)).await.unwrap();
+            let _ = client_stream.next().await;
+        });
+        tokio::task::spawn(fut);
+
+        let (incoming, _) = listener.accept().await.unwrap();
+        let session = builder.connect_incoming(incoming).await;
+        session.await;
+
+        match builder.active_session_rx.next().await.unwrap() {
+            ActiveSessionMessage::ClosedOnConnectionError { error, .. } => {
+                assert!(matches!(error, EthStreamError::EthInvalidMessageError(67, _)));
+            }
+            msg => panic!("unexpected message {msg:?}"),
+        }
+    }
 }
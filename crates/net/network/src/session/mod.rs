@@ -15,9 +15,10 @@ use fnv::FnvHashMap;
 use futures::{future::Either, io, FutureExt, StreamExt};
 use reth_ecies::{stream::ECIESStream, ECIESError};
 use reth_eth_wire::{
-    capability::{Capabilities, CapabilityMessage},
+    capability::{Capabilities, CapabilityMessage, Protocol},
     errors::EthStreamError,
     DisconnectReason, HelloMessage, Status, UnauthedEthStream, UnauthedP2PStream,
+    SNAP_PROTOCOL_NAME,
 };
 use reth_net_common::bandwidth_meter::{BandwidthMeter, MeteredStream};
 use reth_primitives::{ForkFilter, ForkId, ForkTransition, PeerId, H256, U256};
@@ -365,6 +366,9 @@ impl SessionManager {
 
                 let messages = PeerRequestSender::new(peer_id, to_session_tx);
 
+                let mut conn = conn;
+                let snap = conn.inner_mut().take_protocol_stream(SNAP_PROTOCOL_NAME);
+
                 let session = ActiveSession {
                     next_id: 0,
                     remote_peer_id: peer_id,
@@ -375,7 +379,9 @@ impl SessionManager {
                     to_session: self.active_session_tx.clone(),
                     request_tx: ReceiverStream::new(messages_rx).fuse(),
                     inflight_requests: Default::default(),
+                    inflight_snap_requests: Default::default(),
                     conn,
+                    snap,
                     queued_outgoing: Default::default(),
                     received_requests: Default::default(),
                     timeout_interval: tokio::time::interval(self.request_timeout),
@@ -726,7 +732,7 @@ async fn authenticate(
             }
         }
     };
-    let unauthed = UnauthedP2PStream::new(stream);
+    let unauthed = UnauthedP2PStream::new(stream).with_protocol(Protocol::snap());
 
     let auth = authenticate_stream(
         unauthed,
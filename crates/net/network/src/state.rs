@@ -6,7 +6,7 @@ use crate::{
     fetch::{BlockResponseOutcome, FetchAction, StateFetcher},
     message::{
         BlockRequest, NewBlockMessage, PeerRequest, PeerRequestSender, PeerResponse,
-        PeerResponseResult,
+        PeerResponseResult, SnapRequest,
     },
     peers::{PeerAction, PeerKind, PeersManager},
     FetchClient,
@@ -123,7 +123,12 @@ where
         // find the corresponding block number
         let block_number =
             self.client.block_number(status.blockhash).ok().flatten().unwrap_or_default();
-        self.state_fetcher.new_active_peer(peer, status.blockhash, block_number);
+        self.state_fetcher.new_active_peer(
+            peer,
+            status.blockhash,
+            block_number,
+            capabilities.supports_snap_v1(),
+        );
 
         self.active_peers.insert(
             peer,
@@ -326,6 +331,12 @@ where
                     let response = PeerResponse::BlockBodies { response: rx };
                     (request, response)
                 }
+                BlockRequest::GetReceipts(request) => {
+                    let (response, rx) = oneshot::channel();
+                    let request = PeerRequest::GetReceipts { request, response };
+                    let response = PeerResponse::Receipts { response: rx };
+                    (request, response)
+                }
             };
             let _ = peer.request_tx.to_session_tx.try_send(request);
             peer.pending_response = Some(response);
@@ -356,6 +367,10 @@ where
                 let outcome = self.state_fetcher.on_block_bodies_response(peer, res)?;
                 self.on_block_response_outcome(outcome)
             }
+            PeerResponseResult::Receipts(res) => {
+                let outcome = self.state_fetcher.on_receipts_response(peer, res)?;
+                self.on_block_response_outcome(outcome)
+            }
             _ => None,
         }
     }
@@ -377,6 +392,9 @@ where
                     FetchAction::BlockRequest { peer_id, request } => {
                         self.handle_block_request(peer_id, request)
                     }
+                    FetchAction::SnapRequest { peer_id, request } => self
+                        .queued_messages
+                        .push_back(StateAction::SnapRequest { peer_id, request }),
                 }
             }
 
@@ -481,6 +499,13 @@ pub(crate) enum StateAction {
         /// The reported [`ForkId`] by this peer.
         fork_id: ForkId,
     },
+    /// Dispatch a `snap` request to the peer
+    SnapRequest {
+        /// Target of the request
+        peer_id: PeerId,
+        /// The request to send
+        request: SnapRequest,
+    },
     /// A peer was added
     PeerAdded(PeerId),
     /// A peer was dropped
@@ -231,6 +231,10 @@ where
                 let msg = PeerMessage::NewBlockHashes(hashes);
                 self.sessions.send_message(&peer_id, msg);
             }
+            StateAction::SnapRequest { peer_id, request } => {
+                let msg = PeerMessage::SnapRequest(request);
+                self.sessions.send_message(&peer_id, msg);
+            }
             StateAction::PeerAdded(peer_id) => return Some(SwarmEvent::PeerAdded(peer_id)),
             StateAction::PeerRemoved(peer_id) => return Some(SwarmEvent::PeerRemoved(peer_id)),
             StateAction::DiscoveredEnrForkId { peer_id, fork_id } => {
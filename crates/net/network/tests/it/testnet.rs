@@ -8,7 +8,9 @@ use reth_network::{
     NetworkHandle, NetworkManager,
 };
 use reth_primitives::PeerId;
-use reth_provider::{test_utils::NoopProvider, BlockProvider, HeaderProvider};
+use reth_provider::{
+    test_utils::NoopProvider, BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory,
+};
 use secp256k1::SecretKey;
 use std::{
     fmt,
@@ -35,7 +37,7 @@ pub struct Testnet<C> {
 
 impl<C> Testnet<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + StateProviderFactory,
 {
     /// Same as [`Self::try_create_with`] but panics on error
     pub async fn create_with(num_peers: usize, provider: Arc<C>) -> Self {
@@ -114,7 +116,7 @@ where
 
 impl<C> Testnet<C>
 where
-    C: BlockProvider + HeaderProvider + 'static,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + StateProviderFactory + 'static,
 {
     /// Spawns the testnet to a separate task
     pub fn spawn(self) -> TestnetHandle<C> {
@@ -168,7 +170,7 @@ impl<C> fmt::Debug for Testnet<C> {
 
 impl<C> Future for Testnet<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + StateProviderFactory,
 {
     type Output = ();
 
@@ -211,7 +213,7 @@ pub struct Peer<C> {
 
 impl<C> Peer<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + StateProviderFactory,
 {
     pub fn num_peers(&self) -> usize {
         self.network.num_connected_peers()
@@ -238,7 +240,7 @@ where
 
 impl<C> Future for Peer<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + StateProviderFactory,
 {
     type Output = ();
 
@@ -263,7 +265,7 @@ pub struct PeerConfig<C = NoopProvider> {
 
 impl<C> PeerConfig<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + StateProviderFactory,
 {
     pub fn new(client: Arc<C>) -> Self {
         let secret_key = SecretKey::new(&mut rand::thread_rng());
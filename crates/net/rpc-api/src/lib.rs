@@ -17,10 +17,18 @@ mod eth_filter;
 mod eth_pubsub;
 mod net;
 mod trace;
+mod txpool;
 mod web3;
 
 pub use self::{
     admin::AdminApiServer, debug::DebugApiServer, engine::EngineApiServer, eth::EthApiServer,
     eth_filter::EthFilterApiServer, eth_pubsub::EthPubSubApiServer, net::NetApiServer,
-    web3::Web3ApiServer,
+    txpool::TxPoolApiServer, web3::Web3ApiServer,
+};
+
+#[cfg(feature = "client")]
+pub use self::{
+    debug::DebugApiClient, engine::EngineApiClient, eth::EthApiClient,
+    eth_filter::EthFilterApiClient, net::NetApiClient, trace::TraceApiClient,
+    txpool::TxPoolApiClient, web3::Web3ApiClient,
 };
@@ -0,0 +1,233 @@
+//! JWT authentication of the engine API.
+//!
+//! See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/authentication.md>
+
+use futures_util::future::Either;
+use hyper::{header::AUTHORIZATION, Body, Request, Response, StatusCode};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    future::{ready, Ready},
+    io::Write,
+    path::Path,
+    task::{Context, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::{Layer, Service};
+
+/// The maximum number of seconds the `iat` claim of a token may differ from the local time.
+const MAX_IAT_DIFFERENCE: u64 = 60;
+
+/// Errors of reading the [JwtSecret] or validating a token.
+#[derive(Debug, thiserror::Error)]
+pub enum JwtError {
+    /// The secret is not 32 hex encoded bytes.
+    #[error("JWT secret must be 32 hex encoded bytes")]
+    InvalidSecret,
+    /// The file of the secret could not be read or written.
+    #[error("failed to access JWT secret file: {0}")]
+    Io(#[from] std::io::Error),
+    /// The token could not be decoded or its signature is invalid.
+    #[error("invalid JWT: {0}")]
+    InvalidToken(#[from] jsonwebtoken::errors::Error),
+    /// The token was issued too long before or after the local time.
+    #[error("JWT issued at {iat} differs more than {MAX_IAT_DIFFERENCE}s from the local time")]
+    StaleToken {
+        /// The `iat` claim of the token.
+        iat: u64,
+    },
+}
+
+/// The secret that is shared with the consensus layer client to authenticate the engine API.
+#[derive(Clone)]
+pub struct JwtSecret([u8; 32]);
+
+impl JwtSecret {
+    /// Parses the secret from its hex encoding, with or without `0x` prefix.
+    pub fn from_hex(hex: impl AsRef<str>) -> Result<Self, JwtError> {
+        let hex = hex.as_ref().trim();
+        let bytes = hex::decode(hex.strip_prefix("0x").unwrap_or(hex))
+            .map_err(|_| JwtError::InvalidSecret)?;
+        Ok(Self(bytes.try_into().map_err(|_| JwtError::InvalidSecret)?))
+    }
+
+    /// Generates a new random secret.
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    /// Reads the secret from the given file.
+    ///
+    /// If the file does not exist, it is created with a new random secret. On unix the file is
+    /// only readable and writable by its owner.
+    pub fn try_create(path: impl AsRef<Path>) -> Result<Self, JwtError> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Self::from_hex(fs::read_to_string(path)?)
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let secret = Self::random();
+        options.open(path)?.write_all(hex::encode(secret.0).as_bytes())?;
+        Ok(secret)
+    }
+
+    /// Validates a token of the consensus layer client.
+    ///
+    /// The token must be signed with this secret using `HS256` and must have been issued within
+    /// [MAX_IAT_DIFFERENCE] seconds of the local time.
+    pub fn validate(&self, token: &str) -> Result<(), JwtError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        // The `iat` claim is the only claim that is required by the spec
+        validation.validate_exp = false;
+        validation.required_spec_claims.clear();
+
+        let claims = decode::<Claims>(token, &DecodingKey::from_secret(&self.0), &validation)?;
+        let iat = claims.claims.iat;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        if now.abs_diff(iat) > MAX_IAT_DIFFERENCE {
+            return Err(JwtError::StaleToken { iat })
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for JwtSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("JwtSecret").field(&"<redacted>").finish()
+    }
+}
+
+/// The claims of a token.
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    /// The time the token was issued at, in seconds since the unix epoch.
+    iat: u64,
+}
+
+/// A [Layer] that rejects all requests without a valid bearer token, see [JwtSecret::validate].
+#[derive(Debug, Clone)]
+pub struct AuthLayer {
+    secret: JwtSecret,
+}
+
+impl AuthLayer {
+    /// Creates a new layer that validates the tokens with the given secret.
+    pub fn new(secret: JwtSecret) -> Self {
+        Self { secret }
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = AuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthService { secret: self.secret.clone(), inner }
+    }
+}
+
+/// The [Service] of the [AuthLayer].
+#[derive(Debug, Clone)]
+pub struct AuthService<S> {
+    secret: JwtSecret,
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for AuthService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Either<Ready<Result<Self::Response, Self::Error>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let token = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        let result = match token {
+            Some(token) => self.secret.validate(token).map_err(|err| err.to_string()),
+            None => Err("missing bearer token".to_string()),
+        };
+        match result {
+            Ok(()) => Either::Right(self.inner.call(req)),
+            Err(message) => {
+                let response = Response::builder()
+                    .status(StatusCode::UNAUTHORIZED)
+                    .body(Body::from(message))
+                    .expect("response is valid");
+                Either::Left(ready(Ok(response)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    fn token(secret: &JwtSecret, iat: u64) -> String {
+        let key = EncodingKey::from_secret(&secret.0);
+        encode(&Header::new(Algorithm::HS256), &Claims { iat }, &key).unwrap()
+    }
+
+    #[test]
+    fn parse_secret() {
+        let hex = "f".repeat(64);
+        assert_eq!(JwtSecret::from_hex(&hex).unwrap().0, [0xff; 32]);
+        assert_eq!(JwtSecret::from_hex(format!("0x{hex}\n")).unwrap().0, [0xff; 32]);
+        assert_matches!(JwtSecret::from_hex("0xff"), Err(JwtError::InvalidSecret));
+        assert_matches!(JwtSecret::from_hex("z".repeat(64)), Err(JwtError::InvalidSecret));
+    }
+
+    #[test]
+    fn validate_token() {
+        let secret = JwtSecret::random();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+        assert_matches!(secret.validate(&token(&secret, now)), Ok(()));
+        assert_matches!(
+            secret.validate(&token(&secret, now - MAX_IAT_DIFFERENCE - 10)),
+            Err(JwtError::StaleToken { .. })
+        );
+        assert_matches!(
+            secret.validate(&token(&JwtSecret::random(), now)),
+            Err(JwtError::InvalidToken(_))
+        );
+    }
+
+    #[test]
+    fn create_secret_file() {
+        let path = std::env::temp_dir().join(format!("reth-jwt-{}.hex", rand::random::<u64>()));
+        let secret = JwtSecret::try_create(&path).unwrap();
+        assert_eq!(JwtSecret::try_create(&path).unwrap().0, secret.0);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -7,13 +7,33 @@
 
 //! Configure reth RPC
 
-use jsonrpsee::{server::ServerBuilder, RpcModule};
-use reth_ipc::server::{Builder as IpcServerBuilder, Endpoint};
-use reth_provider::{BlockProvider, StateProviderFactory};
+use jsonrpsee::{
+    core::{server::rpc_module::Methods, Error as RpcError},
+    server::ServerHandle,
+    RpcModule,
+};
+use reth_executor::Config as ExecutorConfig;
+use reth_ipc::server::Endpoint;
+use reth_network::NetworkHandle;
+use reth_primitives::IntoRecoveredTransaction;
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, TransactionsProvider,
+};
+use reth_rpc::{AdminApi, EngineApi, EthApi, NetApi, TxPoolApi, Web3Api};
+use reth_rpc_api::{
+    AdminApiServer, EngineApiServer, EthApiServer, NetApiServer, TxPoolApiServer, Web3ApiServer,
+};
 use reth_transaction_pool::TransactionPool;
 use serde::{Deserialize, Serialize, Serializer};
-use std::{fmt, net::SocketAddr};
-use strum::{AsRefStr, EnumString, EnumVariantNames};
+use std::{fmt, net::SocketAddr, sync::Arc};
+use strum::{AsRefStr, EnumIter, EnumString, EnumVariantNames, IntoEnumIterator};
+
+mod auth;
+pub use auth::{AuthLayer, AuthService, JwtError, JwtSecret};
+
+// re-export the server builders so they can be used to configure the servers
+pub use jsonrpsee::server::ServerBuilder;
+pub use reth_ipc::server::Builder as IpcServerBuilder;
 
 /// A builder type to configure the RPC module: See [RpcModule]
 ///
@@ -25,21 +45,31 @@ use strum::{AsRefStr, EnumString, EnumVariantNames};
 ///     .with_config(RpcModuleConfig::Selection(vec![RethRpcModule::Eth]));
 /// ```
 #[derive(Debug)]
-pub struct RpcModuleBuilder<Client, Pool> {
+pub struct RpcModuleBuilder<Client, Pool, Network> {
     /// The Client type to when creating all rpc handlers
     client: Client,
     /// The Pool type to when creating all rpc handlers
     pool: Pool,
+    /// The Network type to when creating all rpc handlers
+    network: Network,
+    /// The config the `eth` handlers execute calls with
+    executor_config: ExecutorConfig,
     /// What modules to configure
     config: RpcModuleConfig,
 }
 
 // === impl RpcBuilder ===
 
-impl<Client, Pool> RpcModuleBuilder<Client, Pool> {
+impl<Client, Pool, Network> RpcModuleBuilder<Client, Pool, Network> {
     /// Create a new instance of the builder
-    pub fn new(client: Client, pool: Pool) -> Self {
-        Self { client, pool, config: Default::default() }
+    pub fn new(client: Client, pool: Pool, network: Network) -> Self {
+        Self {
+            client,
+            pool,
+            network,
+            executor_config: ExecutorConfig::new_ethereum(),
+            config: Default::default(),
+        }
     }
 
     /// Configures what RPC modules should be installed
@@ -48,42 +78,90 @@ impl<Client, Pool> RpcModuleBuilder<Client, Pool> {
         self
     }
 
+    /// Configures the chain id and hardforks the `eth` handlers execute calls with.
+    pub fn with_executor_config(mut self, executor_config: ExecutorConfig) -> Self {
+        self.executor_config = executor_config;
+        self
+    }
+
     /// Configure the client instance.
-    pub fn with_client<C>(self, client: C) -> RpcModuleBuilder<C, Pool>
+    pub fn with_client<C>(self, client: C) -> RpcModuleBuilder<C, Pool, Network>
     where
         C: BlockProvider + StateProviderFactory + 'static,
     {
-        let Self { pool, config, .. } = self;
-        RpcModuleBuilder { client, config, pool }
+        let Self { pool, network, executor_config, config, .. } = self;
+        RpcModuleBuilder { client, pool, network, executor_config, config }
     }
 
     /// Configure the transaction pool instance.
-    pub fn with_pool<P>(self, pool: P) -> RpcModuleBuilder<Client, P>
+    pub fn with_pool<P>(self, pool: P) -> RpcModuleBuilder<Client, P, Network>
     where
         P: TransactionPool + 'static,
     {
-        let Self { client, config, .. } = self;
-        RpcModuleBuilder { client, config, pool }
+        let Self { client, network, executor_config, config, .. } = self;
+        RpcModuleBuilder { client, pool, network, executor_config, config }
+    }
+
+    /// Configure the network instance.
+    pub fn with_network<N>(self, network: N) -> RpcModuleBuilder<Client, Pool, N> {
+        let Self { client, pool, executor_config, config, .. } = self;
+        RpcModuleBuilder { client, pool, network, executor_config, config }
     }
 }
 
-impl<Client, Pool> RpcModuleBuilder<Client, Pool>
+impl<Client, Pool> RpcModuleBuilder<Client, Pool, NetworkHandle>
 where
-    Client: BlockProvider + StateProviderFactory + 'static,
-    Pool: TransactionPool + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateProviderFactory
+        + 'static,
+    Pool: TransactionPool + Clone + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
 {
     /// Configures the [RpcModule] which can be used to start the server(s).
+    ///
+    /// Modules that have no handlers yet are skipped.
     pub fn build(self) -> RpcModule<()> {
-        let Self { client: _, pool: _, config: _ } = self;
-        let _io = RpcModule::new(());
+        let Self { client, pool, network, executor_config, config } = self;
+        let client = Arc::new(client);
+        let eth_api =
+            || EthApi::with_executor_config(client.clone(), pool.clone(), executor_config.clone());
 
-        todo!("configure all handlers")
+        let mut module = RpcModule::new(());
+        for rpc_module in config.modules() {
+            let methods: Methods = match rpc_module {
+                RethRpcModule::Admin => AdminApi::new(network.clone()).into_rpc().into(),
+                RethRpcModule::Eth => eth_api().into_rpc().into(),
+                RethRpcModule::Net => {
+                    NetApi::new(network.clone(), Box::new(eth_api())).into_rpc().into()
+                }
+                RethRpcModule::Web3 => Web3Api::default().into_rpc().into(),
+                RethRpcModule::Txpool => TxPoolApi::new(pool.clone()).into_rpc().into(),
+                // TODO: add the handlers of the remaining modules
+                RethRpcModule::Trace | RethRpcModule::Debug => continue,
+            };
+            module.merge(methods).expect("modules have distinct method names");
+        }
+        module
+    }
+
+    /// Configures the [RpcModule] of the authenticated server: the engine API together with the
+    /// `eth` module, which consensus layer clients use as well.
+    ///
+    /// The configured modules are ignored.
+    pub fn build_auth(self, engine_api: EngineApi) -> RpcModule<()> {
+        let mut module =
+            self.with_config(RpcModuleConfig::Selection(vec![RethRpcModule::Eth])).build();
+        module.merge(engine_api.into_rpc()).expect("modules have distinct method names");
+        module
     }
 }
 
-impl Default for RpcModuleBuilder<(), ()> {
+impl Default for RpcModuleBuilder<(), (), ()> {
     fn default() -> Self {
-        RpcModuleBuilder::new((), ())
+        RpcModuleBuilder::new((), (), ())
     }
 }
 
@@ -97,9 +175,27 @@ pub enum RpcModuleConfig {
     Selection(Vec<RethRpcModule>),
 }
 
+impl RpcModuleConfig {
+    /// Returns the modules to install, without duplicates.
+    pub fn modules(&self) -> Vec<RethRpcModule> {
+        match self {
+            RpcModuleConfig::All => RethRpcModule::iter().collect(),
+            RpcModuleConfig::Selection(selection) => {
+                let mut modules = Vec::with_capacity(selection.len());
+                for module in selection {
+                    if !modules.contains(module) {
+                        modules.push(*module);
+                    }
+                }
+                modules
+            }
+        }
+    }
+}
+
 /// Represents RPC modules that are supported by reth
 #[derive(
-    Debug, Clone, Copy, Eq, PartialEq, AsRefStr, EnumVariantNames, EnumString, Deserialize,
+    Debug, Clone, Copy, Eq, PartialEq, AsRefStr, EnumIter, EnumVariantNames, EnumString, Deserialize,
 )]
 #[serde(rename_all = "snake_case")]
 #[strum(serialize_all = "kebab-case")]
@@ -108,12 +204,16 @@ pub enum RethRpcModule {
     Admin,
     /// `eth_` module
     Eth,
+    /// `net_` module
+    Net,
     /// `web3_` module
     Web3,
     /// `trace_` module
     Trace,
     /// `debug_` module
     Debug,
+    /// `txpool_` module
+    Txpool,
 }
 
 impl fmt::Display for RethRpcModule {
@@ -131,6 +231,17 @@ impl Serialize for RethRpcModule {
     }
 }
 
+/// The [RpcModule]s that are served over the different transports.
+#[derive(Debug, Default)]
+pub struct TransportRpcModules {
+    /// The module served over http.
+    pub http: Option<RpcModule<()>>,
+    /// The module served over ws.
+    pub ws: Option<RpcModule<()>>,
+    /// The module served over ipc.
+    pub ipc: Option<RpcModule<()>>,
+}
+
 /// A builder type for configuring and launching the servers that will handle RPC requests.
 ///
 /// Supported server transports are:
@@ -138,17 +249,208 @@ impl Serialize for RethRpcModule {
 ///    - ws
 ///    - ipc
 ///
-/// Http and WS share the same settings: [`ServerBuilder`].
+/// Http and WS servers are configured with [`ServerBuilder`], they listen on separate addresses.
 ///
-/// Once the [RpcModule] is built via [RpcModuleBuilder] the servers can be started, See also
-/// [ServerBuilder::build] and [Server::start](jsonrpsee::server::Server::start).
+/// Once the [RpcModule]s are built via [RpcModuleBuilder] the servers can be started, See also
+/// [RpcServerBuilder::start].
+#[derive(Default)]
 pub struct RpcServerBuilder {
-    /// Configs for JSON-RPC Http and WS server
-    pub http_ws_server_config: Option<ServerBuilder>,
-    /// Address where to bind the http and ws server to
-    pub http_ws_addr: Option<SocketAddr>,
+    /// Configs for JSON-RPC Http server
+    pub http_server_config: Option<ServerBuilder>,
+    /// Address where to bind the http server to
+    pub http_addr: Option<SocketAddr>,
+    /// Configs for JSON-RPC WS server
+    pub ws_server_config: Option<ServerBuilder>,
+    /// Address where to bind the ws server to
+    pub ws_addr: Option<SocketAddr>,
     /// Configs for JSON-RPC IPC server
     pub ipc_server_config: Option<IpcServerBuilder>,
     /// The Endpoint where to launch the ipc server
     pub ipc_server_path: Option<Endpoint>,
 }
+
+// === impl RpcServerBuilder ===
+
+impl RpcServerBuilder {
+    /// Configures the http server that listens on the given address.
+    pub fn with_http(mut self, config: ServerBuilder, addr: SocketAddr) -> Self {
+        self.http_server_config = Some(config.http_only());
+        self.http_addr = Some(addr);
+        self
+    }
+
+    /// Configures the ws server that listens on the given address.
+    pub fn with_ws(mut self, config: ServerBuilder, addr: SocketAddr) -> Self {
+        self.ws_server_config = Some(config.ws_only());
+        self.ws_addr = Some(addr);
+        self
+    }
+
+    /// Configures the ipc server that listens on the given endpoint.
+    pub fn with_ipc(mut self, config: IpcServerBuilder, endpoint: impl Into<String>) -> Self {
+        self.ipc_server_config = Some(config);
+        self.ipc_server_path = Some(Endpoint::new(endpoint.into()));
+        self
+    }
+
+    /// Starts the configured servers.
+    ///
+    /// Each server serves the module of its transport, servers without a module are not
+    /// started.
+    pub async fn start(self, modules: TransportRpcModules) -> Result<RpcServerHandle, RpcError> {
+        let mut handle = RpcServerHandle::default();
+
+        if let (Some(config), Some(addr), Some(module)) =
+            (self.http_server_config, self.http_addr, modules.http)
+        {
+            let server = config.build(addr).await?;
+            handle.http_local_addr = Some(server.local_addr()?);
+            handle.http = Some(server.start(module)?);
+        }
+
+        if let (Some(config), Some(addr), Some(module)) =
+            (self.ws_server_config, self.ws_addr, modules.ws)
+        {
+            let server = config.build(addr).await?;
+            handle.ws_local_addr = Some(server.local_addr()?);
+            handle.ws = Some(server.start(module)?);
+        }
+
+        if let (Some(config), Some(endpoint), Some(module)) =
+            (self.ipc_server_config, self.ipc_server_path, modules.ipc)
+        {
+            handle.ipc_endpoint = Some(endpoint.path().to_string());
+            handle.ipc = Some(config.build_with_endpoint(endpoint)?.start(module).await?);
+        }
+
+        Ok(handle)
+    }
+}
+
+impl fmt::Debug for RpcServerBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcServerBuilder")
+            .field("http_addr", &self.http_addr)
+            .field("ws_addr", &self.ws_addr)
+            .field("ipc_server_path", &self.ipc_server_path.as_ref().map(|e| e.path().to_string()))
+            .finish_non_exhaustive()
+    }
+}
+
+/// The handles of the servers started by [RpcServerBuilder::start].
+///
+/// The servers are stopped once the handle is dropped.
+#[derive(Debug, Default)]
+#[must_use = "Servers are stopped when the handle is dropped"]
+pub struct RpcServerHandle {
+    /// The address the http server listens on.
+    pub http_local_addr: Option<SocketAddr>,
+    /// The address the ws server listens on.
+    pub ws_local_addr: Option<SocketAddr>,
+    /// The endpoint the ipc server listens on.
+    pub ipc_endpoint: Option<String>,
+    http: Option<ServerHandle>,
+    ws: Option<ServerHandle>,
+    ipc: Option<ServerHandle>,
+}
+
+impl RpcServerHandle {
+    /// Stops all servers.
+    pub fn stop(self) -> Result<(), RpcError> {
+        for handle in [self.http, self.ws, self.ipc].into_iter().flatten() {
+            handle.stop()?;
+        }
+        Ok(())
+    }
+}
+
+/// Starts the authenticated server of the engine API that listens on the given address.
+///
+/// The server rejects all requests without a token signed with the given secret, see
+/// [AuthLayer].
+pub async fn launch_auth_server(
+    module: RpcModule<()>,
+    addr: SocketAddr,
+    secret: JwtSecret,
+) -> Result<(SocketAddr, ServerHandle), RpcError> {
+    let middleware = tower::ServiceBuilder::new().layer(AuthLayer::new(secret));
+    let server = ServerBuilder::default().set_middleware(middleware).build(addr).await?;
+    let local_addr = server.local_addr()?;
+    Ok((local_addr, server.start(module)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::http_client::HttpClientBuilder;
+    use reth_primitives::{Address, TransactionKind, U64};
+    use reth_rpc_api::TxPoolApiClient;
+    use reth_transaction_pool::{
+        test_utils::{testing_pool, MockTransaction},
+        PoolTransaction, TransactionOrigin,
+    };
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serve_txpool_module() {
+        let pool = testing_pool();
+        let to = Address::random();
+        let pending = MockTransaction::eip1559().with_to(TransactionKind::Call(to));
+        // the nonce gap keeps the transaction out of the pending sub-pool
+        let queued = MockTransaction::eip1559().inc_nonce();
+        pool.add_transactions(TransactionOrigin::External, vec![pending.clone(), queued.clone()])
+            .await
+            .unwrap();
+
+        let mut module = RpcModule::new(());
+        module.merge(TxPoolApi::new(pool).into_rpc()).unwrap();
+        let handle = RpcServerBuilder::default()
+            .with_http(ServerBuilder::default(), "127.0.0.1:0".parse().unwrap())
+            .start(TransportRpcModules { http: Some(module), ..Default::default() })
+            .await
+            .unwrap();
+        let client = HttpClientBuilder::default()
+            .build(format!("http://{}", handle.http_local_addr.unwrap()))
+            .unwrap();
+
+        let status = TxPoolApiClient::status(&client).await.unwrap();
+        assert_eq!(status.pending, U64::from(1));
+        assert_eq!(status.queued, U64::from(1));
+
+        let content = TxPoolApiClient::content(&client).await.unwrap();
+        let tx = &content.pending[&pending.sender()][&0];
+        assert_eq!(tx.from, pending.sender());
+        assert_eq!(tx.to, Some(to));
+        assert_eq!(content.queued[&queued.sender()][&1].from, queued.sender());
+
+        let inspect = TxPoolApiClient::inspect(&client).await.unwrap();
+        let summary = inspect.pending[&pending.sender()][&0];
+        assert_eq!(summary.to, Some(to));
+        assert_eq!(summary.gas, pending.gas_limit());
+        assert!(inspect.queued[&queued.sender()].contains_key(&1));
+
+        let content_from = TxPoolApiClient::content_from(&client, queued.sender()).await.unwrap();
+        assert!(content_from.pending.is_empty());
+        assert_eq!(content_from.queued.len(), 1);
+
+        handle.stop().unwrap();
+    }
+
+    #[test]
+    fn parse_rpc_modules() {
+        assert_eq!("eth".parse::<RethRpcModule>().unwrap(), RethRpcModule::Eth);
+        assert_eq!("net".parse::<RethRpcModule>().unwrap(), RethRpcModule::Net);
+        assert_eq!("txpool".parse::<RethRpcModule>().unwrap(), RethRpcModule::Txpool);
+        assert!("personal".parse::<RethRpcModule>().is_err());
+    }
+
+    #[test]
+    fn selected_modules_are_unique() {
+        let config = RpcModuleConfig::Selection(vec![
+            RethRpcModule::Eth,
+            RethRpcModule::Web3,
+            RethRpcModule::Eth,
+        ]);
+        assert_eq!(config.modules(), vec![RethRpcModule::Eth, RethRpcModule::Web3]);
+        assert!(RpcModuleConfig::All.modules().contains(&RethRpcModule::Net));
+    }
+}
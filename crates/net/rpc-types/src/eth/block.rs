@@ -1,5 +1,8 @@
-use crate::Transaction;
-use reth_primitives::{Address, Bloom, Bytes, H256, H64, U256};
+use crate::{engine::Withdrawal, Transaction};
+use reth_primitives::{
+    Address, Block as PrimitiveBlock, Bloom, Bytes, Header as PrimitiveHeader, H256, H64, U256,
+};
+use reth_rlp::Encodable;
 use serde::{ser::Error, Deserialize, Serialize, Serializer};
 use std::{collections::BTreeMap, ops::Deref};
 
@@ -31,6 +34,86 @@ pub struct Block {
     /// Base Fee for post-EIP1559 blocks.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_fee_per_gas: Option<U256>,
+    /// Withdrawals for post-Shanghai blocks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+/// Error that can occur when converting other types to blocks
+#[derive(Debug, thiserror::Error)]
+pub enum BlockError {
+    /// A transaction failed sender recovery
+    #[error("transaction failed sender recovery")]
+    InvalidSignature,
+}
+
+impl Block {
+    /// Converts the given primitive block into a [Block] response with the given total
+    /// difficulty.
+    ///
+    /// If `full` is true, the response contains the full transaction objects, otherwise only
+    /// the transaction hashes.
+    pub fn from_block(
+        block: PrimitiveBlock,
+        total_difficulty: U256,
+        full: bool,
+    ) -> Result<Self, BlockError> {
+        let block_hash = block.header.hash_slow();
+        let block_number = block.number;
+        let size = U256::from(block.length());
+        let uncles = block.ommers.iter().map(|ommer| ommer.hash_slow()).collect();
+
+        let transactions = if full {
+            let transactions = block
+                .body
+                .into_iter()
+                .enumerate()
+                .map(|(index, tx)| {
+                    let tx = tx.into_ecrecovered().ok_or(BlockError::InvalidSignature)?;
+                    Ok(Transaction::from_recovered_with_block_context(
+                        tx,
+                        block_hash,
+                        block_number,
+                        index,
+                    ))
+                })
+                .collect::<Result<Vec<_>, BlockError>>()?;
+            BlockTransactions::Full(transactions)
+        } else {
+            BlockTransactions::Hashes(block.body.iter().map(|tx| tx.hash).collect())
+        };
+
+        Ok(Self {
+            header: Header::from_primitive_with_hash(block.header.clone(), block_hash),
+            total_difficulty,
+            uncles,
+            transactions,
+            size: Some(size),
+            base_fee_per_gas: block.header.base_fee_per_gas.map(U256::from),
+            withdrawals: block
+                .withdrawals
+                .map(|withdrawals| withdrawals.into_iter().map(Into::into).collect()),
+        })
+    }
+
+    /// Builds a [Block] response for an uncle from its header.
+    ///
+    /// Uncles are returned without transactions and uncles, and their total difficulty is not
+    /// tracked.
+    pub fn uncle_block_from_header(header: PrimitiveHeader) -> Self {
+        let hash = header.hash_slow();
+        let block = PrimitiveBlock { header, body: vec![], ommers: vec![], withdrawals: None };
+        let size = U256::from(block.length());
+        Self {
+            base_fee_per_gas: block.header.base_fee_per_gas.map(U256::from),
+            header: Header::from_primitive_with_hash(block.header, hash),
+            total_difficulty: U256::ZERO,
+            uncles: vec![],
+            transactions: BlockTransactions::Hashes(vec![]),
+            size: Some(size),
+            withdrawals: None,
+        }
+    }
 }
 
 /// Block header representation.
@@ -72,6 +155,54 @@ pub struct Header {
     pub nonce: Option<H64>,
     /// Size in bytes
     pub size: Option<U256>,
+    /// Withdrawals root hash, present since Shanghai
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<H256>,
+}
+
+impl Header {
+    /// Converts the primitive header type to this RPC type, using the given hash of the header.
+    pub fn from_primitive_with_hash(primitive_header: PrimitiveHeader, block_hash: H256) -> Self {
+        let PrimitiveHeader {
+            parent_hash,
+            ommers_hash,
+            beneficiary,
+            state_root,
+            transactions_root,
+            receipts_root,
+            logs_bloom,
+            difficulty,
+            number,
+            gas_limit,
+            gas_used,
+            timestamp,
+            nonce,
+            extra_data,
+            withdrawals_root,
+            ..
+        } = primitive_header;
+
+        Self {
+            hash: Some(block_hash),
+            parent_hash,
+            uncles_hash: ommers_hash,
+            author: beneficiary,
+            miner: beneficiary,
+            state_root,
+            transactions_root,
+            receipts_root,
+            number: Some(U256::from(number)),
+            gas_used: U256::from(gas_used),
+            gas_limit: U256::from(gas_limit),
+            extra_data: extra_data.into(),
+            logs_bloom,
+            timestamp: U256::from(timestamp),
+            difficulty,
+            nonce: Some(H64::from_low_u64_be(nonce)),
+            size: None,
+            withdrawals_root,
+        }
+    }
 }
 
 /// A Block representation that allows to include additional fields
@@ -90,6 +221,12 @@ pub struct Rich<T> {
     pub extra_info: BTreeMap<String, serde_json::Value>,
 }
 
+impl<T> From<T> for Rich<T> {
+    fn from(inner: T) -> Self {
+        Self { inner, extra_info: Default::default() }
+    }
+}
+
 impl<T> Deref for Rich<T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
@@ -3,7 +3,9 @@
 #![allow(missing_docs)]
 
 use bytes::BytesMut;
-use reth_primitives::{Address, Bloom, Bytes, SealedBlock, H256, H64, U256, U64};
+use reth_primitives::{
+    Address, Bloom, Bytes, SealedBlock, Withdrawal as PrimitiveWithdrawal, H256, H64, U256, U64,
+};
 use reth_rlp::Encodable;
 use serde::{Deserialize, Serialize};
 
@@ -30,7 +32,7 @@ pub struct ExecutionPayload {
     /// Array of [`Withdrawal`] enabled with V2
     /// See <https://github.com/ethereum/execution-apis/blob/6709c2a795b707202e93c4f2867fa0bf2640a84f/src/engine/shanghai.md#executionpayloadv2>
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub withdrawal: Option<Withdrawal>,
+    pub withdrawals: Option<Vec<Withdrawal>>,
 }
 
 impl From<SealedBlock> for ExecutionPayload {
@@ -59,7 +61,9 @@ impl From<SealedBlock> for ExecutionPayload {
             base_fee_per_gas: U256::from(value.base_fee_per_gas.unwrap_or_default()),
             block_hash: value.hash(),
             transactions,
-            withdrawal: None,
+            withdrawals: value
+                .withdrawals
+                .map(|withdrawals| withdrawals.into_iter().map(Into::into).collect()),
         }
     }
 }
@@ -73,10 +77,30 @@ pub struct Withdrawal {
     pub index: U64,
     pub validator_index: U64,
     pub address: Address,
-    /// Note: the amount value is represented on the beacon chain as a little-endian value in units
-    /// of Gwei, whereas the amount in this structure MUST be converted to a big-endian value in
-    /// units of Wei.
-    pub amount: U256,
+    /// The value of the withdrawal in units of Gwei.
+    pub amount: U64,
+}
+
+impl From<PrimitiveWithdrawal> for Withdrawal {
+    fn from(value: PrimitiveWithdrawal) -> Self {
+        Withdrawal {
+            index: value.index.into(),
+            validator_index: value.validator_index.into(),
+            address: value.address,
+            amount: value.amount.into(),
+        }
+    }
+}
+
+impl From<Withdrawal> for PrimitiveWithdrawal {
+    fn from(value: Withdrawal) -> Self {
+        PrimitiveWithdrawal {
+            index: value.index.as_u64(),
+            validator_index: value.validator_index.as_u64(),
+            address: value.address,
+            amount: value.amount.as_u64(),
+        }
+    }
 }
 
 /// This structure encapsulates the fork choice state
@@ -99,7 +123,7 @@ pub struct PayloadAttributes {
     /// Array of [`Withdrawal`] enabled with V2
     /// See <https://github.com/ethereum/execution-apis/blob/6709c2a795b707202e93c4f2867fa0bf2640a84f/src/engine/shanghai.md#executionpayloadv2>
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub withdrawal: Option<Withdrawal>, // TODO: should be a vec
+    pub withdrawals: Option<Vec<Withdrawal>>,
 }
 
 /// This structure contains the result of processing a payload
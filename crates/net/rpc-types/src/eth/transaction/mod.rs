@@ -7,8 +7,12 @@ pub use request::TransactionRequest;
 pub use typed::*;
 
 use reth_primitives::{
-    rpc::transaction::eip2930::AccessListItem, Address, Bytes, H256, H512, U256, U64,
+    keccak256,
+    rpc::{self, transaction::eip2930::AccessListItem},
+    Address, Bytes, Transaction as PrimitiveTransaction, TransactionKind,
+    TransactionSignedEcRecovered, TxEip1559, TxEip2930, TxLegacy, TxType, H256, H512, U256, U64,
 };
+use reth_rlp::Encodable;
 use serde::{Deserialize, Serialize};
 
 /// Transaction object
@@ -67,3 +71,123 @@ pub struct Transaction {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub transaction_type: Option<U256>,
 }
+
+impl Transaction {
+    /// Create a new rpc transaction result for a _pending_ signed transaction, setting block
+    /// environment related fields to `None`.
+    pub fn from_recovered(tx: TransactionSignedEcRecovered) -> Self {
+        Self::fill(tx, None, None, None)
+    }
+
+    /// Create a new rpc transaction result for a mined transaction, using the given block hash,
+    /// number, and tx index fields to populate the corresponding fields in the rpc result.
+    pub fn from_recovered_with_block_context(
+        tx: TransactionSignedEcRecovered,
+        block_hash: H256,
+        block_number: u64,
+        tx_index: usize,
+    ) -> Self {
+        Self::fill(tx, Some(block_hash), Some(block_number), Some(U256::from(tx_index)))
+    }
+
+    fn fill(
+        tx: TransactionSignedEcRecovered,
+        block_hash: Option<H256>,
+        block_number: Option<u64>,
+        transaction_index: Option<U256>,
+    ) -> Self {
+        let signer = tx.signer();
+        let signed_tx = tx.into_signed();
+
+        let to = match signed_tx.kind() {
+            TransactionKind::Create => None,
+            TransactionKind::Call(to) => Some(*to),
+        };
+        let creates = to.is_none().then(|| contract_address(signer, signed_tx.nonce()));
+
+        let (gas_price, max_fee_per_gas, max_priority_fee_per_gas) = match &signed_tx.transaction {
+            PrimitiveTransaction::Legacy(TxLegacy { gas_price, .. }) |
+            PrimitiveTransaction::Eip2930(TxEip2930 { gas_price, .. }) => {
+                (Some(U256::from(*gas_price)), None, None)
+            }
+            PrimitiveTransaction::Eip1559(TxEip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                ..
+            }) => (
+                None,
+                Some(U256::from(*max_fee_per_gas)),
+                Some(U256::from(*max_priority_fee_per_gas)),
+            ),
+        };
+
+        let access_list = match &signed_tx.transaction {
+            PrimitiveTransaction::Legacy(_) => None,
+            PrimitiveTransaction::Eip2930(TxEip2930 { access_list, .. }) |
+            PrimitiveTransaction::Eip1559(TxEip1559 { access_list, .. }) => Some(
+                access_list
+                    .0
+                    .iter()
+                    .map(|item| AccessListItem {
+                        address: rpc::H160(item.address.0),
+                        storage_keys: item
+                            .storage_keys
+                            .iter()
+                            .map(|key| rpc::H256(key.0))
+                            .collect(),
+                    })
+                    .collect(),
+            ),
+        };
+
+        let signature = signed_tx.signature;
+        let chain_id = signed_tx.chain_id();
+        let parity = signature.odd_y_parity as u64;
+        let v = match (signed_tx.tx_type(), chain_id) {
+            // EIP-155: v = {0, 1} + CHAIN_ID * 2 + 35
+            (TxType::Legacy, Some(chain_id)) => chain_id * 2 + 35 + parity,
+            (TxType::Legacy, None) => parity + 27,
+            _ => parity,
+        };
+        let transaction_type = match signed_tx.tx_type() {
+            TxType::Legacy => None,
+            tx_type => Some(U256::from(tx_type as u8)),
+        };
+
+        Self {
+            hash: signed_tx.hash,
+            nonce: U256::from(signed_tx.nonce()),
+            block_hash,
+            block_number: block_number.map(U256::from),
+            transaction_index,
+            from: signer,
+            to,
+            value: U256::from(*signed_tx.value()),
+            gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            gas: U256::from(signed_tx.gas_limit()),
+            input: signed_tx.input().clone(),
+            creates,
+            raw: signed_tx.envelope_encoded(),
+            public_key: None,
+            chain_id: chain_id.map(U64::from),
+            standard_v: U256::from(parity),
+            v: U256::from(v),
+            r: signature.r,
+            s: signature.s,
+            access_list,
+            transaction_type,
+        }
+    }
+}
+
+/// Returns the address of the contract created by the given sender with the given nonce.
+fn contract_address(sender: Address, nonce: u64) -> Address {
+    let mut out = Vec::new();
+    let header = reth_rlp::Header { list: true, payload_length: sender.length() + nonce.length() };
+    header.encode(&mut out);
+    sender.encode(&mut out);
+    nonce.encode(&mut out);
+    Address::from_slice(&keccak256(&out)[12..])
+}
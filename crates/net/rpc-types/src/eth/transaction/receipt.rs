@@ -1,4 +1,5 @@
-use reth_primitives::{rpc::Log, Address, Bloom, H256, U256, U64};
+use crate::Log;
+use reth_primitives::{Address, Bloom, H256, U256, U64};
 use serde::{Deserialize, Serialize};
 
 /// Transaction receipt
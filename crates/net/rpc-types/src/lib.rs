@@ -10,5 +10,7 @@
 //! Provides all relevant types for the various RPC endpoints, grouped by namespace.
 
 mod eth;
+mod txpool;
 
 pub use eth::*;
+pub use txpool::*;
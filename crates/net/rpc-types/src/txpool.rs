@@ -0,0 +1,114 @@
+//! Types for the `txpool` namespace.
+
+use crate::Transaction;
+use reth_primitives::{Address, U256, U64};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// Transactions grouped by their nonce.
+pub type TxpoolNonceMap<T> = BTreeMap<u64, T>;
+
+/// Transactions grouped by their sender and nonce.
+pub type TxpoolSenderMap<T> = BTreeMap<Address, TxpoolNonceMap<T>>;
+
+/// The number of pending and queued transactions, see `txpool_status`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolStatus {
+    /// Number of transactions that can be included in the next block.
+    pub pending: U64,
+    /// Number of transactions that can't be included in the next block yet.
+    pub queued: U64,
+}
+
+/// The pending and queued transactions of the pool, see `txpool_content`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolContent {
+    /// Transactions that can be included in the next block.
+    pub pending: TxpoolSenderMap<Transaction>,
+    /// Transactions that can't be included in the next block yet.
+    pub queued: TxpoolSenderMap<Transaction>,
+}
+
+/// The pending and queued transactions of a single sender, see `txpool_contentFrom`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolContentFrom {
+    /// Transactions that can be included in the next block.
+    pub pending: TxpoolNonceMap<Transaction>,
+    /// Transactions that can't be included in the next block yet.
+    pub queued: TxpoolNonceMap<Transaction>,
+}
+
+/// Summaries of the pending and queued transactions of the pool, see `txpool_inspect`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolInspect {
+    /// Transactions that can be included in the next block.
+    pub pending: TxpoolSenderMap<TxpoolInspectSummary>,
+    /// Transactions that can't be included in the next block yet.
+    pub queued: TxpoolSenderMap<TxpoolInspectSummary>,
+}
+
+/// A summary of a transaction.
+///
+/// This is serialized like geth does it:
+/// `<to>: <value> wei + <gas limit> gas × <gas price> wei`, where `<to>` is `contract creation`
+/// for transactions that create a contract.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxpoolInspectSummary {
+    /// The recipient, `None` for contract creations.
+    pub to: Option<Address>,
+    /// The transferred value.
+    pub value: U256,
+    /// The gas limit.
+    pub gas: u64,
+    /// The gas price or, for EIP-1559 transactions, the max fee per gas.
+    pub gas_price: u128,
+}
+
+impl fmt::Display for TxpoolInspectSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to {
+            Some(to) => write!(f, "{to:?}: ")?,
+            None => write!(f, "contract creation: ")?,
+        }
+        write!(f, "{} wei + {} gas × {} wei", self.value, self.gas, self.gas_price)
+    }
+}
+
+/// Error returned when parsing a malformed [TxpoolInspectSummary].
+#[derive(Debug, thiserror::Error)]
+#[error("invalid transaction summary: {0}")]
+pub struct ParseTxpoolInspectSummaryError(String);
+
+impl FromStr for TxpoolInspectSummary {
+    type Err = ParseTxpoolInspectSummaryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseTxpoolInspectSummaryError(s.to_string());
+        let (to, rest) = s.split_once(": ").ok_or_else(err)?;
+        let to = match to {
+            "contract creation" => None,
+            to => Some(to.parse().map_err(|_| err())?),
+        };
+        let (value, rest) = rest.split_once(" wei + ").ok_or_else(err)?;
+        let (gas, rest) = rest.split_once(" gas × ").ok_or_else(err)?;
+        let gas_price = rest.strip_suffix(" wei").ok_or_else(err)?;
+        Ok(Self {
+            to,
+            value: value.parse().map_err(|_| err())?,
+            gas: gas.parse().map_err(|_| err())?,
+            gas_price: gas_price.parse().map_err(|_| err())?,
+        })
+    }
+}
+
+impl Serialize for TxpoolInspectSummary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TxpoolInspectSummary {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
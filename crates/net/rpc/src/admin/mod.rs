@@ -3,11 +3,27 @@ use reth_network::{peers::PeerKind, NetworkHandle};
 use reth_primitives::NodeRecord;
 use reth_rpc_api::AdminApiServer;
 
-struct AdminApi {
+/// `admin` API implementation.
+///
+/// This type provides the functionality for handling `admin` related requests.
+pub struct AdminApi {
     /// An interface to interact with the network
     network: NetworkHandle,
 }
 
+impl AdminApi {
+    /// Creates a new instance that manages the peers of the given network.
+    pub fn new(network: NetworkHandle) -> Self {
+        Self { network }
+    }
+}
+
+impl std::fmt::Debug for AdminApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminApi").finish_non_exhaustive()
+    }
+}
+
 impl AdminApiServer for AdminApi {
     fn add_peer(&self, record: NodeRecord) -> RpcResult<bool> {
         self.network.add_peer(record.id, record.tcp_addr());
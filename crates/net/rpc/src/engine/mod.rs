@@ -1,6 +1,9 @@
 use crate::result::rpc_err;
 use async_trait::async_trait;
-use jsonrpsee::core::{Error, RpcResult as Result};
+use jsonrpsee::{
+    core::{Error, RpcResult as Result},
+    types::error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+};
 use reth_consensus::engine::{EngineApiError, EngineApiResult, EngineMessage};
 use reth_interfaces::consensus::ForkchoiceState;
 use reth_primitives::H64;
@@ -26,35 +29,58 @@ impl std::fmt::Debug for EngineApi {
 }
 
 impl EngineApi {
+    /// Creates a new instance that delegates all requests to the consensus engine listening on
+    /// the given channel.
+    pub fn new(engine_tx: UnboundedSender<EngineMessage>) -> Self {
+        Self { engine_tx }
+    }
+
     async fn delegate_request<T>(
         &self,
         msg: EngineMessage,
         rx: Receiver<EngineApiResult<T>>,
     ) -> Result<T> {
         let _ = self.engine_tx.send(msg);
-        rx.await.map_err(|err| Error::Custom(err.to_string()))?.map_err(|err| {
-            let code = match err {
-                EngineApiError::PayloadUnknown => -38001,
-                // Any other server error
-                _ => jsonrpsee::types::error::INTERNAL_ERROR_CODE,
-            };
-            rpc_err(code, err.to_string(), None)
-        })
+        rx.await.map_err(|err| Error::Custom(err.to_string()))?.map_err(engine_api_err)
     }
 }
 
+/// Converts the [EngineApiError] to the rpc error with the matching error code.
+fn engine_api_err(err: EngineApiError) -> Error {
+    let code = match err {
+        EngineApiError::PayloadUnknown => -38001,
+        EngineApiError::PayloadAttributesTimestamp { .. } => -38003,
+        EngineApiError::HasWithdrawalsPreShanghai { .. } |
+        EngineApiError::NoWithdrawalsPostShanghai { .. } |
+        EngineApiError::WithdrawalsNotSupportedInV1 => INVALID_PARAMS_CODE,
+        // Any other server error
+        _ => INTERNAL_ERROR_CODE,
+    };
+    rpc_err(code, err.to_string(), None)
+}
+
+/// Rejects the withdrawals of a V1 request, they are only supported since V2.
+fn ensure_no_withdrawals_v1<T>(withdrawals: Option<&Vec<T>>) -> Result<()> {
+    if withdrawals.is_some() {
+        return Err(engine_api_err(EngineApiError::WithdrawalsNotSupportedInV1))
+    }
+    Ok(())
+}
+
 #[async_trait]
 impl EngineApiServer for EngineApi {
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_newpayloadv1>
     /// Caution: This should not accept the `withdrawals` field
     async fn new_payload_v1(&self, payload: ExecutionPayload) -> Result<PayloadStatus> {
+        ensure_no_withdrawals_v1(payload.withdrawals.as_ref())?;
         let (tx, rx) = oneshot::channel();
         self.delegate_request(EngineMessage::NewPayload(payload, tx), rx).await
     }
 
-    /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_newpayloadv1>
-    async fn new_payload_v2(&self, _payload: ExecutionPayload) -> Result<PayloadStatus> {
-        todo!()
+    /// See also <https://github.com/ethereum/execution-apis/blob/6709c2a795b707202e93c4f2867fa0bf2640a84f/src/engine/shanghai.md#engine_newpayloadv2>
+    async fn new_payload_v2(&self, payload: ExecutionPayload) -> Result<PayloadStatus> {
+        let (tx, rx) = oneshot::channel();
+        self.delegate_request(EngineMessage::NewPayload(payload, tx), rx).await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_forkchoiceUpdatedV1>
@@ -65,6 +91,9 @@ impl EngineApiServer for EngineApi {
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<PayloadAttributes>,
     ) -> Result<ForkchoiceUpdated> {
+        ensure_no_withdrawals_v1(
+            payload_attributes.as_ref().and_then(|attributes| attributes.withdrawals.as_ref()),
+        )?;
         let (tx, rx) = oneshot::channel();
         self.delegate_request(
             EngineMessage::ForkchoiceUpdated(fork_choice_state, payload_attributes, tx),
@@ -73,13 +102,18 @@ impl EngineApiServer for EngineApi {
         .await
     }
 
-    /// See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/specification.md#engine_forkchoiceupdatedv2>
+    /// See also <https://github.com/ethereum/execution-apis/blob/6709c2a795b707202e93c4f2867fa0bf2640a84f/src/engine/shanghai.md#engine_forkchoiceupdatedv2>
     async fn fork_choice_updated_v2(
         &self,
-        _fork_choice_state: ForkchoiceState,
-        _payload_attributes: Option<PayloadAttributes>,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
     ) -> Result<ForkchoiceUpdated> {
-        todo!()
+        let (tx, rx) = oneshot::channel();
+        self.delegate_request(
+            EngineMessage::ForkchoiceUpdated(fork_choice_state, payload_attributes, tx),
+            rx,
+        )
+        .await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_getPayloadV1>
@@ -87,12 +121,15 @@ impl EngineApiServer for EngineApi {
     /// Caution: This should not return the `withdrawals` field
     async fn get_payload_v1(&self, payload_id: H64) -> Result<ExecutionPayload> {
         let (tx, rx) = oneshot::channel();
-        self.delegate_request(EngineMessage::GetPayload(payload_id, tx), rx).await
+        let payload = self.delegate_request(EngineMessage::GetPayload(payload_id, tx), rx).await?;
+        ensure_no_withdrawals_v1(payload.withdrawals.as_ref())?;
+        Ok(payload)
     }
 
-    /// See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/specification.md#engine_getpayloadv2>
-    async fn get_payload_v2(&self, _payload_id: H64) -> Result<ExecutionPayload> {
-        todo!()
+    /// See also <https://github.com/ethereum/execution-apis/blob/6709c2a795b707202e93c4f2867fa0bf2640a84f/src/engine/shanghai.md#engine_getpayloadv2>
+    async fn get_payload_v2(&self, payload_id: H64) -> Result<ExecutionPayload> {
+        let (tx, rx) = oneshot::channel();
+        self.delegate_request(EngineMessage::GetPayload(payload_id, tx), rx).await
     }
 
     /// See also <https://github.com/ethereum/execution-apis/blob/8db51dcd2f4bdfbd9ad6e4a7560aac97010ad063/src/engine/specification.md#engine_exchangeTransitionConfigurationV1>
@@ -0,0 +1,54 @@
+//! Contains RPC handler implementations specific to blocks.
+
+use crate::{
+    eth::api::EthApi,
+    result::{internal_rpc_err, ToRpcResult},
+};
+use jsonrpsee::core::RpcResult as Result;
+use reth_primitives::{rpc::BlockId, Block};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, TransactionsProvider,
+};
+use reth_rpc_types::{Index, RichBlock};
+use reth_transaction_pool::TransactionPool;
+
+impl<Pool, Client> EthApi<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateProviderFactory
+        + 'static,
+{
+    /// Returns the block with the given id, or `None` if the block is not found.
+    pub(crate) fn block(&self, id: BlockId) -> Result<Option<Block>> {
+        self.client().block(id).with_message("failed to read block")
+    }
+
+    /// Returns the rpc representation of the block with the given id, including the full
+    /// transaction objects if `full` is true.
+    pub(crate) fn rich_block(&self, id: BlockId, full: bool) -> Result<Option<RichBlock>> {
+        let Some(block) = self.block(id)? else { return Ok(None) };
+        let total_difficulty = self
+            .client()
+            .header_td(&block.header.hash_slow())
+            .with_message("failed to read total difficulty")?
+            .unwrap_or_default();
+        let block = reth_rpc_types::Block::from_block(block, total_difficulty, full)
+            .map_err(|err| internal_rpc_err(err.to_string()))?;
+        Ok(Some(block.into()))
+    }
+
+    /// Returns the rpc representation of the uncle at the given index of the block with the
+    /// given id.
+    pub(crate) fn uncle_by_index(&self, id: BlockId, index: Index) -> Result<Option<RichBlock>> {
+        let Some(block) = self.block(id)? else { return Ok(None) };
+        Ok(block
+            .ommers
+            .into_iter()
+            .nth(index.into())
+            .map(|uncle| reth_rpc_types::Block::uncle_block_from_header(uncle).into()))
+    }
+}
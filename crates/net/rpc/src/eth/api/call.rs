@@ -0,0 +1,240 @@
+//! Contains RPC handler implementations specific to executing calls.
+
+use crate::{
+    eth::{
+        api::EthApi,
+        revm_utils::{call_output, fill_tx_env_with_call, to_rpc_access_list, AccessListInspector},
+    },
+    result::{internal_rpc_err, ToRpcResult},
+};
+use jsonrpsee::core::RpcResult as Result;
+use reth_executor::revm_wrap::{fill_block_env, State, SubState};
+use reth_primitives::{
+    rpc::{
+        self,
+        transaction::eip2930::{AccessList, AccessListWithGasUsed},
+        BlockId,
+    },
+    Address, Bytes, Header, H256, U256,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProvider, StateProviderFactory,
+    TransactionsProvider,
+};
+use reth_rpc_types::CallRequest;
+use reth_transaction_pool::TransactionPool;
+use revm::{db::CacheDB, return_ok, Env, Return, SpecId, TransactTo, EVM};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The minimum gas required by any transaction.
+const MIN_TRANSACTION_GAS: u64 = 21_000;
+
+impl<Pool, Client> EthApi<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateProviderFactory
+        + 'static,
+{
+    /// Creates the [EVM] that executes the request on top of the given state and block.
+    fn call_evm<'a>(
+        &self,
+        state: &'a dyn StateProvider,
+        header: &Header,
+        request: CallRequest,
+    ) -> EVM<SubState<&'a dyn StateProvider>> {
+        let config = self.executor_config();
+        let spec_id = config.spec_upgrades.revm_spec(header.number);
+
+        let mut env = Env::default();
+        env.cfg.chain_id = config.chain_id;
+        env.cfg.spec_id = spec_id;
+        fill_block_env(&mut env.block, header, spec_id >= SpecId::MERGE);
+        fill_tx_env_with_call(&mut env.tx, &mut env.block, request);
+
+        let mut evm = EVM::new();
+        evm.env = env;
+        evm.database(CacheDB::new(State::new(state)));
+        evm
+    }
+
+    /// Executes the request on top of the state of the given block and returns its output.
+    pub(crate) fn call_at(&self, request: CallRequest, at: BlockId) -> Result<Bytes> {
+        let (state, header) = self.state_at_block_id(at)?;
+        let mut evm = self.call_evm(&*state, &header, request);
+        let (result, _) = evm.transact();
+        call_output(result)
+    }
+
+    /// Estimates the gas needed by the request on top of the state of the given block.
+    ///
+    /// The estimate is the lowest gas limit the request succeeds with, found by binary search
+    /// between the gas used by the request and the requested gas limit, which defaults to the
+    /// block gas limit. If the request has a gas price, the gas limit is also capped by the gas
+    /// the caller can pay for.
+    pub(crate) fn estimate_gas_at(&self, request: CallRequest, at: BlockId) -> Result<U256> {
+        let (state, header) = self.state_at_block_id(at)?;
+        let mut evm = self.call_evm(&*state, &header, request);
+
+        let gas_price = evm.env.tx.gas_price;
+        if gas_price > U256::ZERO {
+            let balance = state
+                .basic_account(evm.env.tx.caller)
+                .with_message("failed to read account")?
+                .map(|account| account.balance)
+                .unwrap_or_default();
+            let allowance = balance.saturating_sub(evm.env.tx.value) / gas_price;
+            if allowance < U256::from(evm.env.tx.gas_limit) {
+                evm.env.tx.gas_limit = allowance.to::<u64>();
+            }
+        }
+
+        // The request must succeed with the highest gas limit
+        let mut highest = evm.env.tx.gas_limit;
+        let (result, _) = evm.transact();
+        let gas_used = result.gas_used;
+        call_output(result)?;
+
+        // Transactions that do not call any code always use the same amount of gas
+        if let TransactTo::Call(to) = evm.env.tx.transact_to {
+            let account = state.basic_account(to).with_message("failed to read account")?;
+            if gas_used == MIN_TRANSACTION_GAS &&
+                account.map_or(true, |account| account.bytecode_hash.is_none())
+            {
+                return Ok(U256::from(gas_used))
+            }
+        }
+
+        // Refunds are paid back after execution, so the needed gas limit is at least the used gas
+        let mut lowest = gas_used.saturating_sub(1).max(MIN_TRANSACTION_GAS - 1);
+        while lowest + 1 < highest {
+            let mid = lowest + (highest - lowest) / 2;
+            evm.env.tx.gas_limit = mid;
+            let (result, _) = evm.transact();
+            match result.exit_reason {
+                return_ok!() => highest = mid,
+                Return::FatalExternalError => return Err(internal_rpc_err("failed to read state")),
+                _ => lowest = mid,
+            }
+        }
+
+        Ok(U256::from(highest))
+    }
+
+    /// Creates an access list with the accounts and storage slots accessed by the request on top
+    /// of the state of the given block, together with the gas used by the request when it is
+    /// sent with that access list.
+    pub(crate) fn create_access_list_at(
+        &self,
+        request: CallRequest,
+        at: BlockId,
+    ) -> Result<AccessListWithGasUsed> {
+        let (state, header) = self.state_at_block_id(at)?;
+        let mut evm = self.call_evm(&*state, &header, request);
+
+        let from = evm.env.tx.caller;
+        let to = match evm.env.tx.transact_to {
+            TransactTo::Call(to) => Some(to),
+            TransactTo::Create(_) => None,
+        };
+        let mut access_list = BTreeMap::<Address, BTreeSet<H256>>::new();
+        for (address, slots) in &evm.env.tx.access_list {
+            let slots = slots.iter().map(|slot| H256::from(slot.to_be_bytes::<32>()));
+            access_list.entry(*address).or_default().extend(slots);
+        }
+
+        let (result, _) = evm.inspect(AccessListInspector::new(&mut access_list, from, to));
+        call_output(result)?;
+
+        // Execute the request again with the collected access list to get the gas used
+        let access_list = to_rpc_access_list(access_list);
+        evm.env.tx.access_list = access_list
+            .iter()
+            .map(|item| {
+                let slots = item.storage_keys.iter().map(|key| U256::from_be_bytes(key.0));
+                (Address::from(item.address.0), slots.collect())
+            })
+            .collect();
+        let (result, _) = evm.transact();
+        let gas_used = result.gas_used;
+        call_output(result)?;
+
+        Ok(AccessListWithGasUsed {
+            access_list: AccessList(access_list),
+            gas_used: rpc::U256::from(gas_used),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{rpc::BlockNumber, Block};
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_transaction_pool::test_utils::testing_pool;
+    use std::sync::Arc;
+
+    /// Code that fails unless at least 100k gas is left when it starts: `GAS PUSH3 100000 GT
+    /// PUSH1 10 JUMPI STOP JUMPDEST INVALID`
+    const GAS_THRESHOLD_CODE: [u8; 12] =
+        [0x5a, 0x62, 0x01, 0x86, 0xa0, 0x11, 0x60, 0x0a, 0x57, 0x00, 0x5b, 0xfe];
+
+    /// The gas limit needed by a call to [GAS_THRESHOLD_CODE]: the intrinsic gas, the gas of the
+    /// `GAS` opcode and the 100k gas that must be left.
+    const GAS_THRESHOLD_LIMIT: u64 = 21_000 + 2 + 100_000;
+
+    fn eth_api(client: MockEthProvider) -> EthApi<impl TransactionPool, MockEthProvider> {
+        let header = Header { gas_limit: 30_000_000, ..Default::default() };
+        client.add_block(H256::random(), Block { header, ..Default::default() });
+        EthApi::new(Arc::new(client), testing_pool())
+    }
+
+    #[test]
+    fn estimate_gas_binary_search() {
+        let client = MockEthProvider::default();
+        let contract = Address::random();
+        client.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(GAS_THRESHOLD_CODE.to_vec().into()),
+        );
+        let api = eth_api(client);
+
+        let request = CallRequest { to: Some(contract), ..Default::default() };
+        let estimate = api.estimate_gas_at(request, BlockId::Number(BlockNumber::Latest));
+        assert_eq!(estimate.unwrap(), U256::from(GAS_THRESHOLD_LIMIT));
+    }
+
+    #[test]
+    fn estimate_gas_capped_by_balance() {
+        let client = MockEthProvider::default();
+        let (caller, contract) = (Address::random(), Address::random());
+        client.add_account(caller, ExtendedAccount::new(0, U256::from(200_000)));
+        client.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO).with_bytecode(GAS_THRESHOLD_CODE.to_vec().into()),
+        );
+        let api = eth_api(client);
+        let at = BlockId::Number(BlockNumber::Latest);
+
+        // The caller can pay for 200k gas, which is less than the block gas limit
+        let request = CallRequest {
+            from: Some(caller),
+            to: Some(contract),
+            gas_price: Some(U256::from(1)),
+            ..Default::default()
+        };
+        assert_eq!(api.estimate_gas_at(request, at).unwrap(), U256::from(GAS_THRESHOLD_LIMIT));
+
+        // The caller can not pay for the gas needed by the call
+        let request = CallRequest {
+            from: Some(caller),
+            to: Some(contract),
+            gas_price: Some(U256::from(2)),
+            ..Default::default()
+        };
+        assert!(api.estimate_gas_at(request, at).is_err());
+    }
+}
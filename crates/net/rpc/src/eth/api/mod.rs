@@ -1,13 +1,20 @@
 //! Provides everything related to `eth_` namespace
 
+use reth_executor::Config as ExecutorConfig;
 use reth_interfaces::Result;
 use reth_primitives::U64;
-use reth_provider::{BlockProvider, ChainInfo, StateProviderFactory};
-use reth_rpc_types::Transaction;
+use reth_provider::{
+    BlockProvider, ChainInfo, HeaderProvider, ReceiptProvider, StateProviderFactory,
+    TransactionsProvider,
+};
 use reth_transaction_pool::TransactionPool;
 use std::sync::Arc;
 
+mod block;
+mod call;
 mod server;
+mod state;
+mod transactions;
 
 /// `Eth` API trait.
 ///
@@ -40,11 +47,23 @@ pub struct EthApi<Pool, Client> {
 impl<Pool, Client> EthApi<Pool, Client>
 where
     Pool: TransactionPool + 'static,
-    Client: BlockProvider + StateProviderFactory + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateProviderFactory
+        + 'static,
 {
     /// Creates a new, shareable instance.
+    ///
+    /// Calls are executed with the Ethereum mainnet hardforks.
     pub fn new(client: Arc<Client>, pool: Pool) -> Self {
-        let inner = EthApiInner { client, pool };
+        Self::with_executor_config(client, pool, ExecutorConfig::new_ethereum())
+    }
+
+    /// Creates a new, shareable instance that executes calls with the given config.
+    pub fn with_executor_config(client: Arc<Client>, pool: Pool, config: ExecutorConfig) -> Self {
+        let inner = EthApiInner { client, pool, config };
         Self { inner: Arc::new(inner) }
     }
 
@@ -52,12 +71,22 @@ where
     fn client(&self) -> &Arc<Client> {
         &self.inner.client
     }
+
+    /// Returns the config used to execute calls.
+    fn executor_config(&self) -> &ExecutorConfig {
+        &self.inner.config
+    }
 }
 
 impl<Pool, Client> EthApiSpec for EthApi<Pool, Client>
 where
-    Pool: TransactionPool<Transaction = Transaction> + Clone + 'static,
-    Client: BlockProvider + StateProviderFactory + 'static,
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateProviderFactory
+        + 'static,
 {
     /// Returns the current ethereum protocol version.
     ///
@@ -66,9 +95,9 @@ where
         1u64.into()
     }
 
-    /// Returns the chain id
+    /// Returns the chain id of the executor config.
     fn chain_id(&self) -> U64 {
-        todo!()
+        self.executor_config().chain_id.to::<u64>().into()
     }
 
     /// Returns the current info for the chain
@@ -84,5 +113,7 @@ struct EthApiInner<Pool, Client> {
     pool: Pool,
     /// The client that can interact with the chain.
     client: Arc<Client>,
+    /// The chain id and hardforks used to execute calls.
+    config: ExecutorConfig,
     // TODO needs network access to handle things like `eth_syncing`
 }
@@ -4,10 +4,12 @@
 use crate::{eth::api::EthApi, result::ToRpcResult};
 use jsonrpsee::core::RpcResult as Result;
 use reth_primitives::{
-    rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId},
-    Address, BlockNumber, Bytes, H256, H64, U256, U64,
+    rpc::{self, transaction::eip2930::AccessListWithGasUsed, BlockId, BlockNumber},
+    Address, Bytes, H256, H64, U256, U64,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, TransactionsProvider,
 };
-use reth_provider::{BlockProvider, StateProviderFactory};
 use reth_rpc_api::EthApiServer;
 use reth_rpc_types::{
     CallRequest, EIP1186AccountProofResponse, FeeHistory, Index, RichBlock, SyncStatus,
@@ -23,7 +25,12 @@ impl<Pool, Client> EthApiServer for EthApi<Pool, Client>
 where
     Self: EthApiSpec,
     Pool: TransactionPool + 'static,
-    Client: BlockProvider + StateProviderFactory + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateProviderFactory
+        + 'static,
 {
     fn protocol_version(&self) -> Result<U64> {
         Ok(EthApiSpec::protocol_version(self))
@@ -51,123 +58,133 @@ where
         Ok(Some(EthApiSpec::chain_id(self)))
     }
 
-    async fn block_by_hash(&self, _hash: H256, _full: bool) -> Result<Option<RichBlock>> {
-        todo!()
+    async fn block_by_hash(&self, hash: H256, full: bool) -> Result<Option<RichBlock>> {
+        EthApi::rich_block(self, block_id(hash), full)
     }
 
-    async fn block_by_number(
-        &self,
-        _number: BlockNumber,
-        _full: bool,
-    ) -> Result<Option<RichBlock>> {
-        todo!()
+    async fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<RichBlock>> {
+        EthApi::rich_block(self, BlockId::Number(number), full)
     }
 
-    async fn block_transaction_count_by_hash(&self, _hash: H256) -> Result<Option<U256>> {
-        todo!()
+    async fn block_transaction_count_by_hash(&self, hash: H256) -> Result<Option<U256>> {
+        Ok(EthApi::block(self, block_id(hash))?.map(|block| U256::from(block.body.len())))
     }
 
-    async fn block_transaction_count_by_number(
-        &self,
-        _number: BlockNumber,
-    ) -> Result<Option<U256>> {
-        todo!()
+    async fn block_transaction_count_by_number(&self, number: BlockNumber) -> Result<Option<U256>> {
+        Ok(EthApi::block(self, BlockId::Number(number))?.map(|block| U256::from(block.body.len())))
     }
 
-    async fn block_uncles_count_by_hash(&self, _hash: H256) -> Result<U256> {
-        todo!()
+    async fn block_uncles_count_by_hash(&self, hash: H256) -> Result<U256> {
+        Ok(U256::from(
+            EthApi::block(self, block_id(hash))?
+                .map(|block| block.ommers.len())
+                .unwrap_or_default(),
+        ))
     }
 
-    async fn block_uncles_count_by_number(&self, _number: BlockNumber) -> Result<U256> {
-        todo!()
+    async fn block_uncles_count_by_number(&self, number: BlockNumber) -> Result<U256> {
+        Ok(U256::from(
+            EthApi::block(self, BlockId::Number(number))?
+                .map(|block| block.ommers.len())
+                .unwrap_or_default(),
+        ))
     }
 
     async fn uncle_by_block_hash_and_index(
         &self,
-        _hash: H256,
-        _index: Index,
+        hash: H256,
+        index: Index,
     ) -> Result<Option<RichBlock>> {
-        todo!()
+        EthApi::uncle_by_index(self, block_id(hash), index)
     }
 
     async fn uncle_by_block_number_and_index(
         &self,
-        _number: BlockNumber,
-        _index: Index,
+        number: BlockNumber,
+        index: Index,
     ) -> Result<Option<RichBlock>> {
-        todo!()
+        EthApi::uncle_by_index(self, BlockId::Number(number), index)
     }
 
-    async fn transaction_by_hash(
-        &self,
-        _hash: H256,
-    ) -> Result<Option<reth_rpc_types::Transaction>> {
-        todo!()
+    async fn transaction_by_hash(&self, hash: H256) -> Result<Option<reth_rpc_types::Transaction>> {
+        EthApi::transaction_by_hash(self, hash)
     }
 
     async fn transaction_by_block_hash_and_index(
         &self,
-        _hash: H256,
-        _index: Index,
+        hash: H256,
+        index: Index,
     ) -> Result<Option<reth_rpc_types::Transaction>> {
-        todo!()
+        EthApi::transaction_by_block_and_index(self, block_id(hash), index)
     }
 
     async fn transaction_by_block_number_and_index(
         &self,
-        _number: BlockNumber,
-        _index: Index,
+        number: BlockNumber,
+        index: Index,
     ) -> Result<Option<reth_rpc_types::Transaction>> {
-        todo!()
+        EthApi::transaction_by_block_and_index(self, BlockId::Number(number), index)
     }
 
-    async fn transaction_receipt(&self, _hash: H256) -> Result<Option<TransactionReceipt>> {
-        todo!()
+    async fn transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>> {
+        EthApi::transaction_receipt(self, hash)
     }
 
-    async fn balance(&self, _address: Address, _block_number: Option<BlockId>) -> Result<U256> {
-        todo!()
+    async fn balance(&self, address: Address, block_number: Option<BlockId>) -> Result<U256> {
+        let at = block_number.unwrap_or(BlockId::Number(BlockNumber::Latest));
+        EthApi::balance_at(self, address, at)
     }
 
     async fn storage_at(
         &self,
-        _address: Address,
-        _index: U256,
-        _block_number: Option<BlockId>,
+        address: Address,
+        index: U256,
+        block_number: Option<BlockId>,
     ) -> Result<H256> {
-        todo!()
+        let at = block_number.unwrap_or(BlockId::Number(BlockNumber::Latest));
+        EthApi::storage_at(self, address, index, at)
     }
 
     async fn transaction_count(
         &self,
-        _address: Address,
-        _block_number: Option<BlockId>,
+        address: Address,
+        block_number: Option<BlockId>,
     ) -> Result<U256> {
-        todo!()
+        let at = block_number.unwrap_or(BlockId::Number(BlockNumber::Latest));
+        EthApi::transaction_count_at(self, address, at)
     }
 
-    async fn get_code(&self, _address: Address, _block_number: Option<BlockId>) -> Result<Bytes> {
-        todo!()
+    async fn get_code(&self, address: Address, block_number: Option<BlockId>) -> Result<Bytes> {
+        let at = block_number.unwrap_or(BlockId::Number(BlockNumber::Latest));
+        EthApi::code_at(self, address, at)
     }
 
-    async fn call(&self, _request: CallRequest, _block_number: Option<BlockId>) -> Result<Bytes> {
-        todo!()
+    async fn call(&self, request: CallRequest, block_number: Option<BlockId>) -> Result<Bytes> {
+        EthApi::call_at(self, request, block_number.unwrap_or(BlockId::Number(BlockNumber::Latest)))
     }
 
     async fn create_access_list(
         &self,
-        _request: CallRequest,
-        _block_number: Option<BlockId>,
+        request: CallRequest,
+        block_number: Option<BlockId>,
     ) -> Result<AccessListWithGasUsed> {
-        todo!()
+        EthApi::create_access_list_at(
+            self,
+            request,
+            block_number.unwrap_or(BlockId::Number(BlockNumber::Latest)),
+        )
     }
 
     async fn estimate_gas(
         &self,
-        _request: CallRequest,
-        _block_number: Option<BlockId>,
+        request: CallRequest,
+        block_number: Option<BlockId>,
     ) -> Result<U256> {
-        todo!()
+        EthApi::estimate_gas_at(
+            self,
+            request,
+            block_number.unwrap_or(BlockId::Number(BlockNumber::Latest)),
+        )
     }
 
     async fn gas_price(&self) -> Result<U256> {
@@ -236,3 +253,8 @@ where
         todo!()
     }
 }
+
+/// Converts the block hash into a [BlockId].
+fn block_id(hash: H256) -> BlockId {
+    BlockId::Hash(rpc::H256(hash.0))
+}
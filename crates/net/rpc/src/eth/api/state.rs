@@ -0,0 +1,146 @@
+//! Contains RPC handler implementations specific to state.
+
+use crate::{
+    eth::api::EthApi,
+    result::{internal_rpc_err, invalid_params_rpc_err, rpc_err, ToRpcResult},
+};
+use jsonrpsee::core::RpcResult as Result;
+use reth_interfaces::provider::Error as ProviderError;
+use reth_primitives::{
+    rpc::{BlockId, BlockNumber},
+    Address, Bytes, Header, H256, U256,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProvider, StateProviderFactory,
+    TransactionsProvider,
+};
+use reth_transaction_pool::TransactionPool;
+
+/// The error code geth returns if the state of a block is not available.
+const STATE_UNAVAILABLE_CODE: i32 = -32000;
+
+impl<Pool, Client> EthApi<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateProviderFactory
+        + 'static,
+{
+    /// Returns the state after the block with the given id together with the header of the block.
+    ///
+    /// The latest block is the latest executed block. The pending block is not tracked yet, so it
+    /// resolves to the latest block. Blocks given by hash must be canonical.
+    pub(crate) fn state_at_block_id(
+        &self,
+        at: BlockId,
+    ) -> Result<(Box<dyn StateProvider + '_>, Header)> {
+        let client = self.client();
+        let at = match at {
+            BlockId::Number(BlockNumber::Pending) => BlockId::Number(BlockNumber::Latest),
+            at => at,
+        };
+
+        let best_number =
+            client.chain_info().with_message("failed to read chain info")?.best_number;
+        let number = client
+            .block_number_for_id(at)
+            .with_message("failed to read block number")?
+            .ok_or_else(|| match at {
+                BlockId::Number(BlockNumber::Finalized) => {
+                    invalid_params_rpc_err("finalized block not known yet")
+                }
+                BlockId::Number(BlockNumber::Safe) => {
+                    invalid_params_rpc_err("safe block not known yet")
+                }
+                at => invalid_params_rpc_err(format!("block not found: {at:?}")),
+            })?;
+        if number > best_number {
+            return Err(rpc_err(
+                STATE_UNAVAILABLE_CODE,
+                format!(
+                    "state for block {number} is not available yet (synced up to {best_number})"
+                ),
+                None,
+            ))
+        }
+
+        let hash = client
+            .block_hash(U256::from(number))
+            .with_message("failed to read block hash")?
+            .ok_or_else(|| invalid_params_rpc_err(format!("block not found: {number}")))?;
+        if let BlockId::Hash(requested) = at {
+            if requested.0 != hash.0 {
+                return Err(invalid_params_rpc_err(format!("block {requested:?} is not canonical")))
+            }
+        }
+        let header = client
+            .header(&hash)
+            .with_message("failed to read header")?
+            .ok_or_else(|| invalid_params_rpc_err(format!("header not found: {hash:?}")))?;
+
+        let state: Box<dyn StateProvider + '_> = if number == best_number {
+            Box::new(client.latest().with_message("failed to open latest state")?)
+        } else {
+            Box::new(client.history_by_block_number(number).map_err(|err| match err {
+                reth_interfaces::Error::Provider(
+                    ProviderError::BlockTransition { .. } |
+                    ProviderError::HistoryIndexBehind { .. },
+                ) => state_unavailable(number),
+                err => internal_rpc_err(format!("failed to open historical state: {err}")),
+            })?)
+        };
+        Ok((state, header))
+    }
+
+    /// Returns the balance of the account after the block with the given id.
+    pub(crate) fn balance_at(&self, address: Address, at: BlockId) -> Result<U256> {
+        let (state, header) = self.state_at_block_id(at)?;
+        let account = read_state(state.basic_account(address), header.number)?;
+        Ok(account.map(|account| account.balance).unwrap_or_default())
+    }
+
+    /// Returns the value of the storage slot of the account after the block with the given id.
+    pub(crate) fn storage_at(&self, address: Address, index: U256, at: BlockId) -> Result<H256> {
+        let (state, header) = self.state_at_block_id(at)?;
+        let slot = H256::from(index.to_be_bytes::<32>());
+        let value = read_state(state.storage(address, slot), header.number)?;
+        Ok(H256::from(value.unwrap_or_default().to_be_bytes::<32>()))
+    }
+
+    /// Returns the nonce of the account after the block with the given id.
+    pub(crate) fn transaction_count_at(&self, address: Address, at: BlockId) -> Result<U256> {
+        let (state, header) = self.state_at_block_id(at)?;
+        let account = read_state(state.basic_account(address), header.number)?;
+        Ok(U256::from(account.map(|account| account.nonce).unwrap_or_default()))
+    }
+
+    /// Returns the code of the account after the block with the given id.
+    pub(crate) fn code_at(&self, address: Address, at: BlockId) -> Result<Bytes> {
+        let (state, header) = self.state_at_block_id(at)?;
+        let account = read_state(state.basic_account(address), header.number)?;
+        let Some(code_hash) = account.and_then(|account| account.bytecode_hash) else {
+            return Ok(Bytes::default())
+        };
+        let code = read_state(state.bytecode_by_hash(code_hash), header.number)?;
+        Ok(code.unwrap_or_default())
+    }
+}
+
+/// Converts the result of a state read, reporting missing changesets as unavailable state.
+fn read_state<T>(result: reth_interfaces::Result<T>, number: u64) -> Result<T> {
+    result.map_err(|err| match err {
+        reth_interfaces::Error::Provider(
+            ProviderError::AccountChangeset { .. } | ProviderError::StorageChangeset { .. },
+        ) => state_unavailable(number),
+        err => internal_rpc_err(format!("failed to read state: {err}")),
+    })
+}
+
+/// The error returned if the historical state of the block is not available, e.g. if it has been
+/// pruned.
+fn state_unavailable(number: u64) -> jsonrpsee::core::Error {
+    rpc_err(STATE_UNAVAILABLE_CODE, format!("state for block {number} is not available"), None)
+}
@@ -0,0 +1,138 @@
+//! Contains RPC handler implementations specific to transactions.
+
+use crate::{
+    eth::api::EthApi,
+    result::{internal_rpc_err, ToRpcResult},
+};
+use jsonrpsee::core::RpcResult as Result;
+use reth_primitives::{
+    rpc::BlockId, TransactionSigned, TransactionSignedEcRecovered, H256, U256, U64,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, ReceiptProvider, StateProviderFactory, TransactionsProvider,
+};
+use reth_rpc_types::{Index, Log, Transaction, TransactionReceipt};
+use reth_transaction_pool::TransactionPool;
+
+impl<Pool, Client> EthApi<Pool, Client>
+where
+    Pool: TransactionPool + 'static,
+    Client: BlockProvider
+        + HeaderProvider
+        + TransactionsProvider
+        + ReceiptProvider
+        + StateProviderFactory
+        + 'static,
+{
+    /// Returns the rpc representation of the mined transaction with the given hash.
+    pub(crate) fn transaction_by_hash(&self, hash: H256) -> Result<Option<Transaction>> {
+        let Some((tx, meta)) = self
+            .client()
+            .transaction_by_hash_with_meta(hash)
+            .with_message("failed to read transaction")?
+        else {
+            return Ok(None)
+        };
+        let tx = recover(tx)?;
+        Ok(Some(Transaction::from_recovered_with_block_context(
+            tx,
+            meta.block_hash,
+            meta.block_number,
+            meta.index as usize,
+        )))
+    }
+
+    /// Returns the rpc representation of the transaction at the given index of the block with
+    /// the given id.
+    pub(crate) fn transaction_by_block_and_index(
+        &self,
+        id: BlockId,
+        index: Index,
+    ) -> Result<Option<Transaction>> {
+        let Some(block) = self.block(id)? else { return Ok(None) };
+        let block_hash = block.header.hash_slow();
+        let index: usize = index.into();
+        let Some(tx) = block.body.get(index).cloned() else { return Ok(None) };
+        Ok(Some(Transaction::from_recovered_with_block_context(
+            recover(tx)?,
+            block_hash,
+            block.number,
+            index,
+        )))
+    }
+
+    /// Returns the receipt of the mined transaction with the given hash.
+    pub(crate) fn transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>> {
+        let Some((tx, meta)) = self
+            .client()
+            .transaction_by_hash_with_meta(hash)
+            .with_message("failed to read transaction")?
+        else {
+            return Ok(None)
+        };
+        let Some(receipts) = self
+            .client()
+            .receipts_by_block(meta.block_hash.into())
+            .with_message("failed to read receipts")?
+        else {
+            return Ok(None)
+        };
+
+        let index = meta.index as usize;
+        let Some(receipt) = receipts.get(index) else { return Ok(None) };
+        // gas used and log indices are derived from the receipts of the previous transactions
+        let previous_gas_used =
+            index.checked_sub(1).map(|prev| receipts[prev].cumulative_gas_used).unwrap_or_default();
+        let log_index_offset = receipts[..index].iter().map(|r| r.logs.len()).sum::<usize>();
+
+        let effective_gas_price = tx.effective_gas_price(meta.base_fee);
+        let tx_type = tx.tx_type();
+        let tx = Transaction::from_recovered_with_block_context(
+            recover(tx)?,
+            meta.block_hash,
+            meta.block_number,
+            index,
+        );
+
+        let logs = receipt
+            .logs
+            .iter()
+            .enumerate()
+            .map(|(tx_log_index, log)| Log {
+                address: log.address,
+                topics: log.topics.clone(),
+                data: log.data.clone().into(),
+                block_hash: Some(meta.block_hash),
+                block_number: Some(U256::from(meta.block_number)),
+                transaction_hash: Some(hash),
+                transaction_index: Some(U256::from(index)),
+                log_index: Some(U256::from(log_index_offset + tx_log_index)),
+                transaction_log_index: Some(U256::from(tx_log_index)),
+                removed: false,
+            })
+            .collect();
+
+        Ok(Some(TransactionReceipt {
+            transaction_hash: Some(hash),
+            transaction_index: Some(U256::from(index)),
+            block_hash: Some(meta.block_hash),
+            block_number: Some(U256::from(meta.block_number)),
+            from: tx.from,
+            to: tx.to,
+            cumulative_gas_used: U256::from(receipt.cumulative_gas_used),
+            gas_used: Some(U256::from(receipt.cumulative_gas_used - previous_gas_used)),
+            contract_address: tx.creates,
+            logs,
+            state_root: None,
+            logs_bloom: receipt.bloom,
+            status_code: Some(U64::from(receipt.success as u64)),
+            effective_gas_price: U256::from(effective_gas_price),
+            transaction_type: U256::from(tx_type as u8),
+        }))
+    }
+}
+
+/// Recovers the signer of the transaction.
+fn recover(tx: TransactionSigned) -> Result<TransactionSignedEcRecovered> {
+    tx.into_ecrecovered().ok_or_else(|| internal_rpc_err("failed to recover transaction signer"))
+}
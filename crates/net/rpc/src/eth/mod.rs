@@ -2,6 +2,7 @@
 
 mod api;
 mod pubsub;
+mod revm_utils;
 
 pub use api::{EthApi, EthApiSpec};
 pub use pubsub::EthPubSub;
@@ -0,0 +1,216 @@
+//! Helpers for executing calls with revm.
+
+use crate::result::{internal_rpc_err, rpc_err};
+use jsonrpsee::core::RpcResult as Result;
+use reth_primitives::{
+    rpc::{self, transaction::eip2930::AccessListItem},
+    Address, Bytes, H256, U256,
+};
+use reth_rpc_types::CallRequest;
+use revm::{
+    opcode, return_ok, return_revert, BlockEnv, Database, EVMData, ExecutionResult, Inspector,
+    Interpreter, Return, TransactOut, TransactTo, TxEnv,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The error code geth returns if a call is reverted.
+const EXECUTION_REVERTED_CODE: i32 = 3;
+
+/// The error code geth returns if a call fails.
+const EXECUTION_ERROR_CODE: i32 = -32000;
+
+/// Fill the transaction environment from the [CallRequest].
+///
+/// If the request does not specify a gas limit, the gas limit of the block is used. If it does
+/// not specify any gas price, the call is executed for free and the base fee of the block is
+/// ignored.
+pub(crate) fn fill_tx_env_with_call(
+    tx_env: &mut TxEnv,
+    block_env: &mut BlockEnv,
+    call: CallRequest,
+) {
+    let CallRequest {
+        from,
+        to,
+        gas_price,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        gas,
+        value,
+        data,
+        nonce,
+        access_list,
+        ..
+    } = call;
+
+    if gas_price.is_none() && max_fee_per_gas.is_none() {
+        block_env.basefee = U256::ZERO;
+    }
+
+    tx_env.caller = from.unwrap_or_default();
+    tx_env.gas_limit = gas
+        .and_then(|gas| u64::try_from(gas).ok())
+        .unwrap_or_else(|| u64::try_from(block_env.gas_limit).unwrap_or(u64::MAX));
+    tx_env.gas_price = max_fee_per_gas.or(gas_price).unwrap_or_default();
+    tx_env.gas_priority_fee = max_priority_fee_per_gas;
+    tx_env.transact_to = to.map(TransactTo::Call).unwrap_or_else(TransactTo::create);
+    tx_env.value = value.unwrap_or_default();
+    tx_env.data = data.map(|data| data.0).unwrap_or_default();
+    tx_env.chain_id = None;
+    tx_env.nonce = nonce.and_then(|nonce| u64::try_from(nonce).ok());
+    tx_env.access_list = access_list
+        .unwrap_or_default()
+        .into_iter()
+        .map(|item| {
+            (
+                Address::from(item.address.0),
+                item.storage_keys.iter().map(|key| U256::from_be_bytes(key.0)).collect(),
+            )
+        })
+        .collect();
+}
+
+/// Returns the output of the executed call, or an error if the call failed.
+pub(crate) fn call_output(result: ExecutionResult) -> Result<Bytes> {
+    let output = match result.out {
+        TransactOut::None => Bytes::default(),
+        TransactOut::Call(output) => output.into(),
+        TransactOut::Create(output, _) => output.into(),
+    };
+
+    match result.exit_reason {
+        return_ok!() => Ok(output),
+        return_revert!() => {
+            Err(rpc_err(EXECUTION_REVERTED_CODE, "execution reverted", Some(output.as_ref())))
+        }
+        Return::FatalExternalError => Err(internal_rpc_err("failed to read state")),
+        reason => Err(rpc_err(EXECUTION_ERROR_CODE, format!("execution failed: {reason:?}"), None)),
+    }
+}
+
+/// An [Inspector] that collects the accounts and storage slots accessed during execution.
+///
+/// The sender, the recipient and the precompiles are warm anyway and are not collected.
+#[derive(Debug)]
+pub(crate) struct AccessListInspector<'a> {
+    /// The accounts that are never added to the access list.
+    excluded: BTreeSet<Address>,
+    /// The collected accounts and storage slots.
+    access_list: &'a mut BTreeMap<Address, BTreeSet<H256>>,
+}
+
+impl<'a> AccessListInspector<'a> {
+    /// Creates a new inspector that adds the accessed accounts and slots to the given access
+    /// list.
+    pub(crate) fn new(
+        access_list: &'a mut BTreeMap<Address, BTreeSet<H256>>,
+        from: Address,
+        to: Option<Address>,
+    ) -> Self {
+        // The precompiles of all hardforks live at the addresses 0x01..=0x09
+        let precompiles = (1..=9u64).map(Address::from_low_u64_be);
+        let excluded = precompiles.chain(std::iter::once(from)).chain(to).collect();
+        Self { excluded, access_list }
+    }
+
+    fn add_account(&mut self, address: Address) {
+        if !self.excluded.contains(&address) {
+            self.access_list.entry(address).or_default();
+        }
+    }
+}
+
+impl<'a, DB: Database> Inspector<DB> for AccessListInspector<'a> {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> Return {
+        match interp.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(slot) = interp.stack().peek(0) {
+                    let address = interp.contract.address;
+                    let slot = H256::from(slot.to_be_bytes::<32>());
+                    self.access_list.entry(address).or_default().insert(slot);
+                }
+            }
+            opcode::EXTCODECOPY |
+            opcode::EXTCODEHASH |
+            opcode::EXTCODESIZE |
+            opcode::BALANCE |
+            opcode::SELFDESTRUCT => {
+                if let Ok(address) = interp.stack().peek(0) {
+                    self.add_account(to_address(address));
+                }
+            }
+            opcode::DELEGATECALL | opcode::CALL | opcode::STATICCALL | opcode::CALLCODE => {
+                if let Ok(address) = interp.stack().peek(1) {
+                    self.add_account(to_address(address));
+                }
+            }
+            _ => {}
+        }
+
+        Return::Continue
+    }
+}
+
+/// Converts the collected accounts and slots to an rpc access list.
+pub(crate) fn to_rpc_access_list(
+    access_list: BTreeMap<Address, BTreeSet<H256>>,
+) -> Vec<AccessListItem> {
+    access_list
+        .into_iter()
+        .map(|(address, slots)| AccessListItem {
+            address: rpc::H160(address.0),
+            storage_keys: slots.into_iter().map(|slot| rpc::H256(slot.0)).collect(),
+        })
+        .collect()
+}
+
+/// Converts a stack value to an address by taking its lower 20 bytes.
+fn to_address(value: U256) -> Address {
+    Address::from_slice(&value.to_be_bytes::<32>()[12..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn call_defaults_to_free_call_with_block_gas_limit() {
+        let mut block_env = BlockEnv {
+            gas_limit: U256::from(30_000_000),
+            basefee: U256::from(7),
+            ..Default::default()
+        };
+        let mut tx_env = TxEnv::default();
+        let to = Address::random();
+        let call = CallRequest { to: Some(to), ..Default::default() };
+
+        fill_tx_env_with_call(&mut tx_env, &mut block_env, call);
+        assert_eq!(block_env.basefee, U256::ZERO);
+        assert_eq!(tx_env.gas_limit, 30_000_000);
+        assert_eq!(tx_env.gas_price, U256::ZERO);
+        assert!(matches!(tx_env.transact_to, TransactTo::Call(addr) if addr == to));
+    }
+
+    #[test]
+    fn call_with_gas_price_pays_base_fee() {
+        let mut block_env = BlockEnv { basefee: U256::from(7), ..Default::default() };
+        let mut tx_env = TxEnv::default();
+        let call = CallRequest {
+            gas: Some(U256::from(50_000)),
+            max_fee_per_gas: Some(U256::from(10)),
+            max_priority_fee_per_gas: Some(U256::from(1)),
+            ..Default::default()
+        };
+
+        fill_tx_env_with_call(&mut tx_env, &mut block_env, call);
+        assert_eq!(block_env.basefee, U256::from(7));
+        assert_eq!(tx_env.gas_limit, 50_000);
+        assert_eq!(tx_env.gas_price, U256::from(10));
+        assert_eq!(tx_env.gas_priority_fee, Some(U256::from(1)));
+    }
+}
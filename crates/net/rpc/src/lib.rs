@@ -15,9 +15,14 @@ mod admin;
 mod engine;
 mod eth;
 mod net;
+mod txpool;
+mod web3;
 
+pub use admin::AdminApi;
 pub use engine::EngineApi;
 pub use eth::{EthApi, EthApiSpec, EthPubSub};
 pub use net::NetApi;
+pub use txpool::TxPoolApi;
+pub use web3::Web3Api;
 
 pub(crate) mod result;
@@ -20,6 +20,16 @@ impl std::fmt::Debug for NetApi {
     }
 }
 
+// === impl NetApi ===
+
+impl NetApi {
+    /// Creates a new instance that reports the peers of the given network and the chain id of
+    /// the `eth` API.
+    pub fn new(network: NetworkHandle, eth: Box<dyn EthApiSpec>) -> Self {
+        Self { network, eth }
+    }
+}
+
 /// Net rpc implementation
 impl NetApiServer for NetApi {
     fn version(&self) -> Result<String> {
@@ -0,0 +1,110 @@
+use jsonrpsee::core::RpcResult as Result;
+use reth_primitives::{Address, IntoRecoveredTransaction, TransactionKind, U256};
+use reth_rpc_api::TxPoolApiServer;
+use reth_rpc_types::{
+    Transaction, TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolInspectSummary,
+    TxpoolSenderMap, TxpoolStatus,
+};
+use reth_transaction_pool::{PoolTransaction, TransactionPool, ValidPoolTransaction};
+use std::sync::Arc;
+
+/// `txpool` API implementation.
+///
+/// This type provides the functionality for handling `txpool` related requests.
+#[derive(Clone)]
+pub struct TxPoolApi<Pool> {
+    /// An interface to interact with the pool
+    pool: Pool,
+}
+
+impl<Pool> std::fmt::Debug for TxPoolApi<Pool> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TxPoolApi").finish_non_exhaustive()
+    }
+}
+
+// === impl TxPoolApi ===
+
+impl<Pool> TxPoolApi<Pool> {
+    /// Creates a new instance that serves the transactions of the given pool.
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Groups the transactions by sender and nonce and converts them with the given function.
+fn group_by_sender<T, R>(
+    transactions: Vec<Arc<ValidPoolTransaction<T>>>,
+    f: impl Fn(&T) -> R,
+) -> TxpoolSenderMap<R>
+where
+    T: PoolTransaction,
+{
+    let mut grouped = TxpoolSenderMap::default();
+    for tx in transactions {
+        grouped.entry(tx.sender()).or_default().insert(tx.nonce(), f(&tx.transaction));
+    }
+    grouped
+}
+
+/// Converts the pool transaction into the rpc transaction type.
+fn to_rpc_transaction<T: IntoRecoveredTransaction>(tx: &T) -> Transaction {
+    Transaction::from_recovered(tx.to_recovered_transaction())
+}
+
+/// Returns the `txpool_inspect` summary of the pool transaction.
+fn to_inspect_summary<T: IntoRecoveredTransaction>(tx: &T) -> TxpoolInspectSummary {
+    let tx = tx.to_recovered_transaction();
+    let to = match tx.kind() {
+        TransactionKind::Call(to) => Some(*to),
+        TransactionKind::Create => None,
+    };
+    TxpoolInspectSummary {
+        to,
+        value: U256::from(*tx.value()),
+        gas: tx.gas_limit(),
+        gas_price: tx.max_fee_per_gas(),
+    }
+}
+
+impl<Pool> TxPoolApiServer for TxPoolApi<Pool>
+where
+    Pool: TransactionPool + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    fn status(&self) -> Result<TxpoolStatus> {
+        let size = self.pool.status();
+        Ok(TxpoolStatus {
+            pending: (size.pending as u64).into(),
+            queued: ((size.basefee + size.queued) as u64).into(),
+        })
+    }
+
+    fn inspect(&self) -> Result<TxpoolInspect> {
+        Ok(TxpoolInspect {
+            pending: group_by_sender(self.pool.pending_transactions(), to_inspect_summary),
+            queued: group_by_sender(self.pool.queued_transactions(), to_inspect_summary),
+        })
+    }
+
+    fn content_from(&self, from: Address) -> Result<TxpoolContentFrom> {
+        let by_nonce = |transactions: Vec<Arc<ValidPoolTransaction<Pool::Transaction>>>| {
+            transactions
+                .into_iter()
+                .filter(|tx| tx.sender() == from)
+                .map(|tx| (tx.nonce(), to_rpc_transaction(&tx.transaction)))
+                .collect()
+        };
+        Ok(TxpoolContentFrom {
+            pending: by_nonce(self.pool.pending_transactions()),
+            queued: by_nonce(self.pool.queued_transactions()),
+        })
+    }
+
+    fn content(&self) -> Result<TxpoolContent> {
+        Ok(TxpoolContent {
+            pending: group_by_sender(self.pool.pending_transactions(), to_rpc_transaction),
+            queued: group_by_sender(self.pool.queued_transactions(), to_rpc_transaction),
+        })
+    }
+}
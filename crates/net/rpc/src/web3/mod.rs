@@ -0,0 +1,31 @@
+use jsonrpsee::core::RpcResult as Result;
+use reth_primitives::{keccak256, Bytes, H256};
+use reth_rpc_api::Web3ApiServer;
+
+/// `web3` API implementation.
+///
+/// This type provides the functionality for handling `web3` related requests.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct Web3Api;
+
+impl Web3ApiServer for Web3Api {
+    fn client_version(&self) -> Result<String> {
+        Ok(format!("reth/v{}", env!("CARGO_PKG_VERSION")))
+    }
+
+    fn sha3(&self, input: Bytes) -> Result<H256> {
+        Ok(keccak256(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::KECCAK_EMPTY;
+
+    #[test]
+    fn sha3_of_empty_input() {
+        assert_eq!(Web3Api::default().sha3(Bytes::default()).unwrap(), KECCAK_EMPTY);
+    }
+}
@@ -1,10 +1,11 @@
-use crate::{Header, SealedHeader, TransactionSigned, H256};
-use reth_rlp::{Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable};
+use crate::{Header, SealedHeader, TransactionSigned, Withdrawal, H256};
+use bytes::BufMut;
+use reth_rlp::{Decodable, DecodeError, Encodable};
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
 
 /// Ethereum full block.
-#[derive(Debug, Clone, PartialEq, Eq, Default, RlpEncodable, RlpDecodable)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Block {
     /// Block header.
     pub header: Header,
@@ -12,6 +13,25 @@ pub struct Block {
     pub body: Vec<TransactionSigned>,
     /// Ommers/uncles header
     pub ommers: Vec<Header>,
+    /// Block withdrawals, present since Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+impl Encodable for Block {
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_block(out, &self.header, &self.body, &self.ommers, self.withdrawals.as_deref())
+    }
+
+    fn length(&self) -> usize {
+        block_length(&self.header, &self.body, &self.ommers, self.withdrawals.as_deref())
+    }
+}
+
+impl Decodable for Block {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let (header, body, ommers, withdrawals) = decode_block(buf)?;
+        Ok(Self { header, body, ommers, withdrawals })
+    }
 }
 
 impl Deref for Block {
@@ -22,7 +42,7 @@ impl Deref for Block {
 }
 
 /// Sealed Ethereum full block.
-#[derive(Debug, Clone, PartialEq, Eq, Default, RlpEncodable, RlpDecodable)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct SealedBlock {
     /// Locked block header.
     pub header: SealedHeader,
@@ -30,6 +50,25 @@ pub struct SealedBlock {
     pub body: Vec<TransactionSigned>,
     /// Ommer/uncle headers
     pub ommers: Vec<SealedHeader>,
+    /// Block withdrawals, present since Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+impl Encodable for SealedBlock {
+    fn encode(&self, out: &mut dyn BufMut) {
+        encode_block(out, &self.header, &self.body, &self.ommers, self.withdrawals.as_deref())
+    }
+
+    fn length(&self) -> usize {
+        block_length(&self.header, &self.body, &self.ommers, self.withdrawals.as_deref())
+    }
+}
+
+impl Decodable for SealedBlock {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let (header, body, ommers, withdrawals) = decode_block(buf)?;
+        Ok(Self { header, body, ommers, withdrawals })
+    }
 }
 
 impl SealedBlock {
@@ -43,12 +82,18 @@ impl SealedBlock {
         (self.header, self.body, self.ommers)
     }
 
+    /// Returns the withdrawals of the block, or an empty slice if the block has none.
+    pub fn withdrawals(&self) -> &[Withdrawal] {
+        self.withdrawals.as_deref().unwrap_or_default()
+    }
+
     /// Unseal the block
     pub fn unseal(self) -> Block {
         Block {
             header: self.header.unseal(),
             body: self.body,
             ommers: self.ommers.into_iter().map(|o| o.unseal()).collect(),
+            withdrawals: self.withdrawals,
         }
     }
 }
@@ -60,6 +105,74 @@ impl Deref for SealedBlock {
     }
 }
 
+fn block_payload_length<H: Encodable>(
+    header: &H,
+    body: &[TransactionSigned],
+    ommers: &[H],
+    withdrawals: Option<&[Withdrawal]>,
+) -> usize {
+    header.length() +
+        reth_rlp::list_length::<TransactionSigned, _>(body) +
+        reth_rlp::list_length::<H, _>(ommers) +
+        withdrawals.map(reth_rlp::list_length::<Withdrawal, _>).unwrap_or_default()
+}
+
+fn block_length<H: Encodable>(
+    header: &H,
+    body: &[TransactionSigned],
+    ommers: &[H],
+    withdrawals: Option<&[Withdrawal]>,
+) -> usize {
+    let payload_length = block_payload_length(header, body, ommers, withdrawals);
+    payload_length + reth_rlp::length_of_length(payload_length)
+}
+
+/// Encode a block as the list `[header, transactions, ommers, withdrawals]`, where the withdrawals
+/// are only appended since Shanghai.
+fn encode_block<H: Encodable>(
+    out: &mut dyn BufMut,
+    header: &H,
+    body: &[TransactionSigned],
+    ommers: &[H],
+    withdrawals: Option<&[Withdrawal]>,
+) {
+    let payload_length = block_payload_length(header, body, ommers, withdrawals);
+    reth_rlp::Header { list: true, payload_length }.encode(out);
+    header.encode(out);
+    reth_rlp::encode_list::<TransactionSigned, _>(body, out);
+    reth_rlp::encode_list::<H, _>(ommers, out);
+    if let Some(withdrawals) = withdrawals {
+        reth_rlp::encode_list::<Withdrawal, _>(withdrawals, out);
+    }
+}
+
+/// Decode a block encoded by [encode_block].
+#[allow(clippy::type_complexity)]
+fn decode_block<H: Decodable>(
+    buf: &mut &[u8],
+) -> Result<(H, Vec<TransactionSigned>, Vec<H>, Option<Vec<Withdrawal>>), DecodeError> {
+    let rlp_head = reth_rlp::Header::decode(buf)?;
+    if !rlp_head.list {
+        return Err(DecodeError::UnexpectedString)
+    }
+    let started_len = buf.len();
+    let header = Decodable::decode(buf)?;
+    let body = Decodable::decode(buf)?;
+    let ommers = Decodable::decode(buf)?;
+    let mut withdrawals = None;
+    if started_len - buf.len() < rlp_head.payload_length {
+        withdrawals = Some(Decodable::decode(buf)?);
+    }
+    let consumed = started_len - buf.len();
+    if consumed != rlp_head.payload_length {
+        return Err(DecodeError::ListLengthMismatch {
+            expected: rlp_head.payload_length,
+            got: consumed,
+        })
+    }
+    Ok((header, body, ommers, withdrawals))
+}
+
 /// Either a block hash _or_ a block number
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BlockHashOrNumber {
@@ -12,3 +12,23 @@ pub const KECCAK_EMPTY: H256 =
 /// Ommer root of empty list.
 pub const EMPTY_OMMER_ROOT: H256 =
     H256(hex!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"));
+
+/// The id of the execution stage in the sync stage table. The latest state is the state after
+/// the block the execution stage has reached.
+pub const EXECUTION_STAGE_ID: &str = "Execution";
+
+/// The id of the account history indexing stage in the sync stage table.
+pub const INDEX_ACCOUNT_HISTORY_STAGE_ID: &str = "IndexAccountHistory";
+
+/// The id of the storage history indexing stage in the sync stage table.
+pub const INDEX_STORAGE_HISTORY_STAGE_ID: &str = "IndexStorageHistory";
+
+/// Multiplier for converting gwei to wei.
+pub const GWEI_TO_WEI: u64 = 1_000_000_000;
+
+/// Initial base fee as defined in: https://eips.ethereum.org/EIPS/eip-1559
+pub const EIP1559_INITIAL_BASE_FEE: u64 = 1_000_000_000;
+/// Base fee max change denominator as defined in: https://eips.ethereum.org/EIPS/eip-1559
+pub const EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+/// Elasticity multiplier as defined in: https://eips.ethereum.org/EIPS/eip-1559
+pub const EIP1559_ELASTICITY_MULTIPLIER: u64 = 2;
@@ -19,6 +19,8 @@ pub enum Hardfork {
     London,
     ArrowGlacier,
     GrayGlacier,
+    Paris,
+    Shanghai,
     Latest,
 }
 
@@ -38,7 +40,21 @@ impl Hardfork {
             Hardfork::Berlin => 12244000,
             Hardfork::London => 12965000,
             Hardfork::ArrowGlacier => 13773000,
-            Hardfork::GrayGlacier | Hardfork::Latest => 15050000,
+            Hardfork::GrayGlacier => 15050000,
+            Hardfork::Paris => 15537394,
+            Hardfork::Shanghai | Hardfork::Latest => 17034870,
+        }
+    }
+
+    /// Get the value that is mixed into the EIP-2124 fork hash when the hardfork activates.
+    ///
+    /// This is the fork block, except for Shanghai which is scheduled by timestamp, in which case
+    /// it is the activation timestamp as specified by EIP-6122. Timestamps are always greater than
+    /// block numbers, so the forks stay ordered.
+    pub fn fork_id_value(&self) -> u64 {
+        match *self {
+            Hardfork::Shanghai | Hardfork::Latest => 1681338455,
+            _ => self.fork_block(),
         }
     }
 
@@ -79,14 +95,20 @@ impl Hardfork {
             Hardfork::ArrowGlacier => {
                 ForkId { hash: ForkHash([0x20, 0xc3, 0x27, 0xfc]), next: 15050000 }
             }
-            Hardfork::Latest | Hardfork::GrayGlacier => {
+            // The merge does not change the fork id
+            Hardfork::GrayGlacier | Hardfork::Paris => {
+                ForkId { hash: ForkHash([0xf0, 0xaf, 0xd0, 0xe3]), next: 1681338455 }
+            }
+            Hardfork::Latest | Hardfork::Shanghai => {
                 // update `next` when another fork block num is known
-                ForkId { hash: ForkHash([0xf0, 0xaf, 0xd0, 0xe3]), next: 0 }
+                ForkId { hash: ForkHash([0xdc, 0xe9, 0x6c, 0x2d]), next: 0 }
             }
         }
     }
 
     /// This returns all known hardforks in order.
+    ///
+    /// Paris is not included because the merge does not change the fork id.
     pub fn all_forks() -> Vec<Self> {
         vec![
             Hardfork::Homestead,
@@ -102,6 +124,7 @@ impl Hardfork {
             Hardfork::London,
             Hardfork::ArrowGlacier,
             Hardfork::GrayGlacier,
+            Hardfork::Shanghai,
         ]
     }
 
@@ -110,21 +133,25 @@ impl Hardfork {
         Hardfork::all_forks().iter().map(|f| f.fork_block()).collect()
     }
 
+    /// This returns the values of all known hardforks that make up the fork hash, see
+    /// [Hardfork::fork_id_value].
+    pub fn all_fork_id_values() -> Vec<u64> {
+        Hardfork::all_forks().iter().map(|f| f.fork_id_value()).collect()
+    }
+
     /// Creates a [`ForkFilter`](crate::ForkFilter) for the given hardfork.
     ///
     /// **CAUTION**: This assumes the current hardfork's block number is the current head and uses
     /// all known future hardforks to initialize the filter.
     pub fn fork_filter(&self) -> ForkFilter {
-        let all_forks = Hardfork::all_forks();
-        let future_forks: Vec<BlockNumber> = all_forks
-            .iter()
-            .filter(|f| f.fork_block() > self.fork_block())
-            .map(|f| f.fork_block())
+        let future_forks: Vec<u64> = Hardfork::all_fork_id_values()
+            .into_iter()
+            .filter(|value| *value > self.fork_id_value())
             .collect();
 
         // this data structure is not chain-agnostic, so we can pass in the constant mainnet
         // genesis
-        ForkFilter::new(self.fork_block(), MAINNET_GENESIS, future_forks)
+        ForkFilter::new(self.fork_id_value(), MAINNET_GENESIS, future_forks)
     }
 }
 
@@ -148,6 +175,8 @@ impl FromStr for Hardfork {
             "london" | "12" => Hardfork::London,
             "arrowglacier" | "13" => Hardfork::ArrowGlacier,
             "grayglacier" => Hardfork::GrayGlacier,
+            "paris" => Hardfork::Paris,
+            "shanghai" => Hardfork::Shanghai,
             "latest" | "14" => Hardfork::Latest,
             _ => return Err(format!("Unknown hardfork {s}")),
         };
@@ -176,8 +205,10 @@ impl From<BlockNumber> for Hardfork {
             _i if num < 12_965_000 => Hardfork::Berlin,
             _i if num < 13_773_000 => Hardfork::London,
             _i if num < 15_050_000 => Hardfork::ArrowGlacier,
+            _i if num < 15_537_394 => Hardfork::GrayGlacier,
+            _i if num < 17_034_870 => Hardfork::Paris,
 
-            _ => Hardfork::Latest,
+            _ => Hardfork::Shanghai,
         }
     }
 }
@@ -203,6 +234,12 @@ mod tests {
 
         let hf: Hardfork = 12244000u64.into();
         assert_eq!(hf, Hardfork::Berlin);
+
+        let hf: Hardfork = 15537394u64.into();
+        assert_eq!(hf, Hardfork::Paris);
+
+        let hf: Hardfork = 17034870u64.into();
+        assert_eq!(hf, Hardfork::Shanghai);
     }
 
     #[test]
@@ -225,8 +262,11 @@ mod tests {
 
         // check that the curr_forkhash we compute matches the output of each fork_id returned
         for hardfork in hardforks {
-            curr_forkhash += hardfork.fork_block();
+            curr_forkhash += hardfork.fork_id_value();
             assert_eq!(curr_forkhash, hardfork.fork_id().hash);
         }
+
+        assert_eq!(Hardfork::GrayGlacier.fork_id().next, Hardfork::Shanghai.fork_id_value());
+        assert_eq!(Hardfork::Shanghai.fork_filter().current(), Hardfork::Shanghai.fork_id());
     }
 }
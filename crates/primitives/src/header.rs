@@ -1,4 +1,5 @@
 use crate::{
+    basefee::calculate_next_block_base_fee,
     keccak256,
     proofs::{EMPTY_LIST_HASH, EMPTY_ROOT},
     BlockHash, BlockNumber, Bloom, H160, H256, U256,
@@ -64,6 +65,9 @@ pub struct Header {
     /// above the gas target, and decreasing when blocks are below the gas target. The base fee per
     /// gas is burned.
     pub base_fee_per_gas: Option<u64>,
+    /// The Keccak 256-bit hash of the withdrawals list portion of this block.
+    /// <https://eips.ethereum.org/EIPS/eip-4895>
+    pub withdrawals_root: Option<H256>,
     /// An arbitrary byte array containing data relevant to this block. This must be 32 bytes or
     /// fewer; formally Hx.
     pub extra_data: bytes::Bytes,
@@ -88,6 +92,7 @@ impl Default for Header {
             mix_hash: Default::default(),
             nonce: 0,
             base_fee_per_gas: None,
+            withdrawals_root: None,
         }
     }
 }
@@ -101,9 +106,18 @@ impl Header {
         keccak256(&out)
     }
 
-    /// Checks if the header is empty - has no transactions and no ommers
+    /// Checks if the header is empty - has no transactions, no ommers and no withdrawals
     pub fn is_empty(&self) -> bool {
-        self.ommers_hash == EMPTY_LIST_HASH && self.transactions_root == EMPTY_ROOT
+        self.ommers_hash == EMPTY_LIST_HASH &&
+            self.transactions_root == EMPTY_ROOT &&
+            self.withdrawals_root.map_or(true, |root| root == EMPTY_ROOT)
+    }
+
+    /// Returns the base fee of the child of this block.
+    ///
+    /// Returns `None` if this block has no base fee, i.e. it precedes the London hardfork.
+    pub fn next_block_base_fee(&self) -> Option<u64> {
+        Some(calculate_next_block_base_fee(self.gas_used, self.gas_limit, self.base_fee_per_gas?))
     }
 
     /// Calculate hash and seal the Header so that it can't be changed.
@@ -130,6 +144,7 @@ impl Header {
         length += self.mix_hash.length();
         length += H64::from_low_u64_be(self.nonce).length();
         length += self.base_fee_per_gas.map(|fee| U256::from(fee).length()).unwrap_or_default();
+        length += self.withdrawals_root.map(|root| root.length()).unwrap_or_default();
         length
     }
 }
@@ -157,6 +172,9 @@ impl Encodable for Header {
         if let Some(ref base_fee) = self.base_fee_per_gas {
             U256::from(*base_fee).encode(out);
         }
+        if let Some(ref root) = self.withdrawals_root {
+            root.encode(out);
+        }
     }
 
     fn length(&self) -> usize {
@@ -191,12 +209,17 @@ impl Decodable for Header {
             mix_hash: Decodable::decode(buf)?,
             nonce: H64::decode(buf)?.to_low_u64_be(),
             base_fee_per_gas: None,
+            withdrawals_root: None,
         };
         let consumed = started_len - buf.len();
         if consumed < rlp_head.payload_length {
             this.base_fee_per_gas = Some(U256::decode(buf)?.to::<u64>());
         }
         let consumed = started_len - buf.len();
+        if consumed < rlp_head.payload_length {
+            this.withdrawals_root = Some(Decodable::decode(buf)?);
+        }
+        let consumed = started_len - buf.len();
         if consumed != rlp_head.payload_length {
             return Err(reth_rlp::DecodeError::ListLengthMismatch {
                 expected: rlp_head.payload_length,
@@ -355,7 +378,7 @@ impl From<HeadersDirection> for bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{Decodable, Encodable, Header, H256};
+    use super::{Decodable, Encodable, Header, EMPTY_ROOT, H256};
     use crate::{Address, U256};
     use ethers_core::{
         types::Bytes,
@@ -410,6 +433,7 @@ mod tests {
             mix_hash: H256::from_str("0000000000000000000000000000000000000000000000000000000000000000").unwrap(),
             nonce: 0,
             base_fee_per_gas: Some(0x036b_u64),
+            withdrawals_root: None,
         };
         assert_eq!(header.hash_slow(), expected_hash);
     }
@@ -434,4 +458,18 @@ mod tests {
         let header = <Header as Decodable>::decode(&mut data.as_slice()).unwrap();
         assert_eq!(header, expected);
     }
+
+    #[test]
+    fn test_shanghai_block_header_roundtrip() {
+        let header = Header {
+            number: 0x01_u64,
+            base_fee_per_gas: Some(0x07_u64),
+            withdrawals_root: Some(EMPTY_ROOT),
+            ..Default::default()
+        };
+        let mut data = vec![];
+        header.encode(&mut data);
+        assert_eq!(header.length(), data.len());
+        assert_eq!(<Header as Decodable>::decode(&mut data.as_slice()).unwrap(), header);
+    }
 }
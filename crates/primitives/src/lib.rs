@@ -10,6 +10,7 @@
 //! This crate contains Ethereum primitive types and helper functions.
 
 mod account;
+pub mod basefee;
 mod block;
 pub mod bloom;
 mod chain;
@@ -27,6 +28,7 @@ mod peer;
 mod receipt;
 mod storage;
 mod transaction;
+mod withdrawal;
 
 /// Helper function for calculating Merkle proofs and hashes
 pub mod proofs;
@@ -35,7 +37,11 @@ pub use account::Account;
 pub use block::{Block, BlockHashOrNumber, SealedBlock};
 pub use bloom::Bloom;
 pub use chain::Chain;
-pub use constants::{EMPTY_OMMER_ROOT, KECCAK_EMPTY, MAINNET_GENESIS};
+pub use constants::{
+    EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR, EIP1559_ELASTICITY_MULTIPLIER,
+    EIP1559_INITIAL_BASE_FEE, EMPTY_OMMER_ROOT, EXECUTION_STAGE_ID, GWEI_TO_WEI,
+    INDEX_ACCOUNT_HISTORY_STAGE_ID, INDEX_STORAGE_HISTORY_STAGE_ID, KECCAK_EMPTY, MAINNET_GENESIS,
+};
 pub use forkid::{ForkFilter, ForkHash, ForkId, ForkTransition, ValidationError};
 pub use hardfork::Hardfork;
 pub use header::{Header, HeadersDirection, SealedHeader};
@@ -46,12 +52,13 @@ pub use log::Log;
 pub use net::NodeRecord;
 pub use peer::{PeerId, WithPeerId};
 pub use receipt::Receipt;
-pub use storage::StorageEntry;
+pub use storage::{StorageEntry, StorageTrieEntry};
 pub use transaction::{
     AccessList, AccessListItem, FromRecoveredTransaction, IntoRecoveredTransaction, Signature,
     Transaction, TransactionKind, TransactionSigned, TransactionSignedEcRecovered, TxEip1559,
     TxEip2930, TxLegacy, TxType,
 };
+pub use withdrawal::Withdrawal;
 
 /// A block hash.
 pub type BlockHash = H256;
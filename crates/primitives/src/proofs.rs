@@ -1,7 +1,8 @@
-use crate::{keccak256, Header, Log, Receipt, TransactionSigned, H256};
+use crate::{keccak256, Header, Log, Receipt, TransactionSigned, Withdrawal, H256};
 use hash_db::Hasher;
 use hex_literal::hex;
 use plain_hasher::PlainHasher;
+use reth_rlp::Encodable;
 use triehash::ordered_trie_root;
 
 /// Keccak-256 hash of the RLP of an empty list, KEC("\xc0").
@@ -41,6 +42,20 @@ pub fn calculate_transaction_root<'a>(
     }))
 }
 
+/// Calculates the root hash of the withdrawals.
+///
+/// Iterates over the given withdrawals and calculates the merkle trie root of
+/// `(rlp(index), rlp(withdrawal))` pairs.
+pub fn calculate_withdrawals_root<'a>(
+    withdrawals: impl IntoIterator<Item = &'a Withdrawal>,
+) -> H256 {
+    ordered_trie_root::<KeccakHasher, _>(withdrawals.into_iter().map(|withdrawal| {
+        let mut withdrawal_rlp = Vec::new();
+        withdrawal.encode(&mut withdrawal_rlp);
+        withdrawal_rlp
+    }))
+}
+
 /// Calculates the receipt root for a header.
 pub fn calculate_receipt_root<'a>(receipts: impl Iterator<Item = &'a Receipt>) -> H256 {
     ordered_trie_root::<KeccakHasher, _>(receipts.into_iter().map(|receipt| {
@@ -71,7 +86,10 @@ mod tests {
 
     use crate::{
         hex_literal::hex,
-        proofs::{calculate_receipt_root, calculate_transaction_root},
+        proofs::{
+            calculate_receipt_root, calculate_transaction_root, calculate_withdrawals_root,
+            EMPTY_ROOT,
+        },
         Block, Bloom, Log, Receipt, TxType, H160, H256,
     };
     use bytes::Bytes;
@@ -105,4 +123,9 @@ mod tests {
             H256(hex!("fe70ae4a136d98944951b2123859698d59ad251a381abc9960fa81cae3d0d4a0"))
         );
     }
+
+    #[test]
+    fn check_withdrawals_root() {
+        assert_eq!(calculate_withdrawals_root([].iter()), EMPTY_ROOT);
+    }
 }
@@ -29,3 +29,33 @@ impl Compact for StorageEntry {
         (Self { key, value }, out)
     }
 }
+
+/// Account storage trie node.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StorageTrieEntry {
+    /// Hash of the trie node. Acts as `DupSort::SubKey`.
+    pub hash: H256,
+    /// Encoded trie node.
+    pub node: Vec<u8>,
+}
+
+// NOTE: Removing main_codec and manually encode subkey
+// and compress second part of the value. If we have compression
+// over whole value (Even SubKey) that would mess up fetching of values with seek_by_key_subkey
+impl Compact for StorageTrieEntry {
+    fn to_compact(self, buf: &mut impl bytes::BufMut) -> usize {
+        // for now put full bytes and later compress it.
+        buf.put_slice(&self.hash.to_fixed_bytes()[..]);
+        buf.put_slice(&self.node[..]);
+        self.node.len() + 32
+    }
+
+    fn from_compact(buf: &[u8], len: usize) -> (Self, &[u8])
+    where
+        Self: Sized,
+    {
+        let hash = H256::from_slice(&buf[..32]);
+        let node = Vec::from(&buf[32..len]);
+        (Self { hash, node }, &buf[len..])
+    }
+}
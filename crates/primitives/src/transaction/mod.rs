@@ -196,6 +196,15 @@ impl Transaction {
         }
     }
 
+    /// Get the transaction's chain id, if any.
+    pub fn chain_id(&self) -> Option<ChainId> {
+        match self {
+            Transaction::Legacy(TxLegacy { chain_id, .. }) => *chain_id,
+            Transaction::Eip2930(TxEip2930 { chain_id, .. }) |
+            Transaction::Eip1559(TxEip1559 { chain_id, .. }) => Some(*chain_id),
+        }
+    }
+
     /// Gets the transaction's [`TransactionKind`], which is the address of the recipient or
     /// [`TransactionKind::Create`] if the transaction is a contract creation.
     pub fn kind(&self) -> &TransactionKind {
@@ -251,6 +260,27 @@ impl Transaction {
         }
     }
 
+    /// Returns the gas price paid per unit of gas if the transaction is included in a block with
+    /// the given base fee.
+    ///
+    /// For EIP-1559 transactions this is
+    /// `min(max_fee_per_gas, base_fee + max_priority_fee_per_gas)`, all other transactions pay
+    /// their gas price.
+    pub fn effective_gas_price(&self, base_fee: Option<u64>) -> u128 {
+        match self {
+            Transaction::Legacy(TxLegacy { gas_price, .. }) |
+            Transaction::Eip2930(TxEip2930 { gas_price, .. }) => *gas_price,
+            Transaction::Eip1559(TxEip1559 {
+                max_fee_per_gas, max_priority_fee_per_gas, ..
+            }) => match base_fee {
+                Some(base_fee) => {
+                    (base_fee as u128 + *max_priority_fee_per_gas).min(*max_fee_per_gas)
+                }
+                None => *max_fee_per_gas,
+            },
+        }
+    }
+
     /// Get the transaction's input field.
     pub fn input(&self) -> &Bytes {
         match self {
@@ -711,6 +741,14 @@ impl TransactionSigned {
         }
     }
 
+    /// Returns the [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718) encoding of the
+    /// transaction, as used for the raw transaction data.
+    pub fn envelope_encoded(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+        self.encode_inner(&mut buf, false);
+        buf.freeze().into()
+    }
+
     /// Calculate transaction hash, eip2728 transaction does not contain rlp header and start with
     /// tx type.
     pub fn recalculate_hash(&self) -> H256 {
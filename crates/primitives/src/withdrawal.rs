@@ -0,0 +1,52 @@
+use crate::{constants::GWEI_TO_WEI, Address, U256};
+use reth_codecs::{main_codec, Compact};
+use reth_rlp::{RlpDecodable, RlpEncodable};
+
+/// Withdrawal represents a validator withdrawal from the consensus layer, introduced in
+/// [EIP-4895](https://eips.ethereum.org/EIPS/eip-4895).
+#[main_codec]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Hash, RlpEncodable, RlpDecodable)]
+pub struct Withdrawal {
+    /// Monotonically increasing identifier issued by consensus layer.
+    pub index: u64,
+    /// Index of validator associated with withdrawal.
+    pub validator_index: u64,
+    /// Target address for withdrawn ether.
+    pub address: Address,
+    /// Value of the withdrawal in gwei.
+    pub amount: u64,
+}
+
+impl Withdrawal {
+    /// Return the withdrawal amount in wei.
+    pub fn amount_wei(&self) -> U256 {
+        U256::from(self.amount) * U256::from(GWEI_TO_WEI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex_literal::hex;
+    use reth_rlp::{Decodable, Encodable};
+
+    #[test]
+    fn withdrawal_rlp_roundtrip() {
+        let data = hex!("dc808094c94f5374fce5edbc8e2a8697c15331677e6ebf0b8401312d00");
+        let withdrawal = Withdrawal::decode(&mut &data[..]).unwrap();
+        assert_eq!(
+            withdrawal,
+            Withdrawal {
+                index: 0,
+                validator_index: 0,
+                address: Address::from(hex!("c94f5374fce5edbc8e2a8697c15331677e6ebf0b")),
+                amount: 20_000_000,
+            }
+        );
+        assert_eq!(withdrawal.amount_wei(), U256::from(20_000_000u64) * U256::from(GWEI_TO_WEI));
+
+        let mut encoded = Vec::new();
+        withdrawal.encode(&mut encoded);
+        assert_eq!(encoded, data);
+    }
+}
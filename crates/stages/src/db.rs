@@ -1,19 +1,27 @@
 #![allow(dead_code)]
 use std::{
+    collections::BTreeMap,
     fmt::Debug,
-    ops::{Deref, DerefMut},
+    ops::{Deref, DerefMut, RangeInclusive},
+    sync::Mutex,
 };
 
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW},
     database::{Database, DatabaseGAT},
-    models::{BlockNumHash, StoredBlockBody},
+    models::{
+        AccountBeforeTx, BlockNumHash, StoredBlockBody, TransitionIdAddress,
+        NUM_OF_INDICES_IN_SHARD,
+    },
     table::Table,
-    tables,
+    tables::{self, TransitionList},
     transaction::{DbTx, DbTxMut},
     Error,
 };
-use reth_primitives::{BlockHash, BlockNumber, TransitionId, TxNumber};
+use reth_primitives::{
+    Account, Address, BlockHash, BlockNumber, Header, StorageEntry, TransitionId, TxNumber, H256,
+    U256,
+};
 
 use crate::{DatabaseIntegrityError, StageError};
 
@@ -29,8 +37,13 @@ pub struct Transaction<'this, DB: Database> {
     /// A handle to the DB.
     pub(crate) db: &'this DB,
     tx: Option<<DB as DatabaseGAT<'this>>::TXMut>,
+    /// Callbacks to invoke once the current inner transaction is committed.
+    on_commit: Mutex<Vec<OnCommit>>,
 }
 
+/// A callback that is invoked after a commit.
+type OnCommit = Box<dyn FnOnce() + Send + Sync>;
+
 impl<'a, DB: Database> Debug for Transaction<'a, DB> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Transaction").finish()
@@ -71,7 +84,7 @@ where
     ///
     /// A new inner transaction will be opened.
     pub fn new(db: &'this DB) -> Result<Self, Error> {
-        Ok(Self { db, tx: Some(db.tx_mut()?) })
+        Ok(Self { db, tx: Some(db.tx_mut()?), on_commit: Default::default() })
     }
 
     /// Accessor to the internal Database
@@ -87,10 +100,20 @@ where
     /// [Transaction::close] was called without following up with a call to [Transaction::open].
     pub fn commit(&mut self) -> Result<bool, Error> {
         let success = if let Some(tx) = self.tx.take() { tx.commit()? } else { false };
+        for callback in std::mem::take(self.on_commit.get_mut().expect("not poisoned")) {
+            callback();
+        }
         self.tx = Some(self.db.tx_mut()?);
         Ok(success)
     }
 
+    /// Registers a callback that is invoked once the current inner transaction is committed.
+    ///
+    /// The callback is dropped if the inner transaction is closed or dropped without a commit.
+    pub fn on_commit(&self, callback: impl FnOnce() + Send + Sync + 'static) {
+        self.on_commit.lock().expect("not poisoned").push(Box::new(callback));
+    }
+
     /// Open a new inner transaction.
     pub fn open(&mut self) -> Result<(), Error> {
         self.tx = Some(self.db.tx_mut()?);
@@ -100,6 +123,7 @@ where
     /// Close the current inner transaction.
     pub fn close(&mut self) {
         self.tx.take();
+        self.on_commit.get_mut().expect("not poisoned").clear();
     }
 
     /// Query [tables::CanonicalHeaders] table for block hash by block number
@@ -118,6 +142,15 @@ where
         Ok((number, self.get_block_hash(number)?).into())
     }
 
+    /// Query the block header by number
+    pub(crate) fn get_header_by_num(&self, number: BlockNumber) -> Result<Header, StageError> {
+        let key = self.get_block_numhash(number)?;
+        let header = self
+            .get::<tables::Headers>(key)?
+            .ok_or(DatabaseIntegrityError::Header { number, hash: key.hash() })?;
+        Ok(header)
+    }
+
     /// Query the block body by [BlockNumHash] key
     pub(crate) fn get_block_body(&self, key: BlockNumHash) -> Result<StoredBlockBody, StageError> {
         let body = self
@@ -176,6 +209,141 @@ where
         Ok((prev_body.start_tx_id + prev_body.tx_count, last_transition + 1))
     }
 
+    /// Get the range of transitions that contain the state changes of the blocks after `from` up
+    /// to and including `to`.
+    pub(crate) fn get_transition_range(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<RangeInclusive<TransitionId>, StageError> {
+        let from_transition = self.get_block_transition_by_num(from)? + 1;
+        let to_transition = self.get_block_transition_by_num(to)?;
+        Ok(from_transition..=to_transition)
+    }
+
+    /// Walk the [tables::AccountChangeSet] over the transition range and return the changed
+    /// accounts together with their state before the first change in the range.
+    pub(crate) fn get_account_changes(
+        &self,
+        range: RangeInclusive<TransitionId>,
+    ) -> Result<BTreeMap<Address, Option<Account>>, Error> {
+        let (start, end) = range.into_inner();
+        let mut accounts = BTreeMap::new();
+        let mut cursor = self.cursor_dup::<tables::AccountChangeSet>()?;
+        let mut walker = cursor.walk(start)?;
+        while let Some((transition_id, AccountBeforeTx { address, info })) =
+            walker.next().transpose()?
+        {
+            if transition_id > end {
+                break
+            }
+            // The first entry holds the state before the range.
+            accounts.entry(address).or_insert(info);
+        }
+        Ok(accounts)
+    }
+
+    /// Walk the [tables::StorageChangeSet] over the transition range and return the changed
+    /// storage slots of each account together with their value before the first change in the
+    /// range.
+    pub(crate) fn get_storage_changes(
+        &self,
+        range: RangeInclusive<TransitionId>,
+    ) -> Result<BTreeMap<Address, BTreeMap<H256, U256>>, Error> {
+        let (start, end) = range.into_inner();
+        let mut storages: BTreeMap<Address, BTreeMap<H256, U256>> = BTreeMap::new();
+        let mut cursor = self.cursor_dup::<tables::StorageChangeSet>()?;
+        let mut walker = cursor.walk(TransitionIdAddress((start, Address::zero())))?;
+        while let Some((key, StorageEntry { key: slot, value })) = walker.next().transpose()? {
+            if key.transition_id() > end {
+                break
+            }
+            // The first entry holds the value before the range.
+            storages.entry(key.address()).or_default().entry(slot).or_insert(value);
+        }
+        Ok(storages)
+    }
+
+    /// Append the transition ids to the history index of each key.
+    ///
+    /// The indices are split into shards of [NUM_OF_INDICES_IN_SHARD] entries, each keyed by the
+    /// highest transition id it contains, except for the last shard of a key which is keyed by
+    /// `u64::MAX`. The transition ids of each key must be sorted and higher than the ones already
+    /// indexed.
+    pub(crate) fn insert_history_index<T, K, F>(
+        &self,
+        indices: BTreeMap<K, Vec<TransitionId>>,
+        mut sharded_key: F,
+    ) -> Result<(), Error>
+    where
+        T: Table<Value = TransitionList>,
+        K: Copy,
+        F: FnMut(K, TransitionId) -> T::Key,
+    {
+        for (key, transitions) in indices {
+            let mut list = Vec::with_capacity(transitions.len());
+            // Extend the last shard of the key
+            if let Some(last_shard) = self.get::<T>(sharded_key(key, u64::MAX))? {
+                self.delete::<T>(sharded_key(key, u64::MAX), None)?;
+                list.extend(last_shard.iter(0).map(|id| id as TransitionId));
+            }
+            list.extend(transitions);
+
+            let mut chunks = list.chunks(NUM_OF_INDICES_IN_SHARD).peekable();
+            while let Some(chunk) = chunks.next() {
+                let highest = match chunks.peek() {
+                    Some(_) => *chunk.last().expect("chunks are never empty"),
+                    None => u64::MAX,
+                };
+                self.put::<T>(sharded_key(key, highest), TransitionList::from(chunk.to_vec()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the transition ids starting from the given one from the history index of each key.
+    ///
+    /// The remaining transition ids of the key are moved to the last shard, keyed by `u64::MAX`.
+    pub(crate) fn unwind_history_index<T, K, F, G>(
+        &self,
+        indices: BTreeMap<K, TransitionId>,
+        mut sharded_key: F,
+        mut key_of: G,
+    ) -> Result<(), Error>
+    where
+        T: Table<Value = TransitionList>,
+        K: Copy + PartialEq,
+        F: FnMut(K, TransitionId) -> T::Key,
+        G: FnMut(&T::Key) -> K,
+    {
+        let mut cursor = self.cursor_mut::<T>()?;
+        for (key, from_transition) in indices {
+            let mut remaining = Vec::new();
+            // Walk the shards of the key backwards starting from the last one
+            let mut entry = cursor.seek_exact(sharded_key(key, u64::MAX))?;
+            while let Some((shard_key, list)) = entry {
+                if key_of(&shard_key) != key {
+                    break
+                }
+                cursor.delete_current()?;
+
+                let list = list.iter(0).map(|id| id as TransitionId).collect::<Vec<_>>();
+                if list.first().map_or(true, |first| *first >= from_transition) {
+                    entry = cursor.prev()?;
+                    continue
+                }
+
+                remaining = list.into_iter().take_while(|id| *id < from_transition).collect();
+                break
+            }
+
+            if !remaining.is_empty() {
+                cursor.upsert(sharded_key(key, u64::MAX), TransitionList::from(remaining))?;
+            }
+        }
+        Ok(())
+    }
+
     /// Unwind table by some number key
     #[inline]
     pub(crate) fn unwind_table_by_num<T>(&self, num: u64) -> Result<(), Error>
@@ -19,6 +19,7 @@ mod error;
 mod id;
 mod pipeline;
 mod stage;
+mod trie;
 mod util;
 
 #[cfg(test)]
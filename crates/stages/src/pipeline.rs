@@ -16,10 +16,12 @@ use tracing::*;
 mod ctrl;
 mod event;
 mod state;
+mod sync;
 
 use ctrl::*;
 pub use event::*;
 use state::*;
+pub use sync::*;
 
 #[cfg_attr(doc, aquamarine::aquamarine)]
 /// A staged sync pipeline.
@@ -150,6 +152,26 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
         }
     }
 
+    /// Run the pipeline until all stages have reached the chain tip, i.e. until a pass over all
+    /// stages does not make any progress anymore.
+    ///
+    /// Unlike [Pipeline::run], the pipeline does not keep running once it has synced.
+    pub async fn run_to_tip(&mut self, db: Arc<DB>) -> Result<(), PipelineError> {
+        loop {
+            let mut state = PipelineState {
+                events_sender: self.events_sender.clone(),
+                max_block: self.max_block,
+                maximum_progress: None,
+                minimum_progress: None,
+            };
+            let next_action = self.run_loop(&mut state, db.as_ref()).await?;
+
+            if let ControlFlow::NoProgress = next_action {
+                return Ok(())
+            }
+        }
+    }
+
     /// Performs one pass of the pipeline across all stages. After successful
     /// execution of each stage, it proceeds to commit it to the database.
     ///
@@ -452,6 +474,43 @@ mod tests {
         );
     }
 
+    /// Runs a pipeline until it stops making progress.
+    #[tokio::test]
+    async fn run_pipeline_to_tip() {
+        let (tx, rx) = channel(2);
+        let db = test_utils::create_test_db(EnvKind::RW);
+
+        // Run pipeline
+        tokio::spawn(async move {
+            Pipeline::<Env<WriteMap>, NoopSyncStateUpdate>::default()
+                .with_channel(tx)
+                .push(
+                    TestStage::new(StageId("A"))
+                        .add_exec(Ok(ExecOutput { stage_progress: 10, done: true }))
+                        .add_exec(Ok(ExecOutput { stage_progress: 10, done: true })),
+                )
+                .run_to_tip(db)
+                .await
+        });
+
+        // Check that the pipeline stopped after the pass without progress
+        assert_eq!(
+            ReceiverStream::new(rx).collect::<Vec<PipelineEvent>>().await,
+            vec![
+                PipelineEvent::Running { stage_id: StageId("A"), stage_progress: None },
+                PipelineEvent::Ran {
+                    stage_id: StageId("A"),
+                    result: ExecOutput { stage_progress: 10, done: true },
+                },
+                PipelineEvent::Running { stage_id: StageId("A"), stage_progress: Some(10) },
+                PipelineEvent::Ran {
+                    stage_id: StageId("A"),
+                    result: ExecOutput { stage_progress: 10, done: true },
+                },
+            ]
+        );
+    }
+
     /// Unwinds a simple pipeline.
     #[tokio::test]
     async fn unwind_pipeline() {
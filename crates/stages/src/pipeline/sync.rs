@@ -0,0 +1,414 @@
+use crate::{
+    db::Transaction,
+    stages::{
+        bodies::BODIES,
+        execution::{changed_accounts, insert_execution_results, EXECUTION},
+        hashing_account::{hash_account_changes, ACCOUNT_HASHING},
+        hashing_storage::{hash_storage_changes, STORAGE_HASHING},
+        headers::HEADERS,
+        index_account_history::{index_account_changes, INDEX_ACCOUNT_HISTORY},
+        index_storage_history::{index_storage_changes, INDEX_STORAGE_HISTORY},
+        merkle::MERKLE,
+        sender_recovery::SENDER_RECOVERY,
+        total_difficulty::TOTAL_DIFFICULTY,
+    },
+    trie::{DBTrieLoader, TrieError},
+    Pipeline, PipelineError, StageId,
+};
+use futures_util::FutureExt;
+use reth_db::{
+    database::Database,
+    models::BlockNumHash,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    Error as DbError,
+};
+use reth_executor::{
+    executor,
+    revm_wrap::{State, SubState},
+    Config,
+};
+use reth_interfaces::{
+    consensus::{self, Consensus, ForkchoiceState},
+    provider::Error as ProviderError,
+    sync::{ChainSync, SyncFuture, SyncStateProvider, SyncStateUpdater},
+};
+use reth_primitives::{
+    Account, Address, Block, BlockNumber, Header, SealedBlock, TransactionSignedEcRecovered, H256,
+    U256,
+};
+use reth_provider::{
+    insert_canonical_block, CanonBlock, CanonStateNotification, CanonStateNotificationSender,
+    LatestStateProviderRef,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Formatter},
+    ops::Deref,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tokio::sync::oneshot;
+use tracing::*;
+
+/// The stages that write a block committed by [PipelineSync], in the order they are run by the
+/// pipeline.
+const COMMIT_STAGES: [StageId; 10] = [
+    HEADERS,
+    TOTAL_DIFFICULTY,
+    BODIES,
+    SENDER_RECOVERY,
+    EXECUTION,
+    ACCOUNT_HASHING,
+    STORAGE_HASHING,
+    MERKLE,
+    INDEX_ACCOUNT_HISTORY,
+    INDEX_STORAGE_HISTORY,
+];
+
+/// Runs the [Pipeline] on demand of the consensus layer.
+///
+/// The pipeline runs in a separate task until it has synced the chain to the latest fork choice
+/// state. If the head of the fork choice state is a known block that is not the local head, the
+/// pipeline first unwinds to the canonical ancestor of that block.
+///
+/// While the pipeline is idle, blocks that extend the local head are executed on the blocking
+/// thread pool and committed directly, see [ChainSync::commit_block]. A block is written as every
+/// stage of the pipeline would write it and is only committed if its state root matches.
+pub struct PipelineSync<DB: Database, U: SyncStateUpdater, C: Consensus> {
+    /// The database the pipeline syncs.
+    db: Arc<DB>,
+    /// The consensus the pipeline gets the chain tip from.
+    consensus: Arc<C>,
+    /// The configuration of the executor for blocks that are committed directly.
+    config: Config,
+    /// Where to send notifications about blocks that are committed directly.
+    notifications: Option<CanonStateNotificationSender>,
+    /// The pipeline, unless it is running.
+    state: PipelineStatus<DB, U>,
+}
+
+/// The state of the pipeline of a [PipelineSync].
+enum PipelineStatus<DB: Database, U: SyncStateUpdater> {
+    /// The pipeline is idle.
+    Idle(Pipeline<DB, U>),
+    /// The pipeline is running in a separate task and is handed back once it has finished.
+    Running(oneshot::Receiver<(Pipeline<DB, U>, Result<(), PipelineError>)>),
+    /// The task running the pipeline panicked.
+    Terminated,
+}
+
+impl<DB: Database, U: SyncStateUpdater, C: Consensus> Debug for PipelineSync<DB, U, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PipelineSync").field("is_syncing", &self.is_syncing()).finish()
+    }
+}
+
+impl<DB: Database, U: SyncStateUpdater, C: Consensus> PipelineSync<DB, U, C> {
+    /// Create a new sync that runs the pipeline on the given database.
+    pub fn new(pipeline: Pipeline<DB, U>, db: Arc<DB>, consensus: Arc<C>, config: Config) -> Self {
+        Self { db, consensus, config, notifications: None, state: PipelineStatus::Idle(pipeline) }
+    }
+
+    /// Send a [CanonStateNotification] for every block that is committed directly.
+    ///
+    /// Blocks executed by the pipeline are announced by its execution stage.
+    pub fn with_notifications(mut self, notifications: CanonStateNotificationSender) -> Self {
+        self.notifications = Some(notifications);
+        self
+    }
+}
+
+impl<DB: Database, U: SyncStateUpdater, C: Consensus> SyncStateProvider for PipelineSync<DB, U, C> {
+    fn is_syncing(&self) -> bool {
+        !matches!(self.state, PipelineStatus::Idle(_))
+    }
+}
+
+impl<DB, U, C> ChainSync for PipelineSync<DB, U, C>
+where
+    DB: Database + 'static,
+    U: SyncStateUpdater + 'static,
+    C: Consensus + 'static,
+{
+    fn sync_to(&mut self, state: ForkchoiceState) {
+        // The header stage downloads the headers up to the head of the latest fork choice state
+        self.consensus.notify_fork_choice_state(state.clone());
+
+        match std::mem::replace(&mut self.state, PipelineStatus::Terminated) {
+            PipelineStatus::Idle(mut pipeline) => {
+                let (tx, rx) = oneshot::channel();
+                let db = self.db.clone();
+                tokio::spawn(async move {
+                    let result = async {
+                        // Reorgs to known blocks are handled by unwinding to their canonical
+                        // ancestor, the pipeline then syncs to the new head from there.
+                        let target = db.view(|tx| reorg_target(tx, state.head_block_hash))??;
+                        if let Some(target) = target {
+                            info!(target: "sync::pipeline", head = ?state.head_block_hash, target, "Unwinding to reorg");
+                            pipeline.unwind(db.as_ref(), target, None).await?;
+                        }
+                        pipeline.run_to_tip(db).await
+                    }
+                    .await;
+                    let _ = tx.send((pipeline, result));
+                });
+                self.state = PipelineStatus::Running(rx);
+            }
+            state => self.state = state,
+        }
+    }
+
+    fn commit_block(&mut self, block: SealedBlock) -> SyncFuture<()> {
+        let db = self.db.clone();
+        let consensus = self.consensus.clone();
+        let config = self.config.clone();
+        let notifications = self.notifications.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            commit_block(db.as_ref(), consensus.as_ref(), &config, notifications.as_ref(), block)
+        });
+        Box::pin(async move {
+            match task.await {
+                Ok(result) => result,
+                // The task can only be cancelled while the runtime shuts down
+                Err(err) => std::panic::resume_unwind(err.into_panic()),
+            }
+        })
+    }
+
+    fn state_root(&mut self, block: Block) -> SyncFuture<H256> {
+        let db = self.db.clone();
+        let consensus = self.consensus.clone();
+        let config = self.config.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            state_root(db.as_ref(), consensus.as_ref(), &config, block)
+        });
+        Box::pin(async move {
+            match task.await {
+                Ok(result) => result,
+                // The task can only be cancelled while the runtime shuts down
+                Err(err) => std::panic::resume_unwind(err.into_panic()),
+            }
+        })
+    }
+
+    fn poll_sync(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let PipelineStatus::Running(rx) = &mut self.state else { return Poll::Pending };
+        match ready!(rx.poll_unpin(cx)) {
+            Ok((pipeline, result)) => {
+                if let Err(err) = result {
+                    error!(target: "sync::pipeline", %err, "Pipeline failed");
+                }
+                self.state = PipelineStatus::Idle(pipeline);
+            }
+            Err(_) => {
+                error!(target: "sync::pipeline", "Pipeline task terminated unexpectedly");
+                self.state = PipelineStatus::Terminated;
+            }
+        }
+        Poll::Ready(())
+    }
+}
+
+/// Executes the block on top of the local head and commits it together with the progress of all
+/// [COMMIT_STAGES].
+///
+/// Nothing is committed if the block is invalid.
+fn commit_block<DB: Database, C: Consensus>(
+    db: &DB,
+    consensus: &C,
+    config: &Config,
+    notifications: Option<&CanonStateNotificationSender>,
+    block: SealedBlock,
+) -> reth_interfaces::Result<()> {
+    let mut tx = Transaction::new(db)?;
+    let inserted = insert_block(&tx, consensus, config, &block)?;
+    if inserted.state_root != block.state_root {
+        warn!(target: "sync::pipeline", number = block.number, got = ?inserted.state_root, expected = ?block.state_root, "State root mismatch");
+        return Err(consensus::Error::BodyStateRootDiff {
+            got: inserted.state_root,
+            expected: block.state_root,
+        }
+        .into())
+    }
+
+    for stage_id in COMMIT_STAGES {
+        stage_id.save_progress(tx.deref(), block.number)?;
+    }
+    tx.commit()?;
+
+    debug!(target: "sync::pipeline", number = block.number, hash = ?block.hash(), "Committed block");
+    if let Some(notifications) = notifications {
+        let tip = block.header.clone();
+        let committed =
+            vec![CanonBlock { header: block.header, transactions: inserted.transactions }];
+        // an error only means that there are currently no receivers
+        let _ = notifications.send(Arc::new(CanonStateNotification {
+            tip,
+            reverted: Vec::new(),
+            committed,
+            changed_accounts: inserted.changed_accounts,
+            reset: false,
+        }));
+    }
+    Ok(())
+}
+
+/// Executes the block on top of the local head and returns the state root after it.
+///
+/// The block is written like [commit_block] does, but the changes are discarded.
+fn state_root<DB: Database, C: Consensus>(
+    db: &DB,
+    consensus: &C,
+    config: &Config,
+    block: Block,
+) -> reth_interfaces::Result<H256> {
+    let tx = Transaction::new(db)?;
+    let block = SealedBlock {
+        header: block.header.seal(),
+        body: block.body,
+        ommers: block.ommers.into_iter().map(Header::seal).collect(),
+        withdrawals: block.withdrawals,
+    };
+    // The transaction is dropped without being committed
+    Ok(insert_block(&tx, consensus, config, &block)?.state_root)
+}
+
+/// A block that was executed and written by [insert_block].
+struct InsertedBlock {
+    /// The state root after the block.
+    state_root: H256,
+    /// The recovered transactions of the block.
+    transactions: Vec<TransactionSignedEcRecovered>,
+    /// The accounts changed by the block.
+    changed_accounts: BTreeMap<Address, Option<Account>>,
+}
+
+/// Validates and executes the block on top of its parent, which must be the local head, and
+/// writes it as the stages of the pipeline do.
+///
+/// The state root of the block header is not checked, the state root after the block is returned
+/// instead.
+fn insert_block<DB: Database, C: Consensus>(
+    tx: &Transaction<'_, DB>,
+    consensus: &C,
+    config: &Config,
+    block: &SealedBlock,
+) -> reth_interfaces::Result<InsertedBlock> {
+    // The block must extend the local head, i.e. the last block that every stage has reached
+    let number = block.number.saturating_sub(1);
+    for stage_id in COMMIT_STAGES {
+        if stage_id.get_progress(tx.deref())?.unwrap_or_default() != number {
+            return Err(consensus::Error::ParentUnknown { hash: block.parent_hash }.into())
+        }
+    }
+    let parent_hash = tx.get::<tables::CanonicalHeaders>(number)?;
+    if block.number == 0 || parent_hash != Some(block.parent_hash) {
+        return Err(consensus::Error::ParentUnknown { hash: block.parent_hash }.into())
+    }
+    let parent_key = BlockNumHash((number, block.parent_hash));
+    let parent = tx
+        .get::<tables::Headers>(parent_key)?
+        .ok_or(ProviderError::BlockHash { block_hash: block.parent_hash })?
+        .seal();
+
+    consensus.validate_header(&block.header, &parent)?;
+    consensus.pre_validate_block(block)?;
+
+    let transactions = block
+        .body
+        .iter()
+        .map(|transaction| transaction.try_ecrecovered())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(consensus::Error::TransactionSignerRecoveryError)?;
+    let ommers = block.ommers.iter().map(|ommer| ommer.as_ref().clone()).collect::<Vec<_>>();
+    let state_provider = SubState::new(State::new(LatestStateProviderRef::new(tx.deref())));
+    let result = executor::execute_and_verify_receipt(
+        &block.header,
+        &transactions,
+        &ommers,
+        block.withdrawals(),
+        config,
+        state_provider,
+    )?;
+
+    // Write the block as the header, total difficulty, bodies and sender recovery stages do
+    let td: U256 = tx
+        .get::<tables::HeaderTD>(parent_key)?
+        .ok_or(ProviderError::TotalDifficulty {
+            block_number: number,
+            block_hash: parent_key.hash(),
+        })?
+        .into();
+    let key = BlockNumHash((block.number, block.hash()));
+    insert_canonical_block(
+        tx.deref(),
+        block,
+        consensus.has_block_reward(block.number),
+        config.spec_upgrades.has_withdrawals(block.number),
+    )?;
+    tx.put::<tables::HeaderTD>(key, (td + block.difficulty).into())?;
+    let body = tx
+        .get::<tables::BlockBodies>(key)?
+        .ok_or(ProviderError::BlockBody { block_number: key.number(), block_hash: key.hash() })?;
+    for (tx_id, transaction) in body.tx_id_range().zip(block.body.iter()) {
+        tx.put::<tables::TxHashNumber>(transaction.hash(), tx_id)?;
+    }
+
+    // Write the execution results as the execution stage does
+    let parent_transition_id = tx.get::<tables::BlockTransitionIndex>(parent_key)?.ok_or(
+        ProviderError::BlockTransition { block_number: number, block_hash: parent_key.hash() },
+    )?;
+    let transition_id =
+        tx.get::<tables::BlockTransitionIndex>(key)?.ok_or(ProviderError::BlockTransition {
+            block_number: key.number(),
+            block_hash: key.hash(),
+        })?;
+    let results = vec![result];
+    let changed_accounts = changed_accounts(&results);
+    insert_execution_results(tx.deref(), results, parent_transition_id + 1)?;
+
+    // Hash the changed state and update the trie as the hashing and merkle stages do
+    let range = parent_transition_id + 1..=transition_id;
+    hash_account_changes(tx, range.clone())?;
+    hash_storage_changes(tx, range.clone())?;
+    let loader = DBTrieLoader::default();
+    let state_root = match loader.update_root(tx, parent.state_root, range.clone()) {
+        Err(TrieError::MissingRoot(root)) => {
+            warn!(target: "sync::pipeline", ?root, "Trie root not found, rebuilding trie");
+            loader.calculate_root(tx)?
+        }
+        res => res?,
+    };
+
+    // Index the changes as the history index stages do
+    index_account_changes(tx, range.clone())?;
+    index_storage_changes(tx, range)?;
+
+    Ok(InsertedBlock { state_root, transactions, changed_accounts })
+}
+
+/// Returns the block the pipeline has to unwind to before it can sync to the given head.
+///
+/// This is the canonical ancestor of the head if the head is a known block that is not ahead of
+/// the local head, i.e. if it is either a canonical block below the local head or a block of a
+/// side chain.
+fn reorg_target<'a>(tx: &impl DbTx<'a>, head: H256) -> Result<Option<BlockNumber>, DbError> {
+    let Some(mut number) = tx.get::<tables::HeaderNumbers>(head)? else { return Ok(None) };
+    let progress = EXECUTION.get_progress(tx)?.unwrap_or_default();
+
+    // Walk back to the canonical chain
+    let mut hash = head;
+    while tx.get::<tables::CanonicalHeaders>(number)? != Some(hash) {
+        let Some(header) = tx.get::<tables::Headers>(BlockNumHash((number, hash)))? else {
+            return Ok(None)
+        };
+        if number == 0 {
+            return Ok(None)
+        }
+        hash = header.parent_hash;
+        number -= 1;
+    }
+
+    Ok((number < progress).then_some(number))
+}
@@ -42,6 +42,17 @@ impl ExecInput {
             ExecAction::Done { stage_progress, target: end_block }
         }
     }
+
+    /// Return true if the stage should rebuild its data from scratch instead of updating it
+    /// from the changesets.
+    ///
+    /// This is the case on the first run of the stage, or if more than `clean_threshold` blocks
+    /// are to be executed.
+    pub fn should_rebuild(&self, clean_threshold: u64) -> bool {
+        let stage_progress = self.stage_progress.unwrap_or_default();
+        stage_progress == 0 ||
+            self.previous_stage_progress().saturating_sub(stage_progress) > clean_threshold
+    }
 }
 
 /// Stage unwind input, see [Stage::unwind].
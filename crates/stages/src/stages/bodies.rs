@@ -6,7 +6,7 @@ use futures_util::StreamExt;
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW},
     database::{Database, DatabaseGAT},
-    models::{BlockNumHash, StoredBlockBody, StoredBlockOmmers},
+    models::{BlockNumHash, StoredBlockBody, StoredBlockOmmers, StoredBlockWithdrawals},
     tables,
     transaction::{DbTx, DbTxMut},
 };
@@ -39,6 +39,7 @@ pub(crate) const BODIES: StageId = StageId("Bodies");
 /// The bodies are processed and data is inserted into these tables:
 ///
 /// - [`BlockOmmers`][reth_interfaces::db::tables::BlockOmmers]
+/// - [`BlockWithdrawals`][reth_interfaces::db::tables::BlockWithdrawals]
 /// - [`Transactions`][reth_interfaces::db::tables::Transactions]
 /// - [`TransactionHashNumber`][reth_interfaces::db::tables::TransactionHashNumber]
 ///
@@ -83,9 +84,10 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
 
         let bodies_to_download = self.bodies_to_download::<DB>(tx, start_block, end_block)?;
 
-        // Cursors used to write bodies, ommers and transactions
+        // Cursors used to write bodies, ommers, withdrawals and transactions
         let mut body_cursor = tx.cursor_mut::<tables::BlockBodies>()?;
         let mut ommers_cursor = tx.cursor_mut::<tables::BlockOmmers>()?;
+        let mut withdrawals_cursor = tx.cursor_mut::<tables::BlockWithdrawals>()?;
         let mut tx_cursor = tx.cursor_mut::<tables::Transactions>()?;
 
         // Cursors used to write state transition mapping
@@ -113,6 +115,7 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
             let block_header = response.header();
             let numhash: BlockNumHash = block_header.num_hash().into();
 
+            let mut has_withdrawals = false;
             match response {
                 BlockResponse::Full(block) => {
                     trace!(target: "sync::stages::bodies", ommers = block.ommers.len(), txs = block.body.len(), ?numhash, "Writing full block");
@@ -135,6 +138,12 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
                         },
                     )?;
 
+                    if let Some(withdrawals) = block.withdrawals {
+                        has_withdrawals = !withdrawals.is_empty();
+                        withdrawals_cursor
+                            .append(numhash, StoredBlockWithdrawals { withdrawals })?;
+                    }
+
                     // Write transactions
                     for transaction in block.body {
                         // Insert the transaction hash to number mapping
@@ -146,22 +155,26 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
                         transition_id += 1;
                     }
                 }
-                BlockResponse::Empty(_) => {
+                BlockResponse::Empty(header) => {
                     trace!(target: "sync::stages::bodies", ?numhash, "Writing empty block");
                     body_cursor.append(
                         numhash,
                         StoredBlockBody { start_tx_id: current_tx_id, tx_count: 0 },
                     )?;
+                    // Blocks since Shanghai always have a (possibly empty) withdrawals list
+                    if header.withdrawals_root.is_some() {
+                        withdrawals_cursor.append(numhash, StoredBlockWithdrawals::default())?;
+                    }
                 }
             };
 
             // The block transition marks the final state at the end of the block.
-            // Increment the transition if the block contains an addition block reward.
-            // If the block does not have a reward, the transition will be the same as the
+            // Increment the transition if the block contains an addition block reward or
+            // withdrawals. If the block has neither, the transition will be the same as the
             // transition at the last transaction of this block.
             let has_reward = self.consensus.has_block_reward(numhash.number());
-            trace!(target: "sync::stages::bodies", has_reward, ?numhash, "Block reward");
-            if has_reward {
+            trace!(target: "sync::stages::bodies", has_reward, has_withdrawals, ?numhash, "Block reward");
+            if has_reward || has_withdrawals {
                 transition_id += 1;
             }
             block_transition_cursor.append(numhash, transition_id)?;
@@ -184,9 +197,10 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
         input: UnwindInput,
     ) -> Result<UnwindOutput, StageError> {
         info!(target: "sync::stages::bodies", to_block = input.unwind_to, "Unwinding");
-        // Cursors to unwind bodies, ommers, transactions and tx hash to number
+        // Cursors to unwind bodies, ommers, withdrawals, transactions and tx hash to number
         let mut body_cursor = tx.cursor_mut::<tables::BlockBodies>()?;
         let mut ommers_cursor = tx.cursor_mut::<tables::BlockOmmers>()?;
+        let mut withdrawals_cursor = tx.cursor_mut::<tables::BlockWithdrawals>()?;
         let mut transaction_cursor = tx.cursor_mut::<tables::Transactions>()?;
         let mut tx_hash_number_cursor = tx.cursor_mut::<tables::TxHashNumber>()?;
         // Cursors to unwind transitions
@@ -204,6 +218,11 @@ impl<DB: Database, D: BodyDownloader, C: Consensus> Stage<DB> for BodyStage<D, C
                 ommers_cursor.delete_current()?;
             }
 
+            // Delete the withdrawals value if any
+            if withdrawals_cursor.seek_exact(key)?.is_some() {
+                withdrawals_cursor.delete_current()?;
+            }
+
             // Delete the block transition if any
             if block_transition_cursor.seek_exact(key)?.is_some() {
                 block_transition_cursor.delete_current()?;
@@ -552,6 +571,7 @@ mod tests {
                 Ok(BlockBody {
                     transactions: block.body.clone(),
                     ommers: block.ommers.iter().cloned().map(|ommer| ommer.unseal()).collect(),
+                    withdrawals: block.withdrawals.clone(),
                 }),
             )
         }
@@ -827,6 +847,7 @@ mod tests {
                         header: header.clone(),
                         body: result.transactions,
                         ommers: result.ommers.into_iter().map(|header| header.seal()).collect(),
+                        withdrawals: None,
                     }))
                 })))
             }
@@ -8,19 +8,33 @@ use reth_db::{
     models::{BlockNumHash, StoredBlockBody, TransitionIdAddress},
     tables,
     transaction::{DbTx, DbTxMut},
+    Error as DbError,
 };
 use reth_executor::{
     config::SpecUpgrades,
-    executor::AccountChangeSet,
+    executor::{AccountChangeSet, AccountInfoChangeSet, ExecutionResult},
     revm_wrap::{State, SubState},
     Config,
 };
-use reth_primitives::{Address, Header, StorageEntry, TransactionSignedEcRecovered, H256, U256};
-use reth_provider::LatestStateProviderRef;
-use std::fmt::Debug;
+use reth_primitives::{
+    Account, Address, BlockNumber, Header, StorageEntry, TransactionSignedEcRecovered,
+    TransitionId, Withdrawal, EXECUTION_STAGE_ID, H256, U256,
+};
+use reth_provider::{
+    CanonBlock, CanonStateNotification, CanonStateNotificationSender, LatestStateProviderRef,
+};
+use std::{collections::BTreeMap, fmt::Debug, ops::RangeInclusive, sync::Arc};
 use tracing::*;
 
-const EXECUTION: StageId = StageId("Execution");
+pub(crate) const EXECUTION: StageId = StageId(EXECUTION_STAGE_ID);
+
+/// The maximum number of blocks that a [CanonStateNotification] describes.
+///
+/// Larger batches are only executed far from the tip, so their notification is a reset instead.
+const MAX_NOTIFIED_BLOCKS: u64 = 64;
+
+/// The header, body, ommers and withdrawals of a block to execute.
+type BlockBatchItem = (Header, StoredBlockBody, Vec<Header>, Vec<Withdrawal>);
 
 /// The execution stage executes all transactions and
 /// update history indexes.
@@ -30,6 +44,7 @@ const EXECUTION: StageId = StageId("Execution");
 /// [tables::Headers] get for revm environment variables.
 /// [tables::CumulativeTxCount] to get tx number
 /// [tables::Transactions] to execute
+/// [tables::BlockWithdrawals] to apply withdrawals after Shanghai
 ///
 /// For state access [StateProvider] provides us latest state and history state
 /// For latest most recent state [StateProvider] would need (Used for execution Stage):
@@ -49,12 +64,18 @@ const EXECUTION: StageId = StageId("Execution");
 /// [tables::AccountHistory] to remove change set and apply old values to
 /// [tables::PlainAccountState] [tables::StorageHistory] to remove change set and apply old values
 /// to [tables::PlainStorageState]
+///
+/// If configured, a [CanonStateNotification] is sent for every executed and unwound batch of
+/// blocks once the pipeline has committed the changes of the stage. Batches of more than
+/// [MAX_NOTIFIED_BLOCKS] blocks are announced with a reset notification.
 #[derive(Debug)]
 pub struct ExecutionStage {
     /// Executor configuration.
     pub config: Config,
     /// Commit threshold
     pub commit_threshold: u64,
+    /// Where to send notifications about executed and unwound blocks to.
+    pub notifications: Option<CanonStateNotificationSender>,
 }
 
 impl Default for ExecutionStage {
@@ -62,6 +83,7 @@ impl Default for ExecutionStage {
         Self {
             config: Config { chain_id: U256::from(1), spec_upgrades: SpecUpgrades::new_ethereum() },
             commit_threshold: 1000,
+            notifications: None,
         }
     }
 }
@@ -69,7 +91,52 @@ impl Default for ExecutionStage {
 impl ExecutionStage {
     /// Create new execution stage with specified config.
     pub fn new(config: Config, commit_threshold: u64) -> Self {
-        Self { config, commit_threshold }
+        Self { config, commit_threshold, notifications: None }
+    }
+
+    /// Send a [CanonStateNotification] for every executed and unwound batch of blocks.
+    pub fn with_notifications(mut self, notifications: CanonStateNotificationSender) -> Self {
+        self.notifications = Some(notifications);
+        self
+    }
+
+    /// Sends the notification once the transaction is committed, if notifications are enabled.
+    fn notify<DB: Database>(&self, tx: &Transaction<'_, DB>, notification: CanonStateNotification) {
+        if let Some(notifications) = self.notifications.clone() {
+            tx.on_commit(move || {
+                // an error only means that there are currently no receivers
+                let _ = notifications.send(Arc::new(notification));
+            });
+        }
+    }
+
+    /// Sends the notification of an unwind, if notifications are enabled.
+    ///
+    /// The unwound blocks are only read if there are at most [MAX_NOTIFIED_BLOCKS] of them.
+    fn notify_unwind<DB: Database>(
+        &self,
+        tx: &Transaction<'_, DB>,
+        input: UnwindInput,
+        reverted_accounts: BTreeMap<Address, Option<Account>>,
+    ) -> Result<(), StageError> {
+        if self.notifications.is_none() {
+            return Ok(())
+        }
+
+        let tip = tx.get_header_by_num(input.unwind_to)?.seal();
+        let notification = if input.stage_progress - input.unwind_to <= MAX_NOTIFIED_BLOCKS {
+            CanonStateNotification {
+                tip,
+                reverted: read_blocks(tx, input.unwind_to + 1..=input.stage_progress)?,
+                committed: Vec::new(),
+                changed_accounts: reverted_accounts,
+                reset: false,
+            }
+        } else {
+            CanonStateNotification::reset(tip)
+        };
+        self.notify(tx, notification);
+        Ok(())
     }
 }
 
@@ -98,6 +165,8 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
         let mut bodies_cursor = tx.cursor::<tables::BlockBodies>()?;
         // Get ommers with canonical hashes.
         let mut ommers_cursor = tx.cursor::<tables::BlockOmmers>()?;
+        // Get withdrawals with canonical hashes.
+        let mut withdrawals_cursor = tx.cursor::<tables::BlockWithdrawals>()?;
         // Get transaction of the block that we are executing.
         let mut tx_cursor = tx.cursor::<tables::Transactions>()?;
         // Skip sender recovery and load signer from database.
@@ -113,7 +182,7 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
         // Get block headers and bodies from canonical hashes
         let block_batch = canonical_batch
             .iter()
-            .map(|key| -> Result<(Header, StoredBlockBody, Vec<Header>), StageError> {
+            .map(|key| -> Result<BlockBatchItem, StageError> {
                 // NOTE: It probably will be faster to fetch all items from one table with cursor,
                 // but to reduce complexity we are using `seek_exact` to skip some
                 // edge cases that can happen.
@@ -126,14 +195,19 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
                     .seek_exact(*key)?
                     .ok_or(DatabaseIntegrityError::BlockBody { number: key.number() })?;
                 let (_, stored_ommers) = ommers_cursor.seek_exact(*key)?.unwrap_or_default();
+                let (_, stored_withdrawals) =
+                    withdrawals_cursor.seek_exact(*key)?.unwrap_or_default();
 
-                Ok((header, body, stored_ommers.ommers))
+                Ok((header, body, stored_ommers.ommers, stored_withdrawals.withdrawals))
             })
             .collect::<Result<Vec<_>, _>>()?;
 
         // Fetch transactions, execute them and generate results
         let mut block_change_patches = Vec::with_capacity(canonical_batch.len());
-        for (header, body, ommers) in block_batch.iter() {
+        let notify_blocks =
+            self.notifications.is_some() && end_block - start_block < MAX_NOTIFIED_BLOCKS;
+        let mut executed_blocks = Vec::new();
+        for (header, body, ommers, withdrawals) in block_batch.iter() {
             let num = header.number;
             tracing::trace!(target: "sync::stages::execution", ?num, "Execute block.");
             // iterate over all transactions
@@ -193,6 +267,7 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
                             header,
                             &recovered_transactions,
                             ommers,
+                            withdrawals,
                             &self.config,
                             state_provider,
                         )
@@ -202,84 +277,37 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
             })
             .map_err(|error| StageError::ExecutionError { block: header.number, error })?;
             block_change_patches.push(changeset);
-        }
-
-        // Get last tx count so that we can know amount of transaction in the block.
-        let mut current_transition_id = tx.get_block_transition_by_num(last_block)? + 1;
-        info!(target: "sync::stages::execution", current_transition_id, blocks = block_change_patches.len(), "Inserting execution results");
-
-        // apply changes to plain database.
-        for results in block_change_patches.into_iter() {
-            // insert state change set
-            for result in results.changesets.into_iter() {
-                // TODO insert to transitionId to tx_index
-                for (address, account_change_set) in result.changeset.into_iter() {
-                    let AccountChangeSet { account, wipe_storage, storage } = account_change_set;
-                    // apply account change to db. Updates AccountChangeSet and PlainAccountState
-                    // tables.
-                    trace!(target: "sync::stages::execution", ?address, current_transition_id, ?account, wipe_storage, "Applying account changeset");
-                    account.apply_to_db(&**tx, address, current_transition_id)?;
-
-                    // wipe storage
-                    if wipe_storage {
-                        // TODO insert all changes to StorageChangeSet
-                        tx.delete::<tables::PlainStorageState>(address, None)?;
-                    }
-                    // insert storage changeset
-                    let storage_id = TransitionIdAddress((current_transition_id, address));
-                    for (key, (old_value, new_value)) in storage {
-                        let hkey = H256(key.to_be_bytes());
-
-                        trace!(target: "sync::stages::execution", ?address, current_transition_id, ?hkey, ?old_value, ?new_value, "Applying storage changeset");
-
-                        // insert into StorageChangeSet
-                        tx.put::<tables::StorageChangeSet>(
-                            storage_id.clone(),
-                            StorageEntry { key: hkey, value: old_value },
-                        )?;
-                        tracing::debug!(
-                            target = "sync::stages::execution",
-                            "{address} setting storage:{key} ({old_value} -> {new_value})"
-                        );
 
-                        // Always delete old value as duplicate table put will not override it
-                        tx.delete::<tables::PlainStorageState>(
-                            address,
-                            Some(StorageEntry { key: hkey, value: old_value }),
-                        )?;
-                        if new_value != U256::ZERO {
-                            tx.put::<tables::PlainStorageState>(
-                                address,
-                                StorageEntry { key: hkey, value: new_value },
-                            )?;
-                        }
-                    }
-                    current_transition_id += 1;
-                }
-                // insert bytecode
-                for (hash, bytecode) in result.new_bytecodes.into_iter() {
-                    // make different types of bytecode. Checked and maybe even analyzed (needs to
-                    // be packed). Currently save only raw bytes.
-                    let bytecode = bytecode.bytes();
-                    trace!(target: "sync::stages::execution", ?hash, ?bytecode, len = bytecode.len(), "Inserting bytecode");
-                    tx.put::<tables::Bytecodes>(hash, bytecode[..bytecode.len()].to_vec())?;
-
-                    // NOTE: bytecode bytes are not inserted in change set and it stand in saparate
-                    // table
-                }
+            if notify_blocks {
+                executed_blocks.push(CanonBlock {
+                    header: header.clone().seal(),
+                    transactions: recovered_transactions,
+                });
             }
+        }
 
-            // If there is block reward we will add account changeset to db
-            if let Some(block_reward_changeset) = results.block_reward {
-                // we are sure that block reward index is present.
-                for (address, changeset) in block_reward_changeset.into_iter() {
-                    trace!(target: "sync::stages::execution", ?address, current_transition_id, "Applying block reward");
-                    changeset.apply_to_db(&**tx, address, current_transition_id)?;
+        if let Some((header, ..)) = block_batch.last().filter(|_| self.notifications.is_some()) {
+            let tip = header.clone().seal();
+            let notification = if notify_blocks {
+                CanonStateNotification {
+                    tip,
+                    reverted: Vec::new(),
+                    committed: executed_blocks,
+                    changed_accounts: changed_accounts(&block_change_patches),
+                    reset: false,
                 }
-                current_transition_id += 1;
-            }
+            } else {
+                CanonStateNotification::reset(tip)
+            };
+            self.notify(tx, notification);
         }
 
+        // Get last tx count so that we can know amount of transaction in the block.
+        let current_transition_id = tx.get_block_transition_by_num(last_block)? + 1;
+        info!(target: "sync::stages::execution", current_transition_id, blocks = block_change_patches.len(), "Inserting execution results");
+
+        insert_execution_results(&**tx, block_change_patches, current_transition_id)?;
+
         let done = !capped;
         info!(target: "sync::stages::execution", stage_progress = end_block, done, "Sync iteration finished");
         Ok(ExecOutput { stage_progress: end_block, done })
@@ -313,6 +341,7 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
         // if there is no transaction ids, this means blocks were empty and block reward change set
         // is not present.
         if num_of_tx == 0 {
+            self.notify_unwind(tx, input, BTreeMap::new())?;
             return Ok(UnwindOutput { stage_progress: input.unwind_to })
         }
 
@@ -327,7 +356,9 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
             .collect::<Result<Vec<_>, _>>()?;
 
         // revert all changes to PlainState
+        let mut reverted_accounts = BTreeMap::new();
         for (_, changeset) in account_changeset_batch.into_iter().rev() {
+            reverted_accounts.insert(changeset.address, changeset.info);
             // TODO refactor in db fn called tx.aplly_account_changeset
             if let Some(account_info) = changeset.info {
                 tx.put::<tables::PlainAccountState>(changeset.address, account_info)?;
@@ -376,10 +407,149 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
             entry = storage_changeset.prev()?;
         }
 
+        self.notify_unwind(tx, input, reverted_accounts)?;
+
         Ok(UnwindOutput { stage_progress: input.unwind_to })
     }
 }
 
+/// Reads the canonical blocks in the range with their recovered transactions.
+fn read_blocks<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    range: RangeInclusive<BlockNumber>,
+) -> Result<Vec<CanonBlock>, StageError> {
+    range
+        .map(|number| {
+            let header = tx.get_header_by_num(number)?;
+            let body = tx.get_block_body_by_num(number)?;
+            let transactions = body
+                .tx_id_range()
+                .map(|id| {
+                    let transaction = tx
+                        .get::<tables::Transactions>(id)?
+                        .ok_or(DatabaseIntegrityError::Transaction { id })?;
+                    let signer = tx
+                        .get::<tables::TxSenders>(id)?
+                        .ok_or(DatabaseIntegrityError::TransactionsSignerGap { missing: id })?;
+                    Ok(TransactionSignedEcRecovered::from_signed_transaction(transaction, signer))
+                })
+                .collect::<Result<Vec<_>, StageError>>()?;
+            Ok(CanonBlock { header: header.seal(), transactions })
+        })
+        .collect()
+}
+
+/// Returns the final state of all accounts that were changed by the execution results of
+/// consecutive blocks.
+///
+/// Destroyed accounts map to `None`.
+pub(crate) fn changed_accounts(
+    block_results: &[ExecutionResult],
+) -> BTreeMap<Address, Option<Account>> {
+    let mut accounts = BTreeMap::new();
+    let mut apply = |address: Address, change: &AccountInfoChangeSet| match change {
+        AccountInfoChangeSet::Created { new } | AccountInfoChangeSet::Changed { new, .. } => {
+            accounts.insert(address, Some(*new));
+        }
+        AccountInfoChangeSet::Destroyed { .. } => {
+            accounts.insert(address, None);
+        }
+        AccountInfoChangeSet::NoChange => {}
+    };
+    for results in block_results {
+        for result in results.changesets.iter() {
+            for (address, changeset) in result.changeset.iter() {
+                apply(*address, &changeset.account);
+            }
+        }
+        for (address, change) in results.block_reward.iter().flatten() {
+            apply(*address, change);
+        }
+    }
+    accounts
+}
+
+/// Applies the execution results of consecutive blocks to the plain state and writes the account
+/// and storage changesets, starting at the given transition id.
+pub(crate) fn insert_execution_results<'a, TX: DbTxMut<'a> + DbTx<'a>>(
+    tx: &TX,
+    block_results: Vec<ExecutionResult>,
+    mut current_transition_id: TransitionId,
+) -> Result<(), DbError> {
+    // apply changes to plain database.
+    for results in block_results.into_iter() {
+        // insert state change set
+        for result in results.changesets.into_iter() {
+            // TODO insert to transitionId to tx_index
+            for (address, account_change_set) in result.changeset.into_iter() {
+                let AccountChangeSet { account, wipe_storage, storage } = account_change_set;
+                // apply account change to db. Updates AccountChangeSet and PlainAccountState
+                // tables.
+                trace!(target: "sync::stages::execution", ?address, current_transition_id, ?account, wipe_storage, "Applying account changeset");
+                account.apply_to_db(tx, address, current_transition_id)?;
+
+                // wipe storage
+                if wipe_storage {
+                    // TODO insert all changes to StorageChangeSet
+                    tx.delete::<tables::PlainStorageState>(address, None)?;
+                }
+                // insert storage changeset
+                let storage_id = TransitionIdAddress((current_transition_id, address));
+                for (key, (old_value, new_value)) in storage {
+                    let hkey = H256(key.to_be_bytes());
+
+                    trace!(target: "sync::stages::execution", ?address, current_transition_id, ?hkey, ?old_value, ?new_value, "Applying storage changeset");
+
+                    // insert into StorageChangeSet
+                    tx.put::<tables::StorageChangeSet>(
+                        storage_id.clone(),
+                        StorageEntry { key: hkey, value: old_value },
+                    )?;
+                    tracing::debug!(
+                        target = "sync::stages::execution",
+                        "{address} setting storage:{key} ({old_value} -> {new_value})"
+                    );
+
+                    // Always delete old value as duplicate table put will not override it
+                    tx.delete::<tables::PlainStorageState>(
+                        address,
+                        Some(StorageEntry { key: hkey, value: old_value }),
+                    )?;
+                    if new_value != U256::ZERO {
+                        tx.put::<tables::PlainStorageState>(
+                            address,
+                            StorageEntry { key: hkey, value: new_value },
+                        )?;
+                    }
+                }
+                current_transition_id += 1;
+            }
+            // insert bytecode
+            for (hash, bytecode) in result.new_bytecodes.into_iter() {
+                // make different types of bytecode. Checked and maybe even analyzed (needs to
+                // be packed). Currently save only raw bytes.
+                let bytecode = bytecode.bytes();
+                trace!(target: "sync::stages::execution", ?hash, ?bytecode, len = bytecode.len(), "Inserting bytecode");
+                tx.put::<tables::Bytecodes>(hash, bytecode[..bytecode.len()].to_vec())?;
+
+                // NOTE: bytecode bytes are not inserted in change set and it stand in saparate
+                // table
+            }
+        }
+
+        // If there is block reward we will add account changeset to db
+        if let Some(block_reward_changeset) = results.block_reward {
+            // we are sure that block reward index is present.
+            for (address, changeset) in block_reward_changeset.into_iter() {
+                trace!(target: "sync::stages::execution", ?address, current_transition_id, "Applying block reward");
+                changeset.apply_to_db(tx, address, current_transition_id)?;
+            }
+            current_transition_id += 1;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::ops::{Deref, DerefMut};
@@ -389,7 +559,7 @@ mod tests {
     use super::*;
     use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
     use reth_primitives::{hex_literal::hex, keccak256, Account, SealedBlock, H160, U256};
-    use reth_provider::insert_canonical_block;
+    use reth_provider::{canon_state_notification_channel, insert_canonical_block};
     use reth_rlp::Decodable;
 
     #[tokio::test]
@@ -407,8 +577,8 @@ mod tests {
         let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
         let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
         let block = SealedBlock::decode(&mut block_rlp).unwrap();
-        insert_canonical_block(tx.deref_mut(), &genesis, true).unwrap();
-        insert_canonical_block(tx.deref_mut(), &block, true).unwrap();
+        insert_canonical_block(tx.deref_mut(), &genesis, true, false).unwrap();
+        insert_canonical_block(tx.deref_mut(), &block, true, false).unwrap();
         tx.commit().unwrap();
 
         // insert pre state
@@ -434,11 +604,14 @@ mod tests {
         tx.commit().unwrap();
 
         // execute
-        let mut execution_stage = ExecutionStage::default();
+        let (notifications, mut canon_state) = canon_state_notification_channel(1);
+        let mut execution_stage = ExecutionStage::default().with_notifications(notifications);
         execution_stage.config.spec_upgrades = SpecUpgrades::new_berlin_activated();
         let output = execution_stage.execute(&mut tx, input).await.unwrap();
+        assert!(canon_state.try_recv().is_err(), "notified before commit");
         tx.commit().unwrap();
         assert_eq!(output, ExecOutput { stage_progress: 1, done: true });
+        let notification = canon_state.try_recv().unwrap();
         let tx = tx.deref_mut();
         // check post state
         let account1 = H160(hex!("1000000000000000000000000000000000000000"));
@@ -473,6 +646,14 @@ mod tests {
             Ok(Some(account3_info)),
             "Post changed of a account"
         );
+        // assert notification
+        assert_eq!(notification.tip, block.header);
+        assert!(!notification.reset);
+        assert_eq!(
+            notification.mined_transactions().collect::<Vec<_>>(),
+            vec![block.body[0].hash()]
+        );
+        assert_eq!(notification.changed_accounts.get(&account3), Some(&Some(account3_info)));
         // assert storage
         // Get on dupsort would return only first value. This is good enought for this test.
         assert_eq!(
@@ -498,8 +679,8 @@ mod tests {
         let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
         let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
         let block = SealedBlock::decode(&mut block_rlp).unwrap();
-        insert_canonical_block(tx.deref_mut(), &genesis, true).unwrap();
-        insert_canonical_block(tx.deref_mut(), &block, true).unwrap();
+        insert_canonical_block(tx.deref_mut(), &genesis, true, false).unwrap();
+        insert_canonical_block(tx.deref_mut(), &block, true, false).unwrap();
         tx.commit().unwrap();
 
         // variables
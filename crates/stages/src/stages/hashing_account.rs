@@ -0,0 +1,276 @@
+use crate::{
+    db::Transaction, ExecAction, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput,
+    UnwindOutput,
+};
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    Error as DbError,
+};
+use reth_primitives::{keccak256, TransitionId};
+use std::ops::RangeInclusive;
+use tracing::*;
+
+pub(crate) const ACCOUNT_HASHING: StageId = StageId("AccountHashing");
+
+/// The account hashing stage hashes the addresses of the plain account state and stores the
+/// accounts in [`HashedAccount`][tables::HashedAccount], which is used by the merkle stage to
+/// compute the state root.
+///
+/// On the first sync, or if too many blocks need to be processed, the table is rebuilt from the
+/// whole [`PlainAccountState`][tables::PlainAccountState]. Otherwise only the accounts found in the
+/// [`AccountChangeSet`][tables::AccountChangeSet] of the executed blocks are rehashed.
+#[derive(Debug)]
+pub struct AccountHashingStage {
+    /// The number of blocks above which the hashed state is rebuilt from scratch instead of being
+    /// updated from the changesets.
+    pub clean_threshold: u64,
+}
+
+impl Default for AccountHashingStage {
+    fn default() -> Self {
+        Self { clean_threshold: 500_000 }
+    }
+}
+
+#[async_trait::async_trait]
+impl<DB: Database> Stage<DB> for AccountHashingStage {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        ACCOUNT_HASHING
+    }
+
+    /// Execute the stage.
+    async fn execute(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        let (start_block, end_block) = match input.next_action(None) {
+            ExecAction::Run { range, .. } => range.into_inner(),
+            ExecAction::Done { stage_progress, target } => {
+                info!(target: "sync::stages::hashing_account", stage_progress, target, "Target block already reached");
+                return Ok(ExecOutput { stage_progress, done: true })
+            }
+        };
+        let stage_progress = start_block - 1;
+
+        if input.should_rebuild(self.clean_threshold) {
+            debug!(target: "sync::stages::hashing_account", start_block, end_block, "Rebuilding hashed accounts");
+            tx.clear::<tables::HashedAccount>()?;
+
+            let mut accounts = tx.cursor::<tables::PlainAccountState>()?;
+            let mut walker = accounts.walk(Default::default())?;
+            while let Some((address, account)) = walker.next().transpose()? {
+                tx.put::<tables::HashedAccount>(keccak256(address), account)?;
+            }
+        } else {
+            let range = tx.get_transition_range(stage_progress, end_block)?;
+            debug!(target: "sync::stages::hashing_account", start_block, end_block, ?range, "Hashing changed accounts");
+            hash_account_changes(tx, range)?;
+        }
+
+        info!(target: "sync::stages::hashing_account", stage_progress = end_block, "Sync iteration finished");
+        Ok(ExecOutput { stage_progress: end_block, done: true })
+    }
+
+    /// Unwind the stage.
+    async fn unwind(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        info!(target: "sync::stages::hashing_account", to_block = input.unwind_to, "Unwinding");
+        if input.unwind_to >= input.stage_progress {
+            return Ok(UnwindOutput { stage_progress: input.unwind_to })
+        }
+
+        // Restore the state of the changed accounts before the unwound blocks
+        let range = tx.get_transition_range(input.unwind_to, input.stage_progress)?;
+        for (address, info) in tx.get_account_changes(range)? {
+            let hashed_address = keccak256(address);
+            match info {
+                Some(account) => tx.put::<tables::HashedAccount>(hashed_address, account)?,
+                None => {
+                    tx.delete::<tables::HashedAccount>(hashed_address, None)?;
+                }
+            }
+        }
+
+        Ok(UnwindOutput { stage_progress: input.unwind_to })
+    }
+}
+
+/// Rehashes the accounts that changed in the given transition range from the plain account state.
+pub(crate) fn hash_account_changes<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    range: RangeInclusive<TransitionId>,
+) -> Result<(), DbError> {
+    for address in tx.get_account_changes(range)?.into_keys() {
+        let hashed_address = keccak256(address);
+        match tx.get::<tables::PlainAccountState>(address)? {
+            Some(account) => tx.put::<tables::HashedAccount>(hashed_address, account)?,
+            None => {
+                tx.delete::<tables::HashedAccount>(hashed_address, None)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        stage_test_suite_ext, ExecuteStageTestRunner, StageTestRunner, TestRunnerError,
+        TestTransaction, UnwindStageTestRunner, PREV_STAGE_ID,
+    };
+    use assert_matches::assert_matches;
+    use reth_db::models::AccountBeforeTx;
+    use reth_primitives::{Account, Address, H256, U256};
+    use std::collections::BTreeMap;
+
+    stage_test_suite_ext!(AccountHashingTestRunner);
+
+    fn account(nonce: u64, balance: u64) -> Account {
+        Account { nonce, balance: U256::from(balance), bytecode_hash: None }
+    }
+
+    #[tokio::test]
+    async fn execute_rebuilds_hashed_accounts() {
+        let tx = TestTransaction::default();
+        tx.insert_blocks_with_transitions(1).expect("failed to insert blocks");
+        let (alice, bob) = (Address::random(), Address::random());
+        tx.commit(|tx| {
+            tx.put::<tables::PlainAccountState>(alice, account(1, 10))?;
+            tx.put::<tables::PlainAccountState>(bob, account(2, 20))
+        })
+        .expect("failed to insert accounts");
+
+        let mut db_tx = tx.inner();
+        let input = ExecInput { previous_stage: Some((PREV_STAGE_ID, 1)), stage_progress: None };
+        let output = AccountHashingStage::default().execute(&mut db_tx, input).await;
+        assert_matches!(output, Ok(ExecOutput { stage_progress: 1, done: true }));
+        assert_eq!(db_tx.get::<tables::HashedAccount>(keccak256(alice)), Ok(Some(account(1, 10))));
+        assert_eq!(db_tx.get::<tables::HashedAccount>(keccak256(bob)), Ok(Some(account(2, 20))));
+    }
+
+    #[tokio::test]
+    async fn execute_and_unwind_changed_accounts() {
+        let tx = TestTransaction::default();
+        tx.insert_blocks_with_transitions(2).expect("failed to insert blocks");
+        let (alice, bob) = (Address::random(), Address::random());
+
+        // State after block 1: only alice exists
+        tx.commit(|tx| {
+            tx.put::<tables::PlainAccountState>(alice, account(1, 10))?;
+            tx.put::<tables::HashedAccount>(keccak256(alice), account(1, 10))
+        })
+        .expect("failed to insert state");
+
+        // Block 2 updates alice and creates bob
+        tx.commit(|tx| {
+            tx.put::<tables::AccountChangeSet>(
+                2,
+                AccountBeforeTx { address: alice, info: Some(account(1, 10)) },
+            )?;
+            tx.put::<tables::AccountChangeSet>(2, AccountBeforeTx { address: bob, info: None })?;
+            tx.put::<tables::PlainAccountState>(alice, account(2, 5))?;
+            tx.put::<tables::PlainAccountState>(bob, account(0, 5))
+        })
+        .expect("failed to insert changes");
+
+        let mut stage = AccountHashingStage::default();
+        let mut db_tx = tx.inner();
+        let input = ExecInput { previous_stage: Some((PREV_STAGE_ID, 2)), stage_progress: Some(1) };
+        let output = stage.execute(&mut db_tx, input).await;
+        assert_matches!(output, Ok(ExecOutput { stage_progress: 2, done: true }));
+        assert_eq!(db_tx.get::<tables::HashedAccount>(keccak256(alice)), Ok(Some(account(2, 5))));
+        assert_eq!(db_tx.get::<tables::HashedAccount>(keccak256(bob)), Ok(Some(account(0, 5))));
+
+        let input = UnwindInput { stage_progress: 2, unwind_to: 1, bad_block: None };
+        let output = stage.unwind(&mut db_tx, input).await;
+        assert_matches!(output, Ok(UnwindOutput { stage_progress: 1 }));
+        assert_eq!(db_tx.get::<tables::HashedAccount>(keccak256(alice)), Ok(Some(account(1, 10))));
+        assert_eq!(db_tx.get::<tables::HashedAccount>(keccak256(bob)), Ok(None));
+    }
+
+    #[derive(Default)]
+    struct AccountHashingTestRunner {
+        tx: TestTransaction,
+    }
+
+    impl StageTestRunner for AccountHashingTestRunner {
+        type S = AccountHashingStage;
+
+        fn tx(&self) -> &TestTransaction {
+            &self.tx
+        }
+
+        fn stage(&self) -> Self::S {
+            AccountHashingStage::default()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ExecuteStageTestRunner for AccountHashingTestRunner {
+        type Seed = ();
+
+        fn seed_execution(&mut self, input: ExecInput) -> Result<Self::Seed, TestRunnerError> {
+            let stage_progress = input.stage_progress.unwrap_or_default();
+            let end = input.previous_stage_progress();
+            self.tx.insert_blocks_with_transitions(end)?;
+
+            // Create a new account in every block to execute
+            self.tx.commit(|tx| {
+                for block in stage_progress + 1..=end {
+                    let address = Address::random();
+                    tx.put::<tables::AccountChangeSet>(
+                        block,
+                        AccountBeforeTx { address, info: None },
+                    )?;
+                    tx.put::<tables::PlainAccountState>(address, account(block, block))?;
+                }
+                Ok(())
+            })?;
+            Ok(())
+        }
+
+        fn validate_execution(
+            &self,
+            _input: ExecInput,
+            _output: Option<ExecOutput>,
+        ) -> Result<(), TestRunnerError> {
+            self.check_hashed_accounts()
+        }
+    }
+
+    impl UnwindStageTestRunner for AccountHashingTestRunner {
+        fn validate_unwind(&self, _input: UnwindInput) -> Result<(), TestRunnerError> {
+            self.check_hashed_accounts()
+        }
+    }
+
+    impl AccountHashingTestRunner {
+        /// Check that the hashed accounts match the plain account state.
+        fn check_hashed_accounts(&self) -> Result<(), TestRunnerError> {
+            self.tx.query(|tx| {
+                let mut plain_accounts = tx.cursor::<tables::PlainAccountState>()?;
+                let mut expected = BTreeMap::new();
+                for entry in plain_accounts.walk(Address::zero())? {
+                    let (address, account) = entry?;
+                    expected.insert(keccak256(address), account);
+                }
+
+                let mut hashed_accounts = tx.cursor::<tables::HashedAccount>()?;
+                let hashed =
+                    hashed_accounts.walk(H256::zero())?.collect::<Result<BTreeMap<_, _>, _>>()?;
+                assert_eq!(hashed, expected);
+                Ok(())
+            })?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{
+    db::Transaction, ExecAction, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput,
+    UnwindOutput,
+};
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    Error as DbError,
+};
+use reth_primitives::{keccak256, Address, StorageEntry, TransitionId, H256, U256};
+use std::{collections::BTreeMap, ops::RangeInclusive};
+use tracing::*;
+
+pub(crate) const STORAGE_HASHING: StageId = StageId("StorageHashing");
+
+/// The storage hashing stage hashes the addresses and the slots of the plain storage state and
+/// stores the storage entries in [`HashedStorage`][tables::HashedStorage], which is used by the
+/// merkle stage to compute the storage roots.
+///
+/// On the first sync, or if too many blocks need to be processed, the table is rebuilt from the
+/// whole [`PlainStorageState`][tables::PlainStorageState]. Otherwise only the slots found in the
+/// [`StorageChangeSet`][tables::StorageChangeSet] of the executed blocks are rehashed.
+#[derive(Debug)]
+pub struct StorageHashingStage {
+    /// The number of blocks above which the hashed storage is rebuilt from scratch instead of
+    /// being updated from the changesets.
+    pub clean_threshold: u64,
+}
+
+impl Default for StorageHashingStage {
+    fn default() -> Self {
+        Self { clean_threshold: 500_000 }
+    }
+}
+
+#[async_trait::async_trait]
+impl<DB: Database> Stage<DB> for StorageHashingStage {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        STORAGE_HASHING
+    }
+
+    /// Execute the stage.
+    async fn execute(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        let (start_block, end_block) = match input.next_action(None) {
+            ExecAction::Run { range, .. } => range.into_inner(),
+            ExecAction::Done { stage_progress, target } => {
+                info!(target: "sync::stages::hashing_storage", stage_progress, target, "Target block already reached");
+                return Ok(ExecOutput { stage_progress, done: true })
+            }
+        };
+        let stage_progress = start_block - 1;
+
+        if input.should_rebuild(self.clean_threshold) {
+            debug!(target: "sync::stages::hashing_storage", start_block, end_block, "Rebuilding hashed storage");
+            tx.clear::<tables::HashedStorage>()?;
+
+            let mut storage = tx.cursor_dup::<tables::PlainStorageState>()?;
+            let mut walker = storage.walk(Default::default())?;
+            while let Some((address, StorageEntry { key, value })) = walker.next().transpose()? {
+                tx.put::<tables::HashedStorage>(
+                    keccak256(address),
+                    StorageEntry { key: keccak256(key), value },
+                )?;
+            }
+        } else {
+            let range = tx.get_transition_range(stage_progress, end_block)?;
+            debug!(target: "sync::stages::hashing_storage", start_block, end_block, ?range, "Hashing changed storage");
+            hash_storage_changes(tx, range)?;
+        }
+
+        info!(target: "sync::stages::hashing_storage", stage_progress = end_block, "Sync iteration finished");
+        Ok(ExecOutput { stage_progress: end_block, done: true })
+    }
+
+    /// Unwind the stage.
+    async fn unwind(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        info!(target: "sync::stages::hashing_storage", to_block = input.unwind_to, "Unwinding");
+        if input.unwind_to >= input.stage_progress {
+            return Ok(UnwindOutput { stage_progress: input.unwind_to })
+        }
+
+        // Restore the values of the changed slots before the unwound blocks
+        let range = tx.get_transition_range(input.unwind_to, input.stage_progress)?;
+        let changes = tx.get_storage_changes(range)?;
+        write_hashed_storage(tx, changes)?;
+
+        Ok(UnwindOutput { stage_progress: input.unwind_to })
+    }
+}
+
+/// Rehashes the storage slots that changed in the given transition range from the plain storage
+/// state.
+pub(crate) fn hash_storage_changes<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    range: RangeInclusive<TransitionId>,
+) -> Result<(), DbError> {
+    let mut plain_storage = tx.cursor_dup::<tables::PlainStorageState>()?;
+    let mut changes = BTreeMap::<Address, BTreeMap<H256, U256>>::new();
+    for (address, slots) in tx.get_storage_changes(range)? {
+        let current = changes.entry(address).or_default();
+        for slot in slots.into_keys() {
+            let value = plain_storage
+                .seek_by_key_subkey(address, slot)?
+                .filter(|entry| entry.key == slot)
+                .map(|entry| entry.value)
+                .unwrap_or(U256::ZERO);
+            current.insert(slot, value);
+        }
+    }
+    write_hashed_storage(tx, changes)
+}
+
+/// Set the hashed storage slots of the given accounts to the given values, removing the slots
+/// with a zero value.
+fn write_hashed_storage<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    storages: BTreeMap<Address, BTreeMap<H256, U256>>,
+) -> Result<(), DbError> {
+    let mut hashed_storage = tx.cursor_dup_mut::<tables::HashedStorage>()?;
+    for (address, slots) in storages {
+        let hashed_address = keccak256(address);
+        for (slot, value) in slots {
+            let hashed_slot = keccak256(slot);
+            if hashed_storage
+                .seek_by_key_subkey(hashed_address, hashed_slot)?
+                .filter(|entry| entry.key == hashed_slot)
+                .is_some()
+            {
+                hashed_storage.delete_current()?;
+            }
+
+            if value != U256::ZERO {
+                hashed_storage.upsert(hashed_address, StorageEntry { key: hashed_slot, value })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        stage_test_suite_ext, ExecuteStageTestRunner, StageTestRunner, TestRunnerError,
+        TestTransaction, UnwindStageTestRunner, PREV_STAGE_ID,
+    };
+    use assert_matches::assert_matches;
+
+    stage_test_suite_ext!(StorageHashingTestRunner);
+
+    fn hashed_value<DB: Database>(
+        tx: &Transaction<'_, DB>,
+        address: Address,
+        slot: H256,
+    ) -> Option<U256> {
+        let hashed_slot = keccak256(slot);
+        tx.cursor_dup::<tables::HashedStorage>()
+            .unwrap()
+            .seek_by_key_subkey(keccak256(address), hashed_slot)
+            .unwrap()
+            .filter(|entry| entry.key == hashed_slot)
+            .map(|entry| entry.value)
+    }
+
+    #[tokio::test]
+    async fn execute_rebuilds_hashed_storage() {
+        let tx = TestTransaction::default();
+        tx.insert_blocks_with_transitions(1).expect("failed to insert blocks");
+        let address = Address::random();
+        let (slot1, slot2) = (H256::random(), H256::random());
+        tx.commit(|tx| {
+            tx.put::<tables::PlainStorageState>(
+                address,
+                StorageEntry { key: slot1, value: U256::from(1) },
+            )?;
+            tx.put::<tables::PlainStorageState>(
+                address,
+                StorageEntry { key: slot2, value: U256::from(2) },
+            )
+        })
+        .expect("failed to insert storage");
+
+        let mut db_tx = tx.inner();
+        let input = ExecInput { previous_stage: Some((PREV_STAGE_ID, 1)), stage_progress: None };
+        let output = StorageHashingStage::default().execute(&mut db_tx, input).await;
+        assert_matches!(output, Ok(ExecOutput { stage_progress: 1, done: true }));
+        assert_eq!(hashed_value(&db_tx, address, slot1), Some(U256::from(1)));
+        assert_eq!(hashed_value(&db_tx, address, slot2), Some(U256::from(2)));
+    }
+
+    #[tokio::test]
+    async fn execute_and_unwind_changed_storage() {
+        let tx = TestTransaction::default();
+        tx.insert_blocks_with_transitions(2).expect("failed to insert blocks");
+        let address = Address::random();
+        let (updated, created, cleared) = (H256::random(), H256::random(), H256::random());
+
+        // Storage after block 1
+        tx.commit(|tx| {
+            for (slot, value) in [(updated, 1), (cleared, 3)] {
+                let value = U256::from(value);
+                tx.put::<tables::PlainStorageState>(address, StorageEntry { key: slot, value })?;
+                tx.put::<tables::HashedStorage>(
+                    keccak256(address),
+                    StorageEntry { key: keccak256(slot), value },
+                )?;
+            }
+            Ok(())
+        })
+        .expect("failed to insert storage");
+
+        // Block 2 updates one slot, creates another one and clears the last one
+        tx.commit(|tx| {
+            let key = (2, address).into();
+            for (slot, value) in [(updated, 1), (created, 0), (cleared, 3)] {
+                let entry = StorageEntry { key: slot, value: U256::from(value) };
+                tx.put::<tables::StorageChangeSet>(key, entry)?;
+            }
+            tx.delete::<tables::PlainStorageState>(
+                address,
+                Some(StorageEntry { key: updated, value: U256::from(1) }),
+            )?;
+            tx.delete::<tables::PlainStorageState>(
+                address,
+                Some(StorageEntry { key: cleared, value: U256::from(3) }),
+            )?;
+            tx.put::<tables::PlainStorageState>(
+                address,
+                StorageEntry { key: updated, value: U256::from(10) },
+            )?;
+            tx.put::<tables::PlainStorageState>(
+                address,
+                StorageEntry { key: created, value: U256::from(20) },
+            )
+        })
+        .expect("failed to insert changes");
+
+        let mut stage = StorageHashingStage::default();
+        let mut db_tx = tx.inner();
+        let input = ExecInput { previous_stage: Some((PREV_STAGE_ID, 2)), stage_progress: Some(1) };
+        let output = stage.execute(&mut db_tx, input).await;
+        assert_matches!(output, Ok(ExecOutput { stage_progress: 2, done: true }));
+        assert_eq!(hashed_value(&db_tx, address, updated), Some(U256::from(10)));
+        assert_eq!(hashed_value(&db_tx, address, created), Some(U256::from(20)));
+        assert_eq!(hashed_value(&db_tx, address, cleared), None);
+
+        let input = UnwindInput { stage_progress: 2, unwind_to: 1, bad_block: None };
+        let output = stage.unwind(&mut db_tx, input).await;
+        assert_matches!(output, Ok(UnwindOutput { stage_progress: 1 }));
+        assert_eq!(hashed_value(&db_tx, address, updated), Some(U256::from(1)));
+        assert_eq!(hashed_value(&db_tx, address, created), None);
+        assert_eq!(hashed_value(&db_tx, address, cleared), Some(U256::from(3)));
+    }
+
+    #[derive(Default)]
+    struct StorageHashingTestRunner {
+        tx: TestTransaction,
+    }
+
+    impl StageTestRunner for StorageHashingTestRunner {
+        type S = StorageHashingStage;
+
+        fn tx(&self) -> &TestTransaction {
+            &self.tx
+        }
+
+        fn stage(&self) -> Self::S {
+            StorageHashingStage::default()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ExecuteStageTestRunner for StorageHashingTestRunner {
+        type Seed = ();
+
+        fn seed_execution(&mut self, input: ExecInput) -> Result<Self::Seed, TestRunnerError> {
+            let stage_progress = input.stage_progress.unwrap_or_default();
+            let end = input.previous_stage_progress();
+            self.tx.insert_blocks_with_transitions(end)?;
+
+            // Set a new storage slot in every block to execute
+            self.tx.commit(|tx| {
+                for block in stage_progress + 1..=end {
+                    let (address, slot) = (Address::random(), H256::random());
+                    let entry = StorageEntry { key: slot, value: U256::ZERO };
+                    tx.put::<tables::StorageChangeSet>((block, address).into(), entry)?;
+                    let entry = StorageEntry { key: slot, value: U256::from(block) };
+                    tx.put::<tables::PlainStorageState>(address, entry)?;
+                }
+                Ok(())
+            })?;
+            Ok(())
+        }
+
+        fn validate_execution(
+            &self,
+            _input: ExecInput,
+            _output: Option<ExecOutput>,
+        ) -> Result<(), TestRunnerError> {
+            self.check_hashed_storage()
+        }
+    }
+
+    impl UnwindStageTestRunner for StorageHashingTestRunner {
+        fn validate_unwind(&self, _input: UnwindInput) -> Result<(), TestRunnerError> {
+            self.check_hashed_storage()
+        }
+    }
+
+    impl StorageHashingTestRunner {
+        /// Check that the hashed storage matches the plain storage state.
+        fn check_hashed_storage(&self) -> Result<(), TestRunnerError> {
+            self.tx.query(|tx| {
+                let mut plain_storage = tx.cursor_dup::<tables::PlainStorageState>()?;
+                let mut expected = BTreeMap::new();
+                for entry in plain_storage.walk(Address::zero())? {
+                    let (address, StorageEntry { key, value }) = entry?;
+                    expected.insert((keccak256(address), keccak256(key)), value);
+                }
+
+                let mut hashed_storage = tx.cursor_dup::<tables::HashedStorage>()?;
+                let mut hashed = BTreeMap::new();
+                for entry in hashed_storage.walk(H256::zero())? {
+                    let (hashed_address, StorageEntry { key, value }) = entry?;
+                    hashed.insert((hashed_address, key), value);
+                }
+                assert_eq!(hashed, expected);
+                Ok(())
+            })?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{
+    db::Transaction, exec_or_return, ExecAction, ExecInput, ExecOutput, Stage, StageError, StageId,
+    UnwindInput, UnwindOutput,
+};
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::{AccountBeforeTx, ShardedKey},
+    tables,
+    transaction::DbTx,
+    Error as DbError,
+};
+use reth_primitives::{Address, TransitionId, INDEX_ACCOUNT_HISTORY_STAGE_ID};
+use std::{collections::BTreeMap, ops::RangeInclusive};
+use tracing::*;
+
+pub(crate) const INDEX_ACCOUNT_HISTORY: StageId = StageId(INDEX_ACCOUNT_HISTORY_STAGE_ID);
+
+/// The account history indexing stage walks the [`AccountChangeSet`][tables::AccountChangeSet]
+/// of the executed blocks and appends the transition ids at which each account changed to the
+/// [`AccountHistory`][tables::AccountHistory] index.
+#[derive(Debug)]
+pub struct IndexAccountHistoryStage {
+    /// The number of blocks after which the control
+    /// flow will be returned to the pipeline for commit.
+    pub commit_threshold: u64,
+}
+
+impl Default for IndexAccountHistoryStage {
+    fn default() -> Self {
+        Self { commit_threshold: 100_000 }
+    }
+}
+
+#[async_trait::async_trait]
+impl<DB: Database> Stage<DB> for IndexAccountHistoryStage {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        INDEX_ACCOUNT_HISTORY
+    }
+
+    /// Execute the stage.
+    async fn execute(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        let ((start_block, end_block), capped) =
+            exec_or_return!(input, self.commit_threshold, "sync::stages::index_account_history");
+
+        let range = tx.get_transition_range(start_block - 1, end_block)?;
+        let accounts = index_account_changes(tx, range)?;
+        debug!(target: "sync::stages::index_account_history", start_block, end_block, accounts, "Indexed account history");
+
+        let done = !capped;
+        info!(target: "sync::stages::index_account_history", stage_progress = end_block, done, "Sync iteration finished");
+        Ok(ExecOutput { stage_progress: end_block, done })
+    }
+
+    /// Unwind the stage.
+    async fn unwind(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        info!(target: "sync::stages::index_account_history", to_block = input.unwind_to, "Unwinding");
+        if input.unwind_to >= input.stage_progress {
+            return Ok(UnwindOutput { stage_progress: input.unwind_to })
+        }
+
+        // Find the first unwound transition of each account
+        let (from_transition, to_transition) =
+            tx.get_transition_range(input.unwind_to, input.stage_progress)?.into_inner();
+        let mut indices = BTreeMap::<Address, TransitionId>::new();
+        let mut changesets = tx.cursor_dup::<tables::AccountChangeSet>()?;
+        let mut walker = changesets.walk(from_transition)?;
+        while let Some((transition_id, AccountBeforeTx { address, .. })) =
+            walker.next().transpose()?
+        {
+            if transition_id > to_transition {
+                break
+            }
+            indices.entry(address).or_insert(transition_id);
+        }
+
+        tx.unwind_history_index::<tables::AccountHistory, _, _, _>(
+            indices,
+            ShardedKey::new,
+            |key| key.key,
+        )?;
+
+        Ok(UnwindOutput { stage_progress: input.unwind_to })
+    }
+}
+
+/// Appends the transition ids of the account changes in the given transition range to the
+/// [`AccountHistory`][tables::AccountHistory] index.
+///
+/// Returns the number of changed accounts.
+pub(crate) fn index_account_changes<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    range: RangeInclusive<TransitionId>,
+) -> Result<usize, DbError> {
+    let (from_transition, to_transition) = range.into_inner();
+
+    // Collect the transition ids of each changed account
+    let mut indices = BTreeMap::<Address, Vec<TransitionId>>::new();
+    let mut changesets = tx.cursor_dup::<tables::AccountChangeSet>()?;
+    let mut walker = changesets.walk(from_transition)?;
+    while let Some((transition_id, AccountBeforeTx { address, .. })) = walker.next().transpose()? {
+        if transition_id > to_transition {
+            break
+        }
+        indices.entry(address).or_default().push(transition_id);
+    }
+
+    let accounts = indices.len();
+    tx.insert_history_index::<tables::AccountHistory, _, _>(indices, ShardedKey::new)?;
+    Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        stage_test_suite_ext, ExecuteStageTestRunner, StageTestRunner, TestRunnerError,
+        TestTransaction, UnwindStageTestRunner, PREV_STAGE_ID,
+    };
+    use assert_matches::assert_matches;
+    use reth_db::{models::NUM_OF_INDICES_IN_SHARD, transaction::DbTxMut};
+
+    stage_test_suite_ext!(IndexAccountHistoryTestRunner);
+
+    /// Change the account in every block from `from` to `to`.
+    fn insert_changes(
+        tx: &TestTransaction,
+        from: u64,
+        to: u64,
+        address: Address,
+    ) -> Result<(), DbError> {
+        tx.commit(|tx| {
+            (from..=to).try_for_each(|block| {
+                tx.put::<tables::AccountChangeSet>(block, AccountBeforeTx { address, info: None })
+            })
+        })
+    }
+
+    fn shards<DB: Database>(tx: &Transaction<'_, DB>) -> Vec<(TransitionId, Vec<usize>)> {
+        tx.cursor::<tables::AccountHistory>()
+            .unwrap()
+            .walk(ShardedKey::default())
+            .unwrap()
+            .map(|entry| {
+                let (key, list) = entry.unwrap();
+                (key.highest_tx_number, list.iter(0).collect())
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn execute_and_unwind_history() {
+        let tx = TestTransaction::default();
+        let address = Address::random();
+        let tip = NUM_OF_INDICES_IN_SHARD as u64 + 10;
+        tx.insert_blocks_with_transitions(tip).expect("failed to insert blocks");
+        insert_changes(&tx, 0, tip, address).expect("failed to insert changes");
+
+        let mut stage = IndexAccountHistoryStage::default();
+        let mut db_tx = tx.inner();
+
+        // Index the history in two runs to test the extension of the last shard
+        for (stage_progress, previous_stage) in [(None, 10), (Some(10), tip)] {
+            let input =
+                ExecInput { previous_stage: Some((PREV_STAGE_ID, previous_stage)), stage_progress };
+            let output = stage.execute(&mut db_tx, input).await;
+            assert_matches!(output, Ok(ExecOutput { stage_progress, done: true }) if stage_progress == previous_stage);
+        }
+        let full_shard = (1..=NUM_OF_INDICES_IN_SHARD).collect::<Vec<_>>();
+        assert_eq!(
+            shards(&db_tx),
+            vec![
+                (NUM_OF_INDICES_IN_SHARD as u64, full_shard.clone()),
+                (u64::MAX, (NUM_OF_INDICES_IN_SHARD + 1..=tip as usize).collect())
+            ]
+        );
+
+        // Unwind into the first shard
+        let input = UnwindInput { stage_progress: tip, unwind_to: 50, bad_block: None };
+        let output = stage.unwind(&mut db_tx, input).await;
+        assert_matches!(output, Ok(UnwindOutput { stage_progress: 50 }));
+        assert_eq!(shards(&db_tx), vec![(u64::MAX, (1..=50).collect())]);
+
+        // Unwind the whole history
+        let input = UnwindInput { stage_progress: 50, unwind_to: 0, bad_block: None };
+        let output = stage.unwind(&mut db_tx, input).await;
+        assert_matches!(output, Ok(UnwindOutput { stage_progress: 0 }));
+        assert_eq!(shards(&db_tx), vec![]);
+    }
+
+    #[derive(Default)]
+    struct IndexAccountHistoryTestRunner {
+        tx: TestTransaction,
+    }
+
+    impl StageTestRunner for IndexAccountHistoryTestRunner {
+        type S = IndexAccountHistoryStage;
+
+        fn tx(&self) -> &TestTransaction {
+            &self.tx
+        }
+
+        fn stage(&self) -> Self::S {
+            IndexAccountHistoryStage::default()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ExecuteStageTestRunner for IndexAccountHistoryTestRunner {
+        type Seed = ();
+
+        fn seed_execution(&mut self, input: ExecInput) -> Result<Self::Seed, TestRunnerError> {
+            let stage_progress = input.stage_progress.unwrap_or_default();
+            let end = input.previous_stage_progress();
+            self.tx.insert_blocks_with_transitions(end)?;
+            insert_changes(&self.tx, stage_progress + 1, end, Address::random())?;
+            Ok(())
+        }
+
+        fn validate_execution(
+            &self,
+            _input: ExecInput,
+            _output: Option<ExecOutput>,
+        ) -> Result<(), TestRunnerError> {
+            self.check_history()
+        }
+    }
+
+    impl UnwindStageTestRunner for IndexAccountHistoryTestRunner {
+        fn validate_unwind(&self, _input: UnwindInput) -> Result<(), TestRunnerError> {
+            self.check_history()
+        }
+    }
+
+    impl IndexAccountHistoryTestRunner {
+        /// Check that the history index contains exactly the changesets of each account.
+        fn check_history(&self) -> Result<(), TestRunnerError> {
+            self.tx.query(|tx| {
+                let mut changesets = tx.cursor_dup::<tables::AccountChangeSet>()?;
+                let mut expected = BTreeMap::<Address, Vec<usize>>::new();
+                for entry in changesets.walk(0)? {
+                    let (transition_id, AccountBeforeTx { address, .. }) = entry?;
+                    expected.entry(address).or_default().push(transition_id as usize);
+                }
+
+                let mut history = tx.cursor::<tables::AccountHistory>()?;
+                let mut indexed = BTreeMap::<Address, Vec<usize>>::new();
+                for entry in history.walk(ShardedKey::default())? {
+                    let (key, list) = entry?;
+                    indexed.entry(key.key).or_default().extend(list.iter(0));
+                }
+                assert_eq!(indexed, expected);
+                Ok(())
+            })?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use crate::{
+    db::Transaction, exec_or_return, ExecAction, ExecInput, ExecOutput, Stage, StageError, StageId,
+    UnwindInput, UnwindOutput,
+};
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::{StorageShardedKey, TransitionIdAddress},
+    tables,
+    transaction::DbTx,
+    Error as DbError,
+};
+use reth_primitives::{Address, StorageEntry, TransitionId, H256, INDEX_STORAGE_HISTORY_STAGE_ID};
+use std::{collections::BTreeMap, ops::RangeInclusive};
+use tracing::*;
+
+pub(crate) const INDEX_STORAGE_HISTORY: StageId = StageId(INDEX_STORAGE_HISTORY_STAGE_ID);
+
+/// The storage history indexing stage walks the [`StorageChangeSet`][tables::StorageChangeSet]
+/// of the executed blocks and appends the transition ids at which each storage slot changed to
+/// the [`StorageHistory`][tables::StorageHistory] index.
+#[derive(Debug)]
+pub struct IndexStorageHistoryStage {
+    /// The number of blocks after which the control
+    /// flow will be returned to the pipeline for commit.
+    pub commit_threshold: u64,
+}
+
+impl Default for IndexStorageHistoryStage {
+    fn default() -> Self {
+        Self { commit_threshold: 100_000 }
+    }
+}
+
+#[async_trait::async_trait]
+impl<DB: Database> Stage<DB> for IndexStorageHistoryStage {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        INDEX_STORAGE_HISTORY
+    }
+
+    /// Execute the stage.
+    async fn execute(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        let ((start_block, end_block), capped) =
+            exec_or_return!(input, self.commit_threshold, "sync::stages::index_storage_history");
+
+        let range = tx.get_transition_range(start_block - 1, end_block)?;
+        let slots = index_storage_changes(tx, range)?;
+        debug!(target: "sync::stages::index_storage_history", start_block, end_block, slots, "Indexed storage history");
+
+        let done = !capped;
+        info!(target: "sync::stages::index_storage_history", stage_progress = end_block, done, "Sync iteration finished");
+        Ok(ExecOutput { stage_progress: end_block, done })
+    }
+
+    /// Unwind the stage.
+    async fn unwind(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        info!(target: "sync::stages::index_storage_history", to_block = input.unwind_to, "Unwinding");
+        if input.unwind_to >= input.stage_progress {
+            return Ok(UnwindOutput { stage_progress: input.unwind_to })
+        }
+
+        // Find the first unwound transition of each storage slot
+        let (from_transition, to_transition) =
+            tx.get_transition_range(input.unwind_to, input.stage_progress)?.into_inner();
+        let mut indices = BTreeMap::<(Address, H256), TransitionId>::new();
+        let mut changesets = tx.cursor_dup::<tables::StorageChangeSet>()?;
+        let mut walker =
+            changesets.walk(TransitionIdAddress((from_transition, Address::zero())))?;
+        while let Some((key, StorageEntry { key: slot, .. })) = walker.next().transpose()? {
+            if key.transition_id() > to_transition {
+                break
+            }
+            indices.entry((key.address(), slot)).or_insert(key.transition_id());
+        }
+
+        tx.unwind_history_index::<tables::StorageHistory, _, _, _>(
+            indices,
+            |(address, slot), highest| StorageShardedKey::new(address, slot, highest),
+            |key| (key.address, key.sharded_key.key),
+        )?;
+
+        Ok(UnwindOutput { stage_progress: input.unwind_to })
+    }
+}
+
+/// Appends the transition ids of the storage changes in the given transition range to the
+/// [`StorageHistory`][tables::StorageHistory] index.
+///
+/// Returns the number of changed storage slots.
+pub(crate) fn index_storage_changes<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    range: RangeInclusive<TransitionId>,
+) -> Result<usize, DbError> {
+    let (from_transition, to_transition) = range.into_inner();
+
+    // Collect the transition ids of each changed storage slot
+    let mut indices = BTreeMap::<(Address, H256), Vec<TransitionId>>::new();
+    let mut changesets = tx.cursor_dup::<tables::StorageChangeSet>()?;
+    let mut walker = changesets.walk(TransitionIdAddress((from_transition, Address::zero())))?;
+    while let Some((key, StorageEntry { key: slot, .. })) = walker.next().transpose()? {
+        if key.transition_id() > to_transition {
+            break
+        }
+        indices.entry((key.address(), slot)).or_default().push(key.transition_id());
+    }
+
+    let slots = indices.len();
+    tx.insert_history_index::<tables::StorageHistory, _, _>(
+        indices,
+        |(address, slot), highest| StorageShardedKey::new(address, slot, highest),
+    )?;
+    Ok(slots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        stage_test_suite_ext, ExecuteStageTestRunner, StageTestRunner, TestRunnerError,
+        TestTransaction, UnwindStageTestRunner, PREV_STAGE_ID,
+    };
+    use assert_matches::assert_matches;
+    use reth_db::transaction::DbTxMut;
+    use reth_primitives::U256;
+
+    stage_test_suite_ext!(IndexStorageHistoryTestRunner);
+
+    fn indices<DB: Database>(
+        tx: &Transaction<'_, DB>,
+        address: Address,
+        slot: H256,
+    ) -> Option<Vec<usize>> {
+        tx.get::<tables::StorageHistory>(StorageShardedKey::new(address, slot, u64::MAX))
+            .unwrap()
+            .map(|list| list.iter(0).collect())
+    }
+
+    #[tokio::test]
+    async fn execute_and_unwind_history() {
+        let tx = TestTransaction::default();
+        let address = Address::random();
+        let (slot1, slot2) = (H256::random(), H256::random());
+
+        // The first slot changes in every block and the second one only in block 2
+        tx.insert_blocks_with_transitions(3).expect("failed to insert blocks");
+        tx.commit(|tx| {
+            for block in 0..=3 {
+                let entry = StorageEntry { key: slot1, value: U256::ZERO };
+                tx.put::<tables::StorageChangeSet>((block, address).into(), entry)?;
+            }
+            let entry = StorageEntry { key: slot2, value: U256::ZERO };
+            tx.put::<tables::StorageChangeSet>((2, address).into(), entry)
+        })
+        .expect("failed to insert blocks");
+
+        let mut stage = IndexStorageHistoryStage::default();
+        let mut db_tx = tx.inner();
+        let input = ExecInput { previous_stage: Some((PREV_STAGE_ID, 3)), stage_progress: None };
+        let output = stage.execute(&mut db_tx, input).await;
+        assert_matches!(output, Ok(ExecOutput { stage_progress: 3, done: true }));
+        assert_eq!(indices(&db_tx, address, slot1), Some(vec![1, 2, 3]));
+        assert_eq!(indices(&db_tx, address, slot2), Some(vec![2]));
+
+        let input = UnwindInput { stage_progress: 3, unwind_to: 1, bad_block: None };
+        let output = stage.unwind(&mut db_tx, input).await;
+        assert_matches!(output, Ok(UnwindOutput { stage_progress: 1 }));
+        assert_eq!(indices(&db_tx, address, slot1), Some(vec![1]));
+        assert_eq!(indices(&db_tx, address, slot2), None);
+    }
+
+    #[derive(Default)]
+    struct IndexStorageHistoryTestRunner {
+        tx: TestTransaction,
+    }
+
+    impl StageTestRunner for IndexStorageHistoryTestRunner {
+        type S = IndexStorageHistoryStage;
+
+        fn tx(&self) -> &TestTransaction {
+            &self.tx
+        }
+
+        fn stage(&self) -> Self::S {
+            IndexStorageHistoryStage::default()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ExecuteStageTestRunner for IndexStorageHistoryTestRunner {
+        type Seed = ();
+
+        fn seed_execution(&mut self, input: ExecInput) -> Result<Self::Seed, TestRunnerError> {
+            let stage_progress = input.stage_progress.unwrap_or_default();
+            let end = input.previous_stage_progress();
+            self.tx.insert_blocks_with_transitions(end)?;
+
+            // Change the same slot in every block to execute
+            let (address, slot) = (Address::random(), H256::random());
+            self.tx.commit(|tx| {
+                (stage_progress + 1..=end).try_for_each(|block| {
+                    let entry = StorageEntry { key: slot, value: U256::ZERO };
+                    tx.put::<tables::StorageChangeSet>((block, address).into(), entry)
+                })
+            })?;
+            Ok(())
+        }
+
+        fn validate_execution(
+            &self,
+            _input: ExecInput,
+            _output: Option<ExecOutput>,
+        ) -> Result<(), TestRunnerError> {
+            self.check_history()
+        }
+    }
+
+    impl UnwindStageTestRunner for IndexStorageHistoryTestRunner {
+        fn validate_unwind(&self, _input: UnwindInput) -> Result<(), TestRunnerError> {
+            self.check_history()
+        }
+    }
+
+    impl IndexStorageHistoryTestRunner {
+        /// Check that the history index contains exactly the changesets of each storage slot.
+        fn check_history(&self) -> Result<(), TestRunnerError> {
+            self.tx.query(|tx| {
+                let mut changesets = tx.cursor_dup::<tables::StorageChangeSet>()?;
+                let mut expected = BTreeMap::<(Address, H256), Vec<usize>>::new();
+                for entry in changesets.walk(TransitionIdAddress((0, Address::zero())))? {
+                    let (key, StorageEntry { key: slot, .. }) = entry?;
+                    expected
+                        .entry((key.address(), slot))
+                        .or_default()
+                        .push(key.transition_id() as usize);
+                }
+
+                let mut history = tx.cursor::<tables::StorageHistory>()?;
+                let mut indexed = BTreeMap::<(Address, H256), Vec<usize>>::new();
+                for entry in history.walk(StorageShardedKey::default())? {
+                    let (key, list) = entry?;
+                    indexed
+                        .entry((key.address, key.sharded_key.key))
+                        .or_default()
+                        .extend(list.iter(0));
+                }
+                assert_eq!(indexed, expected);
+                Ok(())
+            })?;
+            Ok(())
+        }
+    }
+}
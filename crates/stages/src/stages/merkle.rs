@@ -0,0 +1,288 @@
+use crate::{
+    db::Transaction,
+    trie::{DBTrieLoader, TrieError},
+    ExecAction, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput, UnwindOutput,
+};
+use reth_db::{database::Database, tables, transaction::DbTxMut};
+use reth_interfaces::consensus;
+use tracing::*;
+
+pub(crate) const MERKLE: StageId = StageId("Merkle");
+
+/// The merkle stage computes the state root of the latest executed block and checks it against
+/// the state root in the block header.
+///
+/// The trie is updated incrementally from the changesets of the executed blocks, or rebuilt from
+/// scratch if there is no previous trie or too many blocks need to be processed. The stage
+/// expects the hashed state to be up to date, see
+/// [AccountHashingStage][crate::stages::hashing_account::AccountHashingStage] and
+/// [StorageHashingStage][crate::stages::hashing_storage::StorageHashingStage].
+///
+/// Input tables:
+/// - [tables::HashedAccount]
+/// - [tables::HashedStorage]
+/// - [tables::AccountChangeSet]
+/// - [tables::StorageChangeSet]
+///
+/// Output tables:
+/// - [tables::AccountsTrie]
+/// - [tables::StoragesTrie]
+#[derive(Debug)]
+pub struct MerkleStage {
+    /// The number of blocks above which the trie is rebuilt from scratch instead of being updated
+    /// incrementally.
+    pub clean_threshold: u64,
+}
+
+impl Default for MerkleStage {
+    fn default() -> Self {
+        Self { clean_threshold: 50_000 }
+    }
+}
+
+#[async_trait::async_trait]
+impl<DB: Database> Stage<DB> for MerkleStage {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        MERKLE
+    }
+
+    /// Execute the stage.
+    async fn execute(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        let (start_block, end_block) = match input.next_action(None) {
+            ExecAction::Run { range, .. } => range.into_inner(),
+            ExecAction::Done { stage_progress, target } => {
+                info!(target: "sync::stages::merkle", stage_progress, target, "Target block already reached");
+                return Ok(ExecOutput { stage_progress, done: true })
+            }
+        };
+        let stage_progress = start_block - 1;
+
+        let loader = DBTrieLoader::default();
+        let trie_root = if input.should_rebuild(self.clean_threshold) {
+            debug!(target: "sync::stages::merkle", start_block, end_block, "Rebuilding trie");
+            loader.calculate_root(tx).map_err(trie_error)?
+        } else {
+            let current_root = tx.get_header_by_num(stage_progress)?.state_root;
+            let range = tx.get_transition_range(stage_progress, end_block)?;
+
+            debug!(target: "sync::stages::merkle", start_block, end_block, ?range, "Updating trie");
+            match loader.update_root(tx, current_root, range) {
+                Err(TrieError::MissingRoot(root)) => {
+                    warn!(target: "sync::stages::merkle", ?root, "Trie root not found, rebuilding trie");
+                    loader.calculate_root(tx).map_err(trie_error)?
+                }
+                res => res.map_err(trie_error)?,
+            }
+        };
+
+        let block_root = tx.get_header_by_num(end_block)?.state_root;
+        if block_root != trie_root {
+            warn!(target: "sync::stages::merkle", block = end_block, got = ?trie_root, expected = ?block_root, "State root mismatch");
+            return Err(StageError::Validation {
+                block: end_block,
+                error: consensus::Error::BodyStateRootDiff { got: trie_root, expected: block_root },
+            })
+        }
+
+        info!(target: "sync::stages::merkle", stage_progress = end_block, "Sync iteration finished");
+        Ok(ExecOutput { stage_progress: end_block, done: true })
+    }
+
+    /// Unwind the stage.
+    async fn unwind(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        info!(target: "sync::stages::merkle", to_block = input.unwind_to, "Unwinding");
+        if input.unwind_to >= input.stage_progress {
+            return Ok(UnwindOutput { stage_progress: input.unwind_to })
+        }
+
+        let current_root = tx.get_header_by_num(input.stage_progress)?.state_root;
+        let target_root = tx.get_header_by_num(input.unwind_to)?.state_root;
+        let range = tx.get_transition_range(input.unwind_to, input.stage_progress)?;
+
+        let loader = DBTrieLoader::default();
+        match loader.revert_root(tx, current_root, range) {
+            Ok(root) if root == target_root => {}
+            res => {
+                // The trie could not be reverted, so we clear it and let the next execution
+                // rebuild it from scratch.
+                warn!(target: "sync::stages::merkle", result = ?res, expected = ?target_root, "Failed to revert trie, clearing it");
+                tx.clear::<tables::AccountsTrie>()?;
+                tx.clear::<tables::StoragesTrie>()?;
+            }
+        }
+
+        Ok(UnwindOutput { stage_progress: input.unwind_to })
+    }
+}
+
+/// Trie errors are not recoverable by retrying the stage.
+fn trie_error(error: TrieError) -> StageError {
+    StageError::Fatal(Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        stage_test_suite_ext, ExecuteStageTestRunner, StageTestRunner, TestRunnerError,
+        TestTransaction, UnwindStageTestRunner, PREV_STAGE_ID,
+    };
+    use assert_matches::assert_matches;
+    use reth_db::{
+        models::{AccountBeforeTx, BlockNumHash},
+        transaction::DbTx,
+    };
+    use reth_interfaces::test_utils::generators::random_header;
+    use reth_primitives::{
+        keccak256, proofs::EMPTY_ROOT, Account, Address, BlockNumber, H256, U256,
+    };
+
+    stage_test_suite_ext!(MerkleTestRunner);
+
+    fn account(nonce: u64, balance: u64) -> Account {
+        Account { nonce, balance: U256::from(balance), bytecode_hash: None }
+    }
+
+    #[tokio::test]
+    async fn execute_empty_state() {
+        let tx = TestTransaction::default();
+        let mut header = random_header(1, None).unseal();
+        header.state_root = EMPTY_ROOT;
+        tx.insert_headers(std::iter::once(&header.seal())).expect("failed to insert header");
+
+        let input = ExecInput { previous_stage: Some((PREV_STAGE_ID, 1)), stage_progress: None };
+        let output = MerkleStage::default().execute(&mut tx.inner(), input).await;
+        assert_matches!(output, Ok(ExecOutput { stage_progress: 1, done: true }));
+    }
+
+    #[tokio::test]
+    async fn execute_state_root_mismatch() {
+        let tx = TestTransaction::default();
+        // The random header has a zero state root
+        let header = random_header(1, None);
+        tx.insert_headers(std::iter::once(&header)).expect("failed to insert header");
+
+        let input = ExecInput { previous_stage: Some((PREV_STAGE_ID, 1)), stage_progress: None };
+        let output = MerkleStage::default().execute(&mut tx.inner(), input).await;
+        assert_matches!(
+            output,
+            Err(StageError::Validation {
+                block: 1,
+                error: consensus::Error::BodyStateRootDiff { got, .. }
+            }) if got == EMPTY_ROOT
+        );
+    }
+
+    #[derive(Default)]
+    struct MerkleTestRunner {
+        tx: TestTransaction,
+    }
+
+    impl StageTestRunner for MerkleTestRunner {
+        type S = MerkleStage;
+
+        fn tx(&self) -> &TestTransaction {
+            &self.tx
+        }
+
+        fn stage(&self) -> Self::S {
+            MerkleStage::default()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ExecuteStageTestRunner for MerkleTestRunner {
+        type Seed = ();
+
+        fn seed_execution(&mut self, input: ExecInput) -> Result<Self::Seed, TestRunnerError> {
+            let stage_progress = input.stage_progress.unwrap_or_default();
+            let end = input.previous_stage_progress();
+            self.tx.insert_blocks_with_transitions(end)?;
+
+            // The trie is up to date with the state at the stage progress
+            self.tx.commit(|tx| tx.put::<tables::HashedAccount>(H256::random(), account(1, 1)))?;
+            let root = self.calculate_root(true)?;
+            self.set_state_root(stage_progress, root)?;
+
+            // Create a new account in every block to execute
+            self.tx.commit(|tx| {
+                for block in stage_progress + 1..=end {
+                    let address = Address::random();
+                    tx.put::<tables::AccountChangeSet>(
+                        block,
+                        AccountBeforeTx { address, info: None },
+                    )?;
+                    tx.put::<tables::HashedAccount>(keccak256(address), account(block, block))?;
+                }
+                Ok(())
+            })?;
+            let root = self.calculate_root(false)?;
+            self.set_state_root(end, root)?;
+            Ok(())
+        }
+
+        fn validate_execution(
+            &self,
+            _input: ExecInput,
+            _output: Option<ExecOutput>,
+        ) -> Result<(), TestRunnerError> {
+            let root = self.calculate_root(false)?;
+            self.check_trie_root(root)
+        }
+    }
+
+    impl UnwindStageTestRunner for MerkleTestRunner {
+        fn validate_unwind(&self, input: UnwindInput) -> Result<(), TestRunnerError> {
+            let root = self.tx.query(|tx| {
+                let hash =
+                    tx.get::<tables::CanonicalHeaders>(input.unwind_to)?.expect("missing header");
+                let key: BlockNumHash = (input.unwind_to, hash).into();
+                Ok(tx.get::<tables::Headers>(key)?.expect("missing header").state_root)
+            })?;
+            self.check_trie_root(root)
+        }
+    }
+
+    impl MerkleTestRunner {
+        /// Calculate the state root from the hashed state, keeping the resulting trie only if
+        /// `commit` is set.
+        fn calculate_root(&self, commit: bool) -> Result<H256, TestRunnerError> {
+            let mut tx = self.tx.inner();
+            let root = DBTrieLoader::default()
+                .calculate_root(&tx)
+                .map_err(|err| TestRunnerError::Internal(Box::new(err)))?;
+            if commit {
+                tx.commit()?;
+            }
+            Ok(root)
+        }
+
+        /// Set the state root in the header of the given block.
+        fn set_state_root(&self, block: BlockNumber, root: H256) -> Result<(), TestRunnerError> {
+            self.tx.commit(|tx| {
+                let hash = tx.get::<tables::CanonicalHeaders>(block)?.expect("missing header");
+                let key: BlockNumHash = (block, hash).into();
+                let mut header = tx.get::<tables::Headers>(key)?.expect("missing header");
+                header.state_root = root;
+                tx.put::<tables::Headers>(key, header)
+            })?;
+            Ok(())
+        }
+
+        /// Check that the trie with the given root is stored in the database.
+        fn check_trie_root(&self, root: H256) -> Result<(), TestRunnerError> {
+            let node = self.tx.query(|tx| tx.get::<tables::AccountsTrie>(root))?;
+            assert!(root == EMPTY_ROOT || node.is_some(), "missing trie root {root:?}");
+            Ok(())
+        }
+    }
+}
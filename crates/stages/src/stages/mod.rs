@@ -2,8 +2,20 @@
 pub mod bodies;
 /// The execution stage that generates state diff.
 pub mod execution;
+/// The account hashing stage.
+pub mod hashing_account;
+/// The storage hashing stage.
+pub mod hashing_storage;
 /// The headers stage.
 pub mod headers;
+/// The account history indexing stage.
+pub mod index_account_history;
+/// The storage history indexing stage.
+pub mod index_storage_history;
+/// The merkle stage that computes and verifies the state root.
+pub mod merkle;
+/// The receipts stage.
+pub mod receipts;
 /// The sender recovery stage.
 pub mod sender_recovery;
 /// The total difficulty stage
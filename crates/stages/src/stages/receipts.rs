@@ -0,0 +1,398 @@
+use crate::{
+    db::Transaction, exec_or_return, DatabaseIntegrityError, ExecAction, ExecInput, ExecOutput,
+    Stage, StageError, StageId, UnwindInput, UnwindOutput,
+};
+use futures_util::StreamExt;
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::{Database, DatabaseGAT},
+    models::BlockNumHash,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_interfaces::{consensus, p2p::receipts::downloader::ReceiptsDownloader};
+use reth_primitives::{BlockNumber, SealedHeader};
+use std::sync::Arc;
+use tracing::*;
+
+pub(crate) const RECEIPTS: StageId = StageId("Receipts");
+
+/// The receipts stage downloads the receipts of all blocks whose bodies are stored locally in the
+/// database.
+///
+/// Downloading receipts instead of executing blocks allows a node to skip execution of historical
+/// blocks, e.g. for a fast sync of pre-merge history or for indexers that are only interested in
+/// logs.
+///
+/// # Tables
+///
+/// The receipts are validated against the receipts root of their header by the downloader and are
+/// inserted into the [`Receipts`][reth_interfaces::db::tables::Receipts] table, keyed by the id of
+/// their transaction.
+///
+/// # Dependencies
+///
+/// This stage expects that the header and body tables have been populated for the synced range
+/// (see [`HeaderStage`][crate::stages::headers::HeaderStage] and
+/// [`BodyStage`][crate::stages::bodies::BodyStage]).
+#[derive(Debug)]
+pub struct ReceiptsStage<D: ReceiptsDownloader> {
+    /// The receipts downloader.
+    pub downloader: Arc<D>,
+    /// The maximum amount of blocks to download the receipts of in one stage execution.
+    ///
+    /// Smaller batch sizes result in less memory usage, but more disk I/O. Larger batch sizes
+    /// result in more memory usage, less disk I/O, and more infrequent checkpoints.
+    pub commit_threshold: u64,
+}
+
+#[async_trait::async_trait]
+impl<DB: Database, D: ReceiptsDownloader> Stage<DB> for ReceiptsStage<D> {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        RECEIPTS
+    }
+
+    /// Download the receipts from the last checkpoint for this stage up until the latest synced
+    /// block body, limited by the stage's batch size.
+    async fn execute(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        let ((start_block, end_block), capped) =
+            exec_or_return!(input, self.commit_threshold, "sync::stages::receipts");
+
+        let headers = self.headers_to_download::<DB>(tx, start_block, end_block)?;
+
+        let mut body_cursor = tx.cursor::<tables::BlockBodies>()?;
+        let mut receipts_cursor = tx.cursor_mut::<tables::Receipts>()?;
+
+        let mut receipts_stream = self.downloader.receipts_stream(headers.iter());
+        let mut highest_block = input.stage_progress.unwrap_or_default();
+        debug!(target: "sync::stages::receipts", stage_progress = highest_block, target = end_block, "Commencing sync");
+        while let Some(result) = receipts_stream.next().await {
+            let Ok(response) = result else {
+                error!(target: "sync::stages::receipts", block = highest_block + 1, error = ?result.unwrap_err(), "Error downloading receipts");
+                return Ok(ExecOutput { stage_progress: highest_block, done: false })
+            };
+
+            let numhash: BlockNumHash = response.header.num_hash().into();
+            let (_, body) = body_cursor
+                .seek_exact(numhash)?
+                .ok_or(DatabaseIntegrityError::BlockBody { number: numhash.number() })?;
+
+            // Every transaction of the block must have exactly one receipt
+            if response.receipts.len() as u64 != body.tx_count {
+                return Err(StageError::Validation {
+                    block: numhash.number(),
+                    error: consensus::Error::TransactionReceiptCountDiff {
+                        transaction_count: body.tx_count as usize,
+                        receipt_count: response.receipts.len(),
+                    },
+                })
+            }
+
+            trace!(target: "sync::stages::receipts", receipts = response.receipts.len(), ?numhash, "Writing receipts");
+            for (tx_id, receipt) in body.tx_id_range().zip(response.receipts) {
+                receipts_cursor.append(tx_id, receipt)?;
+            }
+
+            highest_block = numhash.number();
+        }
+
+        // The stage is "done" if:
+        // - We got fewer blocks than our target
+        // - We reached our target and the target was not limited by the batch size of the stage
+        let done = !capped && highest_block == end_block;
+        info!(target: "sync::stages::receipts", stage_progress = highest_block, target = end_block, done, "Sync iteration finished");
+        Ok(ExecOutput { stage_progress: highest_block, done })
+    }
+
+    /// Unwind the stage.
+    async fn unwind(
+        &mut self,
+        tx: &mut Transaction<'_, DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        info!(target: "sync::stages::receipts", to_block = input.unwind_to, "Unwinding");
+        // Lookup latest tx id that we should unwind to
+        let latest_tx_id = tx.get_block_body_by_num(input.unwind_to)?.last_tx_index();
+        tx.unwind_table_by_num::<tables::Receipts>(latest_tx_id)?;
+        Ok(UnwindOutput { stage_progress: input.unwind_to })
+    }
+}
+
+impl<D: ReceiptsDownloader> ReceiptsStage<D> {
+    /// Collects the headers of the blocks in the given range that we need to download the
+    /// receipts of.
+    fn headers_to_download<DB: Database>(
+        &self,
+        tx: &mut <DB as DatabaseGAT<'_>>::TXMut,
+        starting_block: BlockNumber,
+        target: BlockNumber,
+    ) -> Result<Vec<SealedHeader>, StageError> {
+        let mut header_cursor = tx.cursor::<tables::Headers>()?;
+        let mut header_hashes_cursor = tx.cursor::<tables::CanonicalHeaders>()?;
+        let mut walker = header_hashes_cursor
+            .walk(starting_block)?
+            .take_while(|item| item.as_ref().map_or(false, |(num, _)| *num <= target));
+
+        let mut headers = Vec::new();
+        while let Some(Ok((block_number, header_hash))) = walker.next() {
+            let (_, header) = header_cursor.seek_exact((block_number, header_hash).into())?.ok_or(
+                DatabaseIntegrityError::Header { number: block_number, hash: header_hash },
+            )?;
+            headers.push(SealedHeader::new(header, header_hash));
+        }
+
+        Ok(headers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        stage_test_suite_ext, ExecuteStageTestRunner, StageTestRunner, TestRunnerError,
+        TestTransaction, UnwindStageTestRunner, PREV_STAGE_ID,
+    };
+    use assert_matches::assert_matches;
+    use futures_util::stream;
+    use reth_db::models::StoredBlockBody;
+    use reth_interfaces::{
+        p2p::{
+            downloader::DownloadStream,
+            error::{DownloadError, RequestError},
+            receipts::downloader::BlockReceipts,
+        },
+        test_utils::generators::random_block_range,
+    };
+    use reth_primitives::{Receipt, SealedBlock, H256};
+    use std::collections::HashMap;
+
+    stage_test_suite_ext!(ReceiptsTestRunner);
+
+    /// Checks that the stage downloads at most `commit_threshold` blocks.
+    #[tokio::test]
+    async fn partial_receipts_download() {
+        let (stage_progress, previous_stage) = (1, 200);
+
+        let mut runner = ReceiptsTestRunner::default();
+        runner.set_threshold(10);
+        let input = ExecInput {
+            previous_stage: Some((PREV_STAGE_ID, previous_stage)),
+            stage_progress: Some(stage_progress),
+        };
+        runner.seed_execution(input).expect("failed to seed execution");
+
+        let output = runner.execute(input).await.unwrap();
+        assert_matches!(
+            output,
+            Ok(ExecOutput { stage_progress, done: false }) if stage_progress == 11
+        );
+        assert!(runner.validate_execution(input, output.ok()).is_ok(), "execution validation");
+    }
+
+    /// Checks that the stage returns the progress of the last downloaded block if the downloader
+    /// fails.
+    #[tokio::test]
+    async fn download_error() {
+        let (stage_progress, previous_stage) = (1, 20);
+
+        let mut runner = ReceiptsTestRunner::default();
+        let input = ExecInput {
+            previous_stage: Some((PREV_STAGE_ID, previous_stage)),
+            stage_progress: Some(stage_progress),
+        };
+        let blocks = runner.seed_execution(input).expect("failed to seed execution");
+        runner.responses.remove(&blocks[10].hash());
+
+        let output = runner.execute(input).await.unwrap();
+        let expected_progress = blocks[9].number;
+        assert_matches!(
+            output,
+            Ok(ExecOutput { stage_progress, done: false }) if stage_progress == expected_progress
+        );
+        assert!(runner.validate_execution(input, output.ok()).is_ok(), "execution validation");
+    }
+
+    /// Checks that the stage fails if the number of receipts does not match the number of
+    /// transactions of the block.
+    #[tokio::test]
+    async fn receipt_count_mismatch() {
+        let (stage_progress, previous_stage) = (1, 20);
+
+        let mut runner = ReceiptsTestRunner::default();
+        let input = ExecInput {
+            previous_stage: Some((PREV_STAGE_ID, previous_stage)),
+            stage_progress: Some(stage_progress),
+        };
+        let blocks = runner.seed_execution(input).expect("failed to seed execution");
+        let invalid = &blocks[5];
+        runner.responses.insert(invalid.hash(), random_receipts(invalid.body.len() + 1));
+
+        let output = runner.execute(input).await.unwrap();
+        assert_matches!(
+            output,
+            Err(StageError::Validation {
+                block,
+                error: consensus::Error::TransactionReceiptCountDiff { .. }
+            }) if block == invalid.number
+        );
+    }
+
+    /// Returns `count` receipts with increasing cumulative gas.
+    fn random_receipts(count: usize) -> Vec<Receipt> {
+        (0..count as u64)
+            .map(|i| Receipt { cumulative_gas_used: 21_000 * (i + 1), ..Default::default() })
+            .collect()
+    }
+
+    /// A [ReceiptsDownloader] that returns the receipts of the given map and fails on the first
+    /// block that is missing.
+    #[derive(Debug)]
+    struct TestReceiptsDownloader {
+        responses: HashMap<H256, Vec<Receipt>>,
+    }
+
+    impl ReceiptsDownloader for TestReceiptsDownloader {
+        fn receipts_stream<'a, 'b, I>(&'a self, headers: I) -> DownloadStream<'a, BlockReceipts>
+        where
+            I: IntoIterator<Item = &'b SealedHeader>,
+            <I as IntoIterator>::IntoIter: Send + 'b,
+            'b: 'a,
+        {
+            Box::pin(stream::iter(headers.into_iter().map(|header| {
+                let receipts = self
+                    .responses
+                    .get(&header.hash())
+                    .cloned()
+                    .ok_or(DownloadError::RequestError(RequestError::Timeout))?;
+                Ok(BlockReceipts { header: header.clone(), receipts })
+            })))
+        }
+    }
+
+    struct ReceiptsTestRunner {
+        tx: TestTransaction,
+        responses: HashMap<H256, Vec<Receipt>>,
+        threshold: u64,
+    }
+
+    impl Default for ReceiptsTestRunner {
+        fn default() -> Self {
+            Self { tx: TestTransaction::default(), responses: HashMap::default(), threshold: 1000 }
+        }
+    }
+
+    impl ReceiptsTestRunner {
+        fn set_threshold(&mut self, threshold: u64) {
+            self.threshold = threshold;
+        }
+    }
+
+    impl StageTestRunner for ReceiptsTestRunner {
+        type S = ReceiptsStage<TestReceiptsDownloader>;
+
+        fn tx(&self) -> &TestTransaction {
+            &self.tx
+        }
+
+        fn stage(&self) -> Self::S {
+            ReceiptsStage {
+                downloader: Arc::new(TestReceiptsDownloader { responses: self.responses.clone() }),
+                commit_threshold: self.threshold,
+            }
+        }
+    }
+
+    impl ExecuteStageTestRunner for ReceiptsTestRunner {
+        type Seed = Vec<SealedBlock>;
+
+        fn seed_execution(&mut self, input: ExecInput) -> Result<Self::Seed, TestRunnerError> {
+            let stage_progress = input.stage_progress.unwrap_or_default();
+            let end = input.previous_stage_progress() + 1;
+
+            let blocks = random_block_range(stage_progress..end, H256::zero(), 0..3);
+            self.tx.insert_headers(blocks.iter().map(|block| &block.header))?;
+
+            let mut current_tx_id = 0;
+            for block in &blocks {
+                let receipts = random_receipts(block.body.len());
+                let body = StoredBlockBody {
+                    start_tx_id: current_tx_id,
+                    tx_count: block.body.len() as u64,
+                };
+                self.tx.commit(|tx| {
+                    tx.put::<tables::BlockBodies>(block.header.num_hash().into(), body.clone())?;
+                    for (tx_id, transaction) in body.tx_id_range().zip(block.body.iter()) {
+                        tx.put::<tables::Transactions>(tx_id, transaction.clone())?;
+                        // Insert receipts for previous stage progress
+                        if block.number == stage_progress {
+                            tx.put::<tables::Receipts>(
+                                tx_id,
+                                receipts[(tx_id - current_tx_id) as usize].clone(),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                })?;
+                self.responses.insert(block.hash(), receipts);
+                current_tx_id += block.body.len() as u64;
+            }
+
+            Ok(blocks)
+        }
+
+        fn validate_execution(
+            &self,
+            input: ExecInput,
+            output: Option<ExecOutput>,
+        ) -> Result<(), TestRunnerError> {
+            let highest_block = match output.as_ref() {
+                Some(output) => output.stage_progress,
+                None => input.stage_progress.unwrap_or_default(),
+            };
+            self.check_no_receipts_by_block(highest_block)?;
+
+            self.tx.query(|tx| {
+                let mut body_cursor = tx.cursor::<tables::BlockBodies>()?;
+                let mut walker = body_cursor.walk(BlockNumHash::default())?;
+                while let Some((key, body)) = walker.next().transpose()? {
+                    if key.number() > highest_block {
+                        break
+                    }
+                    let expected = self.responses.get(&key.hash()).expect("no receipts for block");
+                    for (tx_id, receipt) in body.tx_id_range().zip(expected) {
+                        assert_eq!(tx.get::<tables::Receipts>(tx_id)?.as_ref(), Some(receipt));
+                    }
+                }
+                Ok(())
+            })?;
+
+            Ok(())
+        }
+    }
+
+    impl UnwindStageTestRunner for ReceiptsTestRunner {
+        fn validate_unwind(&self, input: UnwindInput) -> Result<(), TestRunnerError> {
+            self.check_no_receipts_by_block(input.unwind_to)
+        }
+    }
+
+    impl ReceiptsTestRunner {
+        fn check_no_receipts_by_block(&self, block: BlockNumber) -> Result<(), TestRunnerError> {
+            match self.tx.inner().get_block_body_by_num(block) {
+                Ok(body) => self
+                    .tx
+                    .check_no_entry_above::<tables::Receipts, _>(body.last_tx_index(), |key| key)?,
+                Err(_) => {
+                    assert!(self.tx.table_is_empty::<tables::Receipts>()?);
+                }
+            };
+
+            Ok(())
+        }
+    }
+}
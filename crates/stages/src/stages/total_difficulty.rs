@@ -11,7 +11,7 @@ use reth_db::{
 use reth_primitives::U256;
 use tracing::*;
 
-const TOTAL_DIFFICULTY: StageId = StageId("TotalDifficulty");
+pub(crate) const TOTAL_DIFFICULTY: StageId = StageId("TotalDifficulty");
 
 /// The total difficulty stage.
 ///
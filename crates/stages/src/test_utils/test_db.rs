@@ -7,6 +7,7 @@ use reth_db::{
     transaction::{DbTx, DbTxMut},
     Error as DbError,
 };
+use reth_interfaces::test_utils::generators::random_header;
 use reth_primitives::{BlockNumber, SealedHeader};
 use std::{borrow::Borrow, sync::Arc};
 
@@ -184,4 +185,17 @@ impl TestTransaction {
             Ok(())
         })
     }
+
+    /// Insert the headers of the blocks `0..=tip` together with their
+    /// [tables::BlockTransitionIndex] entries, where block `n` ends at transition `n`.
+    pub(crate) fn insert_blocks_with_transitions(&self, tip: BlockNumber) -> Result<(), DbError> {
+        let headers = (0..=tip).map(|n| random_header(n, None)).collect::<Vec<_>>();
+        self.insert_headers(headers.iter())?;
+        self.commit(|tx| {
+            headers.iter().try_for_each(|header| {
+                let key: BlockNumHash = (header.number, header.hash()).into();
+                tx.put::<tables::BlockTransitionIndex>(key, header.number)
+            })
+        })
+    }
 }
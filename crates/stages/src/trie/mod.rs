@@ -0,0 +1,464 @@
+use crate::db::Transaction;
+use cita_trie::{PatriciaTrie, Trie};
+use hasher::HasherKeccak;
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_interfaces::provider::Error as ProviderError;
+use reth_primitives::{
+    keccak256, proofs::EMPTY_ROOT, Account, StorageEntry, StorageTrieEntry, TransitionId, H256,
+    KECCAK_EMPTY, U256,
+};
+use reth_rlp::{
+    encode_fixed_size, Decodable, Encodable, RlpDecodable, RlpEncodable, EMPTY_STRING_CODE,
+};
+use std::{collections::BTreeMap, ops::RangeInclusive, sync::Arc};
+use tracing::*;
+
+/// Trie related errors.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum TrieError {
+    /// The trie implementation returned an error.
+    #[error("Trie error: {0}")]
+    Internal(#[from] cita_trie::TrieError),
+    /// The root node of the trie was not found in the database.
+    #[error("The root node {0:?} wasn't found in the database")]
+    MissingRoot(H256),
+    /// A database error occurred.
+    #[error(transparent)]
+    Database(#[from] reth_db::Error),
+    /// A stored trie leaf could not be decoded.
+    #[error(transparent)]
+    Decode(#[from] reth_rlp::DecodeError),
+}
+
+impl From<TrieError> for reth_interfaces::Error {
+    fn from(error: TrieError) -> Self {
+        match error {
+            TrieError::Database(error) => error.into(),
+            error => ProviderError::StateRoot { reason: error.to_string() }.into(),
+        }
+    }
+}
+
+/// Database wrapper for the account trie nodes, see [tables::AccountsTrie].
+struct HashDatabase<'tx, 'itx, DB: Database> {
+    tx: &'tx Transaction<'itx, DB>,
+}
+
+impl<'tx, 'itx, DB: Database> HashDatabase<'tx, 'itx, DB> {
+    /// Instantiates a new database for the accounts trie, with an empty root.
+    fn new(tx: &'tx Transaction<'itx, DB>) -> Result<Self, TrieError> {
+        if tx.get::<tables::AccountsTrie>(EMPTY_ROOT)?.is_none() {
+            tx.put::<tables::AccountsTrie>(EMPTY_ROOT, [EMPTY_STRING_CODE].to_vec())?;
+        }
+        Ok(Self { tx })
+    }
+
+    /// Instantiates a new database for the accounts trie, with an existing root.
+    fn from_root(tx: &'tx Transaction<'itx, DB>, root: H256) -> Result<Self, TrieError> {
+        if root == EMPTY_ROOT {
+            return Self::new(tx)
+        }
+        tx.get::<tables::AccountsTrie>(root)?.ok_or(TrieError::MissingRoot(root))?;
+        Ok(Self { tx })
+    }
+}
+
+impl<'tx, 'itx, DB: Database> cita_trie::DB for HashDatabase<'tx, 'itx, DB> {
+    type Error = TrieError;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self.tx.get::<tables::AccountsTrie>(H256::from_slice(key))?)
+    }
+
+    fn contains(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        Ok(<Self as cita_trie::DB>::get(self, key)?.is_some())
+    }
+
+    fn insert(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), Self::Error> {
+        self.tx.put::<tables::AccountsTrie>(H256::from_slice(&key), value)?;
+        Ok(())
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<(), Self::Error> {
+        self.tx.delete::<tables::AccountsTrie>(H256::from_slice(key), None)?;
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Database wrapper for the storage trie nodes of a single account, see [tables::StoragesTrie].
+struct DupHashDatabase<'tx, 'itx, DB: Database> {
+    tx: &'tx Transaction<'itx, DB>,
+    /// The hashed address of the account owning the storage trie.
+    key: H256,
+}
+
+impl<'tx, 'itx, DB: Database> DupHashDatabase<'tx, 'itx, DB> {
+    /// Instantiates a new database for the storage trie, with an empty root.
+    fn new(tx: &'tx Transaction<'itx, DB>, key: H256) -> Result<Self, TrieError> {
+        let db = Self { tx, key };
+        if <Self as cita_trie::DB>::get(&db, EMPTY_ROOT.as_bytes())?.is_none() {
+            <Self as cita_trie::DB>::insert(
+                &db,
+                EMPTY_ROOT.as_bytes().to_vec(),
+                [EMPTY_STRING_CODE].to_vec(),
+            )?;
+        }
+        Ok(db)
+    }
+
+    /// Instantiates a new database for the storage trie, with an existing root.
+    fn from_root(tx: &'tx Transaction<'itx, DB>, key: H256, root: H256) -> Result<Self, TrieError> {
+        if root == EMPTY_ROOT {
+            return Self::new(tx, key)
+        }
+        let db = Self { tx, key };
+        <Self as cita_trie::DB>::get(&db, root.as_bytes())?.ok_or(TrieError::MissingRoot(root))?;
+        Ok(db)
+    }
+}
+
+impl<'tx, 'itx, DB: Database> cita_trie::DB for DupHashDatabase<'tx, 'itx, DB> {
+    type Error = TrieError;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let hash = H256::from_slice(key);
+        let mut cursor = self.tx.cursor_dup::<tables::StoragesTrie>()?;
+        Ok(cursor
+            .seek_by_key_subkey(self.key, hash)?
+            .filter(|entry| entry.hash == hash)
+            .map(|entry| entry.node))
+    }
+
+    fn contains(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        Ok(<Self as cita_trie::DB>::get(self, key)?.is_some())
+    }
+
+    fn insert(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), Self::Error> {
+        // Duplicate table put will not override an existing node, remove it first.
+        <Self as cita_trie::DB>::remove(self, &key)?;
+        self.tx.put::<tables::StoragesTrie>(
+            self.key,
+            StorageTrieEntry { hash: H256::from_slice(&key), node: value },
+        )?;
+        Ok(())
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<(), Self::Error> {
+        let hash = H256::from_slice(key);
+        let mut cursor = self.tx.cursor_dup_mut::<tables::StoragesTrie>()?;
+        if cursor.seek_by_key_subkey(self.key, hash)?.filter(|entry| entry.hash == hash).is_some() {
+            cursor.delete_current()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// An Ethereum account as it is encoded in the leaves of the state trie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, RlpEncodable, RlpDecodable)]
+pub(crate) struct EthAccount {
+    /// Account nonce.
+    nonce: u64,
+    /// Account balance.
+    balance: U256,
+    /// Root of the account storage trie.
+    storage_root: H256,
+    /// Hash of the account bytecode.
+    code_hash: H256,
+}
+
+impl EthAccount {
+    /// Create the trie representation of the account with the given storage root.
+    pub(crate) fn from_with_root(account: Account, storage_root: H256) -> Self {
+        Self {
+            nonce: account.nonce,
+            balance: account.balance,
+            storage_root,
+            code_hash: account.bytecode_hash.unwrap_or(KECCAK_EMPTY),
+        }
+    }
+}
+
+/// The state an account should have in the trie after an update.
+#[derive(Debug, Default)]
+struct AccountUpdate {
+    /// The account info, or `None` if the account does not exist.
+    info: Option<Account>,
+    /// The changed storage slots of the account keyed by hashed slot, zero values are removed
+    /// from the trie.
+    storage: BTreeMap<H256, U256>,
+}
+
+/// Builds and updates the state trie from the hashed state tables, persisting the trie nodes in
+/// [tables::AccountsTrie] and [tables::StoragesTrie].
+#[derive(Debug, Default)]
+pub(crate) struct DBTrieLoader;
+
+impl DBTrieLoader {
+    /// Calculates the root of the state trie from scratch, saving the trie nodes in the database.
+    pub(crate) fn calculate_root<DB: Database>(
+        &self,
+        tx: &Transaction<'_, DB>,
+    ) -> Result<H256, TrieError> {
+        tx.clear::<tables::AccountsTrie>()?;
+        tx.clear::<tables::StoragesTrie>()?;
+
+        let db = Arc::new(HashDatabase::new(tx)?);
+        let hasher = Arc::new(HasherKeccak::new());
+        let mut trie = PatriciaTrie::new(Arc::clone(&db), Arc::clone(&hasher));
+
+        let mut accounts_cursor = tx.cursor::<tables::HashedAccount>()?;
+        let mut walker = accounts_cursor.walk(H256::zero())?;
+        while let Some((hashed_address, account)) = walker.next().transpose()? {
+            let storage_root = self.calculate_storage_root(tx, hashed_address)?;
+            trace!(target: "sync::trie", ?hashed_address, ?storage_root, "Inserting account");
+
+            let mut out = Vec::new();
+            EthAccount::from_with_root(account, storage_root).encode(&mut out);
+            trie.insert(hashed_address.as_bytes().to_vec(), out)?;
+        }
+
+        Ok(H256::from_slice(trie.root()?.as_slice()))
+    }
+
+    /// Calculates the root of a single account storage trie from scratch.
+    fn calculate_storage_root<DB: Database>(
+        &self,
+        tx: &Transaction<'_, DB>,
+        hashed_address: H256,
+    ) -> Result<H256, TrieError> {
+        let db = Arc::new(DupHashDatabase::new(tx, hashed_address)?);
+        let hasher = Arc::new(HasherKeccak::new());
+        let mut trie = PatriciaTrie::new(Arc::clone(&db), Arc::clone(&hasher));
+
+        let mut storage_cursor = tx.cursor_dup::<tables::HashedStorage>()?;
+        let mut walker = storage_cursor.walk_dup(hashed_address, H256::zero())?;
+        while let Some((_, StorageEntry { key, value })) = walker.next().transpose()? {
+            trie.insert(key.as_bytes().to_vec(), encode_fixed_size(&value).to_vec())?;
+        }
+
+        Ok(H256::from_slice(trie.root()?.as_slice()))
+    }
+
+    /// Updates the state trie with `root` by applying the changes of the given transition range.
+    ///
+    /// The new values are read from the hashed state, which is expected to be at the end of the
+    /// range.
+    pub(crate) fn update_root<DB: Database>(
+        &self,
+        tx: &Transaction<'_, DB>,
+        root: H256,
+        tid_range: RangeInclusive<TransitionId>,
+    ) -> Result<H256, TrieError> {
+        let accounts = tx.get_account_changes(tid_range.clone())?;
+        let storages = tx.get_storage_changes(tid_range)?;
+
+        let mut storage_cursor = tx.cursor_dup::<tables::HashedStorage>()?;
+        let mut updates = BTreeMap::new();
+        for address in accounts.into_keys().chain(storages.keys().copied()) {
+            let hashed_address = keccak256(address);
+            if updates.contains_key(&hashed_address) {
+                continue
+            }
+            let mut update = AccountUpdate {
+                info: tx.get::<tables::HashedAccount>(hashed_address)?,
+                ..Default::default()
+            };
+            for slot in storages.get(&address).into_iter().flat_map(|slots| slots.keys()) {
+                let hashed_slot = keccak256(slot);
+                let value = storage_cursor
+                    .seek_by_key_subkey(hashed_address, hashed_slot)?
+                    .filter(|entry| entry.key == hashed_slot)
+                    .map(|entry| entry.value)
+                    .unwrap_or_default();
+                update.storage.insert(hashed_slot, value);
+            }
+            updates.insert(hashed_address, update);
+        }
+
+        self.apply_updates(tx, root, updates)
+    }
+
+    /// Reverts the state trie with `root` to the state before the given transition range.
+    ///
+    /// The old values are read from the changesets, the hashed state is expected to be at the end
+    /// of the range.
+    pub(crate) fn revert_root<DB: Database>(
+        &self,
+        tx: &Transaction<'_, DB>,
+        root: H256,
+        tid_range: RangeInclusive<TransitionId>,
+    ) -> Result<H256, TrieError> {
+        let accounts = tx.get_account_changes(tid_range.clone())?;
+        let storages = tx.get_storage_changes(tid_range)?;
+
+        let mut updates = BTreeMap::new();
+        for (address, info) in accounts {
+            updates.insert(keccak256(address), AccountUpdate { info, ..Default::default() });
+        }
+        for (address, storage) in storages {
+            let hashed_address = keccak256(address);
+            if !updates.contains_key(&hashed_address) {
+                let info = tx.get::<tables::HashedAccount>(hashed_address)?;
+                updates.insert(hashed_address, AccountUpdate { info, ..Default::default() });
+            }
+            if let Some(update) = updates.get_mut(&hashed_address) {
+                update.storage =
+                    storage.into_iter().map(|(slot, value)| (keccak256(slot), value)).collect();
+            }
+        }
+
+        self.apply_updates(tx, root, updates)
+    }
+
+    /// Applies the account updates to the state trie with `root` and returns the new root.
+    fn apply_updates<DB: Database>(
+        &self,
+        tx: &Transaction<'_, DB>,
+        root: H256,
+        updates: BTreeMap<H256, AccountUpdate>,
+    ) -> Result<H256, TrieError> {
+        let db = Arc::new(HashDatabase::from_root(tx, root)?);
+        let hasher = Arc::new(HasherKeccak::new());
+        let mut trie = PatriciaTrie::from(Arc::clone(&db), Arc::clone(&hasher), root.as_bytes())?;
+
+        for (hashed_address, AccountUpdate { info, storage }) in updates {
+            trace!(target: "sync::trie", ?hashed_address, ?info, slots = storage.len(), "Updating account");
+
+            match info {
+                Some(account) => {
+                    let storage_root = match trie.get(hashed_address.as_bytes())? {
+                        Some(encoded) => EthAccount::decode(&mut encoded.as_slice())?.storage_root,
+                        None => EMPTY_ROOT,
+                    };
+                    let storage_root =
+                        self.update_storage_root(tx, hashed_address, storage_root, storage)?;
+
+                    let mut out = Vec::new();
+                    EthAccount::from_with_root(account, storage_root).encode(&mut out);
+                    trie.insert(hashed_address.as_bytes().to_vec(), out)?;
+                }
+                None => {
+                    trie.remove(hashed_address.as_bytes())?;
+                    tx.delete::<tables::StoragesTrie>(hashed_address, None)?;
+                }
+            }
+        }
+
+        Ok(H256::from_slice(trie.root()?.as_slice()))
+    }
+
+    /// Applies the storage changes to the storage trie with `root` and returns the new root.
+    fn update_storage_root<DB: Database>(
+        &self,
+        tx: &Transaction<'_, DB>,
+        hashed_address: H256,
+        root: H256,
+        storage: BTreeMap<H256, U256>,
+    ) -> Result<H256, TrieError> {
+        if storage.is_empty() {
+            return Ok(root)
+        }
+
+        let db = Arc::new(DupHashDatabase::from_root(tx, hashed_address, root)?);
+        let hasher = Arc::new(HasherKeccak::new());
+        let mut trie = PatriciaTrie::from(Arc::clone(&db), Arc::clone(&hasher), root.as_bytes())?;
+
+        for (hashed_slot, value) in storage {
+            if value == U256::ZERO {
+                trie.remove(hashed_slot.as_bytes())?;
+            } else {
+                trie.insert(hashed_slot.as_bytes().to_vec(), encode_fixed_size(&value).to_vec())?;
+            }
+        }
+
+        Ok(H256::from_slice(trie.root()?.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        models::AccountBeforeTx,
+    };
+    use reth_primitives::Address;
+    use std::{ops::DerefMut, str::FromStr};
+
+    fn account(nonce: u64, balance: u64) -> Account {
+        Account { nonce, balance: U256::from(balance), bytecode_hash: None }
+    }
+
+    fn storage(slot: H256, value: u64) -> StorageEntry {
+        StorageEntry { key: keccak256(slot), value: U256::from(value) }
+    }
+
+    #[test]
+    fn empty_trie() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let tx = Transaction::new(db.as_ref()).unwrap();
+        assert_eq!(DBTrieLoader::default().calculate_root(&tx).unwrap(), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn update_and_revert_match_rebuild() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let mut tx = Transaction::new(db.as_ref()).unwrap();
+        let loader = DBTrieLoader::default();
+
+        let alice = Address::from_str("0xa2c122be93b0074270ebee7f6b7292c7deb45047").unwrap();
+        let bob = Address::from_str("0x5a0b54d5dc17e0aadc383d2db43b0a0d3e029c4c").unwrap();
+        let carol = Address::from_str("0x0000000000000000000000000000000000000003").unwrap();
+        let slot = H256::from_low_u64_be(1);
+
+        // Initial state
+        tx.put::<tables::HashedAccount>(keccak256(alice), account(1, 100)).unwrap();
+        tx.put::<tables::HashedAccount>(keccak256(bob), account(0, 50)).unwrap();
+        tx.put::<tables::HashedStorage>(keccak256(bob), storage(slot, 7)).unwrap();
+        let initial_root = loader.calculate_root(&tx).unwrap();
+        assert_ne!(initial_root, EMPTY_ROOT);
+        tx.commit().unwrap();
+
+        // Transition 1 changes alice, bob's storage and creates carol
+        let db_tx = tx.deref_mut();
+        db_tx
+            .put::<tables::AccountChangeSet>(
+                1,
+                AccountBeforeTx { address: alice, info: Some(account(1, 100)) },
+            )
+            .unwrap();
+        db_tx
+            .put::<tables::AccountChangeSet>(1, AccountBeforeTx { address: carol, info: None })
+            .unwrap();
+        db_tx
+            .put::<tables::StorageChangeSet>(
+                (1, bob).into(),
+                StorageEntry { key: slot, value: U256::from(7) },
+            )
+            .unwrap();
+        db_tx.put::<tables::HashedAccount>(keccak256(alice), account(2, 90)).unwrap();
+        db_tx.put::<tables::HashedAccount>(keccak256(carol), account(0, 10)).unwrap();
+        db_tx.delete::<tables::HashedStorage>(keccak256(bob), None).unwrap();
+        db_tx.put::<tables::HashedStorage>(keccak256(bob), storage(slot, 8)).unwrap();
+        tx.commit().unwrap();
+
+        let updated_root = loader.update_root(&tx, initial_root, 1..=1).unwrap();
+        let reverted_root = loader.revert_root(&tx, updated_root, 1..=1).unwrap();
+        assert_eq!(reverted_root, initial_root);
+
+        assert_eq!(loader.calculate_root(&tx).unwrap(), updated_root);
+    }
+}
@@ -40,8 +40,10 @@ impl_compression_for_compact!(
     Receipt,
     TxType,
     StorageEntry,
+    StorageTrieEntry,
     StoredBlockBody,
-    StoredBlockOmmers
+    StoredBlockOmmers,
+    StoredBlockWithdrawals
 );
 impl_compression_for_compact!(AccountBeforeTx, TransactionSigned);
 impl_compression_for_compact!(CompactU256);
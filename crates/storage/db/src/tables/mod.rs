@@ -11,14 +11,14 @@ use crate::{
         codecs::CompactU256,
         models::{
             accounts::{AccountBeforeTx, TransitionIdAddress},
-            blocks::{HeaderHash, StoredBlockOmmers},
-            BlockNumHash, ShardedKey,
+            blocks::{HeaderHash, StoredBlockOmmers, StoredBlockWithdrawals},
+            BlockNumHash, ShardedKey, StorageShardedKey,
         },
     },
 };
 use reth_primitives::{
     Account, Address, BlockHash, BlockNumber, Header, IntegerList, Receipt, StorageEntry,
-    TransactionSigned, TransitionId, TxHash, TxNumber, H256,
+    StorageTrieEntry, TransactionSigned, TransitionId, TxHash, TxNumber, H256,
 };
 
 use self::models::StoredBlockBody;
@@ -33,13 +33,14 @@ pub enum TableType {
 }
 
 /// Default tables that should be present inside database.
-pub const TABLES: [(TableType, &str); 23] = [
+pub const TABLES: [(TableType, &str); 28] = [
     (TableType::Table, CanonicalHeaders::const_name()),
     (TableType::Table, HeaderTD::const_name()),
     (TableType::Table, HeaderNumbers::const_name()),
     (TableType::Table, Headers::const_name()),
     (TableType::Table, BlockBodies::const_name()),
     (TableType::Table, BlockOmmers::const_name()),
+    (TableType::Table, BlockWithdrawals::const_name()),
     (TableType::Table, NonCanonicalTransactions::const_name()),
     (TableType::Table, Transactions::const_name()),
     (TableType::Table, TxHashNumber::const_name()),
@@ -54,6 +55,10 @@ pub const TABLES: [(TableType, &str); 23] = [
     (TableType::Table, StorageHistory::const_name()),
     (TableType::DupSort, AccountChangeSet::const_name()),
     (TableType::DupSort, StorageChangeSet::const_name()),
+    (TableType::Table, HashedAccount::const_name()),
+    (TableType::DupSort, HashedStorage::const_name()),
+    (TableType::Table, AccountsTrie::const_name()),
+    (TableType::DupSort, StoragesTrie::const_name()),
     (TableType::Table, TxSenders::const_name()),
     (TableType::Table, Config::const_name()),
     (TableType::Table, SyncStage::const_name()),
@@ -136,6 +141,11 @@ table!(
     ( BlockOmmers ) BlockNumHash | StoredBlockOmmers
 );
 
+table!(
+    /// Stores the withdrawals of the block.
+    ( BlockWithdrawals ) BlockNumHash | StoredBlockWithdrawals
+);
+
 table!(
     /// Stores the transaction body from non canonical transactions.
     ( NonCanonicalTransactions ) BlockNumHashTxNumber | TransactionSigned
@@ -190,7 +200,10 @@ dupsort!(
 );
 
 table!(
-    /// Stores the transaction numbers that changed each account.
+    /// Stores the transition ids that changed each account.
+    ///
+    /// The list of an account is split into shards keyed by the highest transition id they
+    /// contain. The last shard of an account is keyed by `u64::MAX`.
     ///
     /// ```
     /// use reth_primitives::{Address, IntegerList};
@@ -236,8 +249,11 @@ table!(
 );
 
 table!(
-    /// Stores pointers to transactions that changed each storage key.
-    ( StorageHistory ) AddressStorageKey | TransitionList
+    /// Stores the transition ids that changed each storage slot.
+    ///
+    /// Sharded the same way as [`AccountHistory`]: the key of the last shard of a slot has
+    /// `u64::MAX` as its highest transition id.
+    ( StorageHistory ) StorageShardedKey | TransitionList
 );
 
 dupsort!(
@@ -254,6 +270,29 @@ dupsort!(
     ( StorageChangeSet ) TransitionIdAddress | [H256] StorageEntry
 );
 
+table!(
+    /// Stores the current state of an [`Account`] indexed with `keccak256(Address)`.
+    /// This table is in preparation for merkelization and calculation of state root.
+    ( HashedAccount ) H256 | Account
+);
+
+dupsort!(
+    /// Stores the current storage values indexed with `keccak256(Address)` and
+    /// hash of storage key `keccak256(key)`.
+    /// This table is in preparation for merkelization and calculation of state root.
+    ( HashedStorage ) H256 | [H256] StorageEntry
+);
+
+table!(
+    /// Stores the nodes of the account trie, keyed by node hash.
+    ( AccountsTrie ) H256 | TrieNode
+);
+
+dupsort!(
+    /// Stores the nodes of each account storage trie, keyed by the hashed address of the account.
+    ( StoragesTrie ) H256 | [H256] StorageTrieEntry
+);
+
 table!(
     /// Stores the transaction sender for each transaction.
     /// It is needed to speed up execution stage and allows fetching signer without doing
@@ -278,6 +317,8 @@ table!(
 pub type TransitionList = IntegerList;
 /// Encoded stage id.
 pub type StageId = Vec<u8>;
+/// RLP encoded trie node.
+pub type TrieNode = Vec<u8>;
 
 //
 // TODO: Temporary types, until they're properly defined alongside with the Encode and Decode Trait
@@ -290,6 +331,4 @@ pub type ConfigValue = Vec<u8>;
 /// Temporary placeholder type for DB.
 pub type BlockNumHashTxNumber = Vec<u8>;
 /// Temporary placeholder type for DB.
-pub type AddressStorageKey = Vec<u8>;
-/// Temporary placeholder type for DB.
 pub type Bytecode = Vec<u8>;
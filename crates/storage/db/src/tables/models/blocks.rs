@@ -9,7 +9,7 @@ use crate::{
 };
 use bytes::Bytes;
 use reth_codecs::{main_codec, Compact};
-use reth_primitives::{BlockHash, BlockNumber, Header, TxNumber, H256};
+use reth_primitives::{BlockHash, BlockNumber, Header, TxNumber, Withdrawal, H256};
 use serde::{Deserialize, Serialize};
 
 /// Total chain number of transactions. Value for [`CumulativeTxCount`]. // TODO:
@@ -58,6 +58,14 @@ pub struct StoredBlockOmmers {
     pub ommers: Vec<Header>,
 }
 
+/// The storage representation of the withdrawals of a block, present since Shanghai.
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+#[main_codec]
+pub struct StoredBlockWithdrawals {
+    /// The withdrawals of this block.
+    pub withdrawals: Vec<Withdrawal>,
+}
+
 /// Hash of the block header. Value for [`CanonicalHeaders`]
 pub type HeaderHash = H256;
 
@@ -167,4 +175,15 @@ mod test {
             ommer.clone() == StoredBlockOmmers::decompress::<Vec<_>>(ommer.compress()).unwrap()
         );
     }
+
+    #[test]
+    fn test_withdrawals() {
+        let mut withdrawals = StoredBlockWithdrawals::default();
+        withdrawals.withdrawals.push(Withdrawal::default());
+        withdrawals.withdrawals.push(Withdrawal { index: 1, amount: 2, ..Default::default() });
+        assert_eq!(
+            withdrawals.clone(),
+            StoredBlockWithdrawals::decompress::<Vec<_>>(withdrawals.compress()).unwrap()
+        );
+    }
 }
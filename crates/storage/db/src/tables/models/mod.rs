@@ -4,11 +4,13 @@ pub mod accounts;
 pub mod blocks;
 pub mod integer_list;
 pub mod sharded_key;
+pub mod storage_sharded_key;
 
 pub use accounts::*;
 pub use blocks::*;
 use reth_primitives::{Address, H256};
-pub use sharded_key::ShardedKey;
+pub use sharded_key::{ShardedKey, NUM_OF_INDICES_IN_SHARD};
+pub use storage_sharded_key::StorageShardedKey;
 
 use crate::{
     table::{Decode, Encode},
@@ -0,0 +1,73 @@
+//! Storage sharded key
+
+use crate::{
+    table::{Decode, Encode},
+    Error,
+};
+use reth_primitives::{Address, TransitionId, H256};
+
+use super::ShardedKey;
+
+/// Number of bytes of an encoded [`StorageShardedKey`].
+const STORAGE_SHARD_KEY_BYTES_SIZE: usize = 20 + 32 + 8;
+
+/// Sometimes data can be too big to be saved for a single key. This helps out by dividing the data
+/// into different shards. Example:
+///
+/// `Address | StorageKey | 200` -> data is from transition 0 to 200.
+///
+/// `Address | StorageKey | 300` -> data is from transition 201 to 300.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct StorageShardedKey {
+    /// Storage account address.
+    pub address: Address,
+    /// Storage slot with highest transition id.
+    pub sharded_key: ShardedKey<H256>,
+}
+
+impl StorageShardedKey {
+    /// Creates a new `StorageShardedKey`.
+    pub fn new(address: Address, storage_key: H256, highest_transition_id: TransitionId) -> Self {
+        Self { address, sharded_key: ShardedKey::new(storage_key, highest_transition_id) }
+    }
+}
+
+impl Encode for StorageShardedKey {
+    type Encoded = Vec<u8>;
+
+    fn encode(self) -> Self::Encoded {
+        let mut buf: Vec<u8> = Encode::encode(self.address).into();
+        buf.extend(Encode::encode(self.sharded_key));
+        buf
+    }
+}
+
+impl Decode for StorageShardedKey {
+    fn decode<B: Into<bytes::Bytes>>(value: B) -> Result<Self, Error> {
+        let value: bytes::Bytes = value.into();
+        if value.len() != STORAGE_SHARD_KEY_BYTES_SIZE {
+            return Err(Error::DecodeError)
+        }
+
+        let address = Address::decode(value.slice(..20))?;
+        let sharded_key = ShardedKey::<H256>::decode(value.slice(20..))?;
+        Ok(Self { address, sharded_key })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_storage_sharded_key() {
+        let key = StorageShardedKey::new(Address::random(), H256::random(), 100);
+
+        let encoded = Encode::encode(key.clone());
+        assert_eq!(encoded.len(), STORAGE_SHARD_KEY_BYTES_SIZE);
+        assert_eq!(&encoded[52..], &100u64.to_be_bytes());
+
+        let decoded: StorageShardedKey = Decode::decode(encoded).unwrap();
+        assert_eq!(decoded, key);
+    }
+}
@@ -1,6 +1,6 @@
 use auto_impl::auto_impl;
 use reth_db::{
-    models::{BlockNumHash, StoredBlockBody, StoredBlockOmmers},
+    models::{BlockNumHash, StoredBlockBody, StoredBlockOmmers, StoredBlockWithdrawals},
     tables,
     transaction::{DbTx, DbTxMut},
 };
@@ -115,12 +115,16 @@ pub struct ChainInfo {
 /// Fill block to database. Useful for tests.
 /// Check parent dependency in [tables::HeaderNumbers] and in [tables::CumulativeTxCount] tables.
 /// Inserts blocks data to [tables::CanonicalHeaders], [tables::Headers], [tables::HeaderNumbers],
-/// and transactions data to [tables::TxSenders], [tables::Transactions],
-/// [tables::CumulativeTxCount] and [tables::BlockBodies]
+/// [tables::BlockOmmers], [tables::BlockWithdrawals] and transactions data to [tables::TxSenders],
+/// [tables::Transactions], [tables::CumulativeTxCount] and [tables::BlockBodies]
+///
+/// The block gets an extra transition after its transactions if it has a block reward or, with
+/// `has_withdrawals` set for Shanghai blocks, any withdrawals, the same as in the executor.
 pub fn insert_canonical_block<'a, TX: DbTxMut<'a> + DbTx<'a>>(
     tx: &TX,
     block: &SealedBlock,
     has_block_reward: bool,
+    has_withdrawals: bool,
 ) -> Result<()> {
     let block_num_hash = BlockNumHash((block.number, block.hash()));
     tx.put::<tables::CanonicalHeaders>(block.number, block.hash())?;
@@ -134,6 +138,15 @@ pub fn insert_canonical_block<'a, TX: DbTxMut<'a> + DbTx<'a>>(
         StoredBlockOmmers { ommers: block.ommers.iter().map(|h| h.as_ref().clone()).collect() },
     )?;
 
+    // insert body withdrawals data
+    let withdrawals = block.withdrawals();
+    if block.withdrawals.is_some() {
+        tx.put::<tables::BlockWithdrawals>(
+            block_num_hash,
+            StoredBlockWithdrawals { withdrawals: withdrawals.to_vec() },
+        )?;
+    }
+
     let (mut current_tx_id, mut transition_id) = {
         if block.number == 0 {
             (0, 0)
@@ -173,7 +186,7 @@ pub fn insert_canonical_block<'a, TX: DbTxMut<'a> + DbTx<'a>>(
         transition_id += 1;
     }
 
-    if has_block_reward {
+    if has_block_reward || (has_withdrawals && !withdrawals.is_empty()) {
         transition_id += 1;
     }
     tx.put::<tables::BlockTransitionIndex>((block.number, block.hash()).into(), transition_id)?;
@@ -0,0 +1,89 @@
+//! Notifications about changes of the canonical chain.
+
+use reth_primitives::{Account, Address, SealedHeader, TransactionSignedEcRecovered, TxHash};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+use tokio::sync::broadcast;
+
+/// The sending half of a [CanonStateNotification] channel.
+pub type CanonStateNotificationSender = broadcast::Sender<Arc<CanonStateNotification>>;
+
+/// The receiving half of a [CanonStateNotification] channel.
+pub type CanonStateNotifications = broadcast::Receiver<Arc<CanonStateNotification>>;
+
+/// Creates a new channel for [CanonStateNotification]s that buffers up to `capacity` notifications
+/// per receiver.
+pub fn canon_state_notification_channel(
+    capacity: usize,
+) -> (CanonStateNotificationSender, CanonStateNotifications) {
+    broadcast::channel(capacity)
+}
+
+/// A block that was added to or removed from the canonical chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonBlock {
+    /// The header of the block.
+    pub header: SealedHeader,
+    /// The transactions of the block with their recovered signers.
+    pub transactions: Vec<TransactionSignedEcRecovered>,
+}
+
+/// A change of the canonical chain.
+///
+/// Blocks are first removed from the tip of the chain, then new blocks are appended. A reorg
+/// therefore has both reverted and committed blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonStateNotification {
+    /// The header of the canonical tip after the change.
+    pub tip: SealedHeader,
+    /// The blocks that were removed from the canonical chain, in ascending order.
+    pub reverted: Vec<CanonBlock>,
+    /// The blocks that were appended to the canonical chain, in ascending order.
+    pub committed: Vec<CanonBlock>,
+    /// The state at the new tip of all accounts that were changed by the reverted or committed
+    /// blocks.
+    ///
+    /// `None` if the account does not exist at the new tip.
+    pub changed_accounts: BTreeMap<Address, Option<Account>>,
+    /// Whether the change spans too many blocks to describe it.
+    ///
+    /// The blocks and changed accounts are then left out, so receivers must resync their state
+    /// at the new tip.
+    pub reset: bool,
+}
+
+impl CanonStateNotification {
+    /// Creates a notification of a change that spans too many blocks to describe it.
+    pub fn reset(tip: SealedHeader) -> Self {
+        Self {
+            tip,
+            reverted: Vec::new(),
+            committed: Vec::new(),
+            changed_accounts: BTreeMap::new(),
+            reset: true,
+        }
+    }
+
+    /// Returns the base fee of the block on top of the new tip.
+    ///
+    /// Returns `None` if the tip precedes the London hardfork.
+    pub fn pending_block_base_fee(&self) -> Option<u64> {
+        self.tip.next_block_base_fee()
+    }
+
+    /// Returns the hashes of all transactions in the committed blocks.
+    pub fn mined_transactions(&self) -> impl Iterator<Item = TxHash> + '_ {
+        self.committed.iter().flat_map(|block| block.transactions.iter().map(|tx| tx.hash()))
+    }
+
+    /// Returns all transactions of the reverted blocks that are not part of a committed block.
+    pub fn reverted_transactions(&self) -> impl Iterator<Item = &TransactionSignedEcRecovered> {
+        let mined = self.mined_transactions().collect::<HashSet<_>>();
+        self.reverted
+            .iter()
+            .flat_map(|block| block.transactions.iter())
+            .filter(move |tx| !mined.contains(&tx.hash()))
+    }
+}
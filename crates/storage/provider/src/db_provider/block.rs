@@ -1,7 +1,20 @@
-use crate::{block::BlockHashProvider, BlockProvider, ChainInfo, HeaderProvider, ProviderImpl};
-use reth_db::{database::Database, tables, transaction::DbTx};
+use crate::{
+    block::BlockHashProvider, BlockProvider, ChainInfo, HeaderProvider, ProviderImpl,
+    ReceiptProvider, TransactionMeta, TransactionsProvider,
+};
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::{BlockNumHash, StoredBlockBody},
+    tables,
+    transaction::DbTx,
+    Error as DbError,
+};
 use reth_interfaces::Result;
-use reth_primitives::{rpc::BlockId, Block, BlockHash, BlockNumber, Header, H256, U256};
+use reth_primitives::{
+    rpc::BlockId, Block, BlockHash, BlockHashOrNumber, BlockNumber, Header, Receipt,
+    TransactionSigned, TxHash, TxNumber, EXECUTION_STAGE_ID, H256, U256,
+};
 
 impl<DB: Database> HeaderProvider for ProviderImpl<DB> {
     fn header(&self, block_hash: &BlockHash) -> Result<Option<Header>> {
@@ -36,21 +49,250 @@ impl<DB: Database> BlockHashProvider for ProviderImpl<DB> {
 }
 
 impl<DB: Database> BlockProvider for ProviderImpl<DB> {
+    /// The best block is the latest executed block, as there is no state for the blocks after it.
     fn chain_info(&self) -> Result<ChainInfo> {
-        Ok(ChainInfo {
-            best_hash: Default::default(),
-            best_number: 0,
-            last_finalized: None,
-            safe_finalized: None,
-        })
+        self.db.view(|tx| {
+            let best_number = tx
+                .get::<tables::SyncStage>(EXECUTION_STAGE_ID.as_bytes().to_vec())?
+                .unwrap_or_default();
+            let best_hash = tx.get::<tables::CanonicalHeaders>(best_number)?.unwrap_or_default();
+            Ok(ChainInfo { best_hash, best_number, last_finalized: None, safe_finalized: None })
+        })?
     }
 
-    fn block(&self, _id: BlockId) -> Result<Option<Block>> {
-        // TODO
-        Ok(None)
+    fn block(&self, id: BlockId) -> Result<Option<Block>> {
+        let Some(number) = self.block_number_for_id(id)? else { return Ok(None) };
+        let Some(hash) = self.block_hash_for_id(id)? else { return Ok(None) };
+        let key: BlockNumHash = (number, hash).into();
+
+        self.db.view(|tx| {
+            let Some(header) = tx.get::<tables::Headers>(key)? else { return Ok(None) };
+            let Some(body) = tx.get::<tables::BlockBodies>(key)? else { return Ok(None) };
+            let ommers = tx.get::<tables::BlockOmmers>(key)?.map(|o| o.ommers).unwrap_or_default();
+            let withdrawals = tx.get::<tables::BlockWithdrawals>(key)?.map(|w| w.withdrawals);
+            let body = read_transactions(tx, &body)?;
+            Ok(Some(Block { header, body, ommers, withdrawals }))
+        })?
     }
 
     fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
         self.db.view(|tx| tx.get::<tables::HeaderNumbers>(hash))?.map_err(Into::into)
     }
 }
+
+impl<DB: Database> TransactionsProvider for ProviderImpl<DB> {
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        self.db.view(|tx| {
+            // the headers stage runs ahead of the bodies stage, so only blocks up to the last
+            // block body are searched
+            let last_header = tx.cursor::<tables::CanonicalHeaders>()?.last()?.map(|(n, _)| n);
+            let last_body =
+                tx.cursor::<tables::BlockBodies>()?.last()?.map(|(key, _)| key.number());
+            let best_number = last_header.unwrap_or_default().min(last_body.unwrap_or_default());
+            let Some(tx_id) = tx.get::<tables::TxHashNumber>(hash)? else { return Ok(None) };
+            let Some(transaction) = tx.get::<tables::Transactions>(tx_id)? else { return Ok(None) };
+            let Some((block_number, block_hash, body)) =
+                find_block_of_transaction(tx, tx_id, best_number)?
+            else {
+                return Ok(None)
+            };
+            let base_fee = tx
+                .get::<tables::Headers>((block_number, block_hash).into())?
+                .and_then(|header| header.base_fee_per_gas);
+            let meta = TransactionMeta {
+                tx_hash: hash,
+                index: tx_id - body.start_tx_id,
+                block_hash,
+                block_number,
+                base_fee,
+            };
+            Ok(Some((transaction, meta)))
+        })?
+    }
+
+    fn transactions_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>> {
+        let Some(key) = self.block_key(block)? else { return Ok(None) };
+        self.db.view(|tx| {
+            let Some(body) = tx.get::<tables::BlockBodies>(key)? else { return Ok(None) };
+            Ok(Some(read_transactions(tx, &body)?))
+        })?
+    }
+}
+
+impl<DB: Database> ReceiptProvider for ProviderImpl<DB> {
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        let Some(key) = self.block_key(block)? else { return Ok(None) };
+        self.db.view(|tx| {
+            let Some(body) = tx.get::<tables::BlockBodies>(key)? else { return Ok(None) };
+            let mut receipts = Vec::with_capacity(body.tx_count as usize);
+            let mut cursor = tx.cursor::<tables::Receipts>()?;
+            for entry in cursor.walk(body.start_tx_id)?.take(body.tx_count as usize) {
+                let (tx_id, receipt) = entry?;
+                if !body.tx_id_range().contains(&tx_id) {
+                    break
+                }
+                receipts.push(receipt);
+            }
+
+            // The receipts of the block are only available if every transaction has one
+            if receipts.len() as u64 != body.tx_count {
+                return Ok(None)
+            }
+            Ok(Some(receipts))
+        })?
+    }
+}
+
+impl<DB: Database> ProviderImpl<DB> {
+    /// Returns the database key of the block with the given hash or canonical number.
+    fn block_key(&self, block: BlockHashOrNumber) -> Result<Option<BlockNumHash>> {
+        let key = match block {
+            BlockHashOrNumber::Hash(hash) => self.block_number(hash)?.map(|num| (num, hash)),
+            BlockHashOrNumber::Number(num) => {
+                self.block_hash(U256::from(num))?.map(|hash| (num, hash))
+            }
+        };
+        Ok(key.map(Into::into))
+    }
+}
+
+/// Read the transactions of the given block body.
+fn read_transactions<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    body: &StoredBlockBody,
+) -> std::result::Result<Vec<TransactionSigned>, DbError> {
+    if body.tx_count == 0 {
+        return Ok(Vec::new())
+    }
+    tx.cursor::<tables::Transactions>()?
+        .walk(body.start_tx_id)?
+        .take(body.tx_count as usize)
+        .map(|entry| entry.map(|(_, transaction)| transaction))
+        .collect()
+}
+
+/// Find the canonical block that includes the transaction with the given id by binary searching
+/// the block bodies up to the given block number.
+///
+/// All canonical blocks up to the given block number must have a body.
+fn find_block_of_transaction<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    tx_id: TxNumber,
+    best_number: BlockNumber,
+) -> std::result::Result<Option<(BlockNumber, BlockHash, StoredBlockBody)>, DbError> {
+    let (mut low, mut high) = (0, best_number);
+    while low <= high {
+        let mid = low + (high - low) / 2;
+        let Some(hash) = tx.get::<tables::CanonicalHeaders>(mid)? else { return Ok(None) };
+        let Some(body) = tx.get::<tables::BlockBodies>((mid, hash).into())? else {
+            return Ok(None)
+        };
+
+        if body.tx_id_range().contains(&tx_id) {
+            return Ok(Some((mid, hash, body)))
+        } else if tx_id < body.start_tx_id {
+            if mid == 0 {
+                break
+            }
+            high = mid - 1;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        transaction::DbTxMut,
+    };
+    use reth_primitives::TxType;
+
+    #[test]
+    fn provider_reads_block_transactions() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let provider = ProviderImpl::new(db.clone());
+
+        // Block `n` contains `n` transactions
+        let headers = (0..4u64)
+            .map(|number| Header { number, base_fee_per_gas: Some(number), ..Default::default() })
+            .collect::<Vec<_>>();
+        let transactions = headers
+            .iter()
+            .flat_map(|header| {
+                (0..header.number).map(|_| {
+                    let transaction =
+                        TransactionSigned { hash: H256::random(), ..Default::default() };
+                    (header.number, header.hash_slow(), transaction)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        db.update(|tx| {
+            let mut tx_id = 0;
+            for header in &headers {
+                let (number, hash) = (header.number, header.hash_slow());
+                tx.put::<tables::CanonicalHeaders>(number, hash)?;
+                tx.put::<tables::HeaderNumbers>(hash, number)?;
+                tx.put::<tables::Headers>((number, hash).into(), header.clone())?;
+                tx.put::<tables::BlockBodies>(
+                    (number, hash).into(),
+                    StoredBlockBody { start_tx_id: tx_id, tx_count: number },
+                )?;
+                for (_, _, transaction) in transactions.iter().filter(|(n, _, _)| *n == number) {
+                    let receipt = Receipt { tx_type: TxType::Legacy, ..Default::default() };
+                    tx.put::<tables::Transactions>(tx_id, transaction.clone())?;
+                    tx.put::<tables::TxHashNumber>(transaction.hash, tx_id)?;
+                    tx.put::<tables::Receipts>(tx_id, receipt)?;
+                    tx_id += 1;
+                }
+            }
+            Ok::<_, DbError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        // headers of blocks whose bodies were not downloaded yet are not searched
+        db.update(|tx| {
+            for number in 4..16u64 {
+                tx.put::<tables::CanonicalHeaders>(number, H256::random())?;
+            }
+            Ok::<_, DbError>(())
+        })
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(provider.chain_info().unwrap().best_number, 0);
+        db.update(|tx| tx.put::<tables::SyncStage>(EXECUTION_STAGE_ID.as_bytes().to_vec(), 2))
+            .unwrap()
+            .unwrap();
+        let chain_info = provider.chain_info().unwrap();
+        assert_eq!((chain_info.best_number, chain_info.best_hash), (2, headers[2].hash_slow()));
+
+        for (index, (number, hash, transaction)) in transactions.iter().enumerate() {
+            let (found, meta) =
+                provider.transaction_by_hash_with_meta(transaction.hash).unwrap().unwrap();
+            assert_eq!(&found, transaction);
+            assert_eq!((meta.block_number, meta.block_hash), (*number, *hash));
+            assert_eq!(meta.base_fee, Some(*number));
+            // Blocks `1..n` contain `n * (n - 1) / 2` transactions
+            assert_eq!(meta.index, index as u64 - number * (number - 1) / 2);
+        }
+
+        let block = provider.block(BlockId::Number(2u64.into())).unwrap().unwrap();
+        assert_eq!(block.number, 2);
+        assert_eq!(block.body.len(), 2);
+        assert_eq!(provider.transactions_by_block(2u64.into()).unwrap(), Some(block.body));
+        assert_eq!(provider.receipts_by_block(3u64.into()).unwrap().map(|r| r.len()), Some(3));
+        assert_eq!(provider.transaction_by_hash(H256::random()).unwrap(), None);
+    }
+}
@@ -5,14 +5,15 @@ use crate::{
 use reth_db::{
     cursor::{DbCursorRO, DbDupCursorRO},
     database::{Database, DatabaseGAT},
-    tables,
+    models::{ShardedKey, StorageShardedKey},
+    tables::{self, TransitionList},
     transaction::DbTx,
 };
 use reth_interfaces::Result;
 
 use reth_primitives::{
-    Account, Address, BlockHash, BlockNumber, Bytes, StorageKey, StorageValue, TransitionId, H256,
-    U256,
+    Account, Address, BlockHash, BlockNumber, Bytes, StorageKey, StorageValue, TransitionId,
+    EXECUTION_STAGE_ID, H256, INDEX_ACCOUNT_HISTORY_STAGE_ID, INDEX_STORAGE_HISTORY_STAGE_ID, U256,
 };
 use std::marker::PhantomData;
 
@@ -37,6 +38,7 @@ impl<DB: Database> StateProviderFactory for ProviderImpl<DB> {
             .get::<tables::BlockTransitionIndex>(block_num_hash.into())?
             .ok_or(Error::BlockTransition { block_number, block_hash })?;
 
+        ensure_history_indexed(&tx)?;
         Ok(HistoricalStateProvider::new(tx, transition))
     }
 
@@ -52,10 +54,28 @@ impl<DB: Database> StateProviderFactory for ProviderImpl<DB> {
             .get::<tables::BlockTransitionIndex>(block_num_hash.into())?
             .ok_or(Error::BlockTransition { block_number, block_hash })?;
 
+        ensure_history_indexed(&tx)?;
         Ok(HistoricalStateProvider::new(tx, transition))
     }
 }
 
+/// Ensure that the history indices cover all executed blocks.
+///
+/// The historical state provider reads the plain state for the accounts and storage slots that
+/// have no indexed change after the requested transition, which is only correct if the changes of
+/// all executed blocks are indexed.
+fn ensure_history_indexed<'a, TX: DbTx<'a>>(tx: &TX) -> Result<()> {
+    let executed =
+        tx.get::<tables::SyncStage>(EXECUTION_STAGE_ID.as_bytes().to_vec())?.unwrap_or_default();
+    for stage in [INDEX_ACCOUNT_HISTORY_STAGE_ID, INDEX_STORAGE_HISTORY_STAGE_ID] {
+        let indexed = tx.get::<tables::SyncStage>(stage.as_bytes().to_vec())?.unwrap_or_default();
+        if indexed < executed {
+            return Err(Error::HistoryIndexBehind { stage, indexed, executed }.into())
+        }
+    }
+    Ok(())
+}
+
 /// State provider for a given transition
 pub struct HistoricalStateProvider<'a, TX: DbTx<'a>> {
     /// Database transaction
@@ -103,6 +123,8 @@ impl<'a, TX: DbTx<'a>> StateProvider for HistoricalStateProvider<'a, TX> {
 /// [tables::StorageHistory]
 /// [tables::AccountChangeSet]
 /// [tables::StorageChangeSet]
+/// [tables::PlainAccountState]
+/// [tables::PlainStorageState]
 pub struct HistoricalStateProviderRef<'a, 'b, TX: DbTx<'a>> {
     /// Transaction
     tx: &'b TX,
@@ -117,13 +139,44 @@ impl<'a, 'b, TX: DbTx<'a>> HistoricalStateProviderRef<'a, 'b, TX> {
     pub fn new(tx: &'b TX, transition: TransitionId) -> Self {
         Self { tx, transition, _phantom: PhantomData {} }
     }
+
+    /// Return the first transition id after the provider's transition contained in the history
+    /// shard.
+    ///
+    /// Shards are keyed by the highest transition id they contain, so the shard found by seeking
+    /// `transition + 1` is the only one that can contain it.
+    fn changed_after(&self, shard: Option<TransitionList>) -> Option<TransitionId> {
+        shard?.iter(0).map(|id| id as TransitionId).find(|id| *id > self.transition)
+    }
 }
 
 impl<'a, 'b, TX: DbTx<'a>> AccountProvider for HistoricalStateProviderRef<'a, 'b, TX> {
     /// Get basic account information.
-    fn basic_account(&self, _address: Address) -> Result<Option<Account>> {
-        // TODO add when AccountHistory is defined
-        Ok(None)
+    ///
+    /// The account changeset at the first change after the transition holds the state of the
+    /// account at the transition. If the account did not change since, the plain state is used.
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        let shard = self
+            .tx
+            .cursor::<tables::AccountHistory>()?
+            .walk(ShardedKey::new(address, self.transition + 1))?
+            .next()
+            .transpose()?
+            .filter(|(key, _)| key.key == address)
+            .map(|(_, list)| list);
+
+        match self.changed_after(shard) {
+            Some(transition_id) => {
+                let entry = self
+                    .tx
+                    .cursor_dup::<tables::AccountChangeSet>()?
+                    .seek_by_key_subkey(transition_id, address)?
+                    .filter(|entry| entry.address == address)
+                    .ok_or(Error::AccountChangeset { transition_id, address })?;
+                Ok(entry.info)
+            }
+            None => self.tx.get::<tables::PlainAccountState>(address).map_err(Into::into),
+        }
     }
 }
 
@@ -136,31 +189,37 @@ impl<'a, 'b, TX: DbTx<'a>> BlockHashProvider for HistoricalStateProviderRef<'a,
 
 impl<'a, 'b, TX: DbTx<'a>> StateProvider for HistoricalStateProviderRef<'a, 'b, TX> {
     /// Get storage.
+    ///
+    /// The storage changeset at the first change after the transition holds the value of the
+    /// slot at the transition. If the slot did not change since, the plain state is used.
     fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
-        // TODO when StorageHistory is defined
-        let transaction_number =
-            self.tx.get::<tables::StorageHistory>(Vec::new())?.map(|_integer_list|
-            // TODO select integer that is one less from transaction_number <- // TODO: (rkrasiuk) not sure this comment is still relevant
-            self.transition);
-
-        if transaction_number.is_none() {
-            return Ok(None)
-        }
-        let num = transaction_number.unwrap();
-        let mut cursor = self.tx.cursor_dup::<tables::StorageChangeSet>()?;
-
-        if let Some((_, entry)) = cursor.seek_exact((num, account).into())? {
-            if entry.key == storage_key {
-                return Ok(Some(entry.value))
-            }
-
-            if let Some((_, entry)) = cursor.seek(storage_key)? {
-                if entry.key == storage_key {
-                    return Ok(Some(entry.value))
-                }
-            }
-        }
-        Ok(None)
+        let shard = self
+            .tx
+            .cursor::<tables::StorageHistory>()?
+            .walk(StorageShardedKey::new(account, storage_key, self.transition + 1))?
+            .next()
+            .transpose()?
+            .filter(|(key, _)| key.address == account && key.sharded_key.key == storage_key)
+            .map(|(_, list)| list);
+
+        let entry = match self.changed_after(shard) {
+            Some(transition_id) => self
+                .tx
+                .cursor_dup::<tables::StorageChangeSet>()?
+                .seek_by_key_subkey((transition_id, account).into(), storage_key)?
+                .filter(|entry| entry.key == storage_key)
+                .ok_or(Error::StorageChangeset { transition_id, address: account, storage_key })?,
+            None => match self
+                .tx
+                .cursor_dup::<tables::PlainStorageState>()?
+                .seek_by_key_subkey(account, storage_key)?
+                .filter(|entry| entry.key == storage_key)
+            {
+                Some(entry) => entry,
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(entry.value))
     }
 
     /// Get account code by its hash
@@ -253,3 +312,103 @@ impl<'a, 'b, TX: DbTx<'a>> StateProvider for LatestStateProviderRef<'a, 'b, TX>
         self.db.get::<tables::Bytecodes>(code_hash).map_err(Into::into).map(|r| r.map(Bytes::from))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        models::AccountBeforeTx,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::StorageEntry;
+
+    fn account(nonce: u64) -> Account {
+        Account { nonce, balance: U256::ZERO, bytecode_hash: None }
+    }
+
+    #[test]
+    fn history_provider_reads_indexed_changes() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let address = Address::random();
+        let slot = H256::random();
+
+        // The account is created at transition 3 and updated at transition 7, the slot is set at
+        // transition 5
+        db.update(|tx| {
+            tx.put::<tables::AccountHistory>(
+                ShardedKey::new(address, u64::MAX),
+                vec![3u64, 7].into(),
+            )?;
+            tx.put::<tables::AccountChangeSet>(3, AccountBeforeTx { address, info: None })?;
+            tx.put::<tables::AccountChangeSet>(
+                7,
+                AccountBeforeTx { address, info: Some(account(1)) },
+            )?;
+            tx.put::<tables::PlainAccountState>(address, account(2))?;
+
+            tx.put::<tables::StorageHistory>(
+                StorageShardedKey::new(address, slot, u64::MAX),
+                vec![5u64].into(),
+            )?;
+            tx.put::<tables::StorageChangeSet>(
+                (5, address).into(),
+                StorageEntry { key: slot, value: U256::ZERO },
+            )?;
+            tx.put::<tables::PlainStorageState>(
+                address,
+                StorageEntry { key: slot, value: U256::from(1) },
+            )
+        })
+        .unwrap()
+        .unwrap();
+
+        let tx = db.tx().unwrap();
+        let at = |transition| HistoricalStateProviderRef::new(&tx, transition);
+
+        assert_eq!(at(2).basic_account(address), Ok(None));
+        assert_eq!(at(3).basic_account(address), Ok(Some(account(1))));
+        assert_eq!(at(6).basic_account(address), Ok(Some(account(1))));
+        assert_eq!(at(7).basic_account(address), Ok(Some(account(2))));
+        assert_eq!(at(100).basic_account(address), Ok(Some(account(2))));
+
+        assert_eq!(at(4).storage(address, slot), Ok(Some(U256::ZERO)));
+        assert_eq!(at(5).storage(address, slot), Ok(Some(U256::from(1))));
+    }
+
+    #[test]
+    fn history_provider_requires_indexed_history() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let hash = H256::random();
+        db.update(|tx| {
+            tx.put::<tables::CanonicalHeaders>(1, hash)?;
+            tx.put::<tables::HeaderNumbers>(hash, 1)?;
+            tx.put::<tables::BlockTransitionIndex>((1, hash).into(), 1)?;
+            tx.put::<tables::SyncStage>(EXECUTION_STAGE_ID.as_bytes().to_vec(), 2)?;
+            tx.put::<tables::SyncStage>(INDEX_ACCOUNT_HISTORY_STAGE_ID.as_bytes().to_vec(), 2)?;
+            tx.put::<tables::SyncStage>(INDEX_STORAGE_HISTORY_STAGE_ID.as_bytes().to_vec(), 1)
+        })
+        .unwrap()
+        .unwrap();
+
+        let provider = ProviderImpl::new(db.clone());
+        assert_eq!(
+            provider.history_by_block_number(1).err(),
+            Some(
+                Error::HistoryIndexBehind {
+                    stage: INDEX_STORAGE_HISTORY_STAGE_ID,
+                    indexed: 1,
+                    executed: 2
+                }
+                .into()
+            )
+        );
+
+        db.update(|tx| {
+            tx.put::<tables::SyncStage>(INDEX_STORAGE_HISTORY_STAGE_ID.as_bytes().to_vec(), 2)
+        })
+        .unwrap()
+        .unwrap();
+        assert!(provider.history_by_block_hash(hash).is_ok());
+    }
+}
@@ -8,9 +8,12 @@
 //! <reth crate template>
 
 mod block;
+mod chain;
 
 pub mod db_provider;
+mod receipts;
 mod state;
+mod transactions;
 
 #[cfg(any(test, feature = "test-utils"))]
 /// Common test helpers for mocking the Provider.
@@ -19,9 +22,15 @@ pub mod test_utils;
 pub use block::{
     insert_canonical_block, BlockHashProvider, BlockProvider, ChainInfo, HeaderProvider,
 };
+pub use chain::{
+    canon_state_notification_channel, CanonBlock, CanonStateNotification,
+    CanonStateNotificationSender, CanonStateNotifications,
+};
 pub use db_provider::{
     self as db, HistoricalStateProvider, HistoricalStateProviderRef, LatestStateProvider,
     LatestStateProviderRef, ProviderImpl,
 };
+pub use receipts::ReceiptProvider;
 pub use reth_interfaces::provider::Error;
 pub use state::{AccountProvider, StateProvider, StateProviderFactory};
+pub use transactions::{TransactionMeta, TransactionsProvider};
@@ -0,0 +1,11 @@
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{BlockHashOrNumber, Receipt};
+
+/// Client trait for fetching [Receipt] data.
+#[auto_impl(&)]
+pub trait ReceiptProvider: Send + Sync {
+    /// Get the receipts of the block, ordered by transaction index. Returns `None` if the block
+    /// or its receipts are not found.
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>>;
+}
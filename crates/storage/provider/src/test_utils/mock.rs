@@ -3,12 +3,14 @@ use reth_interfaces::Result;
 use reth_primitives::{
     keccak256,
     rpc::{BlockId, BlockNumber},
-    Account, Address, Block, BlockHash, Bytes, Header, StorageKey, StorageValue, H256, U256,
+    Account, Address, Block, BlockHash, BlockHashOrNumber, Bytes, Header, Receipt, StorageKey,
+    StorageValue, TransactionSigned, TxHash, H256, U256,
 };
 use std::{collections::HashMap, sync::Arc};
 
 use crate::{
-    AccountProvider, BlockHashProvider, BlockProvider, ChainInfo, HeaderProvider, StateProvider,
+    AccountProvider, BlockHashProvider, BlockProvider, ChainInfo, HeaderProvider, ReceiptProvider,
+    StateProvider, StateProviderFactory, TransactionMeta, TransactionsProvider,
 };
 
 /// A mock implementation for Provider interfaces.
@@ -20,6 +22,8 @@ pub struct MockEthProvider {
     pub headers: Arc<Mutex<HashMap<H256, Header>>>,
     /// Local account store
     pub accounts: Arc<Mutex<HashMap<Address, ExtendedAccount>>>,
+    /// Local receipt store, keyed by block hash
+    pub receipts: Arc<Mutex<HashMap<H256, Vec<Receipt>>>>,
 }
 
 /// An extended account for local store
@@ -87,6 +91,11 @@ impl MockEthProvider {
             self.add_account(address, account)
         }
     }
+
+    /// Add the receipts of a block to local receipt store
+    pub fn add_receipts(&self, hash: H256, receipts: Vec<Receipt>) {
+        self.receipts.lock().insert(hash, receipts);
+    }
 }
 
 impl HeaderProvider for MockEthProvider {
@@ -129,8 +138,15 @@ impl BlockHashProvider for MockEthProvider {
 }
 
 impl BlockProvider for MockEthProvider {
+    /// The best block is the header with the highest number.
     fn chain_info(&self) -> Result<ChainInfo> {
-        todo!()
+        let lock = self.headers.lock();
+        let (best_hash, best_number) = lock
+            .iter()
+            .max_by_key(|(_, header)| header.number)
+            .map(|(hash, header)| (*hash, header.number))
+            .unwrap_or_default();
+        Ok(ChainInfo { best_hash, best_number, last_finalized: None, safe_finalized: None })
     }
 
     fn block(&self, id: BlockId) -> Result<Option<Block>> {
@@ -153,6 +169,52 @@ impl BlockProvider for MockEthProvider {
     }
 }
 
+impl TransactionsProvider for MockEthProvider {
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        let lock = self.blocks.lock();
+        Ok(lock.iter().find_map(|(block_hash, block)| {
+            block.body.iter().enumerate().find(|(_, tx)| tx.hash == hash).map(|(index, tx)| {
+                let meta = TransactionMeta {
+                    tx_hash: hash,
+                    index: index as u64,
+                    block_hash: *block_hash,
+                    block_number: block.number,
+                    base_fee: block.base_fee_per_gas,
+                };
+                (tx.clone(), meta)
+            })
+        }))
+    }
+
+    fn transactions_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>> {
+        let lock = self.blocks.lock();
+        let block = match block {
+            BlockHashOrNumber::Hash(hash) => lock.get(&hash),
+            BlockHashOrNumber::Number(num) => lock.values().find(|b| b.number == num),
+        };
+        Ok(block.map(|b| b.body.clone()))
+    }
+}
+
+impl ReceiptProvider for MockEthProvider {
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        let hash = match block {
+            BlockHashOrNumber::Hash(hash) => hash,
+            BlockHashOrNumber::Number(number) => match self.block_hash(U256::from(number))? {
+                Some(hash) => hash,
+                None => return Ok(None),
+            },
+        };
+        Ok(self.receipts.lock().get(&hash).cloned())
+    }
+}
+
 impl AccountProvider for MockEthProvider {
     fn basic_account(&self, address: Address) -> Result<Option<Account>> {
         Ok(self.accounts.lock().get(&address).cloned().map(|a| a.account))
@@ -177,3 +239,21 @@ impl StateProvider for MockEthProvider {
         Ok(lock.get(&account).and_then(|account| account.storage.get(&storage_key)).cloned())
     }
 }
+
+/// The mock provider has no history, every state is the latest state.
+impl StateProviderFactory for MockEthProvider {
+    type HistorySP<'a> = &'a MockEthProvider where Self: 'a;
+    type LatestSP<'a> = &'a MockEthProvider where Self: 'a;
+
+    fn latest(&self) -> Result<Self::LatestSP<'_>> {
+        Ok(self)
+    }
+
+    fn history_by_block_number(&self, _block: reth_primitives::BlockNumber) -> Result<Self::HistorySP<'_>> {
+        Ok(self)
+    }
+
+    fn history_by_block_hash(&self, _block: BlockHash) -> Result<Self::HistorySP<'_>> {
+        Ok(self)
+    }
+}
@@ -1,6 +1,12 @@
-use crate::{BlockHashProvider, BlockProvider, ChainInfo, HeaderProvider};
+use crate::{
+    AccountProvider, BlockHashProvider, BlockProvider, ChainInfo, HeaderProvider, ReceiptProvider,
+    StateProvider, StateProviderFactory, TransactionMeta, TransactionsProvider,
+};
 use reth_interfaces::Result;
-use reth_primitives::{rpc::BlockId, Block, BlockHash, BlockNumber, Header, H256, U256};
+use reth_primitives::{
+    rpc::BlockId, Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, Bytes,
+    Header, Receipt, StorageKey, StorageValue, TransactionSigned, TxHash, H256, U256,
+};
 
 /// Supports various api interfaces for testing purposes.
 #[derive(Debug, Clone, Default)]
@@ -46,3 +52,58 @@ impl HeaderProvider for NoopProvider {
         Ok(None)
     }
 }
+
+impl TransactionsProvider for NoopProvider {
+    fn transaction_by_hash_with_meta(
+        &self,
+        _hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        Ok(None)
+    }
+
+    fn transactions_by_block(
+        &self,
+        _block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>> {
+        Ok(None)
+    }
+}
+
+impl ReceiptProvider for NoopProvider {
+    fn receipts_by_block(&self, _block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        Ok(None)
+    }
+}
+
+impl AccountProvider for NoopProvider {
+    fn basic_account(&self, _address: Address) -> Result<Option<Account>> {
+        Ok(None)
+    }
+}
+
+impl StateProvider for NoopProvider {
+    fn storage(&self, _account: Address, _storage_key: StorageKey) -> Result<Option<StorageValue>> {
+        Ok(None)
+    }
+
+    fn bytecode_by_hash(&self, _code_hash: H256) -> Result<Option<Bytes>> {
+        Ok(None)
+    }
+}
+
+impl StateProviderFactory for NoopProvider {
+    type HistorySP<'a> = NoopProvider where Self: 'a;
+    type LatestSP<'a> = NoopProvider where Self: 'a;
+
+    fn latest(&self) -> Result<Self::LatestSP<'_>> {
+        Ok(NoopProvider)
+    }
+
+    fn history_by_block_number(&self, _block: BlockNumber) -> Result<Self::HistorySP<'_>> {
+        Ok(NoopProvider)
+    }
+
+    fn history_by_block_hash(&self, _block: BlockHash) -> Result<Self::HistorySP<'_>> {
+        Ok(NoopProvider)
+    }
+}
@@ -0,0 +1,39 @@
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{BlockHashOrNumber, BlockNumber, TransactionSigned, TxHash, H256};
+
+/// Client trait for fetching [TransactionSigned] related data.
+#[auto_impl(&)]
+pub trait TransactionsProvider: Send + Sync {
+    /// Get transaction by transaction hash.
+    fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
+        self.transaction_by_hash_with_meta(hash).map(|tx| tx.map(|(tx, _)| tx))
+    }
+
+    /// Get transaction by transaction hash, together with the block it was included in.
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>>;
+
+    /// Get the transactions of the block. Returns `None` if the block is not found.
+    fn transactions_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>>;
+}
+
+/// Additional fields of a transaction that are derived from the block it was included in.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct TransactionMeta {
+    /// Hash of the transaction.
+    pub tx_hash: H256,
+    /// Index of the transaction in the block.
+    pub index: u64,
+    /// Hash of the block.
+    pub block_hash: H256,
+    /// Number of the block.
+    pub block_number: BlockNumber,
+    /// Base fee of the block.
+    pub base_fee: Option<u64>,
+}
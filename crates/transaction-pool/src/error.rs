@@ -1,6 +1,6 @@
 //! Transaction pool errors
 
-use reth_primitives::{Address, TxHash, U256};
+use reth_primitives::{Address, TxHash, TxType, U256};
 
 /// Transaction pool result type.
 pub type PoolResult<T> = Result<T, PoolError>;
@@ -21,6 +21,13 @@ pub enum PoolError {
     /// respect the size limits of the pool.
     #[error("[{0:?}] Transaction discarded outright due to pool size constraints.")]
     DiscardedOnInsert(TxHash),
+    /// Thrown when the transaction is considered invalid.
+    #[error("[{0:?}] {1}")]
+    InvalidTransaction(TxHash, InvalidPoolTransactionError),
+    /// Any other error that occurred while inserting/validating a transaction. e.g. IO database
+    /// error
+    #[error("[{0:?}] {1}")]
+    Other(TxHash, Box<dyn std::error::Error + Send + Sync>),
 }
 
 // === impl PoolError ===
@@ -33,6 +40,56 @@ impl PoolError {
             PoolError::ProtocolFeeCapTooLow(hash, _) => hash,
             PoolError::SpammerExceededCapacity(_, hash) => hash,
             PoolError::DiscardedOnInsert(hash) => hash,
+            PoolError::InvalidTransaction(hash, _) => hash,
+            PoolError::Other(hash, _) => hash,
         }
     }
 }
+
+/// Represents errors that can happen when validating transactions for the pool
+///
+/// See [`TransactionValidator`](crate::TransactionValidator).
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+pub enum InvalidPoolTransactionError {
+    /// The transaction is bound to a different chain.
+    #[error("Transaction's chain id {0:?} does not match the chain id {1}.")]
+    ChainIdMismatch(Option<u64>, u64),
+    /// The transaction type is not supported at the current fork.
+    #[error("Transaction type {0:?} is not supported.")]
+    TxTypeNotSupported(TxType),
+    /// The nonce of the transaction is lower than the nonce of the sender's account.
+    #[error("Transaction nonce {tx} is lower than the account nonce {state}.")]
+    NonceNotConsistent {
+        /// Nonce of the transaction.
+        tx: u64,
+        /// Current nonce of the sender.
+        state: u64,
+    },
+    /// The sender can't pay for the transaction.
+    #[error("Transaction cost {cost} exceeds the sender's balance {balance}.")]
+    InsufficientFunds {
+        /// Total cost of the transaction.
+        cost: U256,
+        /// Current balance of the sender.
+        balance: U256,
+    },
+    /// The gas limit is lower than the gas the transaction requires before execution.
+    #[error("Transaction gas limit {0} is below the intrinsic gas {1}.")]
+    IntrinsicGasTooLow(u64, u64),
+    /// The gas limit exceeds the block gas limit.
+    #[error("Transaction gas limit {0} exceeds the block gas limit {1}.")]
+    ExceedsGasLimit(u64, u64),
+    /// The init code of a contract creation exceeds the limit of
+    /// [EIP-3860](https://eips.ethereum.org/EIPS/eip-3860).
+    #[error("Transaction init code size {0} exceeds the maximum {1}.")]
+    ExceedsMaxInitCodeSize(usize, usize),
+    /// The encoded transaction exceeds the maximum size the pool accepts.
+    #[error("Transaction size {0} exceeds the maximum {1}.")]
+    OversizedData(usize, usize),
+    /// The sender is a contract, see [EIP-3607](https://eips.ethereum.org/EIPS/eip-3607).
+    #[error("Transaction sender has deployed code.")]
+    SignerAccountHasBytecode,
+    /// The max priority fee of an EIP-1559 transaction is higher than its max fee.
+    #[error("Transaction max priority fee is higher than the max fee.")]
+    TipAboveFeeCap,
+}
@@ -0,0 +1,282 @@
+//! Persistence of pool transactions across restarts.
+//!
+//! Local transactions are recorded in a [TransactionJournal] as they enter the pool, see
+//! [maintain_transaction_journal]. Transactions are stored RLP encoded, one after another.
+
+use crate::{TransactionOrigin, TransactionPool};
+use parking_lot::Mutex;
+use reth_primitives::{FromRecoveredTransaction, IntoRecoveredTransaction, TransactionSigned};
+use reth_rlp::{Decodable, Encodable};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tracing::*;
+
+/// An append-only file of transactions.
+#[derive(Debug)]
+pub struct TransactionJournal {
+    /// The path to the journal file.
+    path: PathBuf,
+    /// The open journal file, if any.
+    writer: Option<BufWriter<File>>,
+}
+
+// === impl TransactionJournal ===
+
+impl TransactionJournal {
+    /// Creates a journal that is stored in the given file.
+    ///
+    /// The file is created once the first transaction is inserted.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), writer: None }
+    }
+
+    /// Returns the path to the journal file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads all transactions of the journal.
+    ///
+    /// See [read_transactions].
+    pub fn load(&self) -> io::Result<Vec<TransactionSigned>> {
+        read_transactions(&self.path)
+    }
+
+    /// Appends the transaction to the journal.
+    pub fn insert(&mut self, transaction: &TransactionSigned) -> io::Result<()> {
+        if self.writer.is_none() {
+            let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+            self.writer = Some(BufWriter::new(file));
+        }
+        let writer = self.writer.as_mut().expect("is open");
+        let mut buf = Vec::with_capacity(transaction.length());
+        transaction.encode(&mut buf);
+        writer.write_all(&buf)?;
+        writer.flush()
+    }
+
+    /// Replaces the content of the journal with the given transactions.
+    ///
+    /// The transactions are written to a temporary file first that then replaces the journal, so
+    /// the journal stays intact if this fails.
+    pub fn rotate<'a>(
+        &mut self,
+        transactions: impl IntoIterator<Item = &'a TransactionSigned>,
+    ) -> io::Result<()> {
+        self.writer = None;
+        let tmp = self.path.with_extension("new");
+        write_transactions(&tmp, transactions)?;
+        fs::rename(tmp, &self.path)
+    }
+}
+
+/// Writes the transactions to the file, replacing its content.
+pub fn write_transactions<'a>(
+    path: &Path,
+    transactions: impl IntoIterator<Item = &'a TransactionSigned>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let mut buf = Vec::new();
+    for transaction in transactions {
+        buf.clear();
+        transaction.encode(&mut buf);
+        writer.write_all(&buf)?;
+    }
+    writer.into_inner()?.sync_all()
+}
+
+/// Reads the transactions written by [write_transactions] or a [TransactionJournal].
+///
+/// A missing file contains no transactions. Reading stops at the first entry that can't be
+/// decoded, e.g. because it was only partially written when the node crashed.
+pub fn read_transactions(path: &Path) -> io::Result<Vec<TransactionSigned>> {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut buf = content.as_slice();
+    let mut transactions = Vec::new();
+    while !buf.is_empty() {
+        match TransactionSigned::decode(&mut buf) {
+            Ok(transaction) => transactions.push(transaction),
+            Err(err) => {
+                warn!(target: "txpool", ?err, file = %path.display(), "Skipping corrupted transactions");
+                break
+            }
+        }
+    }
+    Ok(transactions)
+}
+
+/// Adds the transactions of the file to the pool.
+///
+/// Returns the number of transactions that were added.
+pub async fn load_transactions<Pool>(
+    pool: &Pool,
+    origin: TransactionOrigin,
+    path: &Path,
+) -> io::Result<usize>
+where
+    Pool: TransactionPool,
+    Pool::Transaction: FromRecoveredTransaction,
+{
+    let transactions = read_transactions(path)?
+        .into_iter()
+        .filter_map(TransactionSigned::into_ecrecovered)
+        .map(Pool::Transaction::from_recovered_transaction)
+        .collect::<Vec<_>>();
+    if transactions.is_empty() {
+        return Ok(0)
+    }
+    let added = pool
+        .add_transactions(origin, transactions)
+        .await
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    Ok(added.iter().filter(|result| result.is_ok()).count())
+}
+
+/// Writes the transactions of the pool to the file, optionally only the local ones.
+///
+/// Returns the number of transactions that were written.
+pub fn save_transactions<Pool>(pool: &Pool, path: &Path, local_only: bool) -> io::Result<usize>
+where
+    Pool: TransactionPool,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    let transactions = pool_transactions(pool, local_only);
+    write_transactions(path, &transactions)?;
+    Ok(transactions.len())
+}
+
+/// Returns the transactions of the pool ordered by sender and nonce, optionally only the local
+/// ones.
+fn pool_transactions<Pool>(pool: &Pool, local_only: bool) -> Vec<TransactionSigned>
+where
+    Pool: TransactionPool,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    let mut transactions = pool
+        .get_all(pool.pooled_transactions())
+        .into_iter()
+        .filter(|tx| !local_only || tx.origin.is_local())
+        .collect::<Vec<_>>();
+    transactions.sort_by_key(|tx| tx.transaction_id);
+    transactions
+        .into_iter()
+        .map(|tx| tx.transaction.to_recovered_transaction().into_signed())
+        .collect()
+}
+
+/// Records all local transactions that are added to the pool in the journal, and rewrites the
+/// journal with the local transactions of the pool right away and then every `rotate_interval`,
+/// which drops the transactions that were mined or discarded in the meantime.
+///
+/// The pool skips listeners that are full, so local transactions can be missing from the journal
+/// until the next rotation if they arrive faster than they are written.
+///
+/// This does not add the transactions of the journal to the pool, that should be done with
+/// [load_transactions] beforehand.
+pub async fn maintain_transaction_journal<Pool>(
+    pool: Pool,
+    journal: TransactionJournal,
+    rotate_interval: Duration,
+) where
+    Pool: TransactionPool,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    let journal = Arc::new(Mutex::new(journal));
+    let mut new_transactions = pool.transactions_listener();
+    let mut rotate = tokio::time::interval(rotate_interval);
+    loop {
+        tokio::select! {
+            event = new_transactions.recv() => {
+                let Some(event) = event else { break };
+                if !event.transaction.origin.is_local() {
+                    continue
+                }
+                let transaction = event.transaction.transaction.to_recovered_transaction();
+                let transaction = transaction.into_signed();
+                let insert = move |journal: &mut TransactionJournal| journal.insert(&transaction);
+                if let Err(err) = with_journal(&journal, insert).await {
+                    warn!(target: "txpool", ?err, "Failed to journal local transaction");
+                }
+            }
+            _ = rotate.tick() => {
+                let transactions = pool_transactions(&pool, true);
+                let count = transactions.len();
+                let rewrite = move |journal: &mut TransactionJournal| journal.rotate(&transactions);
+                match with_journal(&journal, rewrite).await {
+                    Ok(()) => debug!(target: "txpool", count, "Rotated transaction journal"),
+                    Err(err) => warn!(target: "txpool", ?err, "Failed to rotate transaction journal"),
+                }
+            }
+        }
+    }
+}
+
+/// Runs the blocking file operation on the journal on the blocking thread pool.
+async fn with_journal<F>(journal: &Arc<Mutex<TransactionJournal>>, f: F) -> io::Result<()>
+where
+    F: FnOnce(&mut TransactionJournal) -> io::Result<()> + Send + 'static,
+{
+    let journal = journal.clone();
+    tokio::task::spawn_blocking(move || f(&mut journal.lock()))
+        .await
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Signature, Transaction, TxEip1559};
+
+    fn transaction(nonce: u64) -> TransactionSigned {
+        let transaction = Transaction::Eip1559(TxEip1559 {
+            chain_id: 1,
+            nonce,
+            gas_limit: 21_000,
+            max_fee_per_gas: 10,
+            ..Default::default()
+        });
+        TransactionSigned::from_transaction_and_signature(transaction, Signature::default())
+    }
+
+    #[test]
+    fn journal_insert_and_rotate() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut journal = TransactionJournal::new(dir.path().join("transactions.rlp"));
+        assert!(journal.load().unwrap().is_empty());
+
+        let first = transaction(0);
+        let second = transaction(1);
+        journal.insert(&first).unwrap();
+        journal.insert(&second).unwrap();
+        assert_eq!(journal.load().unwrap(), vec![first.clone(), second.clone()]);
+
+        journal.rotate([&second]).unwrap();
+        assert_eq!(journal.load().unwrap(), vec![second.clone()]);
+
+        // the journal is appended to after a rotation
+        journal.insert(&first).unwrap();
+        assert_eq!(journal.load().unwrap(), vec![second, first]);
+    }
+
+    #[test]
+    fn skip_partially_written_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transactions.rlp");
+        let first = transaction(0);
+        write_transactions(&path, [&first, &transaction(1)]).unwrap();
+
+        let mut content = fs::read(&path).unwrap();
+        content.truncate(content.len() - 1);
+        fs::write(&path, content).unwrap();
+        assert_eq!(read_transactions(&path).unwrap(), vec![first]);
+    }
+}
@@ -45,7 +45,8 @@
 //! ### Validation
 //!
 //! The pool itself does not validate incoming transactions, instead this should be provided by
-//! implementing `TransactionsValidator`. Only transactions that the validator returns as valid are
+//! implementing `TransactionsValidator`. [`EthTransactionValidator`] validates ethereum
+//! transactions against the latest state. Only transactions that the validator returns as valid are
 //! included in the pool. It is assumed that transaction that are in the pool are either valid on
 //! the current state or could become valid after certain state changes. transaction that can never
 //! become valid (e.g. nonce lower than current on chain nonce) will never be added to the pool and
@@ -59,6 +60,14 @@
 //!   - update using account changes: balance changes
 //!   - base fee updates
 //!
+//! [`maintain_transaction_pool`] drives these updates from the canonical state notifications of the
+//! node and re-injects the transactions of blocks that were removed by a reorg.
+//!
+//! ### Persistence
+//!
+//! Local transactions can be recorded in a [`journal`](crate::journal) and added back to the pool
+//! after a restart.
+//!
 //! ## Implementation details
 //!
 //! The `TransactionPool` trait exposes all externally used functionality of the pool, such as
@@ -81,12 +90,13 @@
 
 pub use crate::{
     config::PoolConfig,
-    ordering::TransactionOrdering,
+    maintain::{maintain_transaction_pool, on_canon_state_change, resync_senders},
+    ordering::{CostOrdering, TransactionOrdering},
     traits::{
-        BestTransactions, OnNewBlockEvent, PoolTransaction, PropagateKind, PropagatedTransactions,
-        TransactionOrigin, TransactionPool,
+        BestTransactions, ChangedAccount, OnNewBlockEvent, PoolTransaction, PooledTransaction,
+        PropagateKind, PropagatedTransactions, StateDiff, TransactionOrigin, TransactionPool,
     },
-    validate::{TransactionValidationOutcome, TransactionValidator},
+    validate::{EthTransactionValidator, TransactionValidationOutcome, TransactionValidator},
 };
 use crate::{
     error::PoolResult,
@@ -94,13 +104,15 @@ use crate::{
     traits::{NewTransactionEvent, PoolSize},
     validate::ValidPoolTransaction,
 };
-use reth_primitives::{TxHash, U256};
+use reth_primitives::{Address, TxHash, U256};
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::mpsc::Receiver;
 
 mod config;
 pub mod error;
 mod identifier;
+pub mod journal;
+mod maintain;
 pub mod metrics;
 mod ordering;
 pub mod pool;
@@ -229,6 +241,14 @@ where
         self.pool.pooled_transactions()
     }
 
+    fn pending_transactions(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        self.pool.pending_transactions()
+    }
+
+    fn queued_transactions(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        self.pool.queued_transactions()
+    }
+
     fn best_transactions(
         &self,
     ) -> Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<Self::Transaction>>>> {
@@ -260,6 +280,10 @@ where
     fn on_propagated(&self, txs: PropagatedTransactions) {
         self.inner().on_propagated(txs)
     }
+
+    fn is_local_sender(&self, sender: &Address) -> bool {
+        self.inner().is_local_sender(sender)
+    }
 }
 
 impl<V: TransactionValidator, O: TransactionOrdering> Clone for Pool<V, O> {
@@ -0,0 +1,301 @@
+//! Support for keeping the pool in sync with the canonical chain.
+
+use crate::{
+    ChangedAccount, OnNewBlockEvent, PoolTransaction, StateDiff, TransactionOrigin, TransactionPool,
+};
+use reth_primitives::{FromRecoveredTransaction, SealedHeader, U256};
+use reth_provider::{
+    AccountProvider, CanonStateNotification, CanonStateNotifications, StateProviderFactory,
+};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::*;
+
+/// Updates the pool with every [CanonStateNotification] until the channel is closed.
+///
+/// If notifications were missed or a notification is a reset, the state of all senders in the pool
+/// is reloaded from the client, see [resync_senders].
+///
+/// See also [on_canon_state_change].
+pub async fn maintain_transaction_pool<Client, Pool>(
+    client: Client,
+    pool: Pool,
+    mut notifications: CanonStateNotifications,
+) where
+    Client: StateProviderFactory,
+    Pool: TransactionPool,
+    Pool::Transaction: FromRecoveredTransaction,
+{
+    // whether notifications were missed since the last resync
+    let mut lagged = false;
+    loop {
+        match notifications.recv().await {
+            Ok(notification) => {
+                if !notification.reset {
+                    on_canon_state_change(&pool, &notification).await;
+                }
+                if notification.reset || lagged {
+                    lagged = false;
+                    resync_senders(&client, &pool, &notification.tip);
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(target: "txpool", skipped, "Missed canonical state notifications, resyncing senders");
+                lagged = true;
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Reloads the state of all senders in the pool from the latest state of the client and sets the
+/// given header as the tip of the pool.
+///
+/// This is used if the changes of the canonical chain are not known, transactions with nonces
+/// that were used in the meantime are removed.
+pub fn resync_senders<Client, Pool>(client: &Client, pool: &Pool, tip: &SealedHeader)
+where
+    Client: StateProviderFactory,
+    Pool: TransactionPool,
+{
+    let senders = pool
+        .get_all(pool.pooled_transactions())
+        .iter()
+        .map(|tx| tx.sender())
+        .collect::<HashSet<_>>();
+
+    let state = match client.latest() {
+        Ok(state) => state,
+        Err(err) => {
+            warn!(target: "txpool", ?err, "Failed to resync senders");
+            return
+        }
+    };
+    let mut state_changes = HashMap::with_capacity(senders.len());
+    for sender in senders {
+        match state.basic_account(sender) {
+            Ok(account) => {
+                let account = account.unwrap_or_default();
+                state_changes.insert(
+                    sender,
+                    ChangedAccount { nonce: account.nonce, balance: account.balance },
+                );
+            }
+            Err(err) => {
+                warn!(target: "txpool", ?err, ?sender, "Failed to resync sender");
+            }
+        }
+    }
+    debug!(target: "txpool", senders = state_changes.len(), tip = ?tip.hash(), "Resynced senders");
+
+    pool.on_new_block(OnNewBlockEvent {
+        hash: tip.hash(),
+        pending_block_base_fee: U256::from(tip.next_block_base_fee().unwrap_or_default()),
+        state_changes: StateDiff(state_changes),
+        mined_transactions: Vec::new(),
+    });
+}
+
+/// Applies a change of the canonical chain to the pool.
+///
+/// Mined transactions are removed, the state of the changed accounts and the base fee of the
+/// pending block are updated. Transactions of blocks that were removed from the canonical chain are
+/// added back to the pool, unless they were mined again. Since mined transactions are no longer in
+/// the pool, their origin is derived from the sender: transactions of senders that added local
+/// transactions are added back as [`TransactionOrigin::Local`].
+pub async fn on_canon_state_change<Pool>(pool: &Pool, notification: &CanonStateNotification)
+where
+    Pool: TransactionPool,
+    Pool::Transaction: FromRecoveredTransaction,
+{
+    let state_changes = notification
+        .changed_accounts
+        .iter()
+        .map(|(address, account)| {
+            let account = account.unwrap_or_default();
+            (*address, ChangedAccount { nonce: account.nonce, balance: account.balance })
+        })
+        .collect();
+    pool.on_new_block(OnNewBlockEvent {
+        hash: notification.tip.hash(),
+        pending_block_base_fee: U256::from(
+            notification.pending_block_base_fee().unwrap_or_default(),
+        ),
+        state_changes: StateDiff(state_changes),
+        mined_transactions: notification.mined_transactions().collect(),
+    });
+
+    let (local, external): (Vec<_>, Vec<_>) = notification
+        .reverted_transactions()
+        .cloned()
+        .map(Pool::Transaction::from_recovered_transaction)
+        .partition(|tx| pool.is_local_sender(&tx.sender()));
+    reinject(pool, TransactionOrigin::Local, local).await;
+    reinject(pool, TransactionOrigin::External, external).await;
+}
+
+/// Adds the transactions of reverted blocks back to the pool.
+async fn reinject<Pool>(
+    pool: &Pool,
+    origin: TransactionOrigin,
+    transactions: Vec<Pool::Transaction>,
+) where
+    Pool: TransactionPool,
+{
+    if transactions.is_empty() {
+        return
+    }
+    let count = transactions.len();
+    match pool.add_transactions(origin, transactions).await {
+        Ok(results) => {
+            let rejected = results.iter().filter(|result| result.is_err()).count();
+            debug!(
+                target: "txpool",
+                ?origin,
+                count,
+                rejected,
+                "Re-injected transactions of reverted blocks"
+            );
+        }
+        Err(err) => {
+            warn!(
+                target: "txpool",
+                ?origin,
+                ?err,
+                "Failed to re-inject transactions of reverted blocks"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{testing_pool, MockTransaction};
+    use reth_primitives::{
+        Account, Address, Header, Signature, Transaction, TransactionSigned,
+        TransactionSignedEcRecovered, TxEip1559,
+    };
+    use reth_provider::{
+        test_utils::{ExtendedAccount, MockEthProvider},
+        CanonBlock,
+    };
+    use std::collections::BTreeMap;
+
+    fn transaction(sender: Address, nonce: u64) -> TransactionSignedEcRecovered {
+        let transaction = Transaction::Eip1559(TxEip1559 {
+            chain_id: 1,
+            nonce,
+            gas_limit: 21_000,
+            max_fee_per_gas: 10,
+            ..Default::default()
+        });
+        TransactionSignedEcRecovered::from_signed_transaction(
+            TransactionSigned::from_transaction_and_signature(transaction, Signature::default()),
+            sender,
+        )
+    }
+
+    fn block(number: u64, transactions: Vec<TransactionSignedEcRecovered>) -> CanonBlock {
+        let header = Header { number, base_fee_per_gas: Some(7), ..Default::default() }.seal();
+        CanonBlock { header, transactions }
+    }
+
+    #[tokio::test]
+    async fn reinject_reverted_and_remove_mined() {
+        let pool = testing_pool();
+        let sender = Address::random();
+        let first = transaction(sender, 0);
+        let second = transaction(sender, 1);
+
+        // a reorg replaces a block with both transactions by a block with only the first
+        let reorg = CanonStateNotification {
+            tip: block(1, vec![]).header,
+            reverted: vec![block(1, vec![first.clone(), second.clone()])],
+            committed: vec![block(1, vec![first.clone()])],
+            changed_accounts: BTreeMap::from([(
+                sender,
+                Some(Account { nonce: 1, ..Default::default() }),
+            )]),
+            reset: false,
+        };
+        on_canon_state_change(&pool, &reorg).await;
+        assert_eq!(pool.pooled_transactions(), vec![second.hash()]);
+
+        let commit = CanonStateNotification {
+            tip: block(2, vec![]).header,
+            reverted: vec![],
+            committed: vec![block(2, vec![second.clone()])],
+            changed_accounts: BTreeMap::from([(
+                sender,
+                Some(Account { nonce: 2, ..Default::default() }),
+            )]),
+            reset: false,
+        };
+        on_canon_state_change(&pool, &commit).await;
+        assert!(pool.pooled_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn reinject_reverted_local_transactions_as_local() {
+        let pool = testing_pool();
+        let local_sender = Address::random();
+        let external_sender = Address::random();
+        let local = transaction(local_sender, 0);
+        let external = transaction(external_sender, 0);
+        pool.add_transaction(
+            TransactionOrigin::Local,
+            MockTransaction::from_recovered_transaction(local.clone()),
+        )
+        .await
+        .unwrap();
+
+        let commit = CanonStateNotification {
+            tip: block(1, vec![]).header,
+            reverted: vec![],
+            committed: vec![block(1, vec![local.clone(), external.clone()])],
+            changed_accounts: BTreeMap::from([
+                (local_sender, Some(Account { nonce: 1, ..Default::default() })),
+                (external_sender, Some(Account { nonce: 1, ..Default::default() })),
+            ]),
+            reset: false,
+        };
+        on_canon_state_change(&pool, &commit).await;
+        assert!(pool.pooled_transactions().is_empty());
+
+        // both transactions are reverted, only the local one keeps its origin
+        let reorg = CanonStateNotification {
+            tip: block(1, vec![]).header,
+            reverted: vec![block(1, vec![local.clone(), external.clone()])],
+            committed: vec![block(1, vec![])],
+            changed_accounts: BTreeMap::from([
+                (local_sender, Some(Account::default())),
+                (external_sender, Some(Account::default())),
+            ]),
+            reset: false,
+        };
+        on_canon_state_change(&pool, &reorg).await;
+        assert_eq!(pool.get(&local.hash()).unwrap().origin, TransactionOrigin::Local);
+        assert_eq!(pool.get(&external.hash()).unwrap().origin, TransactionOrigin::External);
+    }
+
+    #[tokio::test]
+    async fn resync_senders_from_client() {
+        let pool = testing_pool();
+        let sender = Address::random();
+        let first = transaction(sender, 0);
+        let second = transaction(sender, 1);
+        let transactions = vec![first, second.clone()]
+            .into_iter()
+            .map(MockTransaction::from_recovered_transaction)
+            .collect();
+        pool.add_transactions(TransactionOrigin::External, transactions).await.unwrap();
+        assert_eq!(pool.pooled_transactions().len(), 2);
+
+        // the first transaction was mined in a block that the pool was not notified about
+        let client = MockEthProvider::default();
+        client.add_account(sender, ExtendedAccount::new(1, U256::MAX));
+        resync_senders(&client, &pool, &block(5, vec![]).header);
+        assert_eq!(pool.pooled_transactions(), vec![second.hash()]);
+    }
+}
@@ -70,15 +70,21 @@
 use crate::{
     error::{PoolError, PoolResult},
     identifier::{SenderId, SenderIdentifiers, TransactionId},
-    pool::{listener::PoolEventBroadcast, state::SubPool, txpool::TxPool},
+    pool::{
+        listener::PoolEventBroadcast,
+        state::SubPool,
+        txpool::{SenderInfo, TxPool},
+    },
     traits::{
-        NewTransactionEvent, PoolSize, PoolTransaction, PropagatedTransactions, TransactionOrigin,
+        NewTransactionEvent, PoolSize, PoolTransaction, PropagatedTransactions, StateDiff,
+        TransactionOrigin,
     },
     validate::{TransactionValidationOutcome, ValidPoolTransaction},
     OnNewBlockEvent, PoolConfig, TransactionOrdering, TransactionValidator,
 };
 use best::BestTransactions;
 pub use events::TransactionEvent;
+use fnv::FnvHashMap;
 use parking_lot::{Mutex, RwLock};
 use reth_primitives::{Address, TxHash, H256};
 use std::{collections::HashSet, fmt, sync::Arc, time::Instant};
@@ -100,6 +106,8 @@ mod update;
 pub struct PoolInner<V: TransactionValidator, T: TransactionOrdering> {
     /// Internal mapping of addresses to plain ints.
     identifiers: RwLock<SenderIdentifiers>,
+    /// Senders of transactions that were added locally.
+    local_senders: RwLock<HashSet<Address>>,
     /// Transaction validation.
     validator: Arc<V>,
     /// The internal pool that manages all transactions.
@@ -125,6 +133,7 @@ where
     pub(crate) fn new(validator: Arc<V>, ordering: Arc<T>, config: PoolConfig) -> Self {
         Self {
             identifiers: Default::default(),
+            local_senders: Default::default(),
             validator,
             event_listener: Default::default(),
             pool: RwLock::new(TxPool::new(ordering, config.clone())),
@@ -144,6 +153,11 @@ where
         self.identifiers.write().sender_id_or_create(addr)
     }
 
+    /// Returns whether the address sent a transaction that was added locally.
+    pub(crate) fn is_local_sender(&self, sender: &Address) -> bool {
+        self.local_senders.read().contains(sender)
+    }
+
     /// Get the config the pool was configured with.
     pub fn config(&self) -> &PoolConfig {
         &self.config
@@ -177,12 +191,38 @@ where
         pool.all().hashes_iter().collect()
     }
 
+    /// Returns all transactions of the pending sub-pool.
+    pub(crate) fn pending_transactions(&self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.pool.read().pending_transactions()
+    }
+
+    /// Returns all transactions that are not pending.
+    pub(crate) fn queued_transactions(&self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.pool.read().queued_transactions()
+    }
+
     /// Updates the entire pool after a new block was executed.
     pub(crate) fn on_new_block(&self, block: OnNewBlockEvent) {
-        let outcome = self.pool.write().on_new_block(block);
+        let changed_senders = self.changed_senders(&block.state_changes);
+        let outcome = self.pool.write().on_new_block(block, changed_senders);
         self.notify_on_new_block(outcome);
     }
 
+    /// Returns the new state of all senders in the diff that are known to the pool.
+    ///
+    /// Accounts without an assigned `SenderId` have no transactions in the pool.
+    fn changed_senders(&self, state_changes: &StateDiff) -> FnvHashMap<SenderId, SenderInfo> {
+        let identifiers = self.identifiers.read();
+        state_changes
+            .0
+            .iter()
+            .filter_map(|(address, account)| {
+                let sender = identifiers.sender_id(address)?;
+                Some((sender, SenderInfo::from(*account)))
+            })
+            .collect()
+    }
+
     /// Add a single validated transaction into the pool.
     ///
     /// Note: this is only used internally by [`Self::add_transactions()`], all new transaction(s)
@@ -194,6 +234,9 @@ where
     ) -> PoolResult<TxHash> {
         match tx {
             TransactionValidationOutcome::Valid { balance, state_nonce, transaction } => {
+                if origin.is_local() {
+                    self.local_senders.write().insert(transaction.sender());
+                }
                 let sender_id = self.get_sender_id(transaction.sender());
                 let transaction_id = TransactionId::new(sender_id, transaction.nonce());
 
@@ -227,6 +270,11 @@ where
                 listener.discarded(tx.hash());
                 Err(err)
             }
+            TransactionValidationOutcome::Error(tx, err) => {
+                let mut listener = self.event_listener.write();
+                listener.discarded(tx.hash());
+                Err(PoolError::Other(*tx.hash(), err))
+            }
         }
     }
 
@@ -290,7 +338,8 @@ where
                 if matches!(err, mpsc::error::TrySendError::Full(_)) {
                     warn!(
                         target: "txpool",
-                        "skipping transaction on full transaction listener",
+                        "[{:?}] skipping transaction on full transaction listener",
+                        event.transaction.hash(),
                     );
                     true
                 } else {
@@ -12,7 +12,7 @@ use crate::{
         update::{Destination, PoolUpdate},
         AddedPendingTransaction, AddedTransaction, OnNewBlockOutcome,
     },
-    traits::{PoolSize, StateDiff},
+    traits::{ChangedAccount, PoolSize},
     OnNewBlockEvent, PoolConfig, PoolResult, PoolTransaction, TransactionOrdering,
     ValidPoolTransaction, U256,
 };
@@ -126,10 +126,11 @@ impl<T: TransactionOrdering> TxPool<T> {
 
     /// Updates the pool based on the changed base fee.
     ///
-    /// This enforces the dynamic fee requirement.
-    pub(crate) fn update_base_fee(&mut self, _new_base_fee: U256) {
-        // TODO update according to the changed base_fee
-        todo!()
+    /// This enforces the dynamic fee requirement: transactions that no longer satisfy the new base
+    /// fee are moved from the `pending` to the `basefee` sub-pool and vice versa.
+    pub(crate) fn update_base_fee(&mut self, new_base_fee: U256) -> UpdateOutcome<T::Transaction> {
+        let updates = self.all_transactions.update(new_base_fee, &Default::default());
+        self.process_updates(updates)
     }
 
     /// Returns an iterator that yields transactions that are ready to be included in the block.
@@ -158,11 +159,33 @@ impl<T: TransactionOrdering> TxPool<T> {
         txs.into_iter().filter_map(|tx| self.get(&tx))
     }
 
+    /// Returns all transactions of the pending sub-pool, ordered by sender and nonce.
+    pub(crate) fn pending_transactions(&self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.all_transactions.transactions_by_subpool(|subpool| subpool.is_pending())
+    }
+
+    /// Returns all transactions of the basefee and queued sub-pools, ordered by sender and nonce.
+    pub(crate) fn queued_transactions(&self) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.all_transactions.transactions_by_subpool(|subpool| !subpool.is_pending())
+    }
+
     /// Updates the entire pool after a new block was mined.
     ///
     /// This removes all mined transactions, updates according to the new base fee and rechecks
     /// sender allowance.
-    pub(crate) fn on_new_block(&mut self, event: OnNewBlockEvent) -> OnNewBlockOutcome {
+    ///
+    /// `changed_senders` contains the new state of all senders in the pool that were affected by
+    /// the block.
+    pub(crate) fn on_new_block(
+        &mut self,
+        event: OnNewBlockEvent,
+        changed_senders: FnvHashMap<SenderId, SenderInfo>,
+    ) -> OnNewBlockOutcome {
+        // Track the new state of the senders
+        for (sender, info) in &changed_senders {
+            self.sender_info.insert(*sender, info.clone());
+        }
+
         // Remove all transaction that were included in the block
         for tx_hash in &event.mined_transactions {
             self.remove_transaction_by_hash(tx_hash);
@@ -171,8 +194,7 @@ impl<T: TransactionOrdering> TxPool<T> {
         }
 
         // Apply the state changes to the total set of transactions which triggers sub-pool updates.
-        let updates =
-            self.all_transactions.update(event.pending_block_base_fee, &event.state_changes);
+        let updates = self.all_transactions.update(event.pending_block_base_fee, &changed_senders);
 
         // Process the sub-pool updates
         let UpdateOutcome { promoted, discarded, .. } = self.process_updates(updates);
@@ -273,11 +295,15 @@ impl<T: TransactionOrdering> TxPool<T> {
             let PoolUpdate { id, hash, current, destination } = update;
             match destination {
                 Destination::Discard => {
+                    self.remove_transaction(&id);
                     outcome.discarded.push(hash);
                 }
                 Destination::Pool(move_to) => {
                     debug_assert!(!move_to.eq(&current), "destination must be different");
                     self.move_transaction(current, move_to, &id);
+                    if move_to.is_pending() {
+                        outcome.promoted.push(hash);
+                    }
                 }
             }
         }
@@ -509,6 +535,18 @@ impl<T: PoolTransaction> AllTransactions<T> {
         self.by_hash.contains_key(tx_hash)
     }
 
+    /// Returns all transactions whose sub-pool matches the predicate, ordered by sender and nonce.
+    pub(crate) fn transactions_by_subpool(
+        &self,
+        mut predicate: impl FnMut(SubPool) -> bool,
+    ) -> Vec<Arc<ValidPoolTransaction<T>>> {
+        self.txs
+            .values()
+            .filter(|tx| predicate(tx.subpool))
+            .map(|tx| Arc::clone(&tx.transaction))
+            .collect()
+    }
+
     /// Returns the internal transaction with additional metadata
     #[cfg(test)]
     pub(crate) fn get(&self, id: &TransactionId) -> Option<&PoolInternalTransaction<T>> {
@@ -540,18 +578,19 @@ impl<T: PoolTransaction> AllTransactions<T> {
     /// For all transactions:
     ///   - decreased basefee: promotes from `basefee` to `pending` sub-pool.
     ///   - increased basefee: demotes from `pending` to `basefee` sub-pool.
-    /// Individually:
+    /// Individually, for all senders in `changed_senders`:
+    ///   - increased nonce: discards all transactions below the new nonce.
     ///   - decreased sender allowance: demote from (`basefee`|`pending`) to `queued`.
     ///   - increased sender allowance: promote from `queued` to
     ///       - `pending` if basefee condition is met.
     ///       - `basefee` if basefee condition is _not_ met.
     ///
-    /// Additionally, this will also update the `cumulative_gas_used` for transactions of a sender
+    /// Additionally, this will also update the `cumulative_cost` for transactions of a sender
     /// that got transaction included in the block.
     pub(crate) fn update(
         &mut self,
         pending_block_base_fee: U256,
-        _state_diffs: &StateDiff,
+        changed_senders: &FnvHashMap<SenderId, SenderInfo>,
     ) -> Vec<PoolUpdate> {
         // update new basefee
         self.pending_basefee = pending_block_base_fee;
@@ -582,15 +621,66 @@ impl<T: PoolTransaction> AllTransactions<T> {
                     }
                 };
             }
+
+            // The state of this sender changed, so all of its transactions need to be rechecked
+            // against the new nonce and balance.
+            if let Some(info) = changed_senders.get(&id.sender) {
+                if id.nonce < info.state_nonce {
+                    // the nonce is already used, so this transaction can never be included
+                    updates.push(PoolUpdate {
+                        id: *id,
+                        hash: *tx.transaction.hash(),
+                        current: tx.subpool,
+                        destination: Destination::Discard,
+                    });
+                    continue
+                }
+
+                let (mut id, mut tx) = (id, tx);
+                let mut next_nonce = info.state_nonce;
+                let mut cumulative_cost = U256::ZERO;
+                let mut has_parked_ancestor = false;
+                loop {
+                    if id.nonce == next_nonce {
+                        tx.state.insert(TxState::NO_NONCE_GAPS);
+                        next_nonce = id.next_nonce();
+                    } else {
+                        tx.state.remove(TxState::NO_NONCE_GAPS);
+                    }
+
+                    tx.cumulative_cost = cumulative_cost;
+                    cumulative_cost = tx.next_cumulative_cost();
+                    if cumulative_cost > info.balance {
+                        tx.state.remove(TxState::ENOUGH_BALANCE);
+                    } else {
+                        tx.state.insert(TxState::ENOUGH_BALANCE);
+                    }
+
+                    if has_parked_ancestor {
+                        tx.state.remove(TxState::NO_PARKED_ANCESTORS);
+                    } else {
+                        tx.state.insert(TxState::NO_PARKED_ANCESTORS);
+                    }
+
+                    Self::update_base_fee(&pending_block_base_fee, tx);
+                    Self::record_subpool_update(&mut updates, tx);
+                    has_parked_ancestor = !tx.state.is_pending();
+
+                    match iter.peek() {
+                        Some((peek, _)) if peek.sender == id.sender => {
+                            (id, tx) = iter.next().expect("exists; qed");
+                        }
+                        _ => continue 'unique_sender,
+                    }
+                }
+            }
+
             // If there's a nonce gap, we can shortcircuit, because there's nothing to update.
             if tx.state.has_nonce_gap() {
                 next_sender!(iter);
                 continue
             }
 
-            // TODO(mattsse): if account has balance changes or mined transactions the balance needs
-            // to be checked here
-
             // Since this is the first transaction of the sender, it has no parked ancestors
             tx.state.insert(TxState::NO_PARKED_ANCESTORS);
 
@@ -620,11 +710,11 @@ impl<T: PoolTransaction> AllTransactions<T> {
                 } else {
                     tx.state.insert(TxState::NO_PARKED_ANCESTORS);
                 }
-                has_parked_ancestor = !tx.state.is_pending();
 
                 // Update and record sub-pool changes.
                 Self::update_base_fee(&pending_block_base_fee, tx);
                 Self::record_subpool_update(&mut updates, tx);
+                has_parked_ancestor = !tx.state.is_pending();
 
                 // Advance iterator
                 iter.next();
@@ -1096,7 +1186,7 @@ impl<T: PoolTransaction> fmt::Debug for PruneResult<T> {
 
 /// Stores relevant context about a sender.
 #[derive(Debug, Clone, Default)]
-struct SenderInfo {
+pub(crate) struct SenderInfo {
     /// current nonce of the sender.
     state_nonce: u64,
     /// Balance of the sender at the current point.
@@ -1117,11 +1207,17 @@ impl SenderInfo {
     }
 }
 
+impl From<ChangedAccount> for SenderInfo {
+    fn from(account: ChangedAccount) -> Self {
+        Self { state_nonce: account.nonce, balance: account.balance }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        test_utils::{MockTransaction, MockTransactionFactory},
+        test_utils::{mock_tx_pool, MockTransaction, MockTransactionFactory},
         traits::TransactionOrigin,
     };
 
@@ -1340,4 +1436,82 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn update_base_fee() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = mock_tx_pool();
+        let tx = MockTransaction::eip1559().inc_price().inc_limit();
+        let fee_cap = tx.get_max_fee().unwrap();
+        pool.add_transaction(f.validated(tx.clone()), U256::from(1_000), 0).unwrap();
+        assert_eq!(pool.pending().len(), 1);
+
+        // the fee cap no longer covers the base fee of the pending block
+        let outcome = pool.update_base_fee(fee_cap + U256::from(1));
+        assert!(outcome.promoted.is_empty());
+        assert!(pool.pending().is_empty());
+        assert_eq!(pool.base_fee().len(), 1);
+
+        let outcome = pool.update_base_fee(fee_cap);
+        assert_eq!(outcome.promoted, vec![*tx.hash()]);
+        assert_eq!(pool.pending().len(), 1);
+        assert!(pool.base_fee().is_empty());
+    }
+
+    #[test]
+    fn list_pending_and_queued_transactions() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = mock_tx_pool();
+        let tx = MockTransaction::eip1559().inc_price().inc_limit();
+        let first = f.validated(tx.clone());
+        let second = f.validated(tx.next());
+        let gapped = f.validated(tx.skip(2));
+
+        // the second transaction is added first but becomes pending once the gap is filled
+        for tx in [second.clone(), first.clone(), gapped.clone()] {
+            pool.add_transaction(tx, U256::from(1_000), 0).unwrap();
+        }
+        let hashes = |txs: Vec<Arc<ValidPoolTransaction<MockTransaction>>>| {
+            txs.iter().map(|tx| *tx.hash()).collect::<Vec<_>>()
+        };
+        assert_eq!(hashes(pool.pending_transactions()), vec![*first.hash(), *second.hash()]);
+        assert_eq!(hashes(pool.queued_transactions()), vec![*gapped.hash()]);
+    }
+
+    #[test]
+    fn on_new_block_updates_senders() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = mock_tx_pool();
+        let tx = MockTransaction::eip1559().inc_price().inc_limit();
+        let first = f.validated(tx.clone());
+        let second = f.validated(tx.next());
+        let third = f.validated(tx.next().next());
+
+        // the balance only covers the first transaction
+        let balance = first.cost;
+        for tx in [first.clone(), second.clone(), third.clone()] {
+            pool.add_transaction(tx, balance, 0).unwrap();
+        }
+        assert_eq!(pool.pending().len(), 1);
+        assert_eq!(pool.queued().len(), 2);
+
+        // the nonce of the first transaction was used and the sender received funds
+        let changed_senders = FnvHashMap::from_iter([(
+            first.sender_id(),
+            SenderInfo { state_nonce: 1, balance: second.cost + third.cost },
+        )]);
+        let event = OnNewBlockEvent {
+            hash: H256::random(),
+            pending_block_base_fee: U256::ZERO,
+            state_changes: Default::default(),
+            mined_transactions: vec![],
+        };
+        let outcome = pool.on_new_block(event, changed_senders);
+
+        assert_eq!(outcome.discarded, vec![*first.hash()]);
+        assert_eq!(outcome.promoted, vec![*second.hash(), *third.hash()]);
+        assert!(!pool.contains(first.hash()));
+        assert_eq!(pool.pending().len(), 2);
+        assert!(pool.queued().is_empty());
+    }
 }
@@ -12,8 +12,9 @@ use rand::{
     prelude::Distribution,
 };
 use reth_primitives::{
-    Address, FromRecoveredTransaction, IntoRecoveredTransaction, Transaction,
-    TransactionSignedEcRecovered, TxEip1559, TxHash, TxLegacy, H256, U256,
+    AccessList, Address, Bytes, FromRecoveredTransaction, IntoRecoveredTransaction, Signature,
+    Transaction, TransactionKind, TransactionSigned, TransactionSignedEcRecovered, TxEip1559,
+    TxHash, TxLegacy, TxType, H256, U256,
 };
 use std::{ops::Range, sync::Arc, time::Instant};
 
@@ -85,7 +86,9 @@ pub enum MockTransaction {
         nonce: u64,
         gas_price: U256,
         gas_limit: u64,
+        to: TransactionKind,
         value: U256,
+        input: Bytes,
     },
     Eip1559 {
         hash: H256,
@@ -94,7 +97,9 @@ pub enum MockTransaction {
         max_fee_per_gas: U256,
         max_priority_fee_per_gas: U256,
         gas_limit: u64,
+        to: TransactionKind,
         value: U256,
+        input: Bytes,
     },
 }
 
@@ -106,7 +111,9 @@ impl MockTransaction {
         hash => H256;
         sender => Address;
         gas_limit => u64;
-        value => U256
+        to => TransactionKind;
+        value => U256;
+        input => Bytes
     }
 
     /// Returns a new legacy transaction with random address and hash and empty values
@@ -117,7 +124,9 @@ impl MockTransaction {
             nonce: 0,
             gas_price: U256::ZERO,
             gas_limit: 0,
+            to: TransactionKind::Call(Address::random()),
             value: Default::default(),
+            input: Default::default(),
         }
     }
 
@@ -130,7 +139,9 @@ impl MockTransaction {
             max_fee_per_gas: MIN_PROTOCOL_BASE_FEE,
             max_priority_fee_per_gas: MIN_PROTOCOL_BASE_FEE,
             gas_limit: 0,
+            to: TransactionKind::Call(Address::random()),
             value: Default::default(),
+            input: Default::default(),
         }
     }
 
@@ -334,6 +345,33 @@ impl PoolTransaction for MockTransaction {
     fn size(&self) -> usize {
         0
     }
+
+    fn chain_id(&self) -> Option<u64> {
+        None
+    }
+
+    fn tx_type(&self) -> TxType {
+        match self {
+            MockTransaction::Legacy { .. } => TxType::Legacy,
+            MockTransaction::Eip1559 { .. } => TxType::EIP1559,
+        }
+    }
+
+    fn kind(&self) -> &TransactionKind {
+        get_value!(self => to)
+    }
+
+    fn input(&self) -> &[u8] {
+        get_value!(self => input)
+    }
+
+    fn access_list(&self) -> Option<&AccessList> {
+        None
+    }
+
+    fn encoded_length(&self) -> usize {
+        0
+    }
 }
 
 impl FromRecoveredTransaction for MockTransaction {
@@ -356,7 +394,9 @@ impl FromRecoveredTransaction for MockTransaction {
                 nonce,
                 gas_price: U256::from(gas_price),
                 gas_limit,
+                to,
                 value: U256::from(value),
+                input,
             },
             Transaction::Eip1559(TxEip1559 {
                 chain_id,
@@ -375,7 +415,9 @@ impl FromRecoveredTransaction for MockTransaction {
                 max_fee_per_gas: U256::from(max_fee_per_gas),
                 max_priority_fee_per_gas: U256::from(max_priority_fee_per_gas),
                 gas_limit,
+                to,
                 value: U256::from(value),
+                input,
             },
             Transaction::Eip2930 { .. } => {
                 unimplemented!()
@@ -384,9 +426,47 @@ impl FromRecoveredTransaction for MockTransaction {
     }
 }
 
+/// Note: the hash of the signed transaction is derived from its content and does not match the
+/// hash of the mock.
 impl IntoRecoveredTransaction for MockTransaction {
     fn to_recovered_transaction(&self) -> TransactionSignedEcRecovered {
-        todo!()
+        let transaction = match self.clone() {
+            MockTransaction::Legacy { nonce, gas_price, gas_limit, to, value, input, .. } => {
+                Transaction::Legacy(TxLegacy {
+                    chain_id: None,
+                    nonce,
+                    gas_price: gas_price.to::<u128>(),
+                    gas_limit,
+                    to,
+                    value: value.to::<u128>(),
+                    input,
+                })
+            }
+            MockTransaction::Eip1559 {
+                nonce,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                gas_limit,
+                to,
+                value,
+                input,
+                ..
+            } => Transaction::Eip1559(TxEip1559 {
+                chain_id: 1,
+                nonce,
+                gas_limit,
+                max_fee_per_gas: max_fee_per_gas.to::<u128>(),
+                max_priority_fee_per_gas: max_priority_fee_per_gas.to::<u128>(),
+                to,
+                value: value.to::<u128>(),
+                input,
+                access_list: Default::default(),
+            }),
+        };
+        TransactionSignedEcRecovered::from_signed_transaction(
+            TransactionSigned::from_transaction_and_signature(transaction, Signature::default()),
+            self.get_sender(),
+        )
     }
 }
 
@@ -1,5 +1,9 @@
 use crate::{error::PoolResult, pool::state::SubPool, validate::ValidPoolTransaction};
-use reth_primitives::{Address, FromRecoveredTransaction, PeerId, TxHash, H256, U256};
+use reth_primitives::{
+    AccessList, Address, FromRecoveredTransaction, IntoRecoveredTransaction, PeerId, Transaction,
+    TransactionKind, TransactionSignedEcRecovered, TxHash, TxType, H256, U256,
+};
+use reth_rlp::Encodable;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt, sync::Arc};
 use tokio::sync::mpsc::Receiver;
@@ -72,6 +76,18 @@ pub trait TransactionPool: Send + Sync + Clone {
     /// Consumer: P2P
     fn pooled_transactions(&self) -> Vec<TxHash>;
 
+    /// Returns all transactions that can be included in the next block, ordered by sender and
+    /// nonce.
+    ///
+    /// Consumer: RPC
+    fn pending_transactions(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>>;
+
+    /// Returns all transactions that can't be included in the next block, for example because of
+    /// a nonce gap or a fee cap below the base fee, ordered by sender and nonce.
+    ///
+    /// Consumer: RPC
+    fn queued_transactions(&self) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>>;
+
     /// Returns an iterator that yields transactions that are ready for block production.
     ///
     /// Consumer: Block production
@@ -118,6 +134,9 @@ pub trait TransactionPool: Send + Sync + Clone {
     ///
     /// Consumer: P2P
     fn on_propagated(&self, txs: PropagatedTransactions);
+
+    /// Returns whether a transaction of the sender was added with [`TransactionOrigin::Local`].
+    fn is_local_sender(&self, sender: &Address) -> bool;
 }
 
 /// Represents a transaction that was propagated over the network.
@@ -210,10 +229,19 @@ pub struct OnNewBlockEvent {
     pub mined_transactions: Vec<H256>,
 }
 
-/// Contains a list of changed state
-#[derive(Debug, Clone)]
-pub struct StateDiff {
-    // TODO(mattsse) this could be an `Arc<revm::State>>`
+/// Contains the new state of all accounts that changed in a block.
+///
+/// The pool uses this to update the nonce and balance of the senders of its transactions.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct StateDiff(pub HashMap<Address, ChangedAccount>);
+
+/// The state of an account after it changed in a block.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ChangedAccount {
+    /// The nonce of the account.
+    pub nonce: u64,
+    /// The balance of the account.
+    pub balance: U256,
 }
 
 /// An `Iterator` that only returns transactions that are ready to be executed.
@@ -270,6 +298,150 @@ pub trait PoolTransaction: fmt::Debug + Send + Sync + FromRecoveredTransaction {
 
     /// Returns a measurement of the heap usage of this type and all its internals.
     fn size(&self) -> usize;
+
+    /// Returns the chain id this transaction is bound to, if any.
+    ///
+    /// This will return `None` for legacy transactions without replay protection (pre EIP-155).
+    fn chain_id(&self) -> Option<u64>;
+
+    /// Returns the [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718) type of the transaction.
+    fn tx_type(&self) -> TxType;
+
+    /// Returns the recipient of the transaction, or [`TransactionKind::Create`] if this is a
+    /// contract creation.
+    fn kind(&self) -> &TransactionKind;
+
+    /// Returns the input data of the transaction, which is the init code for contract creations.
+    fn input(&self) -> &[u8];
+
+    /// Returns the [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930) access list.
+    ///
+    /// This will return `None` for legacy transactions.
+    fn access_list(&self) -> Option<&AccessList>;
+
+    /// Returns the length of the rlp encoded transaction as it would be sent over the network.
+    fn encoded_length(&self) -> usize;
+}
+
+/// The default [`PoolTransaction`] for the [`Pool`](crate::Pool).
+///
+/// This is a wrapper around a [`TransactionSignedEcRecovered`] that caches the values the pool
+/// frequently needs for ordering and validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PooledTransaction {
+    /// The recovered transaction.
+    transaction: TransactionSignedEcRecovered,
+    /// `feeCap x gasLimit + transferred_value`, see [`PoolTransaction::cost`].
+    cost: U256,
+}
+
+// === impl PooledTransaction ===
+
+impl PooledTransaction {
+    /// Creates a new pool transaction from the recovered transaction.
+    pub fn new(transaction: TransactionSignedEcRecovered) -> Self {
+        let gas_cost =
+            U256::from(transaction.max_fee_per_gas()) * U256::from(transaction.gas_limit());
+        let cost = gas_cost + U256::from(*transaction.value());
+        Self { transaction, cost }
+    }
+
+    /// Returns the wrapped transaction.
+    pub fn transaction(&self) -> &TransactionSignedEcRecovered {
+        &self.transaction
+    }
+
+    /// Consumes the type and returns the wrapped transaction.
+    pub fn into_transaction(self) -> TransactionSignedEcRecovered {
+        self.transaction
+    }
+}
+
+impl PoolTransaction for PooledTransaction {
+    fn hash(&self) -> &TxHash {
+        &self.transaction.hash
+    }
+
+    fn sender(&self) -> Address {
+        self.transaction.signer()
+    }
+
+    fn nonce(&self) -> u64 {
+        self.transaction.nonce()
+    }
+
+    fn cost(&self) -> U256 {
+        self.cost
+    }
+
+    fn effective_gas_price(&self) -> U256 {
+        U256::from(self.transaction.effective_gas_price(None))
+    }
+
+    fn gas_limit(&self) -> u64 {
+        self.transaction.gas_limit()
+    }
+
+    fn max_fee_per_gas(&self) -> Option<U256> {
+        match &self.transaction.transaction {
+            Transaction::Legacy(_) | Transaction::Eip2930(_) => None,
+            Transaction::Eip1559(tx) => Some(U256::from(tx.max_fee_per_gas)),
+        }
+    }
+
+    fn max_priority_fee_per_gas(&self) -> Option<U256> {
+        match &self.transaction.transaction {
+            Transaction::Legacy(_) | Transaction::Eip2930(_) => None,
+            Transaction::Eip1559(tx) => Some(U256::from(tx.max_priority_fee_per_gas)),
+        }
+    }
+
+    fn size(&self) -> usize {
+        let access_list = self.access_list().map_or(0, |list| {
+            list.0.iter().map(|item| 20 + item.storage_keys.len() * 32).sum::<usize>()
+        });
+        self.input().len() + access_list
+    }
+
+    fn chain_id(&self) -> Option<u64> {
+        self.transaction.chain_id()
+    }
+
+    fn tx_type(&self) -> TxType {
+        self.transaction.tx_type()
+    }
+
+    fn kind(&self) -> &TransactionKind {
+        self.transaction.kind()
+    }
+
+    fn input(&self) -> &[u8] {
+        self.transaction.input()
+    }
+
+    fn access_list(&self) -> Option<&AccessList> {
+        match &self.transaction.transaction {
+            Transaction::Legacy(_) => None,
+            Transaction::Eip2930(tx) => Some(&tx.access_list),
+            Transaction::Eip1559(tx) => Some(&tx.access_list),
+        }
+    }
+
+    fn encoded_length(&self) -> usize {
+        self.transaction.length()
+    }
+}
+
+impl FromRecoveredTransaction for PooledTransaction {
+    fn from_recovered_transaction(tx: TransactionSignedEcRecovered) -> Self {
+        PooledTransaction::new(tx)
+    }
+}
+
+impl IntoRecoveredTransaction for PooledTransaction {
+    fn to_recovered_transaction(&self) -> TransactionSignedEcRecovered {
+        self.transaction.clone()
+    }
 }
 
 /// Represents the current status of the pool.
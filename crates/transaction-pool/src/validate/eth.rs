@@ -0,0 +1,453 @@
+//! Ethereum transaction validator.
+
+use crate::{
+    error::{InvalidPoolTransactionError, PoolError},
+    traits::{PoolTransaction, TransactionOrigin},
+    validate::{TransactionValidationOutcome, TransactionValidator},
+};
+use reth_primitives::{BlockNumber, TransactionKind, TxType, KECCAK_EMPTY};
+use reth_provider::{AccountProvider, BlockProvider, HeaderProvider, StateProviderFactory};
+use std::{fmt, marker::PhantomData};
+
+/// The maximum size of the init code of a contract creation, see
+/// [EIP-3860](https://eips.ethereum.org/EIPS/eip-3860).
+pub const MAX_INIT_CODE_SIZE: usize = 2 * 24_576;
+
+/// The maximum size of an encoded transaction that is accepted by the validator.
+///
+/// Larger transactions are rejected to prevent DOS attacks, this is the same limit geth uses.
+pub const TX_MAX_SIZE: usize = 4 * 32 * 1024;
+
+/// The gas limit of a block the validator assumes if the header of the head is unknown.
+pub const DEFAULT_BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+/// Gas charged for every transaction.
+const TX_GAS: u64 = 21_000;
+/// Gas charged for every contract creation.
+const TX_GAS_CONTRACT_CREATION: u64 = 53_000;
+/// Gas charged per zero byte of the input.
+const TX_DATA_ZERO_GAS: u64 = 4;
+/// Gas charged per non-zero byte of the input.
+const TX_DATA_NON_ZERO_GAS: u64 = 16;
+/// Gas charged per address in the access list.
+const TX_ACCESS_LIST_ADDRESS_GAS: u64 = 2_400;
+/// Gas charged per storage key in the access list.
+const TX_ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1_900;
+/// Gas charged per 32 byte word of the init code after Shanghai.
+const INIT_CODE_WORD_GAS: u64 = 2;
+
+/// A [`TransactionValidator`] that validates ethereum transactions against the latest state.
+///
+/// Transactions are validated against the rules of the block on top of the current head: the
+/// active hardforks follow from its number and transactions must not exceed the gas limit of the
+/// head.
+///
+/// Note: The sender of a pool transaction has already been recovered from its signature, see
+/// [`FromRecoveredTransaction`](reth_primitives::FromRecoveredTransaction), so transactions with
+/// an invalid signature never reach the validator.
+pub struct EthTransactionValidator<Client, T> {
+    /// Provides access to the current head and the latest state.
+    client: Client,
+    /// The chain id transactions must be bound to.
+    chain_id: u64,
+    /// The first block of Berlin, since which
+    /// [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718) typed transactions are accepted.
+    berlin_block: BlockNumber,
+    /// The first block of London, since which
+    /// [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) transactions are accepted.
+    london_block: BlockNumber,
+    /// The first block of Shanghai, since which the size of the init code is limited.
+    shanghai_block: BlockNumber,
+    /// Marker for the transaction type.
+    _marker: PhantomData<T>,
+}
+
+// === impl EthTransactionValidator ===
+
+impl<Client, T> EthTransactionValidator<Client, T> {
+    /// Creates a new validator for the given chain with Berlin and London active from genesis and
+    /// Shanghai not scheduled.
+    pub fn new(client: Client, chain_id: u64) -> Self {
+        Self {
+            client,
+            chain_id,
+            berlin_block: 0,
+            london_block: 0,
+            shanghai_block: BlockNumber::MAX,
+            _marker: Default::default(),
+        }
+    }
+
+    /// Sets the first block of Berlin.
+    pub fn berlin_block(mut self, berlin_block: BlockNumber) -> Self {
+        self.berlin_block = berlin_block;
+        self
+    }
+
+    /// Sets the first block of London.
+    pub fn london_block(mut self, london_block: BlockNumber) -> Self {
+        self.london_block = london_block;
+        self
+    }
+
+    /// Sets the first block of Shanghai.
+    pub fn shanghai_block(mut self, shanghai_block: BlockNumber) -> Self {
+        self.shanghai_block = shanghai_block;
+        self
+    }
+}
+
+impl<Client, T: PoolTransaction> EthTransactionValidator<Client, T> {
+    /// Checks all constraints that don't depend on the state against the rules of the given
+    /// pending block.
+    fn ensure_valid_stateless(
+        &self,
+        transaction: &T,
+        pending_block: BlockNumber,
+        block_gas_limit: u64,
+    ) -> Result<(), InvalidPoolTransactionError> {
+        let shanghai = pending_block >= self.shanghai_block;
+        match transaction.tx_type() {
+            TxType::Legacy => {}
+            TxType::EIP2930 if pending_block >= self.berlin_block => {}
+            TxType::EIP1559 if pending_block >= self.london_block => {}
+            tx_type => return Err(InvalidPoolTransactionError::TxTypeNotSupported(tx_type)),
+        }
+
+        // legacy transactions without replay protection are valid on every chain
+        if let Some(chain_id) = transaction.chain_id() {
+            if chain_id != self.chain_id {
+                return Err(InvalidPoolTransactionError::ChainIdMismatch(
+                    Some(chain_id),
+                    self.chain_id,
+                ));
+            }
+        }
+
+        let size = transaction.encoded_length();
+        if size > TX_MAX_SIZE {
+            return Err(InvalidPoolTransactionError::OversizedData(size, TX_MAX_SIZE))
+        }
+
+        let is_create = matches!(transaction.kind(), TransactionKind::Create);
+        if shanghai && is_create && transaction.input().len() > MAX_INIT_CODE_SIZE {
+            return Err(InvalidPoolTransactionError::ExceedsMaxInitCodeSize(
+                transaction.input().len(),
+                MAX_INIT_CODE_SIZE,
+            ));
+        }
+
+        let gas_limit = transaction.gas_limit();
+        if gas_limit > block_gas_limit {
+            return Err(InvalidPoolTransactionError::ExceedsGasLimit(gas_limit, block_gas_limit))
+        }
+
+        if let (Some(max_fee), Some(max_priority_fee)) =
+            (transaction.max_fee_per_gas(), transaction.max_priority_fee_per_gas())
+        {
+            if max_priority_fee > max_fee {
+                return Err(InvalidPoolTransactionError::TipAboveFeeCap)
+            }
+        }
+
+        let intrinsic_gas = intrinsic_gas(transaction, shanghai);
+        if gas_limit < intrinsic_gas {
+            return Err(InvalidPoolTransactionError::IntrinsicGasTooLow(gas_limit, intrinsic_gas))
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<Client, T> TransactionValidator for EthTransactionValidator<Client, T>
+where
+    Client: StateProviderFactory + BlockProvider + HeaderProvider + 'static,
+    T: PoolTransaction + 'static,
+{
+    type Transaction = T;
+
+    async fn validate_transaction(
+        &self,
+        _origin: TransactionOrigin,
+        transaction: Self::Transaction,
+    ) -> TransactionValidationOutcome<Self::Transaction> {
+        let hash = *transaction.hash();
+        let invalid = |transaction, err| {
+            TransactionValidationOutcome::Invalid(
+                transaction,
+                PoolError::InvalidTransaction(hash, err),
+            )
+        };
+
+        // the number and the gas limit of the block on top of the current head
+        let (pending_block, block_gas_limit) = match self.client.chain_info().and_then(|head| {
+            let header = self.client.header(&head.best_hash)?;
+            Ok((head.best_number + 1, header.map_or(DEFAULT_BLOCK_GAS_LIMIT, |h| h.gas_limit)))
+        }) {
+            Ok(pending_block) => pending_block,
+            Err(err) => return TransactionValidationOutcome::Error(transaction, Box::new(err)),
+        };
+        if let Err(err) = self.ensure_valid_stateless(&transaction, pending_block, block_gas_limit)
+        {
+            return invalid(transaction, err)
+        }
+
+        let account = match self
+            .client
+            .latest()
+            .and_then(|state| state.basic_account(transaction.sender()))
+        {
+            Ok(account) => account.unwrap_or_default(),
+            Err(err) => return TransactionValidationOutcome::Error(transaction, Box::new(err)),
+        };
+
+        // senders with deployed code are rejected, see EIP-3607
+        if account.bytecode_hash.map_or(false, |hash| hash != KECCAK_EMPTY) {
+            return invalid(transaction, InvalidPoolTransactionError::SignerAccountHasBytecode)
+        }
+
+        if transaction.nonce() < account.nonce {
+            return invalid(
+                transaction,
+                InvalidPoolTransactionError::NonceNotConsistent {
+                    tx: transaction.nonce(),
+                    state: account.nonce,
+                },
+            );
+        }
+
+        let cost = transaction.cost();
+        if cost > account.balance {
+            return invalid(
+                transaction,
+                InvalidPoolTransactionError::InsufficientFunds { cost, balance: account.balance },
+            );
+        }
+
+        TransactionValidationOutcome::Valid {
+            balance: account.balance,
+            state_nonce: account.nonce,
+            transaction,
+        }
+    }
+}
+
+impl<Client, T> fmt::Debug for EthTransactionValidator<Client, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EthTransactionValidator")
+            .field("chain_id", &self.chain_id)
+            .field("berlin_block", &self.berlin_block)
+            .field("london_block", &self.london_block)
+            .field("shanghai_block", &self.shanghai_block)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the gas that is charged for the transaction before any code is executed.
+fn intrinsic_gas<T: PoolTransaction>(transaction: &T, shanghai: bool) -> u64 {
+    let is_create = matches!(transaction.kind(), TransactionKind::Create);
+    let mut gas = if is_create { TX_GAS_CONTRACT_CREATION } else { TX_GAS };
+
+    let input = transaction.input();
+    let zero_bytes = input.iter().filter(|byte| **byte == 0).count() as u64;
+    let non_zero_bytes = input.len() as u64 - zero_bytes;
+    gas += zero_bytes * TX_DATA_ZERO_GAS + non_zero_bytes * TX_DATA_NON_ZERO_GAS;
+
+    if let Some(access_list) = transaction.access_list() {
+        let storage_keys: usize = access_list.0.iter().map(|item| item.storage_keys.len()).sum();
+        gas += access_list.0.len() as u64 * TX_ACCESS_LIST_ADDRESS_GAS;
+        gas += storage_keys as u64 * TX_ACCESS_LIST_STORAGE_KEY_GAS;
+    }
+
+    if shanghai && is_create {
+        let words = (input.len() as u64 + 31) / 32;
+        gas += words * INIT_CODE_WORD_GAS;
+    }
+
+    gas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::MockTransaction, traits::PooledTransaction};
+    use reth_primitives::{
+        Address, Header, Signature, Transaction, TransactionSigned, TransactionSignedEcRecovered,
+        TxLegacy, U256,
+    };
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+
+    fn validator(
+        provider: MockEthProvider,
+    ) -> EthTransactionValidator<MockEthProvider, MockTransaction> {
+        EthTransactionValidator::new(provider, 1)
+    }
+
+    fn transfer() -> MockTransaction {
+        MockTransaction::legacy().with_gas_limit(TX_GAS).with_gas_price(U256::from(1))
+    }
+
+    fn assert_invalid<T: PoolTransaction>(
+        outcome: TransactionValidationOutcome<T>,
+        expected: InvalidPoolTransactionError,
+    ) {
+        match outcome {
+            TransactionValidationOutcome::Invalid(_, PoolError::InvalidTransaction(_, err)) => {
+                assert_eq!(err, expected)
+            }
+            outcome => panic!("unexpected outcome {outcome:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn validate_transfer() {
+        let provider = MockEthProvider::default();
+        let tx = transfer().with_nonce(2);
+        provider.add_account(tx.get_sender(), ExtendedAccount::new(1, U256::from(100_000)));
+
+        let outcome = validator(provider).validate_transaction(TransactionOrigin::External, tx);
+        match outcome.await {
+            TransactionValidationOutcome::Valid { balance, state_nonce, .. } => {
+                assert_eq!(balance, U256::from(100_000));
+                assert_eq!(state_nonce, 1);
+            }
+            outcome => panic!("unexpected outcome {outcome:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_state() {
+        let provider = MockEthProvider::default();
+        let tx = transfer();
+        provider.add_account(tx.get_sender(), ExtendedAccount::new(1, U256::from(100_000)));
+        let validator = validator(provider);
+
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx.clone()).await;
+        assert_invalid(
+            outcome,
+            InvalidPoolTransactionError::NonceNotConsistent { tx: 0, state: 1 },
+        );
+
+        let tx = tx.with_nonce(1).with_value(U256::from(100_000));
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(
+            outcome,
+            InvalidPoolTransactionError::InsufficientFunds {
+                cost: U256::from(121_000),
+                balance: U256::from(100_000),
+            },
+        );
+
+        // unknown accounts have no balance
+        let tx = transfer().with_sender(Address::random());
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(
+            outcome,
+            InvalidPoolTransactionError::InsufficientFunds {
+                cost: U256::from(TX_GAS),
+                balance: U256::ZERO,
+            },
+        );
+
+        let contract = Address::random();
+        validator.client.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::from(100_000)).with_bytecode(vec![0x60, 0x00].into()),
+        );
+        let tx = transfer().with_sender(contract);
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(outcome, InvalidPoolTransactionError::SignerAccountHasBytecode);
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_stateless() {
+        let validator =
+            validator(MockEthProvider::default()).london_block(BlockNumber::MAX).shanghai_block(0);
+
+        let tx = MockTransaction::eip1559().with_gas_limit(TX_GAS);
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(outcome, InvalidPoolTransactionError::TxTypeNotSupported(TxType::EIP1559));
+
+        let tx = transfer().with_input(vec![1; 2].into());
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(outcome, InvalidPoolTransactionError::IntrinsicGasTooLow(TX_GAS, 21_032));
+
+        let tx = transfer().with_gas_limit(DEFAULT_BLOCK_GAS_LIMIT + 1);
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(
+            outcome,
+            InvalidPoolTransactionError::ExceedsGasLimit(
+                DEFAULT_BLOCK_GAS_LIMIT + 1,
+                DEFAULT_BLOCK_GAS_LIMIT,
+            ),
+        );
+
+        let tx = transfer()
+            .with_to(TransactionKind::Create)
+            .with_input(vec![0; MAX_INIT_CODE_SIZE + 1].into());
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(
+            outcome,
+            InvalidPoolTransactionError::ExceedsMaxInitCodeSize(
+                MAX_INIT_CODE_SIZE + 1,
+                MAX_INIT_CODE_SIZE,
+            ),
+        );
+    }
+
+    #[tokio::test]
+    async fn validate_against_head() {
+        let provider = MockEthProvider::default();
+        let head = Header { number: 9, gas_limit: 100_000, ..Default::default() };
+        provider.add_header(head.hash_slow(), head);
+        let validator = validator(provider.clone()).london_block(11).shanghai_block(10);
+
+        // the pending block is past Shanghai but before London
+        let tx = MockTransaction::eip1559().with_gas_limit(TX_GAS);
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(outcome, InvalidPoolTransactionError::TxTypeNotSupported(TxType::EIP1559));
+
+        let tx = transfer().with_to(TransactionKind::Create).with_input(vec![0; 32].into());
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(
+            outcome,
+            InvalidPoolTransactionError::IntrinsicGasTooLow(TX_GAS, 53_000 + 32 * 4 + 2),
+        );
+
+        let tx = transfer().with_gas_limit(100_001);
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(outcome, InvalidPoolTransactionError::ExceedsGasLimit(100_001, 100_000));
+
+        // the rules follow the head
+        let head = Header { number: 10, gas_limit: 200_000, ..Default::default() };
+        provider.add_header(head.hash_slow(), head);
+        let tx = MockTransaction::eip1559().with_gas_limit(100_001);
+        let cost = tx.cost();
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(
+            outcome,
+            InvalidPoolTransactionError::InsufficientFunds { cost, balance: U256::ZERO },
+        );
+    }
+
+    #[tokio::test]
+    async fn reject_chain_id_mismatch() {
+        let transaction = Transaction::Legacy(TxLegacy {
+            chain_id: Some(5),
+            gas_limit: TX_GAS,
+            ..Default::default()
+        });
+        let signed =
+            TransactionSigned::from_transaction_and_signature(transaction, Signature::default());
+        let tx = PooledTransaction::new(TransactionSignedEcRecovered::from_signed_transaction(
+            signed,
+            Address::random(),
+        ));
+
+        let validator: EthTransactionValidator<_, PooledTransaction> =
+            EthTransactionValidator::new(MockEthProvider::default(), 1);
+        let outcome = validator.validate_transaction(TransactionOrigin::External, tx).await;
+        assert_invalid(outcome, InvalidPoolTransactionError::ChainIdMismatch(Some(5), 1));
+    }
+}
@@ -8,6 +8,10 @@ use crate::{
 use reth_primitives::{Address, TxHash, U256};
 use std::{fmt, time::Instant};
 
+mod eth;
+
+pub use eth::{EthTransactionValidator, DEFAULT_BLOCK_GAS_LIMIT, MAX_INIT_CODE_SIZE, TX_MAX_SIZE};
+
 /// A Result type returned after checking a transaction's validity.
 #[derive(Debug)]
 pub enum TransactionValidationOutcome<T: PoolTransaction> {
@@ -23,6 +27,12 @@ pub enum TransactionValidationOutcome<T: PoolTransaction> {
     /// The transaction is considered invalid indefinitely: It violates constraints that prevent
     /// this transaction from ever becoming valid.
     Invalid(T, PoolError),
+    /// An error occurred while trying to validate the transaction, for example the state could not
+    /// be read.
+    ///
+    /// Unlike [`TransactionValidationOutcome::Invalid`] this says nothing about the validity of
+    /// the transaction.
+    Error(T, Box<dyn std::error::Error + Send + Sync>),
 }
 
 /// Provides support for validating transaction at any given state of the chain